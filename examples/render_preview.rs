@@ -0,0 +1,16 @@
+//! Renders a preview image of one of the bundled puzzles, without any web
+//! context.
+//!
+//! Run with `cargo run --example render_preview --features ui > preview.svg`.
+
+use sudoku::ui::book::render_preview;
+use sudoku::ui::color::{DigitPalette, Theme};
+use sudoku::ui::geometry::GridGeometry;
+use sudoku::PuzzleSource;
+
+fn main() {
+    let puzzle = PuzzleSource::Embedded("2D-O3").load().unwrap();
+    let geometry = GridGeometry::with_margin(puzzle.order, 800.0, 800.0, 40.0).unwrap();
+    let palette = DigitPalette::generate(puzzle.order, &Theme::default());
+    print!("{}", render_preview(&puzzle, &geometry, &palette));
+}
@@ -0,0 +1,254 @@
+//! Writing and reading puzzles with custom symbols (e.g. letters instead of
+//! digits).
+//!
+//! Puzzles are still numeric internally; an alphabet only changes how values
+//! are presented.
+use crate::sudoku::{infer_shape, parse_boxes_header, BOXES_HEADER};
+use crate::Element;
+use crate::ParseError;
+use crate::Point;
+use crate::Sudoku;
+
+use std::fmt::Write;
+
+/// The symbol used for empty cells, which no alphabet may contain.
+const EMPTY: char = '_';
+
+/// A mapping from values to the symbols used to write them.
+///
+/// The first symbol stands for 1, the second for 2, and so on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alphabet {
+    symbols: Vec<char>,
+}
+
+/// Encodes errors encountered while constructing an alphabet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// The alphabet has no symbols.
+    Empty,
+    /// The symbol appears more than once.
+    Duplicate(char),
+    /// The symbol is whitespace or the empty-cell marker (`_`).
+    Reserved(char),
+}
+
+impl Alphabet {
+    /// Creates an alphabet from its symbols, in order of value.
+    pub fn new<I: IntoIterator<Item = char>>(symbols: I) -> Result<Self, Error> {
+        let symbols = symbols.into_iter().collect::<Vec<_>>();
+        if symbols.is_empty() {
+            return Err(Error::Empty);
+        }
+        for (i, symbol) in symbols.iter().enumerate() {
+            if *symbol == EMPTY || symbol.is_whitespace() {
+                return Err(Error::Reserved(*symbol));
+            }
+            if symbols[..i].contains(symbol) {
+                return Err(Error::Duplicate(*symbol));
+            }
+        }
+        Ok(Self { symbols })
+    }
+
+    /// The digits `1`–`9`.
+    pub fn digits() -> Self {
+        Self {
+            symbols: ('1'..='9').collect(),
+        }
+    }
+
+    /// The capital letters `A`–`Z`.
+    pub fn letters() -> Self {
+        Self {
+            symbols: ('A'..='Z').collect(),
+        }
+    }
+
+    /// Returns the number of symbols.
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Whether the alphabet has no symbols (never true for an alphabet that
+    /// was successfully created).
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Returns the symbol for the given value, if the alphabet has one.
+    pub fn symbol(&self, element: Element) -> Option<char> {
+        (element.0 as usize)
+            .checked_sub(1)
+            .and_then(|i| self.symbols.get(i))
+            .cloned()
+    }
+
+    /// Returns the value written with the given symbol, if any.
+    pub fn element(&self, symbol: char) -> Option<Element> {
+        self.symbols
+            .iter()
+            .position(|s| *s == symbol)
+            .map(|i| Element(i as u8 + 1))
+    }
+}
+
+impl Sudoku {
+    /// Writes the puzzle in the same grid format as `Display`, but with
+    /// values written using the given alphabet.
+    ///
+    /// # Panics
+    /// Panics if the alphabet has fewer symbols than the puzzle has values.
+    pub fn to_string_with(&self, alphabet: &Alphabet) -> String {
+        let axis = self.axis();
+        assert!(
+            alphabet.len() >= axis as usize,
+            "an alphabet of {} symbols is too short for {} values",
+            alphabet.len(),
+            axis
+        );
+        let mut string = String::new();
+        let (rows, columns) = self.box_shape();
+        if rows != columns {
+            let _ = writeln!(string, "{}{}x{}", BOXES_HEADER, rows, columns);
+        }
+        for y in 0..axis {
+            for x in 0..axis {
+                let symbol = self[Point([x, y])]
+                    .and_then(|e| alphabet.symbol(e))
+                    .unwrap_or(EMPTY);
+                string.push(symbol);
+                if x != axis - 1 {
+                    string.push(' ');
+                }
+            }
+            let _ = writeln!(string);
+        }
+        string
+    }
+
+    /// Parses a puzzle written by [`to_string_with`](#method.to_string_with)
+    /// with the same alphabet.
+    ///
+    /// As with `FromStr`, a leading `#boxes: RxC` line gives the shape of
+    /// [rectangular boxes](#method.new_rect).
+    pub fn from_str_with(s: &str, alphabet: &Alphabet) -> Result<Sudoku, ParseError> {
+        let mut rows = s
+            .split('\n')
+            .map(|row| row.trim_end_matches('\r'))
+            .collect::<Vec<_>>();
+        let boxes = if rows[0].starts_with(BOXES_HEADER.trim_end()) {
+            Some(parse_boxes_header(rows.remove(0))?)
+        } else {
+            None
+        };
+        if rows.last() == Some(&"") {
+            let _ = rows.pop();
+        }
+        let axis = rows.len();
+        let shape = infer_shape(axis, boxes)?;
+        let mut elements = Vec::with_capacity(axis.pow(2));
+        for (y, row) in rows.iter().enumerate() {
+            let cells = row.split(' ').collect::<Vec<_>>();
+            if cells.len() != axis {
+                return Err(ParseError::UnequalDimensions);
+            }
+            for (x, cell) in cells.iter().enumerate() {
+                let point = Point([x as u8, y as u8]);
+                let mut chars = cell.chars();
+                let symbol = match (chars.next(), chars.next()) {
+                    (Some(symbol), None) => symbol,
+                    (Some(_), Some(extra)) => {
+                        return Err(ParseError::UnexpectedCharacter(extra, point))
+                    }
+                    (None, _) => return Err(ParseError::UnequalDimensions),
+                };
+                if symbol == EMPTY {
+                    elements.push(None);
+                    continue;
+                }
+                match alphabet.element(symbol) {
+                    Some(Element(value)) if value as usize > axis => {
+                        return Err(ParseError::LargeValue(value, point))
+                    }
+                    Some(element) => elements.push(Some(element)),
+                    None => return Err(ParseError::UnexpectedCharacter(symbol, point)),
+                }
+            }
+        }
+        Ok(Sudoku::from_parts(shape, elements))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alphabet::{Alphabet, Error};
+    use crate::Element;
+    use crate::ParseError;
+    use crate::Sudoku;
+
+    #[test]
+    fn test_alphabet() {
+        assert_eq!(Alphabet::new(vec![]), Err(Error::Empty));
+        assert_eq!(Alphabet::new("ABA".chars()), Err(Error::Duplicate('A')));
+        assert_eq!(Alphabet::new("A_".chars()), Err(Error::Reserved('_')));
+        assert_eq!(Alphabet::new("A B".chars()), Err(Error::Reserved(' ')));
+        let letters = Alphabet::letters();
+        assert_eq!(letters.symbol(Element(1)), Some('A'));
+        assert_eq!(letters.symbol(Element(9)), Some('I'));
+        assert_eq!(letters.symbol(Element(0)), None);
+        assert_eq!(letters.symbol(Element(27)), None);
+        assert_eq!(letters.element('C'), Some(Element(3)));
+        assert_eq!(letters.element('c'), None);
+        assert_eq!(Alphabet::digits().len(), 9);
+    }
+
+    #[test]
+    fn test_letters_round_trip() {
+        let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let letters = Alphabet::letters();
+        let text = puzzle.to_string_with(&letters);
+        assert!(text.starts_with("E _ _ _ I C A F _\n"));
+        assert!(!text.contains(char::is_numeric));
+        assert_eq!(Sudoku::from_str_with(&text, &letters).unwrap(), puzzle);
+        // The digit alphabet matches `Display`.
+        assert_eq!(
+            puzzle.to_string_with(&Alphabet::digits()),
+            puzzle.to_string()
+        );
+    }
+
+    #[test]
+    fn test_rect_round_trip() {
+        let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-R2x3.txt")
+            .parse()
+            .unwrap();
+        let letters = Alphabet::letters();
+        let text = puzzle.to_string_with(&letters);
+        assert!(text.starts_with("#boxes: 2x3\n_ B _ _ _ _\n"));
+        assert_eq!(text.lines().count(), 7);
+        let parsed = Sudoku::from_str_with(&text, &letters).unwrap();
+        assert_eq!(parsed, puzzle);
+        assert_eq!(parsed.box_shape(), (2, 3));
+    }
+
+    #[test]
+    fn test_from_str_with_errors() {
+        let letters = Alphabet::letters();
+        match Sudoku::from_str_with("A _ _ E\n_ _ _ _\n_ _ _ _\n_ _ _ _\n", &letters) {
+            Err(ParseError::LargeValue(5, _)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match Sudoku::from_str_with("A _ _ 1\n_ _ _ _\n_ _ _ _\n_ _ _ _\n", &letters) {
+            Err(ParseError::UnexpectedCharacter('1', _)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match Sudoku::from_str_with("A _ _\n_ _ _ _\n_ _ _ _\n_ _ _ _\n", &letters) {
+            Err(ParseError::UnequalDimensions) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(Sudoku::from_str_with("", &letters).is_err());
+    }
+}
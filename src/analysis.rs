@@ -0,0 +1,541 @@
+//! Comparisons between this crate's difficulty grading and external ones.
+//!
+//! Given puzzles labeled by some other grader (a published collection, say),
+//! [`DifficultyComparison::compute`](struct.DifficultyComparison.html#method.compute)
+//! reports how well the two gradings agree. Labeled collections are usually
+//! kept as CSV files of `fingerprint,label` rows, where the fingerprint is
+//! the one [`fingerprint`](fn.fingerprint.html) computes; see
+//! [`parse_labels`](fn.parse_labels.html) and [`label`](fn.label.html).
+use crate::random::fnv1a;
+use crate::Difficulty;
+use crate::Grid;
+use crate::Score;
+use crate::Sudoku;
+
+use std::{cmp::Ordering, collections::HashMap, convert::TryFrom, fmt};
+
+/// The most disagreements a [`ComparisonReport`](struct.ComparisonReport.html)
+/// lists.
+pub const MAX_DISAGREEMENTS: usize = 10;
+
+/// Returns a short, stable hash of the puzzle's clues (in
+/// [`points`](../trait.Grid.html#tymethod.points) order), for telling
+/// puzzles apart in print and in label files.
+///
+/// This is the high half of
+/// [`Sudoku::fingerprint`](../struct.Sudoku.html#method.fingerprint), in
+/// hexadecimal.
+pub fn fingerprint(puzzle: &Sudoku) -> String {
+    format!("{:08x}", puzzle.fingerprint() >> 32)
+}
+
+impl Sudoku {
+    /// Returns a stable hash of the puzzle's order and clues (in
+    /// [`points`](trait.Grid.html#tymethod.points) order), for recognizing
+    /// puzzles already seen.
+    ///
+    /// Like the [seeded generator](struct.Sudoku.html#method.generate_seeded),
+    /// the hash is frozen: it won't change between releases.
+    pub fn fingerprint(&self) -> u64 {
+        let mut bytes = vec![self.order];
+        bytes.extend(
+            self.points()
+                .into_iter()
+                .map(|p| self[p].map_or(0, |e| e.0)),
+        );
+        fnv1a(&bytes)
+    }
+}
+
+/// A difficulty label from another grader, as an ordinal on a scale of
+/// `1..=N` (where 1 is the easiest).
+///
+/// Callers map the other grader's labels onto the scale themselves (e.g.
+/// "mild" to 1, "fiendish" to 4); only their order matters.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ExternalLabel(pub u8);
+
+/// A puzzle the two gradings disagree about.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Disagreement {
+    /// The puzzle's [fingerprint](fn.fingerprint.html).
+    pub fingerprint: String,
+    /// The puzzle's score.
+    pub score: usize,
+    /// The difficulty this crate grades the puzzle.
+    pub difficulty: Difficulty,
+    /// The puzzle's external label.
+    pub label: ExternalLabel,
+    /// How far apart the puzzle's rank by score and its rank by label are
+    /// (with tied values sharing the average of their ranks).
+    pub distance: f64,
+}
+
+/// The agreement between this crate's grading and an external one, as
+/// computed by
+/// [`DifficultyComparison::compute`](struct.DifficultyComparison.html#method.compute).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComparisonReport {
+    /// The number of puzzles compared.
+    pub compared: usize,
+    /// The confusion matrix: `confusion[d][l]` is the number of puzzles
+    /// graded the difficulty with `d` [stars](../enum.Difficulty.html#method.stars)
+    /// and labeled `l + 1`.
+    ///
+    /// There is a row for every difficulty (in the order of
+    /// [`Difficulty::ALL`](../enum.Difficulty.html#associatedconstant.ALL)),
+    /// and a column for every label up to the highest one given.
+    pub confusion: Vec<Vec<usize>>,
+    /// Spearman's rank correlation between the puzzles' scores and their
+    /// labels, from -1 (perfectly inverted) to 1 (perfectly agreeing).
+    ///
+    /// This is `None` if there are fewer than two puzzles, or if all of the
+    /// scores (or all of the labels) are the same.
+    pub spearman: Option<f64>,
+    /// The worst disagreements (at most
+    /// [`MAX_DISAGREEMENTS`](constant.MAX_DISAGREEMENTS.html)), worst first.
+    pub worst: Vec<Disagreement>,
+    /// The fingerprints of the puzzles that couldn't be scored (because they
+    /// aren't uniquely solvable), which are left out of the comparison.
+    pub unscored: Vec<String>,
+}
+
+/// Compares this crate's difficulty grading with an external one.
+#[derive(Clone, Copy, Debug)]
+pub struct DifficultyComparison;
+
+impl DifficultyComparison {
+    /// Scores each puzzle and compares the results with its external label.
+    pub fn compute(entries: &[(Sudoku, ExternalLabel)]) -> ComparisonReport {
+        let mut unscored = Vec::new();
+        let mut scored = Vec::new();
+        for (puzzle, label) in entries {
+            match puzzle.score() {
+                Some(score) => scored.push((puzzle, score, *label)),
+                None => unscored.push(fingerprint(puzzle)),
+            }
+        }
+        let columns = scored
+            .iter()
+            .map(|(_, _, label)| usize::from(label.0))
+            .max()
+            .unwrap_or(0);
+        let mut confusion = vec![vec![0; columns]; Difficulty::ALL.len()];
+        for (puzzle, score, label) in &scored {
+            let difficulty = Difficulty::from_score_with_boxes(*score, puzzle.box_shape());
+            if label.0 > 0 {
+                confusion[usize::from(difficulty.stars())][usize::from(label.0) - 1] += 1;
+            }
+        }
+        let scores = scored
+            .iter()
+            .map(|(_, score, _)| *score as f64)
+            .collect::<Vec<_>>();
+        let labels = scored
+            .iter()
+            .map(|(_, _, label)| f64::from(label.0))
+            .collect::<Vec<_>>();
+        let (score_ranks, label_ranks) = (ranks(&scores), ranks(&labels));
+        let mut worst = scored
+            .iter()
+            .zip(score_ranks.iter().zip(&label_ranks))
+            .map(|((puzzle, score, label), (x, y))| Disagreement {
+                fingerprint: fingerprint(puzzle),
+                score: *score,
+                difficulty: Difficulty::from_score_with_boxes(*score, puzzle.box_shape()),
+                label: *label,
+                distance: (x - y).abs(),
+            })
+            .filter(|disagreement| disagreement.distance > 0.0)
+            .collect::<Vec<_>>();
+        // The sort is stable, so equally bad disagreements stay in order.
+        worst.sort_by(|a, b| {
+            b.distance
+                .partial_cmp(&a.distance)
+                .unwrap_or(Ordering::Equal)
+        });
+        worst.truncate(MAX_DISAGREEMENTS);
+        ComparisonReport {
+            compared: scored.len(),
+            confusion,
+            spearman: correlation(&score_ranks, &label_ranks),
+            worst,
+            unscored,
+        }
+    }
+}
+
+impl fmt::Display for ComparisonReport {
+    /// Writes the report as plain text: the summary statistics, the
+    /// confusion matrix (a row per difficulty, leaving out an empty
+    /// unplayable row) and the worst disagreements.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Compared: {}", self.compared)?;
+        if !self.unscored.is_empty() {
+            writeln!(f, "Unscored: {}", self.unscored.join(", "))?;
+        }
+        match self.spearman {
+            Some(rho) => writeln!(f, "Spearman: {:.3}", rho)?,
+            None => writeln!(f, "Spearman: n/a")?,
+        }
+        write!(f, "{:>12}", "")?;
+        for label in 1..=self.confusion.first().map_or(0, Vec::len) {
+            write!(f, " {:>4}", label)?;
+        }
+        writeln!(f)?;
+        for (difficulty, row) in Difficulty::ALL.iter().zip(&self.confusion) {
+            if !difficulty.is_playable() && row.iter().all(|count| *count == 0) {
+                continue;
+            }
+            write!(f, "{:>12}", difficulty.to_string())?;
+            for count in row {
+                write!(f, " {:>4}", count)?;
+            }
+            writeln!(f)?;
+        }
+        if !self.worst.is_empty() {
+            writeln!(f, "Worst disagreements:")?;
+        }
+        for disagreement in &self.worst {
+            writeln!(
+                f,
+                "  {}: {} ({}) vs. {}",
+                disagreement.fingerprint,
+                disagreement.difficulty,
+                disagreement.score,
+                disagreement.label.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Ranks the values from 1 (the smallest), giving tied values the average of
+/// the ranks they span.
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order = (0..values.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        values[*a]
+            .partial_cmp(&values[*b])
+            .unwrap_or(Ordering::Equal)
+    });
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        // Ranks `start + 1..=end` are shared.
+        let rank = (start + 1 + end) as f64 / 2.0;
+        for i in &order[start..end] {
+            ranks[*i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// Returns the Pearson correlation of the two (equally long) series, or
+/// `None` if it's undefined.
+fn correlation(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    if xs.len() < 2 {
+        return None;
+    }
+    let n = xs.len() as f64;
+    let (mean_x, mean_y) = (xs.iter().sum::<f64>() / n, ys.iter().sum::<f64>() / n);
+    let (mut covariance, mut variance_x, mut variance_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(ys) {
+        let (dx, dy) = (x - mean_x, y - mean_y);
+        covariance += dx * dy;
+        variance_x += dx * dx;
+        variance_y += dy * dy;
+    }
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return None;
+    }
+    Some(covariance / (variance_x * variance_y).sqrt())
+}
+
+/// Encodes errors encountered while reading labels.
+///
+/// Line numbers are 1-based.
+///
+/// More variants may be added in future releases, so matches outside this
+/// crate need a wildcard arm.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The line isn't of the form `fingerprint,label`.
+    Malformed(usize),
+    /// The line has no fingerprint.
+    MissingFingerprint(usize),
+    /// The line's label is neither on the scale nor a number within it.
+    ///
+    /// The associated values are the line number and the label.
+    UnknownLabel(usize, String),
+    /// No puzzle has the given fingerprint.
+    UnknownFingerprint(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Malformed(line) => {
+                write!(f, "line {} isn't of the form fingerprint,label", line)
+            }
+            Error::MissingFingerprint(line) => write!(f, "line {} has no fingerprint", line),
+            Error::UnknownLabel(line, label) => {
+                write!(f, "line {} has an unknown label (\"{}\")", line, label)
+            }
+            Error::UnknownFingerprint(fingerprint) => {
+                write!(f, "no puzzle has the fingerprint {}", fingerprint)
+            }
+        }
+    }
+}
+
+/// Parses labels from CSV text of the form `fingerprint,label`, one pair per
+/// line.
+///
+/// Each label is either one of the names on the scale (easiest first,
+/// ignoring case) or a number from 1 to the length of the scale; with an
+/// empty scale, any positive number is accepted. Blank lines, lines starting
+/// with `#` and a leading `fingerprint,label` header are skipped, and
+/// whitespace (and double quotes) around each field are ignored.
+pub fn parse_labels(csv: &str, scale: &[&str]) -> Result<Vec<(String, ExternalLabel)>, Error> {
+    let mut labels = Vec::new();
+    for (i, line) in csv.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (fingerprint, label) = line.split_once(',').ok_or(Error::Malformed(number))?;
+        let (fingerprint, label) = (field(fingerprint), field(label));
+        if labels.is_empty()
+            && fingerprint.eq_ignore_ascii_case("fingerprint")
+            && label.eq_ignore_ascii_case("label")
+        {
+            continue;
+        }
+        if label.contains(',') {
+            return Err(Error::Malformed(number));
+        }
+        if fingerprint.is_empty() {
+            return Err(Error::MissingFingerprint(number));
+        }
+        let label =
+            ordinal(label, scale).ok_or_else(|| Error::UnknownLabel(number, label.to_string()))?;
+        labels.push((fingerprint.to_lowercase(), label));
+    }
+    Ok(labels)
+}
+
+/// Pairs each labeled fingerprint with its puzzle, in the order the labels
+/// are given.
+///
+/// Puzzles without labels are left out.
+pub fn label(
+    puzzles: &[Sudoku],
+    labels: &[(String, ExternalLabel)],
+) -> Result<Vec<(Sudoku, ExternalLabel)>, Error> {
+    let puzzles = puzzles
+        .iter()
+        .map(|puzzle| (fingerprint(puzzle), puzzle))
+        .collect::<HashMap<_, _>>();
+    labels
+        .iter()
+        .map(|(fingerprint, label)| match puzzles.get(fingerprint) {
+            Some(puzzle) => Ok(((*puzzle).clone(), *label)),
+            None => Err(Error::UnknownFingerprint(fingerprint.clone())),
+        })
+        .collect()
+}
+
+fn field(s: &str) -> &str {
+    s.trim().trim_matches('"').trim()
+}
+
+fn ordinal(label: &str, scale: &[&str]) -> Option<ExternalLabel> {
+    if let Some(i) = scale
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(label))
+    {
+        return u8::try_from(i + 1).ok().map(ExternalLabel);
+    }
+    let value = label.parse::<u8>().ok()?;
+    if value == 0 || (!scale.is_empty() && usize::from(value) > scale.len()) {
+        return None;
+    }
+    Some(ExternalLabel(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{
+        correlation, fingerprint, label, parse_labels, ranks, DifficultyComparison, Error,
+        ExternalLabel,
+    };
+    #[cfg(feature = "2D")]
+    use crate::Difficulty;
+    #[cfg(feature = "2D")]
+    use crate::Grid;
+    #[cfg(feature = "2D")]
+    use crate::Score;
+    use crate::Sudoku;
+
+    // Six uniquely solvable puzzles with strictly increasing scores, made by
+    // emptying more and more cells of one grid.
+    #[cfg(feature = "2D")]
+    fn ladder() -> Vec<Sudoku> {
+        let grid = Sudoku::generate_seeded(2, Difficulty::Beginner, 0)
+            .unwrap()
+            .solution;
+        let points = grid.points();
+        let puzzles = (1..=6)
+            .map(|empty| {
+                let mut puzzle = grid.clone();
+                for point in &points[..empty] {
+                    puzzle.substitute(*point, None);
+                }
+                puzzle
+            })
+            .collect::<Vec<_>>();
+        let scores = puzzles
+            .iter()
+            .map(|p| p.score().unwrap())
+            .collect::<Vec<_>>();
+        assert!(scores.windows(2).all(|pair| pair[0] < pair[1]));
+        puzzles
+    }
+
+    #[cfg(feature = "2D")]
+    fn labeled(labels: &[u8]) -> Vec<(Sudoku, ExternalLabel)> {
+        ladder()
+            .into_iter()
+            .zip(labels.iter().map(|label| ExternalLabel(*label)))
+            .collect()
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_compare_agreeing() {
+        let report = DifficultyComparison::compute(&labeled(&[1, 2, 3, 4, 5, 6]));
+        assert_eq!(report.compared, 6);
+        assert!((report.spearman.unwrap() - 1.0).abs() < 1e-9);
+        assert!(report.worst.is_empty());
+        assert!(report.unscored.is_empty());
+        // One to three empty cells grade as unplayable, four to seven as
+        // beginner (see `Difficulty::from_score`).
+        assert_eq!(report.confusion.len(), Difficulty::ALL.len());
+        assert_eq!(report.confusion[0], [1, 1, 1, 0, 0, 0]);
+        assert_eq!(report.confusion[1], [0, 0, 0, 1, 1, 1]);
+        assert!(report.confusion[2..]
+            .iter()
+            .flatten()
+            .all(|count| *count == 0));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_compare_inverted() {
+        let report = DifficultyComparison::compute(&labeled(&[6, 5, 4, 3, 2, 1]));
+        assert!((report.spearman.unwrap() + 1.0).abs() < 1e-9);
+        // The extremes are furthest apart.
+        let distances = report.worst.iter().map(|d| d.distance).collect::<Vec<_>>();
+        assert_eq!(distances, [5.0, 5.0, 3.0, 3.0, 1.0, 1.0]);
+        let ladder = ladder();
+        assert_eq!(report.worst[0].fingerprint, fingerprint(&ladder[0]));
+        assert_eq!(report.worst[0].label, ExternalLabel(6));
+        assert_eq!(report.worst[0].difficulty, Difficulty::Unplayable);
+        assert_eq!(report.worst[1].fingerprint, fingerprint(&ladder[5]));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_compare_shuffled() {
+        // The rank differences are 1, 2, -2, 2, -2 and -1, so
+        // rho = 1 - 6 * 18 / (6 * 35) = 17 / 35.
+        let mut entries = labeled(&[2, 4, 1, 6, 3, 5]);
+        entries.push((Sudoku::new(2), ExternalLabel(1)));
+        let report = DifficultyComparison::compute(&entries);
+        assert!((report.spearman.unwrap() - 17.0 / 35.0).abs() < 1e-9);
+        assert_eq!(report.compared, 6);
+        assert_eq!(report.unscored, [fingerprint(&Sudoku::new(2))]);
+        assert_eq!(report.worst.len(), 6);
+        assert_eq!(report.worst[0].distance, 2.0);
+    }
+
+    #[test]
+    fn test_compare_degenerate() {
+        let report = DifficultyComparison::compute(&[]);
+        assert_eq!((report.compared, report.spearman), (0, None));
+        assert!(report.confusion.iter().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn test_ranks() {
+        assert_eq!(ranks(&[30.0, 10.0, 20.0]), [3.0, 1.0, 2.0]);
+        assert_eq!(ranks(&[1.0, 2.0, 2.0, 3.0]), [1.0, 2.5, 2.5, 4.0]);
+        assert_eq!(correlation(&[1.0, 1.0], &[1.0, 2.0]), None);
+        assert_eq!(correlation(&[1.0], &[1.0]), None);
+    }
+
+    #[test]
+    fn test_parse_labels() {
+        let scale = ["easy", "medium", "hard"];
+        let csv = "fingerprint,label\n\n# comment\n0123abcd, Hard\n\"89ABCDEF\",1\n";
+        assert_eq!(
+            parse_labels(csv, &scale),
+            Ok(vec![
+                ("0123abcd".to_string(), ExternalLabel(3)),
+                ("89abcdef".to_string(), ExternalLabel(1)),
+            ])
+        );
+        assert_eq!(
+            parse_labels("0123abcd,7", &[]),
+            Ok(vec![("0123abcd".to_string(), ExternalLabel(7))])
+        );
+        assert_eq!(
+            parse_labels(",hard", &scale),
+            Err(Error::MissingFingerprint(1))
+        );
+        assert_eq!(
+            parse_labels("a,easy\n  ,2", &scale),
+            Err(Error::MissingFingerprint(2))
+        );
+        assert_eq!(
+            parse_labels("0123abcd,fiendish", &scale),
+            Err(Error::UnknownLabel(1, "fiendish".to_string()))
+        );
+        assert_eq!(
+            parse_labels("0123abcd,4", &scale),
+            Err(Error::UnknownLabel(1, "4".to_string()))
+        );
+        assert_eq!(
+            parse_labels("0123abcd,0", &[]),
+            Err(Error::UnknownLabel(1, "0".to_string()))
+        );
+        assert_eq!(parse_labels("0123abcd", &scale), Err(Error::Malformed(1)));
+        assert_eq!(
+            parse_labels("a,easy,hard", &scale),
+            Err(Error::Malformed(1))
+        );
+    }
+
+    #[test]
+    fn test_label() {
+        let puzzles = [Sudoku::new(2), Sudoku::new(3)];
+        let labels = vec![(fingerprint(&puzzles[1]), ExternalLabel(2))];
+        assert_eq!(
+            label(&puzzles, &labels),
+            Ok(vec![(puzzles[1].clone(), ExternalLabel(2))])
+        );
+        let unknown = vec![("ffffffff".to_string(), ExternalLabel(1))];
+        assert_eq!(
+            label(&puzzles, &unknown),
+            Err(Error::UnknownFingerprint("ffffffff".to_string()))
+        );
+    }
+}
@@ -0,0 +1,354 @@
+//! Scoring large batches of puzzles in parallel (with the `rayon` feature).
+//!
+//! [`score_all`](fn.score_all.html) spreads the puzzles over rayon's thread
+//! pool, and reports progress from the calling thread, so the callback
+//! needn't be thread-safe and the workers never wait on it.
+use crate::sol::{solve_and_score_bounded, Bounds, Error as SolveError};
+use crate::Difficulty;
+use crate::Sudoku;
+
+use rayon::prelude::*;
+
+use std::{
+    fmt,
+    panic::resume_unwind,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Options for [`score_all`](fn.score_all.html).
+pub struct BulkOptions {
+    /// The most search nodes each puzzle's uniqueness check may visit
+    /// (unlimited if `None`); puzzles that need more fail with
+    /// [`Exhausted`](../enum.SolveError.html#variant.Exhausted).
+    pub node_budget: Option<usize>,
+    /// When to give up on the batch (never, if `None`). Puzzles not yet
+    /// started are [skipped](enum.BulkOutcome.html#variant.Skipped), and
+    /// those underway fail with
+    /// [`Timeout`](../enum.SolveError.html#variant.Timeout).
+    pub deadline: Option<Instant>,
+    /// Called with the number of puzzles done and the total, from the
+    /// calling thread, at most once per `progress_interval` (and once more
+    /// when the batch is done).
+    pub progress: Option<Box<dyn Fn(usize, usize)>>,
+    /// The least time between progress reports (100 ms by default).
+    pub progress_interval: Duration,
+    // Holds up work on the puzzle at the given index for the returned time,
+    // for simulating puzzles that finish out of order.
+    #[cfg(test)]
+    delay: fn(usize) -> Duration,
+}
+
+impl Default for BulkOptions {
+    fn default() -> Self {
+        Self {
+            node_budget: None,
+            deadline: None,
+            progress: None,
+            progress_interval: Duration::from_millis(100),
+            #[cfg(test)]
+            delay: |_| Duration::from_secs(0),
+        }
+    }
+}
+
+impl BulkOptions {
+    fn limits(&self) -> Limits {
+        Limits {
+            node_budget: self.node_budget,
+            deadline: self.deadline,
+            #[cfg(test)]
+            delay: self.delay,
+        }
+    }
+}
+
+impl fmt::Debug for BulkOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BulkOptions")
+            .field("node_budget", &self.node_budget)
+            .field("deadline", &self.deadline)
+            .field("progress", &self.progress.as_ref().map(|_| ".."))
+            .field("progress_interval", &self.progress_interval)
+            .finish()
+    }
+}
+
+// The options the workers need, which (unlike the progress callback) can be
+// shared between threads.
+#[derive(Clone, Copy)]
+struct Limits {
+    node_budget: Option<usize>,
+    deadline: Option<Instant>,
+    #[cfg(test)]
+    delay: fn(usize) -> Duration,
+}
+
+/// What became of a puzzle in a batch.
+#[derive(Clone, Debug)]
+pub enum BulkOutcome {
+    /// The puzzle was scored.
+    Scored {
+        /// The puzzle's score.
+        score: usize,
+        /// The difficulty the score grades.
+        difficulty: Difficulty,
+    },
+    /// The puzzle couldn't be scored: it doesn't have a unique solution, or
+    /// its search ran past the node budget or the deadline.
+    Failed(SolveError),
+    /// The deadline passed before work on the puzzle began.
+    Skipped,
+}
+
+/// The result of scoring one puzzle of a batch.
+#[derive(Clone, Debug)]
+pub struct BulkResult {
+    /// The puzzle's [fingerprint](../struct.Sudoku.html#method.fingerprint).
+    pub fingerprint: u64,
+    /// What became of the puzzle.
+    pub outcome: BulkOutcome,
+    /// The time spent on the puzzle (zero if it was skipped).
+    pub elapsed: Duration,
+}
+
+/// Scores every puzzle in parallel, returning the results in the same order
+/// as the puzzles.
+///
+/// Each puzzle is solved and scored in a single search, which gives up once
+/// it exceeds the options' node budget or deadline. The workers only bump a
+/// shared counter as they finish puzzles; the calling thread reads it to
+/// report progress.
+pub fn score_all<I: IntoIterator<Item = Sudoku>>(
+    puzzles: I,
+    opts: &BulkOptions,
+) -> Vec<BulkResult> {
+    let puzzles = puzzles.into_iter().collect::<Vec<_>>();
+    let total = puzzles.len();
+    let done = AtomicUsize::new(0);
+    let limits = opts.limits();
+    let progress = match opts.progress {
+        Some(ref progress) => progress,
+        None => return score_each(&puzzles, limits, &done),
+    };
+    let caller = thread::current();
+    thread::scope(|scope| {
+        let (puzzles, done) = (&puzzles, &done);
+        let worker = scope.spawn(move || {
+            let results = score_each(puzzles, limits, done);
+            caller.unpark();
+            results
+        });
+        let mut next = Instant::now() + opts.progress_interval;
+        let mut reported = None;
+        while !worker.is_finished() {
+            thread::park_timeout(next.saturating_duration_since(Instant::now()));
+            let now = Instant::now();
+            if now < next || worker.is_finished() {
+                continue;
+            }
+            let done = done.load(Ordering::Relaxed);
+            if reported != Some(done) {
+                progress(done, total);
+                reported = Some(done);
+            }
+            next = now + opts.progress_interval;
+        }
+        progress(total, total);
+        worker.join().unwrap_or_else(|panic| resume_unwind(panic))
+    })
+}
+
+// Scores the puzzles on rayon's pool, counting each one done.
+fn score_each(puzzles: &[Sudoku], limits: Limits, done: &AtomicUsize) -> Vec<BulkResult> {
+    let Limits {
+        node_budget,
+        deadline,
+        ..
+    } = limits;
+    puzzles
+        .par_iter()
+        .enumerate()
+        .map(|(_index, puzzle)| {
+            let result = if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                BulkResult {
+                    fingerprint: puzzle.fingerprint(),
+                    outcome: BulkOutcome::Skipped,
+                    elapsed: Duration::from_secs(0),
+                }
+            } else {
+                let start = Instant::now();
+                #[cfg(test)]
+                thread::sleep((limits.delay)(_index));
+                let bounds = Bounds {
+                    max_nodes: node_budget,
+                    deadline,
+                    ..Bounds::default()
+                };
+                // The puzzle is scored by the bounded search itself, so the
+                // limits cover all of the work.
+                let outcome = match solve_and_score_bounded(puzzle, bounds) {
+                    Ok((_, score)) => BulkOutcome::Scored {
+                        score,
+                        difficulty: Difficulty::from_score_with_boxes(score, puzzle.box_shape()),
+                    },
+                    Err(error) => BulkOutcome::Failed(error),
+                };
+                BulkResult {
+                    fingerprint: puzzle.fingerprint(),
+                    outcome,
+                    elapsed: start.elapsed(),
+                }
+            };
+            let _ = done.fetch_add(1, Ordering::Relaxed);
+            result
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bulk::{score_all, BulkOptions, BulkOutcome};
+    use crate::Score;
+    use crate::SolveError;
+    use crate::Sudoku;
+
+    use std::{
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    // Distinct, quickly scored puzzles: the fixture with a growing number of
+    // its empty cells filled in from the solution.
+    #[cfg(feature = "2D")]
+    fn puzzles(count: usize) -> Vec<Sudoku> {
+        use crate::{Grid, Solve};
+        let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let solution = puzzle.solution().unwrap();
+        let empty = puzzle
+            .points()
+            .into_iter()
+            .filter(|p| puzzle[*p].is_none())
+            .collect::<Vec<_>>();
+        (0..count)
+            .map(|i| {
+                let mut puzzle = puzzle.clone();
+                for point in &empty[..i] {
+                    puzzle.substitute(*point, solution[*point]);
+                }
+                puzzle
+            })
+            .collect()
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_score_all() {
+        let mut puzzles = puzzles(3);
+        puzzles.push(Sudoku::new(2));
+        let results = score_all(puzzles.clone(), &BulkOptions::default());
+        assert_eq!(results.len(), 4);
+        for (puzzle, result) in puzzles.iter().zip(&results[..3]) {
+            assert_eq!(result.fingerprint, puzzle.fingerprint());
+            match &result.outcome {
+                BulkOutcome::Scored { score, difficulty } => {
+                    assert_eq!(Some(*score), puzzle.score());
+                    assert_eq!(Some(*difficulty), puzzle.difficulty());
+                }
+                other => panic!("unexpected outcome: {:?}", other),
+            }
+        }
+        assert!(matches!(
+            results[3].outcome,
+            BulkOutcome::Failed(SolveError::MultipleSolutions)
+        ));
+        // The budget applies to each puzzle.
+        let opts = BulkOptions {
+            node_budget: Some(1),
+            ..BulkOptions::default()
+        };
+        let results = score_all(puzzles.into_iter().take(1), &opts);
+        assert!(matches!(
+            results[0].outcome,
+            BulkOutcome::Failed(SolveError::Exhausted)
+        ));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_score_all_order() {
+        // Later puzzles finish first, yet the results keep the input order.
+        let puzzles = puzzles(12);
+        let opts = BulkOptions {
+            delay: |index| Duration::from_millis(5 * (12 - index as u64)),
+            ..BulkOptions::default()
+        };
+        let results = score_all(puzzles.clone(), &opts);
+        let fingerprints = puzzles.iter().map(Sudoku::fingerprint).collect::<Vec<_>>();
+        assert_eq!(
+            results.iter().map(|r| r.fingerprint).collect::<Vec<_>>(),
+            fingerprints
+        );
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_score_all_deadline() {
+        let opts = BulkOptions {
+            deadline: Some(Instant::now() + Duration::from_millis(50)),
+            delay: |_| Duration::from_millis(20),
+            ..BulkOptions::default()
+        };
+        let results = score_all(puzzles(40), &opts);
+        assert_eq!(results.len(), 40);
+        let skipped = results
+            .iter()
+            .filter(|r| matches!(r.outcome, BulkOutcome::Skipped))
+            .count();
+        assert!(skipped > 0);
+        for result in &results {
+            match &result.outcome {
+                BulkOutcome::Scored { .. } | BulkOutcome::Failed(SolveError::Timeout) => {}
+                BulkOutcome::Skipped => assert_eq!(result.elapsed, Duration::from_secs(0)),
+                other => panic!("unexpected outcome: {:?}", other),
+            }
+        }
+        // Once the deadline has passed, nothing is started.
+        let opts = BulkOptions {
+            deadline: Some(Instant::now()),
+            ..BulkOptions::default()
+        };
+        let results = score_all(puzzles(3), &opts);
+        assert!(results
+            .iter()
+            .all(|r| matches!(r.outcome, BulkOutcome::Skipped)));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_score_all_progress() {
+        let reports = Arc::new(Mutex::new(vec![]));
+        let log = Arc::clone(&reports);
+        let opts = BulkOptions {
+            progress: Some(Box::new(move |done, total| {
+                log.lock().unwrap().push((done, total))
+            })),
+            progress_interval: Duration::from_millis(20),
+            delay: |_| Duration::from_millis(10),
+            ..BulkOptions::default()
+        };
+        let start = Instant::now();
+        let results = score_all(puzzles(10), &opts);
+        let elapsed = start.elapsed();
+        assert_eq!(results.len(), 10);
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.last(), Some(&(10, 10)));
+        assert!(reports.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        // At most one report per interval, plus the last.
+        let intervals = elapsed.as_millis() / 20;
+        assert!(reports.len() as u128 <= intervals + 1);
+    }
+}
@@ -0,0 +1,605 @@
+//! A compact binary encoding of puzzles, for QR codes and URLs.
+//!
+//! # Layout
+//! The layout (version 1) is frozen: decoders of later versions will keep
+//! reading it. Bits are packed most significant first.
+//!
+//! | Bytes      | Contents                                                   |
+//! |------------|------------------------------------------------------------|
+//! | 1          | The version (1).                                           |
+//! | 1          | The order.                                                 |
+//! | 1          | Flags: the number of dimensions (bits 0–3), whether the boxes are rectangular (bit 4), and whether a given mask follows the cells (bit 5). Bits 6 and 7 are zero. |
+//! | 2, if rectangular | The box rows and columns.                           |
+//! | ⌈`w`·`n`/8⌉ | The `n` cells in [`points`](trait.Grid.html#tymethod.points) order, `w` bits each (zero for an empty cell), where `w` is the number of bits needed to write the axis. |
+//! | ⌈`n`/8⌉, if masked | One bit per cell, set for the givens.               |
+//!
+//! The padding bits at the end of each section are zero. A 9×9 puzzle takes
+//! 44 bytes (55 with a mask), against 82 for one byte per cell.
+//!
+//! The [base64](struct.Sudoku.html#method.to_base64) form encodes the same
+//! bytes with the URL-safe alphabet, without padding.
+use crate::dimensions::validate_order;
+use crate::sudoku::Shape;
+use crate::Element;
+use crate::Grid;
+use crate::LockedSudoku;
+use crate::Point;
+use crate::Sudoku;
+use crate::DIMENSIONS;
+
+use std::fmt;
+
+/// The version of the layout written by
+/// [`to_bytes`](struct.Sudoku.html#method.to_bytes).
+const VERSION: u8 = 1;
+
+const DIMENSIONS_MASK: u8 = 0b0000_1111;
+const RECT_FLAG: u8 = 0b0001_0000;
+const MASK_FLAG: u8 = 0b0010_0000;
+
+/// The URL-safe base64 alphabet (RFC 4648, section 5).
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encodes errors encountered while decoding a puzzle from bytes.
+///
+/// More variants may be added in future releases, so matches outside this
+/// crate need a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input is empty.
+    Empty,
+    /// The input was written with a layout version this release can't read.
+    UnsupportedVersion(u8),
+    /// The order lies outside
+    /// [`MIN_ORDER`](constant.MIN_ORDER.html)`..=`[`MAX_ORDER`](constant.MAX_ORDER.html).
+    UnsupportedOrder(u8),
+    /// The puzzle has a different number of dimensions than this build
+    /// supports.
+    ///
+    /// The associated value is the puzzle's number of dimensions.
+    WrongDimensions(u8),
+    /// The flags byte sets reserved bits.
+    ///
+    /// The associated value is the flags byte.
+    InvalidFlags(u8),
+    /// The box shape (rows, columns) isn't a supported rectangular shape, or
+    /// doesn't match the order.
+    InvalidBoxes(u8, u8),
+    /// The input's length doesn't match its header.
+    ///
+    /// The associated values are the expected and actual number of bytes.
+    WrongLength(usize, usize),
+    /// A cell holds a value too large for the puzzle's order.
+    ///
+    /// The associated values are the value and its location.
+    LargeValue(u8, Point),
+    /// The given mask marks an empty cell as a given.
+    EmptyGiven(Point),
+    /// A padding bit is set.
+    NonzeroPadding,
+    /// The base64 text holds a character outside the alphabet (or is a
+    /// length no encoding has).
+    ///
+    /// The associated value is the byte offset of the offending character
+    /// (or the length of the text).
+    InvalidBase64(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Empty => write!(f, "the input is empty"),
+            Error::UnsupportedVersion(version) => {
+                write!(f, "layout version {} is unsupported", version)
+            }
+            Error::UnsupportedOrder(order) => write!(f, "order {} is unsupported", order),
+            Error::WrongDimensions(dimensions) => write!(
+                f,
+                "the puzzle has {} dimensions, not {}",
+                dimensions, DIMENSIONS
+            ),
+            Error::InvalidFlags(flags) => write!(f, "invalid flags {:#010b}", flags),
+            Error::InvalidBoxes(rows, columns) => {
+                write!(f, "{}×{} boxes aren't supported", rows, columns)
+            }
+            Error::WrongLength(expected, actual) => {
+                write!(f, "expected {} bytes, found {}", expected, actual)
+            }
+            Error::LargeValue(value, point) => {
+                write!(f, "the value {} at {} is too large", value, point)
+            }
+            Error::EmptyGiven(point) => write!(f, "the given at {} is empty", point),
+            Error::NonzeroPadding => write!(f, "a padding bit is set"),
+            Error::InvalidBase64(offset) => write!(f, "invalid base64 at offset {}", offset),
+        }
+    }
+}
+
+impl Sudoku {
+    /// Encodes the puzzle in a compact binary form (see
+    /// [`from_bytes`](#method.from_bytes) for the inverse).
+    ///
+    /// # Layout
+    /// The version (1), the order, a flags byte recording the number of
+    /// dimensions and whether the boxes are rectangular (followed by their
+    /// rows and columns if so), then the cells bit-packed in
+    /// [`points`](trait.Grid.html#tymethod.points) order, each as wide as
+    /// the axis needs. This layout is frozen.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode(self, None)
+    }
+
+    /// Decodes a puzzle encoded by [`to_bytes`](#method.to_bytes) (or by
+    /// [`LockedSudoku::to_bytes`](struct.LockedSudoku.html#method.to_bytes),
+    /// whose given mask is checked, then dropped).
+    ///
+    /// Every part of the input is checked, so corrupted input fails rather
+    /// than decoding to a different puzzle (as far as the layout allows).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Sudoku, Error> {
+        decode(bytes).map(|(sudoku, _)| sudoku)
+    }
+
+    /// Encodes the puzzle as [bytes](#method.to_bytes), written in URL-safe
+    /// base64 without padding.
+    pub fn to_base64(&self) -> String {
+        to_base64(&self.to_bytes())
+    }
+
+    /// Decodes a puzzle encoded by [`to_base64`](#method.to_base64).
+    pub fn from_base64(text: &str) -> Result<Sudoku, Error> {
+        Sudoku::from_bytes(&from_base64(text)?)
+    }
+}
+
+impl LockedSudoku {
+    /// Encodes the puzzle like
+    /// [`Sudoku::to_bytes`](struct.Sudoku.html#method.to_bytes), followed by
+    /// a mask of its frozen cells.
+    ///
+    /// Frozen cells that are empty are left out of the mask.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let frozen = self
+            .points()
+            .into_iter()
+            .map(|point| self.is_frozen(point) && self[point].is_some())
+            .collect::<Vec<_>>();
+        encode(self, Some(&frozen))
+    }
+
+    /// Decodes a puzzle encoded by [`to_bytes`](#method.to_bytes), freezing
+    /// the cells in its mask (or, without a mask, every filled cell).
+    pub fn from_bytes(bytes: &[u8]) -> Result<LockedSudoku, Error> {
+        Ok(match decode(bytes)? {
+            (sudoku, Some(frozen)) => LockedSudoku::with_frozen(sudoku, frozen),
+            (sudoku, None) => LockedSudoku::new(sudoku),
+        })
+    }
+
+    /// Encodes the puzzle as [bytes](#method.to_bytes), written in URL-safe
+    /// base64 without padding.
+    pub fn to_base64(&self) -> String {
+        to_base64(&self.to_bytes())
+    }
+
+    /// Decodes a puzzle encoded by [`to_base64`](#method.to_base64).
+    pub fn from_base64(text: &str) -> Result<LockedSudoku, Error> {
+        LockedSudoku::from_bytes(&from_base64(text)?)
+    }
+}
+
+/// The number of bits a cell of a grid with the given axis takes.
+fn width(axis: u8) -> usize {
+    (8 - axis.leading_zeros()) as usize
+}
+
+fn encode(sudoku: &Sudoku, frozen: Option<&[bool]>) -> Vec<u8> {
+    let (rows, columns) = sudoku.box_shape();
+    let rect = rows != columns;
+    let mut flags = DIMENSIONS as u8;
+    if rect {
+        flags |= RECT_FLAG;
+    }
+    if frozen.is_some() {
+        flags |= MASK_FLAG;
+    }
+    let mut bytes = vec![VERSION, sudoku.order, flags];
+    if rect {
+        bytes.extend([rows, columns]);
+    }
+    let width = width(sudoku.axis());
+    let mut writer = BitWriter::new(bytes);
+    for element in &sudoku.elements {
+        writer.write(element.map_or(0, |e| e.0), width);
+    }
+    let mut bytes = writer.finish();
+    if let Some(frozen) = frozen {
+        let mut writer = BitWriter::new(bytes);
+        for given in frozen {
+            writer.write(u8::from(*given), 1);
+        }
+        bytes = writer.finish();
+    }
+    bytes
+}
+
+/// Decodes a puzzle, along with its given mask (if it has one).
+fn decode(bytes: &[u8]) -> Result<(Sudoku, Option<Vec<bool>>), Error> {
+    let version = *bytes.first().ok_or(Error::Empty)?;
+    if version != VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    let (order, flags) = match bytes {
+        [_, order, flags, ..] => (*order, *flags),
+        _ => return Err(Error::WrongLength(3, bytes.len())),
+    };
+    validate_order(order).map_err(|error| Error::UnsupportedOrder(error.0))?;
+    let dimensions = flags & DIMENSIONS_MASK;
+    if usize::from(dimensions) != DIMENSIONS {
+        return Err(Error::WrongDimensions(dimensions));
+    }
+    if flags & !(DIMENSIONS_MASK | RECT_FLAG | MASK_FLAG) != 0 {
+        return Err(Error::InvalidFlags(flags));
+    }
+    let (shape, header) = if flags & RECT_FLAG == 0 {
+        (Shape::square(order), 3)
+    } else {
+        let (rows, columns) = match bytes {
+            [_, _, _, rows, columns, ..] => (*rows, *columns),
+            _ => return Err(Error::WrongLength(5, bytes.len())),
+        };
+        match Shape::rect(rows, columns) {
+            Some(shape) if rows.min(columns) == order => (shape, 5),
+            _ => return Err(Error::InvalidBoxes(rows, columns)),
+        }
+    };
+    let axis = shape.axis();
+    let cells = shape.len();
+    let width = width(axis);
+    let masked = flags & MASK_FLAG != 0;
+    let cell_bytes = (cells * width).div_ceil(8);
+    let mask_bytes = if masked { cells.div_ceil(8) } else { 0 };
+    let expected = header + cell_bytes + mask_bytes;
+    if bytes.len() != expected {
+        return Err(Error::WrongLength(expected, bytes.len()));
+    }
+    let mut reader = BitReader::new(&bytes[header..header + cell_bytes]);
+    let mut elements = Vec::with_capacity(cells);
+    for index in 0..cells {
+        let value = reader.read(width);
+        if value > axis {
+            return Err(Error::LargeValue(value, shape.unfold(index)));
+        }
+        elements.push(if value == 0 {
+            None
+        } else {
+            Some(Element(value))
+        });
+    }
+    if !reader.rest_is_zero() {
+        return Err(Error::NonzeroPadding);
+    }
+    let frozen = if masked {
+        let mut reader = BitReader::new(&bytes[header + cell_bytes..]);
+        let mut frozen = Vec::with_capacity(cells);
+        for (index, element) in elements.iter().enumerate() {
+            let given = reader.read(1) == 1;
+            if given && element.is_none() {
+                return Err(Error::EmptyGiven(shape.unfold(index)));
+            }
+            frozen.push(given);
+        }
+        if !reader.rest_is_zero() {
+            return Err(Error::NonzeroPadding);
+        }
+        Some(frozen)
+    } else {
+        None
+    };
+    Ok((Sudoku::from_parts(shape, elements), frozen))
+}
+
+/// Appends values to bytes, a few bits at a time.
+struct BitWriter {
+    bytes: Vec<u8>,
+    // The number of bits of the last byte in use (8 when it's full).
+    used: usize,
+}
+
+impl BitWriter {
+    fn new(bytes: Vec<u8>) -> Self {
+        Self { bytes, used: 8 }
+    }
+
+    /// Writes the lowest `width` bits of the value, most significant first.
+    fn write(&mut self, value: u8, width: usize) {
+        for bit in (0..width).rev() {
+            if self.used == 8 {
+                self.bytes.push(0);
+                self.used = 0;
+            }
+            if value >> bit & 1 == 1 {
+                if let Some(last) = self.bytes.last_mut() {
+                    *last |= 0x80 >> self.used;
+                }
+            }
+            self.used += 1;
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reads values from bytes, a few bits at a time.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    // The index of the next bit.
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    /// Reads `width` bits, most significant first; bits past the end read as
+    /// zero.
+    fn read(&mut self, width: usize) -> u8 {
+        let mut value = 0;
+        for _ in 0..width {
+            let byte = self.bytes.get(self.position / 8).cloned().unwrap_or(0);
+            value = value << 1 | (byte >> (7 - self.position % 8) & 1);
+            self.position += 1;
+        }
+        value
+    }
+
+    /// Whether every bit not yet read is zero.
+    fn rest_is_zero(&mut self) -> bool {
+        let end = self.bytes.len() * 8;
+        (self.position..end).all(|_| self.read(1) == 0)
+    }
+}
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+            group | u32::from(*byte) << (16 - 8 * i)
+        });
+        // Each byte takes at least one character and spills into the next.
+        for i in 0..=chunk.len() {
+            text.push(char::from(BASE64[(group >> (18 - 6 * i) & 0x3f) as usize]));
+        }
+    }
+    text
+}
+
+fn from_base64(text: &str) -> Result<Vec<u8>, Error> {
+    let values = text
+        .bytes()
+        .enumerate()
+        .map(|(offset, c)| {
+            BASE64
+                .iter()
+                .position(|symbol| *symbol == c)
+                .map(|value| value as u32)
+                .ok_or(Error::InvalidBase64(offset))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // A lone trailing character can't hold a whole byte.
+    if values.len() % 4 == 1 {
+        return Err(Error::InvalidBase64(text.len()));
+    }
+    let mut bytes = Vec::with_capacity(values.len() * 3 / 4);
+    for (index, chunk) in values.chunks(4).enumerate() {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |group, (i, value)| group | value << (18 - 6 * i));
+        let len = chunk.len() - 1;
+        // The bits past the last whole byte must be zero, so each text
+        // decodes from exactly one encoding.
+        if group & (0xff_ffff >> (8 * len)) != 0 {
+            return Err(Error::InvalidBase64(4 * index + len));
+        }
+        bytes.extend((0..len).map(|i| (group >> (16 - 8 * i)) as u8));
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bytes::{from_base64, to_base64, Error};
+    use crate::random::{SeededSource, Source};
+    use crate::Element;
+    use crate::Grid;
+    use crate::LockedSudoku;
+    use crate::Point;
+    use crate::Sudoku;
+    use crate::MAX_ORDER;
+
+    /// A puzzle of the given order with a scattering of values.
+    fn scattered(mut sudoku: Sudoku) -> Sudoku {
+        let axis = usize::from(sudoku.axis());
+        for (i, point) in sudoku.points().into_iter().enumerate().step_by(3) {
+            sudoku.substitute(point, Some(Element((i % axis) as u8 + 1)));
+        }
+        sudoku
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        for order in 1..=MAX_ORDER.min(5) {
+            let sudoku = scattered(Sudoku::new(order));
+            let bytes = sudoku.to_bytes();
+            assert_eq!(&bytes[..2], &[1, order]);
+            assert_eq!(Sudoku::from_bytes(&bytes), Ok(sudoku.clone()));
+            assert_eq!(Sudoku::from_base64(&sudoku.to_base64()), Ok(sudoku));
+        }
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_bytes_layout() {
+        // The layout is frozen: this vector must never change.
+        let sudoku: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let bytes = sudoku.to_bytes();
+        assert_eq!(bytes.len(), 44);
+        assert_eq!(
+            bytes,
+            vec![
+                0x01, 0x03, 0x02, 0x50, 0x00, 0x93, 0x16, 0x00, 0x00, 0x00, 0x10, 0x00, 0x36, 0x08,
+                0x00, 0x00, 0x00, 0x00, 0x60, 0x09, 0x00, 0x68, 0x00, 0x70, 0x05, 0x20, 0x02, 0x00,
+                0x80, 0x00, 0x00, 0x00, 0x02, 0x09, 0x60, 0x00, 0x50, 0x00, 0x00, 0x04, 0x39, 0x10,
+                0x00, 0x80,
+            ]
+        );
+        assert_eq!(
+            sudoku.to_base64(),
+            "AQMCUACTFgAAABAANggAAAAAYAkAaABwBSACAIAAAAACCWAAUAAABDkQAIA"
+        );
+        let rect = scattered(Sudoku::new_rect(2, 3));
+        let bytes = rect.to_bytes();
+        assert_eq!(&bytes[..5], &[1, 2, 0b0001_0010, 2, 3]);
+        assert_eq!(Sudoku::from_bytes(&bytes), Ok(rect));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_bytes_given_mask() {
+        let mut locked = LockedSudoku::new(scattered(Sudoku::new(3)));
+        locked.set(Point([1, 0]), Some(Element(4))).unwrap();
+        let bytes = locked.to_bytes();
+        assert_eq!(bytes[2], 0b0010_0010);
+        assert_eq!(bytes.len(), 3 + 41 + 11);
+        let decoded = LockedSudoku::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, locked);
+        assert!(!decoded.is_frozen(Point([1, 0])));
+        assert_eq!(
+            LockedSudoku::from_base64(&locked.to_base64()),
+            Ok(locked.clone())
+        );
+        // The plain decoder drops the mask.
+        assert_eq!(Sudoku::from_bytes(&bytes).as_ref(), Ok(&*locked));
+        // Without a mask, the givens are the filled cells.
+        let plain = LockedSudoku::from_bytes(&locked.clone().into_inner().to_bytes()).unwrap();
+        assert!(plain.is_frozen(Point([1, 0])));
+        // A given on an empty cell.
+        let mut corrupt = bytes;
+        let mask = corrupt.len() - 11;
+        corrupt[mask] |= 0b0010_0000;
+        assert_eq!(
+            LockedSudoku::from_bytes(&corrupt),
+            Err(Error::EmptyGiven(Point([2, 0])))
+        );
+    }
+
+    #[test]
+    fn test_bytes_errors() {
+        let bytes = Sudoku::new(2).to_bytes();
+        let flags = bytes[2];
+        assert_eq!(Sudoku::from_bytes(&[]), Err(Error::Empty));
+        assert_eq!(Sudoku::from_bytes(&[2]), Err(Error::UnsupportedVersion(2)));
+        assert_eq!(Sudoku::from_bytes(&[1, 2]), Err(Error::WrongLength(3, 2)));
+        assert_eq!(
+            Sudoku::from_bytes(&[1, 0, flags]),
+            Err(Error::UnsupportedOrder(0))
+        );
+        assert_eq!(
+            Sudoku::from_bytes(&[1, MAX_ORDER + 1, flags]),
+            Err(Error::UnsupportedOrder(MAX_ORDER + 1))
+        );
+        assert_eq!(
+            Sudoku::from_bytes(&[1, 2, flags + 1]),
+            Err(Error::WrongDimensions(flags + 1))
+        );
+        assert_eq!(
+            Sudoku::from_bytes(&[1, 2, flags | 0x80]),
+            Err(Error::InvalidFlags(flags | 0x80))
+        );
+        assert_eq!(
+            Sudoku::from_bytes(&[1, 2, flags | 0x10, 3, 3]),
+            Err(Error::InvalidBoxes(3, 3))
+        );
+        let len = bytes.len();
+        assert_eq!(
+            Sudoku::from_bytes(&bytes[..len - 1]),
+            Err(Error::WrongLength(len, len - 1))
+        );
+        let mut long = bytes.clone();
+        long.push(0);
+        assert_eq!(
+            Sudoku::from_bytes(&long),
+            Err(Error::WrongLength(len, len + 1))
+        );
+        // Order 2 takes 3 bits a cell: the second cell holds 5.
+        let mut corrupt = bytes.clone();
+        corrupt[3] = 0b0001_0100;
+        assert_eq!(
+            Sudoku::from_bytes(&corrupt),
+            Err(Error::LargeValue(5, Point::unfold(1, 2)))
+        );
+        // Order 3 takes 4 bits a cell, leaving 4 bits of padding.
+        let mut padded = Sudoku::new(3).to_bytes();
+        let len = padded.len();
+        padded[len - 1] |= 1;
+        assert_eq!(Sudoku::from_bytes(&padded), Err(Error::NonzeroPadding));
+    }
+
+    #[test]
+    fn test_bytes_corruption() {
+        // Corrupted input fails (or decodes to some puzzle) without panicking.
+        let bytes = scattered(Sudoku::new(2)).to_bytes();
+        for len in 0..bytes.len() {
+            assert!(Sudoku::from_bytes(&bytes[..len]).is_err());
+        }
+        for index in 0..bytes.len() {
+            for bit in 0..8 {
+                let mut corrupt = bytes.clone();
+                corrupt[index] ^= 1 << bit;
+                let _ = Sudoku::from_bytes(&corrupt);
+                let _ = LockedSudoku::from_bytes(&corrupt);
+            }
+        }
+        let mut source = SeededSource::new(0);
+        for _ in 0..1000 {
+            let len = source.below(64);
+            let mut noise = (0..len)
+                .map(|_| source.next_u64() as u8)
+                .collect::<Vec<_>>();
+            if let Some(version) = noise.first_mut() {
+                *version = 1;
+            }
+            let _ = Sudoku::from_bytes(&noise);
+            let _ = LockedSudoku::from_bytes(&noise);
+        }
+    }
+
+    #[test]
+    fn test_base64() {
+        // The test vectors of RFC 4648, unpadded.
+        let vectors = [
+            ("", ""),
+            ("f", "Zg"),
+            ("fo", "Zm8"),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg"),
+            ("fooba", "Zm9vYmE"),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (bytes, text) in vectors.iter() {
+            assert_eq!(to_base64(bytes.as_bytes()), *text);
+            assert_eq!(from_base64(text), Ok(bytes.as_bytes().to_vec()));
+        }
+        assert_eq!(to_base64(&[0xfb, 0xff]), "-_8");
+        assert_eq!(from_base64("Zm9v!"), Err(Error::InvalidBase64(4)));
+        assert_eq!(from_base64("Zm9vY"), Err(Error::InvalidBase64(5)));
+        assert_eq!(from_base64("Zh"), Err(Error::InvalidBase64(1)));
+        assert_eq!(from_base64("Zm8="), Err(Error::InvalidBase64(3)));
+    }
+}
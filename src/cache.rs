@@ -0,0 +1,618 @@
+//! Memoization of uniqueness checks and scores.
+//!
+//! [`UniquenessCache`](struct.UniquenessCache.html) is for a single owner;
+//! [`SharedUniquenessCache`](struct.SharedUniquenessCache.html) and
+//! [`ScoredSudoku`](struct.ScoredSudoku.html) are `Send + Sync`, for sharing
+//! (e.g. behind an `Arc`) between threads.
+use crate::Difficulty;
+use crate::Score;
+use crate::Sudoku;
+use crate::DIMENSIONS;
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+    io::{BufRead, BufReader, Error as IoError, ErrorKind, Read, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock, PoisonError, RwLock,
+    },
+};
+
+/// The number of independently locked shards in a
+/// [`SharedUniquenessCache`](struct.SharedUniquenessCache.html).
+const SHARDS: usize = 16;
+
+/// A cache of puzzle scores, whether owned by one caller or shared.
+///
+/// Generic code can take any cache through this trait; a shared cache also
+/// implements it by reference, so each thread can pass along its own
+/// `&SharedUniquenessCache`.
+pub trait ScoreCache {
+    /// Returns the puzzle's score (`None` if it isn't uniquely solvable),
+    /// scoring it only if it isn't already cached.
+    fn score(&mut self, puzzle: &Sudoku) -> Option<usize>;
+
+    /// Whether the puzzle has a unique solution, checking it only if it isn't
+    /// already cached.
+    fn is_uniquely_solvable(&mut self, puzzle: &Sudoku) -> bool {
+        self.score(puzzle).is_some()
+    }
+}
+
+/// A bounded cache of puzzle scores (and therefore of whether puzzles are
+/// uniquely solvable), for callers that check many closely-related puzzles.
+///
+/// Entries are keyed by the puzzle's full contents (along with its order and
+/// the number of dimensions), so distinct puzzles never share an entry. Once
+/// the cache is full, the oldest entry is evicted to make room for each new
+/// one.
+#[derive(Clone, Debug)]
+pub struct UniquenessCache {
+    entries: HashMap<Vec<u8>, Option<usize>>,
+    // Keys in insertion order, for eviction.
+    queue: VecDeque<Vec<u8>>,
+    capacity: usize,
+    hits: usize,
+    misses: usize,
+}
+
+impl UniquenessCache {
+    /// The capacity of a cache created with [`new`](#method.new).
+    pub const DEFAULT_CAPACITY: usize = 1 << 16;
+
+    /// Creates an empty cache with the default capacity.
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    /// Creates an empty cache holding at most `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            queue: VecDeque::new(),
+            capacity,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the puzzle's score (`None` if it isn't uniquely solvable),
+    /// scoring it only if it isn't already cached.
+    pub fn score(&mut self, puzzle: &Sudoku) -> Option<usize> {
+        let key = fingerprint(puzzle);
+        if let Some(score) = self.entries.get(&key) {
+            self.hits += 1;
+            return *score;
+        }
+        self.misses += 1;
+        let score = puzzle.score();
+        self.insert(key, score);
+        score
+    }
+
+    /// Whether the puzzle has a unique solution, checking it only if it isn't
+    /// already cached.
+    pub fn is_uniquely_solvable(&mut self, puzzle: &Sudoku) -> bool {
+        self.score(puzzle).is_some()
+    }
+
+    /// Returns the number of lookups answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Returns the number of lookups that required a solve.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Returns the number of cached puzzles.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Writes the cached entries, one per line.
+    ///
+    /// The statistics and capacity aren't saved.
+    pub fn save<W: Write>(&self, mut writer: W) -> Result<(), IoError> {
+        for key in &self.queue {
+            let hex = key.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+            match self.entries[key] {
+                Some(score) => writeln!(writer, "{} {}", hex, score)?,
+                None => writeln!(writer, "{} -", hex)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads entries written by [`save`](#method.save) into a cache with the
+    /// default capacity.
+    pub fn load<R: Read>(reader: R) -> Result<Self, IoError> {
+        let invalid = || IoError::new(ErrorKind::InvalidData, "malformed cache entry");
+        let mut cache = Self::new();
+        for line in BufReader::new(reader).lines() {
+            let line = line?;
+            let mut parts = line.split(' ');
+            let (hex, score) = match (parts.next(), parts.next(), parts.next()) {
+                // Checking for ASCII keeps the byte slicing below on char
+                // boundaries.
+                (Some(hex), Some(score), None) if hex.is_ascii() && hex.len() % 2 == 0 => {
+                    (hex, score)
+                }
+                _ => return Err(invalid()),
+            };
+            let key = (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid())?;
+            let score = match score {
+                "-" => None,
+                score => Some(score.parse().map_err(|_| invalid())?),
+            };
+            cache.insert(key, score);
+        }
+        Ok(cache)
+    }
+
+    fn insert(&mut self, key: Vec<u8>, score: Option<usize>) {
+        if self.capacity == 0 {
+            return;
+        }
+        // Updating an entry that's already cached doesn't need room.
+        if let Some(entry) = self.entries.get_mut(&key) {
+            *entry = score;
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            if let Some(oldest) = self.queue.pop_front() {
+                let _ = self.entries.remove(&oldest);
+            }
+        }
+        let _ = self.entries.insert(key.clone(), score);
+        self.queue.push_back(key);
+    }
+}
+
+impl Default for UniquenessCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScoreCache for UniquenessCache {
+    fn score(&mut self, puzzle: &Sudoku) -> Option<usize> {
+        UniquenessCache::score(self, puzzle)
+    }
+}
+
+/// A bounded cache of puzzle scores that many threads can use at once.
+///
+/// Entries are spread over a fixed number of shards by their key, each behind
+/// its own read-write lock, and the statistics are kept in atomics. A hit
+/// only takes its shard's read lock, so concurrent hits never wait on one
+/// another; a miss scores the puzzle without holding any lock, then takes
+/// its shard's write lock briefly to insert the result, blocking only readers
+/// of that shard. Two threads missing on the same puzzle at once may both
+/// score it (the results are identical); use a
+/// [`ScoredSudoku`](struct.ScoredSudoku.html) to score a puzzle exactly once.
+///
+/// The capacity is split evenly among the shards, and each evicts its own
+/// oldest entry once full.
+#[derive(Debug)]
+pub struct SharedUniquenessCache {
+    shards: Vec<RwLock<UniquenessCache>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl SharedUniquenessCache {
+    /// Creates an empty cache with the
+    /// [default capacity](struct.UniquenessCache.html#associatedconstant.DEFAULT_CAPACITY).
+    pub fn new() -> Self {
+        Self::with_capacity(UniquenessCache::DEFAULT_CAPACITY)
+    }
+
+    /// Creates an empty cache holding at most `capacity` entries (rounded up
+    /// to a multiple of the number of shards).
+    pub fn with_capacity(capacity: usize) -> Self {
+        let per_shard = capacity.div_ceil(SHARDS);
+        Self {
+            shards: (0..SHARDS)
+                .map(|_| RwLock::new(UniquenessCache::with_capacity(per_shard)))
+                .collect(),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the puzzle's score (`None` if it isn't uniquely solvable),
+    /// scoring it only if it isn't already cached.
+    pub fn score(&self, puzzle: &Sudoku) -> Option<usize> {
+        let key = fingerprint(puzzle);
+        let shard = &self.shards[shard(&key)];
+        let cached = shard
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .get(&key)
+            .cloned();
+        if let Some(score) = cached {
+            let _ = self.hits.fetch_add(1, Ordering::Relaxed);
+            return score;
+        }
+        let _ = self.misses.fetch_add(1, Ordering::Relaxed);
+        let score = puzzle.score();
+        shard
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, score);
+        score
+    }
+
+    /// Whether the puzzle has a unique solution, checking it only if it isn't
+    /// already cached.
+    pub fn is_uniquely_solvable(&self, puzzle: &Sudoku) -> bool {
+        self.score(puzzle).is_some()
+    }
+
+    /// Returns the puzzle's graded difficulty (`None` if it isn't uniquely
+    /// solvable), scoring it only if it isn't already cached.
+    pub fn difficulty(&self, puzzle: &Sudoku) -> Option<Difficulty> {
+        self.score(puzzle)
+            .map(|score| Difficulty::from_score_with_boxes(score, puzzle.box_shape()))
+    }
+
+    /// Removes every entry (leaving the statistics alone).
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            let mut shard = shard.write().unwrap_or_else(PoisonError::into_inner);
+            shard.entries.clear();
+            shard.queue.clear();
+        }
+    }
+
+    /// Returns the number of lookups answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of lookups that required a solve.
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of cached puzzles.
+    ///
+    /// Other threads may change this as soon as it's returned.
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().unwrap_or_else(PoisonError::into_inner).len())
+            .sum()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for SharedUniquenessCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScoreCache for SharedUniquenessCache {
+    fn score(&mut self, puzzle: &Sudoku) -> Option<usize> {
+        SharedUniquenessCache::score(self, puzzle)
+    }
+}
+
+impl ScoreCache for &SharedUniquenessCache {
+    fn score(&mut self, puzzle: &Sudoku) -> Option<usize> {
+        SharedUniquenessCache::score(self, puzzle)
+    }
+}
+
+/// Picks the shard responsible for a key.
+fn shard(key: &[u8]) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % SHARDS as u64) as usize
+}
+
+/// A puzzle that scores itself the first time it's asked, and remembers the
+/// result.
+///
+/// The score is computed exactly once, however many threads ask for it: the
+/// first caller scores the puzzle while any others asking at the same time
+/// wait for it to finish, and every caller (then and later) sees the same
+/// score. Once scored, reads take no lock at all.
+#[derive(Clone, Debug)]
+pub struct ScoredSudoku {
+    puzzle: Sudoku,
+    score: OnceLock<Option<usize>>,
+    // The scoring function (replaceable in tests, to count calls).
+    scorer: fn(&Sudoku) -> Option<usize>,
+}
+
+impl ScoredSudoku {
+    /// Wraps the puzzle, without scoring it yet.
+    pub fn new(puzzle: Sudoku) -> Self {
+        Self {
+            puzzle,
+            score: OnceLock::new(),
+            scorer: <Sudoku as Score>::score,
+        }
+    }
+
+    /// Returns the puzzle.
+    pub fn puzzle(&self) -> &Sudoku {
+        &self.puzzle
+    }
+
+    /// Unwraps the puzzle.
+    pub fn into_inner(self) -> Sudoku {
+        self.puzzle
+    }
+
+    /// Returns the puzzle's score (`None` if it isn't uniquely solvable),
+    /// scoring it if this is the first call.
+    ///
+    /// See [`Score::score`](trait.Score.html#tymethod.score).
+    pub fn score(&self) -> Option<usize> {
+        *self.score.get_or_init(|| (self.scorer)(&self.puzzle))
+    }
+
+    /// Returns the puzzle's graded difficulty (`None` if it isn't uniquely
+    /// solvable), scoring it if this is the first call.
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        self.score()
+            .map(|score| Difficulty::from_score_with_boxes(score, self.puzzle.box_shape()))
+    }
+
+    /// Whether the puzzle has been scored yet.
+    pub fn is_scored(&self) -> bool {
+        self.score.get().is_some()
+    }
+}
+
+impl From<Sudoku> for ScoredSudoku {
+    fn from(puzzle: Sudoku) -> Self {
+        Self::new(puzzle)
+    }
+}
+
+// The shared types must stay shareable.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SharedUniquenessCache>();
+    assert_send_sync::<ScoredSudoku>();
+};
+
+impl Sudoku {
+    /// Scores the puzzle, going through the cache if one is given.
+    ///
+    /// See [`Score::score`](trait.Score.html#tymethod.score).
+    pub fn score_with_cache(&self, cache: Option<&mut UniquenessCache>) -> Option<usize> {
+        score(self, cache)
+    }
+
+    /// Whether the puzzle has a unique solution, going through the cache if
+    /// one is given.
+    pub fn is_uniquely_solvable_with_cache(&self, cache: Option<&mut UniquenessCache>) -> bool {
+        score(self, cache).is_some()
+    }
+}
+
+/// Scores the puzzle, going through the cache if there is one.
+pub(crate) fn score(puzzle: &Sudoku, cache: Option<&mut UniquenessCache>) -> Option<usize> {
+    match cache {
+        Some(cache) => cache.score(puzzle),
+        None => puzzle.score(),
+    }
+}
+
+/// Encodes everything that determines a puzzle's verdict: the number of
+/// dimensions, the box shape and the order, followed by one byte per cell
+/// (zero for empty cells) in [`points`](trait.Grid.html#tymethod.points)
+/// order.
+///
+/// This is the layout saved caches use, so it mustn't change.
+fn fingerprint(puzzle: &Sudoku) -> Vec<u8> {
+    let (rows, columns) = puzzle.box_shape();
+    let mut key = vec![DIMENSIONS as u8, rows, columns, puzzle.order];
+    key.extend(puzzle.elements.iter().map(|e| e.map_or(0, |e| e.0)));
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::{fingerprint, UniquenessCache};
+    #[cfg(feature = "2D")]
+    use crate::cache::{ScoreCache, ScoredSudoku, SharedUniquenessCache};
+    use crate::Element;
+    use crate::Grid;
+    #[cfg(feature = "2D")]
+    use crate::Score;
+    use crate::Sudoku;
+
+    #[cfg(feature = "2D")]
+    use std::{
+        io::ErrorKind,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            mpsc, Arc, Barrier,
+        },
+        thread,
+        time::Duration,
+    };
+
+    #[cfg(feature = "2D")]
+    const THREADS: usize = 16;
+
+    #[test]
+    fn test_fingerprint() {
+        let mut one = Sudoku::new(2);
+        let two = one.clone();
+        assert_eq!(fingerprint(&one), fingerprint(&two));
+        let point = one.points()[0];
+        one.substitute(point, Some(Element(1)));
+        assert_ne!(fingerprint(&one), fingerprint(&two));
+        assert_ne!(fingerprint(&Sudoku::new(1)), fingerprint(&Sudoku::new(2)));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_cache_score() {
+        let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let mut cache = UniquenessCache::new();
+        assert_eq!(cache.score(&puzzle), puzzle.score());
+        assert!(cache.is_uniquely_solvable(&puzzle));
+        assert!(!cache.is_uniquely_solvable(&Sudoku::new(3)));
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 2, 2));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_cache_eviction() {
+        let mut cache = UniquenessCache::with_capacity(2);
+        for order in 1..=3 {
+            let _ = cache.score(&Sudoku::new(order));
+        }
+        assert_eq!(cache.len(), 2);
+        // The oldest entry was evicted.
+        let _ = cache.score(&Sudoku::new(1));
+        assert_eq!(cache.hits(), 0);
+        let _ = cache.score(&Sudoku::new(3));
+        assert_eq!(cache.hits(), 1);
+    }
+
+    #[test]
+    fn test_cache_reinsert() {
+        let mut cache = UniquenessCache::with_capacity(2);
+        cache.insert(vec![1], Some(1));
+        cache.insert(vec![2], Some(2));
+        // Updating a cached entry doesn't evict the oldest one.
+        cache.insert(vec![2], None);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.queue.len(), 2);
+        assert_eq!(cache.entries.get(&vec![1]), Some(&Some(1)));
+        assert_eq!(cache.entries.get(&vec![2]), Some(&None));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_cache_save_load() {
+        let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let mut cache = UniquenessCache::new();
+        let _ = cache.score(&puzzle);
+        let _ = cache.score(&Sudoku::new(2));
+        let mut saved = vec![];
+        cache.save(&mut saved).unwrap();
+        let mut loaded = UniquenessCache::load(&saved[..]).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.score(&puzzle), puzzle.score());
+        assert_eq!(loaded.score(&Sudoku::new(2)), None);
+        assert_eq!(loaded.hits(), 2);
+        assert!(UniquenessCache::load(&b"0g 12\n"[..]).is_err());
+        assert!(UniquenessCache::load(&b"0203 x\n"[..]).is_err());
+        let error = UniquenessCache::load("a\u{e9}b 1\n".as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_scored_sudoku() {
+        static SOLVES: AtomicUsize = AtomicUsize::new(0);
+        fn counting(puzzle: &Sudoku) -> Option<usize> {
+            let _ = SOLVES.fetch_add(1, Ordering::SeqCst);
+            puzzle.score()
+        }
+        let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let expected = puzzle.score();
+        let mut scored = ScoredSudoku::new(puzzle);
+        scored.scorer = counting;
+        let scored = Arc::new(scored);
+        let barrier = Arc::new(Barrier::new(THREADS));
+        let threads = (0..THREADS)
+            .map(|_| {
+                let scored = Arc::clone(&scored);
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    let _ = barrier.wait();
+                    scored.score()
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), expected);
+        }
+        assert_eq!(SOLVES.load(Ordering::SeqCst), 1);
+        assert!(scored.is_scored());
+        assert_eq!(scored.difficulty(), scored.puzzle().difficulty());
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_shared_cache() {
+        let puzzles = (1..=3)
+            .map(Sudoku::new)
+            .chain(Some("1 2 3 4\n3 4 1 2\n2 _ 4 3\n4 3 _ 1".parse().unwrap()))
+            .collect::<Vec<Sudoku>>();
+        let expected = puzzles.iter().map(Score::score).collect::<Vec<_>>();
+        let cache = Arc::new(SharedUniquenessCache::with_capacity(2 * 16));
+        let puzzles = Arc::new(puzzles);
+        let (done, finished) = mpsc::channel();
+        for i in 0..THREADS {
+            let (cache, puzzles, expected, done) = (
+                Arc::clone(&cache),
+                Arc::clone(&puzzles),
+                expected.clone(),
+                done.clone(),
+            );
+            let _ = thread::spawn(move || {
+                for round in 0..50 {
+                    // Every fourth thread occasionally clears the cache.
+                    if i % 4 == 0 && round % 10 == 0 {
+                        cache.clear();
+                    }
+                    let j = (i + round) % puzzles.len();
+                    assert_eq!(cache.score(&puzzles[j]), expected[j]);
+                }
+                done.send(()).unwrap();
+            });
+        }
+        for _ in 0..THREADS {
+            finished
+                .recv_timeout(Duration::from_secs(60))
+                .expect("a thread deadlocked or panicked");
+        }
+        assert_eq!(cache.hits() + cache.misses(), THREADS * 50);
+        assert!(cache.hits() > 0);
+        assert!(cache.len() <= puzzles.len());
+        // The trait works through a shared reference, as through an owned
+        // cache.
+        fn lookup<C: ScoreCache>(mut cache: C, puzzle: &Sudoku) -> bool {
+            cache.is_uniquely_solvable(puzzle)
+        }
+        assert!(lookup(&*cache, &puzzles[3]));
+        assert!(!lookup(UniquenessCache::new(), &puzzles[1]));
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
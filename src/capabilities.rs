@@ -0,0 +1,138 @@
+//! Reporting of the crate's version and capabilities.
+//!
+//! This is meant for pipelines that record which solver produced a score, so
+//! that they can tell when stored results may be stale.
+use crate::sol::techniques;
+use crate::DIMENSIONS;
+use crate::MAX_ORDER;
+use crate::MIN_ORDER;
+
+use std::fmt;
+
+/// The version of the scoring algorithm.
+///
+/// This is bumped whenever the score of any puzzle may change, so scores
+/// recorded under one version may be compared with each other, but not with
+/// those recorded under another.
+pub const SCORING_VERSION: u32 = 1;
+
+/// A description of what this build of the crate can do.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Capabilities {
+    /// The crate version.
+    pub version: &'static str,
+    /// The number of dimensions puzzles have.
+    pub dimensions: usize,
+    /// The smallest supported order.
+    pub min_order: u8,
+    /// The largest supported order.
+    pub max_order: u8,
+    /// The enabled optional features.
+    pub features: Vec<&'static str>,
+    /// The solving techniques the solver uses.
+    pub techniques: Vec<&'static str>,
+    /// The [scoring version](constant.SCORING_VERSION.html).
+    pub scoring_version: u32,
+}
+
+/// Reports the capabilities of this build of the crate.
+pub fn capabilities() -> Capabilities {
+    let features = [
+        ("rayon", cfg!(feature = "rayon")),
+        ("ui", cfg!(feature = "ui")),
+        ("use_rand", cfg!(feature = "use_rand")),
+        ("use_stdweb", cfg!(feature = "use_stdweb")),
+    ];
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        dimensions: DIMENSIONS,
+        min_order: MIN_ORDER,
+        max_order: MAX_ORDER,
+        features: features
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| *name)
+            .collect(),
+        techniques: techniques(),
+        scoring_version: SCORING_VERSION,
+    }
+}
+
+/// Writes one `key: value` line per capability, with lists comma-separated.
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "version: {}", self.version)?;
+        writeln!(f, "dimensions: {}", self.dimensions)?;
+        writeln!(f, "min_order: {}", self.min_order)?;
+        writeln!(f, "max_order: {}", self.max_order)?;
+        writeln!(f, "features: {}", self.features.join(", "))?;
+        writeln!(f, "techniques: {}", self.techniques.join(", "))?;
+        writeln!(f, "scoring_version: {}", self.scoring_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::capabilities::{capabilities, SCORING_VERSION};
+    use crate::{DIMENSIONS, MAX_ORDER, MIN_ORDER};
+
+    #[test]
+    fn test_capabilities() {
+        let capabilities = capabilities();
+        assert_eq!(capabilities.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(capabilities.dimensions, DIMENSIONS);
+        assert_eq!(
+            (capabilities.min_order, capabilities.max_order),
+            (MIN_ORDER, MAX_ORDER)
+        );
+        assert_eq!(
+            capabilities.techniques,
+            ["naked-single", "hidden-single", "coloring", "backtracking"]
+        );
+        assert_eq!(
+            capabilities.features.contains(&"use_rand"),
+            cfg!(feature = "use_rand")
+        );
+        let text = capabilities.to_string();
+        assert_eq!(text.lines().count(), 7);
+        assert!(text.ends_with(&format!("scoring_version: {}\n", SCORING_VERSION)));
+    }
+
+    // If this fails, scores have changed: bump `SCORING_VERSION` and update
+    // the pinned scores.
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_scores_pinned() {
+        use crate::corpus;
+        use crate::FillMethod;
+        use crate::Grid;
+        use crate::Score;
+        use crate::Sudoku;
+
+        // The order-3 corpus (the order-4 fixture scores 20140, but takes too
+        // long to check routinely).
+        assert_eq!(SCORING_VERSION, 1);
+        let pinned = [
+            ("seeded-beginner-0", 50),
+            ("seeded-easy-1", 246),
+            ("seeded-intermediate-1", 253),
+            ("seeded-intermediate-2", 351),
+            ("2D-O3", 454),
+            ("seeded-difficult-1", 451),
+            ("seeded-advanced-0", 856),
+            ("seeded-advanced-3", 851),
+        ];
+        for (name, score) in pinned.iter() {
+            assert_eq!(corpus::get(name).unwrap().score(), Some(*score), "{}", name);
+        }
+        let mut puzzle = Sudoku::seed_full_grid(3, FillMethod::Shift).unwrap();
+        for point in puzzle.points().into_iter().step_by(4) {
+            puzzle.substitute(point, None);
+        }
+        assert_eq!(puzzle.score(), Some(21));
+        for point in puzzle.points().into_iter().skip(1).step_by(4) {
+            puzzle.substitute(point, None);
+        }
+        assert_eq!(puzzle.score(), Some(41));
+    }
+}
@@ -0,0 +1,249 @@
+//! Human-readable cell references (e.g. `r3c5`, `(4, 2)` or `E3`).
+use crate::Point;
+
+use std::fmt;
+
+/// A notation for cell references.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RefStyle {
+    /// `r3c5`: a 1-based row, then a 1-based column.
+    RowColumn,
+    /// `(4, 2)`: 0-based x and y coordinates, as written by `Point`'s
+    /// `Display` implementation.
+    Coordinates,
+    /// `E3`: a column letter (`A` for the first column, `Z` for the 26th,
+    /// then `AA` and so on), then a 1-based row.
+    Spreadsheet,
+}
+
+/// Encodes errors encountered while parsing a cell reference.
+///
+/// More variants may be added in future releases, so matches outside this
+/// crate need a wildcard arm.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The reference isn't written in any of the understood notations.
+    ///
+    /// The associated value is the whole reference.
+    Malformed(String),
+    /// A row or column number isn't a number.
+    ///
+    /// The associated value is the offending token.
+    InvalidNumber(String),
+    /// A row or column lies outside the grid.
+    ///
+    /// The associated values are the offending token and the number of rows
+    /// (or columns) in the grid.
+    OutOfRange(String, u8),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Malformed(reference) => write!(
+                f,
+                "\"{}\" isn't a cell reference (try r3c5, (4, 2) or E3)",
+                reference
+            ),
+            Error::InvalidNumber(token) => write!(f, "\"{}\" isn't a number", token),
+            Error::OutOfRange(token, axis) => {
+                write!(
+                    f,
+                    "\"{}\" is outside the grid (which is {} wide)",
+                    token, axis
+                )
+            }
+        }
+    }
+}
+
+impl Point {
+    /// Parses a cell reference in any of the [styles](enum.RefStyle.html),
+    /// checking that it lies within a grid of the given order.
+    ///
+    /// Letters may be of either case, and whitespace around the reference
+    /// (or, for coordinates, around each number) is ignored.
+    pub fn parse_ref(s: &str, order: u8) -> Result<Point, Error> {
+        Self::parse_ref_within(s, order.saturating_mul(order))
+    }
+
+    /// Parses a cell reference (like [`parse_ref`](#method.parse_ref)),
+    /// checking that it lies within a grid with the given axis length (so
+    /// grids with rectangular boxes are covered too).
+    pub(crate) fn parse_ref_within(s: &str, axis: u8) -> Result<Point, Error> {
+        let s = s.trim();
+        let malformed = || Error::Malformed(s.to_string());
+        if s.starts_with('(') && s.ends_with(')') && s.len() >= 2 {
+            let mut parts = s[1..s.len() - 1].split(',');
+            let (x, y) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(x), Some(y), None) => (x.trim(), y.trim()),
+                _ => return Err(malformed()),
+            };
+            let x = index(x, axis, 0)?;
+            let y = index(y, axis, 0)?;
+            return Ok(Point([x, y]));
+        }
+        let letters = s
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(s.len());
+        if letters == 0 || letters == s.len() {
+            return Err(malformed());
+        }
+        let (column, rest) = s.split_at(letters);
+        if column.eq_ignore_ascii_case("r") {
+            if let Some(c) = rest.find(['c', 'C']) {
+                let y = index(&rest[..c], axis, 1)?;
+                let x = index(&rest[c + 1..], axis, 1)?;
+                return Ok(Point([x, y]));
+            }
+        }
+        if !rest.chars().all(|c| c.is_ascii_digit()) {
+            return Err(malformed());
+        }
+        let x = column_index(column, axis)?;
+        let y = index(rest, axis, 1)?;
+        Ok(Point([x, y]))
+    }
+
+    /// Writes the point, which should lie within a grid of the given order,
+    /// as a cell reference in the given style.
+    ///
+    /// The reference round-trips through [`parse_ref`](#method.parse_ref).
+    pub fn to_ref(&self, style: RefStyle, order: u8) -> String {
+        self.to_ref_within(style, order.saturating_mul(order))
+    }
+
+    /// Writes the point as a cell reference (like
+    /// [`to_ref`](#method.to_ref)), for a grid with the given axis length.
+    pub(crate) fn to_ref_within(self, style: RefStyle, axis: u8) -> String {
+        debug_assert!(
+            self[0] < axis && self[1] < axis,
+            "{} is outside the grid",
+            self
+        );
+        let (x, y) = (usize::from(self[0]), usize::from(self[1]));
+        match style {
+            RefStyle::RowColumn => format!("r{}c{}", y + 1, x + 1),
+            RefStyle::Coordinates => self.to_string(),
+            RefStyle::Spreadsheet => format!("{}{}", column_name(x), y + 1),
+        }
+    }
+}
+
+// Parses a row or column number with the given base (0 or 1), checking that
+// it lies within the grid.
+fn index(token: &str, axis: u8, base: usize) -> Result<u8, Error> {
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_digit()) {
+        return Err(Error::InvalidNumber(token.to_string()));
+    }
+    match token.parse::<usize>() {
+        Ok(value) if value >= base && value - base < usize::from(axis) => Ok((value - base) as u8),
+        _ => Err(Error::OutOfRange(token.to_string(), axis)),
+    }
+}
+
+// Parses a spreadsheet-style column name (`A`–`Z`, then `AA` onward) made
+// up of ASCII letters.
+fn column_index(token: &str, axis: u8) -> Result<u8, Error> {
+    let mut value = 0usize;
+    for c in token.chars() {
+        let digit = usize::from(c.to_ascii_uppercase() as u8 - b'A') + 1;
+        value = value * 26 + digit;
+        if value > usize::from(axis) {
+            return Err(Error::OutOfRange(token.to_string(), axis));
+        }
+    }
+    Ok((value - 1) as u8)
+}
+
+// Names a 0-based column spreadsheet-style.
+fn column_name(index: usize) -> String {
+    let mut name = vec![];
+    let mut value = index + 1;
+    while value > 0 {
+        name.push(b'A' + ((value - 1) % 26) as u8);
+        value = (value - 1) / 26;
+    }
+    name.reverse();
+    String::from_utf8(name).expect("column names are ASCII")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cellref::{column_name, Error, RefStyle};
+    use crate::Point;
+
+    const STYLES: [RefStyle; 3] = [
+        RefStyle::RowColumn,
+        RefStyle::Coordinates,
+        RefStyle::Spreadsheet,
+    ];
+
+    #[test]
+    fn test_round_trip() {
+        for order in 3..=4 {
+            let axis = order * order;
+            for x in 0..axis {
+                for y in 0..axis {
+                    let point = Point([x, y]);
+                    for style in &STYLES {
+                        let reference = point.to_ref(*style, order);
+                        assert_eq!(Point::parse_ref(&reference, order), Ok(point));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_styles() {
+        let point = Point([4, 2]);
+        assert_eq!(point.to_ref(RefStyle::RowColumn, 3), "r3c5");
+        assert_eq!(point.to_ref(RefStyle::Coordinates, 3), "(4, 2)");
+        assert_eq!(point.to_ref(RefStyle::Spreadsheet, 3), "E3");
+        for reference in &["r3c5", "R3C5", " r3c5\n", "(4,2)", "( 4 , 2 )", "E3", "e3"] {
+            assert_eq!(Point::parse_ref(reference, 3), Ok(point), "{}", reference);
+        }
+        // Boundary cells.
+        assert_eq!(Point::parse_ref("r1c1", 3), Ok(Point([0, 0])));
+        assert_eq!(Point::parse_ref("I9", 3), Ok(Point([8, 8])));
+        assert_eq!(Point::parse_ref("P16", 4), Ok(Point([15, 15])));
+        assert_eq!(Point::parse_ref("(15, 0)", 4), Ok(Point([15, 0])));
+        assert_eq!(Point::parse_ref("r16c1", 4), Ok(Point([0, 15])));
+        // Columns past Z.
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(63), "BL");
+        assert_eq!(Point::parse_ref("BL64", 8), Ok(Point([63, 63])));
+    }
+
+    #[test]
+    fn test_errors() {
+        let out_of_range = |token: &str, axis| Err(Error::OutOfRange(token.to_string(), axis));
+        assert_eq!(Point::parse_ref("r0c5", 3), out_of_range("0", 9));
+        assert_eq!(Point::parse_ref("r10c5", 3), out_of_range("10", 9));
+        assert_eq!(Point::parse_ref("r3c17", 4), out_of_range("17", 16));
+        assert_eq!(Point::parse_ref("(9, 0)", 3), out_of_range("9", 9));
+        assert_eq!(Point::parse_ref("J1", 3), out_of_range("J", 9));
+        assert_eq!(Point::parse_ref("Q1", 4), out_of_range("Q", 16));
+        assert_eq!(Point::parse_ref("A0", 3), out_of_range("0", 9));
+        assert_eq!(Point::parse_ref("A10", 3), out_of_range("10", 9));
+        assert_eq!(Point::parse_ref("rxc5", 3), out_of_range("rxc", 9));
+        assert_eq!(
+            Point::parse_ref("r3c", 3),
+            Err(Error::InvalidNumber("".to_string()))
+        );
+        assert_eq!(
+            Point::parse_ref("(a, 2)", 3),
+            Err(Error::InvalidNumber("a".to_string()))
+        );
+        for reference in &["", "E", "5", "(1, 2, 3)", "E5x", "É5", "r3c5c6"] {
+            match Point::parse_ref(reference, 3) {
+                Err(Error::Malformed(_)) | Err(Error::InvalidNumber(_)) => {}
+                other => panic!("unexpected result for {}: {:?}", reference, other),
+            }
+        }
+    }
+}
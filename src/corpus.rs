@@ -0,0 +1,275 @@
+//! A curated set of puzzles for benchmarks and tests, so that they share one
+//! stable set rather than each keeping its own literals.
+//!
+//! Every puzzle is either one of the crate's fixtures or was generated by
+//! the crate itself (from the seed given in its source, then frozen here as
+//! text, so later changes to the generator don't move the corpus). The
+//! fixtures are copied in as [tables](../macro.sudoku_from_values.html),
+//! checked when the crate is built; none
+//! carries third-party licensing terms. Each puzzle is tagged with its order
+//! (`order-3` or `order-4`) and its tier (`easy`, `medium` or `hard`), and
+//! its tier is checked against the grader (see [`tier`](fn.tier.html)).
+//!
+//! This module is only built for tests and with the `bench-corpus` feature.
+use crate::sudoku_from_values;
+use crate::Difficulty;
+use crate::Sudoku;
+
+/// A puzzle in the corpus.
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+    /// A name unique within the corpus.
+    pub name: &'static str,
+    /// The entry's tags (its order and tier).
+    pub tags: &'static [&'static str],
+    /// Where the puzzle came from.
+    pub source: &'static str,
+    // How the puzzle is written down.
+    text: Text,
+}
+
+/// How a corpus puzzle is written down.
+#[derive(Clone, Copy, Debug)]
+enum Text {
+    /// On one line, with `.` for empty cells.
+    Line(&'static str),
+    /// As a table of values, built by the function.
+    Table(fn() -> Sudoku),
+}
+
+impl Entry {
+    /// Returns the entry's puzzle.
+    pub fn puzzle(&self) -> Sudoku {
+        match self.text {
+            Text::Line(line) => Sudoku::from_ascii(line.as_bytes()).expect("corpus lines parse"),
+            Text::Table(table) => table(),
+        }
+    }
+
+    /// Whether the entry has the given tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(&tag)
+    }
+}
+
+/// Every entry of the corpus, easiest first within each order.
+pub const ENTRIES: &[Entry] = &[
+    Entry {
+        name: "seeded-beginner-0",
+        tags: &["order-3", "easy"],
+        source: "Sudoku::generate_seeded(3, Difficulty::Beginner, 0)",
+        text: Text::Line(
+            "..4.3....6....5.....8.74.9158.64.9..9.13...5..42..13...9.4..87.8.6...1......89...",
+        ),
+    },
+    Entry {
+        name: "seeded-easy-1",
+        tags: &["order-3", "easy"],
+        source: "Sudoku::generate_seeded(3, Difficulty::Easy, 1)",
+        text: Text::Line(
+            "...127..91..8..2...8..49.3.......19.6....2..593.7.1642....8597..9..14..8825....1.",
+        ),
+    },
+    Entry {
+        name: "seeded-intermediate-1",
+        tags: &["order-3", "medium"],
+        source: "Sudoku::generate_seeded(3, Difficulty::Intermediate, 1)",
+        text: Text::Line(
+            "...1.7..91..8..2...8..49.3.......1..6....2..5.3.7.16.2....85.7..9..14...8.5....1.",
+        ),
+    },
+    Entry {
+        name: "seeded-intermediate-2",
+        tags: &["order-3", "medium"],
+        source: "Sudoku::generate_seeded(3, Difficulty::Intermediate, 2)",
+        text: Text::Line(
+            ".....37.14.78123........82....1.......3.84...912.....32.6..8.4...9.2..6....4.59.2",
+        ),
+    },
+    Entry {
+        name: "2D-O3",
+        tags: &["order-3", "hard"],
+        source: "tests/sudokus/solvable/2D-O3.txt",
+        text: Text::Table(fixture_o3),
+    },
+    Entry {
+        name: "seeded-difficult-1",
+        tags: &["order-3", "hard"],
+        source: "Sudoku::generate_seeded(3, Difficulty::Difficult, 1)",
+        text: Text::Line(
+            "...127..91..8..2...8..49.3.......1..6....2..5.3.7.1..2....8597..9..14...825....1.",
+        ),
+    },
+    Entry {
+        name: "seeded-advanced-0",
+        tags: &["order-3", "hard"],
+        source: "Sudoku::generate_seeded(3, Difficulty::Advanced, 0)",
+        text: Text::Line(
+            "....3....6....5.....8.74.915..64....9..3......42..1....9.4..87.8.6...1......89...",
+        ),
+    },
+    Entry {
+        name: "seeded-advanced-3",
+        tags: &["order-3", "hard"],
+        source: "Sudoku::generate_seeded(3, Difficulty::Advanced, 3)",
+        text: Text::Line(
+            "..94...........1.57.1.3.6..8.4.9.76..9...48...653.849..58.4.....4.....2.1....6..4",
+        ),
+    },
+    Entry {
+        name: "2D-O4",
+        tags: &["order-4", "hard"],
+        source: "tests/sudokus/solvable/2D-O4.txt",
+        text: Text::Table(fixture_o4),
+    },
+];
+
+// The fixture in `tests/sudokus/solvable/2D-O3.txt`.
+fn fixture_o3() -> Sudoku {
+    #[rustfmt::skip]
+    let puzzle = sudoku_from_values![
+        5, 0, 0, 0, 9, 3, 1, 6, 0,
+        0, 0, 0, 0, 0, 1, 0, 0, 0,
+        3, 6, 0, 8, 0, 0, 0, 0, 0,
+        0, 0, 0, 6, 0, 0, 9, 0, 0,
+        6, 8, 0, 0, 7, 0, 0, 5, 2,
+        0, 0, 2, 0, 0, 8, 0, 0, 0,
+        0, 0, 0, 0, 0, 2, 0, 9, 6,
+        0, 0, 0, 5, 0, 0, 0, 0, 0,
+        0, 4, 3, 9, 1, 0, 0, 0, 8,
+    ];
+    puzzle
+}
+
+// The fixture in `tests/sudokus/solvable/2D-O4.txt`.
+fn fixture_o4() -> Sudoku {
+    #[rustfmt::skip]
+    let puzzle = sudoku_from_values![
+         0, 10,  0,  0,  0, 14,  5, 12,  0,  0,  9,  0,  1,  0,  0,  0,
+         9,  0,  4,  0,  0,  0, 16,  0,  0,  5,  0, 10,  0,  3, 14,  0,
+        14, 16,  0,  0,  0,  0,  0,  0,  7,  0,  0,  0,  6,  8,  0,  0,
+        11,  3,  1,  0,  0,  6,  0,  0,  0,  0,  8,  0,  0,  0,  9,  0,
+         8,  2, 11, 16,  0,  0,  1,  0, 14,  4,  0,  0, 15, 13,  0,  0,
+         0,  0,  0,  3,  0,  0,  8,  0,  2, 11,  6,  0,  0,  9,  0,  7,
+         0,  6, 14,  1, 12,  3,  0,  0,  8,  0,  5,  0,  2,  4, 10,  0,
+         0,  0,  7,  0,  2, 16,  0,  6, 13,  0, 10,  0,  0,  0,  3,  5,
+        16, 15,  0,  0,  0, 12,  0,  5,  6,  0, 13, 14,  0,  7,  0,  0,
+         0,  7, 13, 14,  0,  1,  0, 16,  0,  0,  3, 12,  5, 15,  6,  0,
+        12,  0,  8,  0,  0,  7,  6, 10,  0,  9,  0,  0,  3,  0,  0,  0,
+         0,  0,  3, 10,  0,  0, 15,  4,  0,  7,  0,  0,  9, 12, 13, 11,
+         0, 11,  0,  0,  0,  9,  0,  0,  0,  0, 12,  0,  0,  1,  8, 14,
+         0,  0,  6, 15,  0,  0,  0, 14,  0,  0,  0,  0,  0,  0,  7,  9,
+         0, 14, 12,  0,  1,  0, 11,  0,  0, 10,  0,  0,  0,  6,  0, 13,
+         0,  0,  0,  9,  0, 13,  0,  0, 15, 16, 14,  0,  0,  0,  5,  0,
+    ];
+    puzzle
+}
+
+/// Returns the difficulty the puzzles of a tier are graded at (within one
+/// band), if the tag names a tier.
+pub fn tier(tag: &str) -> Option<Difficulty> {
+    match tag {
+        "easy" => Some(Difficulty::Easy),
+        "medium" => Some(Difficulty::Intermediate),
+        "hard" => Some(Difficulty::Advanced),
+        _ => None,
+    }
+}
+
+/// Returns every puzzle in the corpus.
+pub fn all() -> Vec<Sudoku> {
+    ENTRIES.iter().map(Entry::puzzle).collect()
+}
+
+/// Returns the puzzles with the given tag.
+pub fn by_tag(tag: &str) -> Vec<Sudoku> {
+    ENTRIES
+        .iter()
+        .filter(|entry| entry.has_tag(tag))
+        .map(Entry::puzzle)
+        .collect()
+}
+
+/// Returns the puzzle with the given name, if there is one.
+pub fn get(name: &str) -> Option<Sudoku> {
+    ENTRIES
+        .iter()
+        .find(|entry| entry.name == name)
+        .map(Entry::puzzle)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::corpus::{all, by_tag, get, tier, Entry, ENTRIES};
+    use crate::Score;
+    use crate::Solve;
+    use crate::Sudoku;
+
+    fn verify(entry: &Entry) {
+        let puzzle = entry.puzzle();
+        assert!(puzzle.is_uniquely_solvable(), "{}", entry.name);
+        let graded = puzzle.difficulty().unwrap().stars();
+        let tiers = entry
+            .tags
+            .iter()
+            .filter_map(|tag| tier(tag))
+            .collect::<Vec<_>>();
+        assert_eq!(tiers.len(), 1, "{} has one tier", entry.name);
+        let tagged = tiers[0].stars();
+        assert!(
+            graded.max(tagged) - graded.min(tagged) <= 1,
+            "{} is graded {} stars, but tagged {}",
+            entry.name,
+            graded,
+            tagged
+        );
+    }
+
+    #[test]
+    fn test_corpus_order_3() {
+        for entry in ENTRIES.iter().filter(|entry| entry.has_tag("order-3")) {
+            assert_eq!(entry.puzzle().order, 3);
+            verify(entry);
+        }
+    }
+
+    // Slow in debug builds; run with `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_corpus_order_4() {
+        for entry in ENTRIES.iter().filter(|entry| entry.has_tag("order-4")) {
+            assert_eq!(entry.puzzle().order, 4);
+            verify(entry);
+        }
+    }
+
+    #[test]
+    fn test_corpus_tables() {
+        // The tables are copies of the fixture files; they mustn't drift.
+        let fixtures = [
+            ("2D-O3", include_str!("../tests/sudokus/solvable/2D-O3.txt")),
+            ("2D-O4", include_str!("../tests/sudokus/solvable/2D-O4.txt")),
+        ];
+        for (name, text) in fixtures.iter() {
+            assert_eq!(get(name), Some(text.parse::<Sudoku>().unwrap()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_corpus_lookup() {
+        assert_eq!(all().len(), ENTRIES.len());
+        let names = ENTRIES.iter().map(|entry| entry.name).collect::<Vec<_>>();
+        let mut unique = names.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), names.len());
+        assert_eq!(by_tag("order-4").len(), 1);
+        assert_eq!(
+            by_tag("easy").len() + by_tag("medium").len() + by_tag("hard").len(),
+            ENTRIES.len()
+        );
+        assert!(by_tag("impossible").is_empty());
+        assert_eq!(get("2D-O3").map(|p| p.order), Some(3));
+        assert_eq!(get("missing"), None);
+    }
+}
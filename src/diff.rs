@@ -0,0 +1,212 @@
+//! Side-by-side comparison of two grids, rendered as a single grid.
+use crate::Element;
+use crate::Point;
+use crate::Sudoku;
+
+use std::fmt;
+
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Controls how [`Sudoku::render_diff`](struct.Sudoku.html#method.render_diff)
+/// marks the cells that differ.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffRenderOptions {
+    /// Prefixed to values filled in only in the left-hand grid.
+    pub only_self: String,
+    /// Prefixed to values filled in only in the right-hand grid.
+    pub only_other: String,
+    /// Whether to color the output with ANSI escape codes: matching cells are
+    /// dimmed, and differing cells are colored.
+    pub ansi: bool,
+}
+
+impl Default for DiffRenderOptions {
+    fn default() -> Self {
+        Self {
+            only_self: "-".to_string(),
+            only_other: "+".to_string(),
+            ansi: false,
+        }
+    }
+}
+
+/// Encodes errors encountered while comparing two grids.
+///
+/// More variants may be added in future releases, so matches outside this
+/// crate need a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The grids have different orders (left, right).
+    OrderMismatch(u8, u8),
+    /// The grids have the same order, but different box shapes.
+    ShapeMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::OrderMismatch(left, right) => write!(
+                f,
+                "can't compare a grid of order {} with one of order {}",
+                left, right
+            ),
+            Error::ShapeMismatch => write!(f, "the grids have different box shapes"),
+        }
+    }
+}
+
+impl Sudoku {
+    /// Renders this grid and another as a single grid, marking the cells
+    /// where they differ, followed by a line counting those cells.
+    ///
+    /// Cells that match are shown as they are (dimmed, with ANSI enabled),
+    /// and cells filled in only one grid are prefixed with the corresponding
+    /// marker from `opts`. Cells with different values are shown as `a→b`,
+    /// where `a` is this grid's value.
+    ///
+    /// Fails without rendering anything if the grids don't have the same
+    /// order and box shape.
+    pub fn render_diff(&self, other: &Sudoku, opts: &DiffRenderOptions) -> Result<String, Error> {
+        if self.order != other.order {
+            return Err(Error::OrderMismatch(self.order, other.order));
+        }
+        if self.box_shape() != other.box_shape() {
+            return Err(Error::ShapeMismatch);
+        }
+        let axis = self.axis();
+        let mut differ = 0;
+        let cells = (0..axis)
+            .flat_map(|y| (0..axis).map(move |x| Point([x, y])))
+            .map(|point| {
+                let cell = Cell::new(self[point], other[point], opts);
+                if cell.color.is_some() {
+                    differ += 1;
+                }
+                cell
+            })
+            .collect::<Vec<_>>();
+        let width = cells
+            .iter()
+            .map(|cell| cell.text.chars().count())
+            .max()
+            .unwrap_or(1);
+        let mut rendered = String::new();
+        for row in cells.chunks(axis as usize) {
+            let line = row
+                .iter()
+                .map(|cell| {
+                    let padded = format!("{:<width$}", cell.text, width = width);
+                    match (opts.ansi, cell.color) {
+                        (false, _) => padded,
+                        (true, Some(color)) => format!("{}{}{}", color, padded, RESET),
+                        (true, None) => format!("{}{}{}", DIM, padded, RESET),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            rendered.push_str(line.trim_end());
+            rendered.push('\n');
+        }
+        if differ == 1 {
+            rendered.push_str("1 cell differs\n");
+        } else {
+            rendered.push_str(&format!("{} cells differ\n", differ));
+        }
+        Ok(rendered)
+    }
+}
+
+struct Cell {
+    text: String,
+    color: Option<&'static str>,
+}
+
+impl Cell {
+    fn new(left: Option<Element>, right: Option<Element>, opts: &DiffRenderOptions) -> Self {
+        let (text, color) = match (left, right) {
+            (None, None) => ("_".to_string(), None),
+            (Some(a), Some(b)) if a == b => (a.to_string(), None),
+            (Some(a), Some(b)) => (format!("{}→{}", a, b), Some(YELLOW)),
+            (Some(a), None) => (format!("{}{}", opts.only_self, a), Some(RED)),
+            (None, Some(b)) => (format!("{}{}", opts.only_other, b), Some(GREEN)),
+        };
+        Self { text, color }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "2D")]
+    use crate::diff::{DiffRenderOptions, Error, DIM, RESET};
+    #[cfg(feature = "2D")]
+    use crate::Sudoku;
+
+    const LEFT: &str = "1 2 3 4\n\
+                        3 4 1 2\n\
+                        2 _ 4 3\n\
+                        4 3 2 1";
+    const RIGHT: &str = "1 2 3 4\n\
+                         3 4 2 2\n\
+                         2 1 4 3\n\
+                         _ 3 2 1";
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_render_diff() {
+        let left: Sudoku = LEFT.parse().unwrap();
+        let right: Sudoku = RIGHT.parse().unwrap();
+        let rendered = left
+            .render_diff(&right, &DiffRenderOptions::default())
+            .unwrap();
+        assert_eq!(
+            rendered,
+            "1   2   3   4\n\
+             3   4   1→2 2\n\
+             2   +1  4   3\n\
+             -4  3   2   1\n\
+             3 cells differ\n"
+        );
+        let markers = DiffRenderOptions {
+            only_self: "<".to_string(),
+            only_other: ">".to_string(),
+            ansi: false,
+        };
+        let rendered = left.render_diff(&right, &markers).unwrap();
+        assert!(rendered.contains(">1"));
+        assert!(rendered.contains("<4"));
+        let same = left
+            .render_diff(&left, &DiffRenderOptions::default())
+            .unwrap();
+        assert!(same.ends_with("0 cells differ\n"));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_render_diff_ansi() {
+        let left: Sudoku = LEFT.parse().unwrap();
+        let right: Sudoku = RIGHT.parse().unwrap();
+        let opts = DiffRenderOptions {
+            ansi: true,
+            ..DiffRenderOptions::default()
+        };
+        let rendered = left.render_diff(&right, &opts).unwrap();
+        assert!(rendered.contains(DIM));
+        assert!(rendered.contains(RESET));
+        assert!(rendered.ends_with("3 cells differ\n"));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_render_diff_mismatch() {
+        let opts = DiffRenderOptions::default();
+        assert_eq!(
+            Sudoku::new(2).render_diff(&Sudoku::new(3), &opts),
+            Err(Error::OrderMismatch(2, 3))
+        );
+    }
+}
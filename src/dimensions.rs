@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[cfg(any(
     all(
         feature = "2D",
@@ -239,3 +241,107 @@ pub const DIMENSIONS: usize = 11;
 /// Some features are missing for higher-dimension sudokus.
 #[cfg(feature = "12D")]
 pub const DIMENSIONS: usize = 12;
+
+/// The smallest sudoku order supported.
+///
+/// An order-1 sudoku is a single cell, which is no puzzle at all (it can't be
+/// generated at any playable difficulty), but every constructor accepts it so
+/// that code generic over the order needn't treat it specially.
+pub const MIN_ORDER: u8 = 1;
+
+/// The largest sudoku order supported by the compiled dimensionality.
+///
+/// Orders are limited to 8 by the width of the solver's candidate sets, and
+/// further (for high dimensionalities) so that every point in the grid can be
+/// [folded](struct.Point.html#method.fold) into a `usize` index without
+/// overflow.
+///
+/// | Dimensions | Maximum order (64-bit) |
+/// |------------|------------------------|
+/// | 2–10       | 8                      |
+/// | 11         | 7                      |
+/// | 12         | 6                      |
+///
+/// Everything that depends on the order (the scoring constant `C`, the
+/// difficulty bands, time-attack budgets, digit palettes) is computed rather
+/// than tabulated, so it's defined for every supported order. The exceptions
+/// are the textual formats, which run out of symbols: single-character cells
+/// go up to 35 (order 5), and an [`Alphabet`](struct.Alphabet.html) only
+/// covers as many values as it has symbols.
+pub const MAX_ORDER: u8 = max_order();
+
+/// Represents an order outside
+/// [`MIN_ORDER`](constant.MIN_ORDER.html)`..=`[`MAX_ORDER`](constant.MAX_ORDER.html).
+///
+/// The associated value is the unsupported order.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OrderError(pub u8);
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "order {} is unsupported (orders {} to {} are supported for {} dimensions)",
+            self.0, MIN_ORDER, MAX_ORDER, DIMENSIONS
+        )
+    }
+}
+
+/// Checks that the order is supported by the compiled dimensionality.
+///
+/// Every constructor and parser goes through this, so that they agree on
+/// which orders are supported.
+pub(crate) fn validate_order(order: u8) -> Result<(), OrderError> {
+    if (MIN_ORDER..=MAX_ORDER).contains(&order) {
+        Ok(())
+    } else {
+        Err(OrderError(order))
+    }
+}
+
+/// Finds the largest order (at most 8) for which `(order²)^DIMENSIONS` fits in
+/// a `usize`.
+const fn max_order() -> u8 {
+    let mut order = 8;
+    while order > 1 {
+        let axis = (order as usize) * (order as usize);
+        let mut total: usize = 1;
+        let mut fits = true;
+        let mut i = 0;
+        while i < DIMENSIONS {
+            match total.checked_mul(axis) {
+                Some(product) => total = product,
+                None => {
+                    fits = false;
+                    break;
+                }
+            }
+            i += 1;
+        }
+        if fits {
+            return order;
+        }
+        order -= 1;
+    }
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dimensions::{validate_order, OrderError};
+    use crate::{MAX_ORDER, MIN_ORDER};
+
+    #[test]
+    fn test_validate_order() {
+        assert_eq!(
+            validate_order(MIN_ORDER - 1),
+            Err(OrderError(MIN_ORDER - 1))
+        );
+        assert_eq!(validate_order(MIN_ORDER), Ok(()));
+        assert_eq!(validate_order(MAX_ORDER), Ok(()));
+        assert_eq!(
+            validate_order(MAX_ORDER + 1),
+            Err(OrderError(MAX_ORDER + 1))
+        );
+    }
+}
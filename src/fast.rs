@@ -0,0 +1,153 @@
+//! Puzzles cut from precomputed templates, for generating without searching.
+use crate::cache::{self, UniquenessCache};
+use crate::dimensions::validate_order;
+use crate::Difficulty;
+use crate::Element;
+use crate::GenerateError;
+use crate::Generated;
+use crate::Sudoku;
+
+/// A complete grid, along with the clues to keep for each difficulty.
+struct Template {
+    order: u8,
+    // The grid's values, in reading order.
+    grid: &'static str,
+    // For each playable difficulty, from easiest to hardest, a `1` for each
+    // clue kept and a `0` for each clue removed (in reading order).
+    masks: &'static [&'static str],
+}
+
+/// The templates, found by hardening the grid filled from seed 0 toward
+/// each difficulty (from seed 0).
+///
+/// No order-2 puzzle is advanced, so order 2 has one mask fewer.
+const TEMPLATES: [Template; 2] = [
+    Template {
+        order: 2,
+        grid: "3214142323414132",
+        masks: &[
+            "0101110111111110",
+            "0101000110011110",
+            "0001000110011100",
+            "0000000110011000",
+        ],
+    },
+    Template {
+        order: 3,
+        grid: "214936785679815234358274691583647912961328457742591368195462873826753149437189526",
+        masks: &[
+            "000000011010001110000010111100010000010010001010101100111011000001001010001110010",
+            "000000011010001110000010111100010000010010001010101100111011000001001010001110000",
+            "000000011010001110000000101100010000010010001010101100111011000001001010001110000",
+            "000000011010000100000000101100010000010010001010101100111011000001001010001110000",
+            "000000011010001110000000101100010000010010001010101100110011000001001010001010000",
+        ],
+    },
+];
+
+/// Cuts the puzzle for the difficulty from the order's template, checking
+/// (once, going through the cache if there is one) that it's unique.
+pub(crate) fn generate_fast_with(
+    order: u8,
+    difficulty: Difficulty,
+    cache: Option<&mut UniquenessCache>,
+) -> Result<Generated, GenerateError> {
+    validate_order(order)?;
+    let template = TEMPLATES
+        .iter()
+        .find(|t| t.order == order)
+        .ok_or(GenerateError::NoTemplate(order))?;
+    let index = Difficulty::ALL
+        .iter()
+        .skip(1)
+        .position(|d| *d == difficulty)
+        // Unplayable requests are treated as beginner requests.
+        .unwrap_or(0)
+        .min(template.masks.len() - 1);
+    let mut solution = Sudoku::new(order);
+    let mut puzzle = Sudoku::new(order);
+    let cells = template.grid.chars().zip(template.masks[index].chars());
+    for (i, (value, keep)) in cells.enumerate() {
+        let value = value.to_digit(10).map(|v| Element(v as u8));
+        solution.elements[i] = value;
+        if keep == '1' {
+            puzzle.elements[i] = value;
+        }
+    }
+    let score = cache::score(&puzzle, cache).ok_or(GenerateError::Unsolvable)?;
+    Ok(Generated {
+        puzzle,
+        solution,
+        difficulty: Difficulty::from_score(score, order),
+        seed: None,
+    })
+}
+
+impl Sudoku {
+    /// Generates a puzzle of the desired order and difficulty without
+    /// searching, for when speed matters more than variety (e.g. on slow
+    /// devices).
+    ///
+    /// The puzzle is cut from a precomputed grid by a fixed mask for the
+    /// difficulty, so every call with the same arguments returns the same
+    /// puzzle. It's checked for uniqueness once, but never hardened. Only
+    /// orders 2 and 3 have templates; other orders fail with
+    /// [`NoTemplate`](enum.GenerateError.html#variant.NoTemplate). An
+    /// advanced order-2 puzzle doesn't exist, so that request settles for
+    /// the hardest mask (check the result's `difficulty`).
+    pub fn generate_fast(order: u8, difficulty: Difficulty) -> Result<Generated, GenerateError> {
+        generate_fast_with(order, difficulty, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fast::{generate_fast_with, TEMPLATES};
+    use crate::Difficulty;
+    use crate::GenerateError;
+    use crate::Solve;
+    use crate::Sudoku;
+    use crate::UniquenessCache;
+
+    #[test]
+    fn test_templates() {
+        for template in TEMPLATES.iter() {
+            // Each mask hits its difficulty.
+            for (mask, difficulty) in template.masks.iter().zip(Difficulty::ALL.iter().skip(1)) {
+                assert_eq!(mask.len(), template.grid.len());
+                let generated = Sudoku::generate_fast(template.order, *difficulty).unwrap();
+                assert_eq!(generated.difficulty, *difficulty);
+                assert!(generated.solution.is_solved());
+                assert_eq!(generated.puzzle.solution().unwrap(), generated.solution);
+            }
+        }
+        let generated = Sudoku::generate_fast(2, Difficulty::Advanced).unwrap();
+        assert_eq!(generated.difficulty, Difficulty::Difficult);
+        assert_eq!(
+            Sudoku::generate_fast(4, Difficulty::Beginner),
+            Err(GenerateError::NoTemplate(4))
+        );
+        assert_eq!(
+            Sudoku::generate_fast(0, Difficulty::Beginner),
+            Err(GenerateError::UnsupportedOrder(0))
+        );
+    }
+
+    #[test]
+    fn test_generate_fast_solves() {
+        // Every score (or uniqueness check) misses a fresh cache once.
+        for seed in 0..3 {
+            let mut cache = UniquenessCache::new();
+            let generated = generate_fast_with(3, Difficulty::Intermediate, Some(&mut cache));
+            assert!(generated.unwrap().puzzle.is_uniquely_solvable());
+            let fast = cache.misses();
+            let mut cache = UniquenessCache::new();
+            let _ =
+                Sudoku::generate_seeded_with_cache(3, Difficulty::Intermediate, seed, &mut cache)
+                    .unwrap();
+            let searched = cache.misses();
+            assert_eq!(fast, 1);
+            assert!(fast * 10 <= searched, "{} vs. {}", fast, searched);
+        }
+    }
+}
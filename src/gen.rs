@@ -1,18 +1,68 @@
-#[cfg(feature = "use_rand")]
-use rand::{thread_rng, Rng};
-#[cfg(feature = "use_stdweb")]
-use stdweb::{__js_raw_asm, _js_impl, js, unstable::TryInto};
-
-use crate::sol::PossibilityMap;
+use crate::cache::{self, UniquenessCache};
+use crate::dimensions::{validate_order, OrderError};
+use crate::logic::propagating_solutions;
+use crate::pool::GridPool;
+use crate::random::{entropy_seed, fnv1a, SeededSource, Source};
+use crate::sol::{backtrack, other_solution, Ascending, CandidateOrder};
 use crate::Difficulty;
 use crate::Element;
 use crate::Grid;
+use crate::Point;
 use crate::Score;
+use crate::Solve;
+use crate::StarRating;
 use crate::Sudoku;
+use crate::Technique;
+use crate::DIMENSIONS;
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt,
+};
+
+/// The maximum number of puzzles the hardening search will score before
+/// giving up on hitting the target difficulty.
+pub(crate) const MAX_HARDEN_EVALUATIONS: usize = 200;
+
+/// The maximum number of fresh grids the generator will try before giving up
+/// on producing a playable puzzle.
+const MAX_GENERATE_ATTEMPTS: u8 = 5;
+
+/// The maximum number of puzzles
+/// [`generate_smooth`](struct.Sudoku.html#method.generate_smooth) will
+/// generate before giving up on staying within the branching limit.
+const MAX_SMOOTH_ATTEMPTS: u8 = 20;
+
+/// The maximum number of puzzles
+/// [`generate_requiring`](struct.Sudoku.html#method.generate_requiring) will
+/// generate before giving up on one that needs the requested technique.
+const MAX_TECHNIQUE_ATTEMPTS: u8 = 20;
+
+/// The maximum number of puzzles
+/// [`generate_cross_checked`](struct.Sudoku.html#method.generate_cross_checked)
+/// will generate before giving up on the solvers agreeing.
+const MAX_CROSS_CHECK_ATTEMPTS: u8 = 5;
+
+/// The maximum number of puzzles
+/// [`generate_stars`](struct.Sudoku.html#method.generate_stars) will
+/// generate before settling for one that falls short of the requested rating.
+const MAX_STAR_ATTEMPTS: u8 = 10;
+
+/// The number of puzzles
+/// [`generate_distribution`](struct.Sudoku.html#method.generate_distribution)
+/// will generate for each one requested before giving up on the
+/// distribution.
+const MAX_DISTRIBUTION_ATTEMPTS: usize = 10;
+
+/// The maximum number of grids
+/// [`generate_with_clues`](struct.Sudoku.html#method.generate_with_clues)
+/// will strip before giving up on reaching the requested clue count.
+const MAX_CLUE_ATTEMPTS: u8 = 16;
 
-/// The maximum number of times the hardening algorithm will try to make a
-/// harder puzzle in a single pass.
-const MAX_HARDEN_ITERATIONS: u8 = 20;
+/// The maximum number of puzzles
+/// [`generate_novel`](struct.Sudoku.html#method.generate_novel) will
+/// generate before giving up on finding one that hasn't been seen.
+const MAX_NOVEL_ATTEMPTS: u8 = 64;
 
 /// Trait to generate a puzzle.
 ///
@@ -23,67 +73,181 @@ pub trait Generate: Score + Sized {
     fn generate(order: u8, difficulty: Difficulty) -> Self;
 }
 
-#[cfg(feature = "use_rand")]
-fn shuffle<T>(vec: &mut Vec<T>) {
-    let mut rng = thread_rng();
-    rng.shuffle(vec);
-}
-#[cfg(feature = "use_stdweb")]
-fn shuffle<T>(vec: &mut Vec<T>) {
-    let len = vec.len() as u32;
-    for i in 0..len {
-        let j = len - i;
-        let index: u32 = js! { return Math.floor(Math.random() * @{j}); }
-            .try_into()
-            .unwrap();
-        vec.swap(index as usize, (j - 1) as usize);
-    }
+/// A generated puzzle, bundled with its solution and achieved difficulty.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Generated {
+    /// The generated puzzle.
+    pub puzzle: Sudoku,
+    /// The puzzle's unique solution.
+    pub solution: Sudoku,
+    /// The difficulty the puzzle actually attained.
+    ///
+    /// This may be lower than the requested difficulty if hardening could not
+    /// reach the target, but it is never
+    /// [`Unplayable`](enum.Difficulty.html#variant.Unplayable).
+    pub difficulty: Difficulty,
+    /// The seed the puzzle was generated from, drawn from entropy if none was
+    /// given, or `None` if the puzzle doesn't depend on a seed alone (as when
+    /// it's [fixed](struct.Sudoku.html#method.generate_fast) or its grid came
+    /// from a [pool](struct.GridPool.html)).
+    ///
+    /// Generation is deterministic given the seed, so the seeded form of the
+    /// call reproduces the puzzle (e.g. one from a bug report):
+    /// [`generate_seeded`](struct.Sudoku.html#method.generate_seeded) for
+    /// puzzles from [`generate`](trait.Generate.html#tymethod.generate) and
+    /// from [`generate_with`](struct.Sudoku.html#method.generate_with) (and
+    /// the methods built on it) with
+    /// [`FillMethod::Random`](enum.FillMethod.html#variant.Random), and
+    /// [`generate_quick`](struct.Sudoku.html#method.generate_quick) for its
+    /// own puzzles.
+    pub seed: Option<u64>,
 }
 
-fn take_random<T>(values: &mut Vec<T>) -> Option<T> {
-    let mut indices = (0..values.len()).collect::<Vec<_>>();
-    shuffle(&mut indices);
-    indices.get(0).map(|index| values.remove(*index))
+/// Encodes errors encountered while generating a puzzle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// The requested date does not exist in the (proleptic) Gregorian
+    /// calendar.
+    InvalidDate,
+    /// No complete grid of the requested order could be constructed.
+    NoGrid,
+    /// The generated puzzle could not be solved (this indicates a bug).
+    Unsolvable,
+    /// No puzzle harder than
+    /// [`Difficulty::Unplayable`](enum.Difficulty.html#variant.Unplayable)
+    /// could be produced.
+    Unplayable,
+    /// No puzzle within the requested branching-factor limit could be
+    /// produced.
+    TooBranchy,
+    /// No puzzle requiring the requested technique could be produced.
+    TooSimple,
+    /// The backtracking and constraint-propagation solvers kept disagreeing
+    /// about the generated puzzles (this indicates a bug).
+    SolversDisagree,
+    /// The clues a puzzle was required to include conflict with one another,
+    /// lie outside the grid, or can't be completed to a full grid.
+    InconsistentGivens,
+    /// Not enough distinct puzzles of some requested difficulty could be
+    /// produced (e.g. because the order is too small for it).
+    UnreachableDistribution,
+    /// The order lies outside
+    /// [`MIN_ORDER`](constant.MIN_ORDER.html)`..=`[`MAX_ORDER`](constant.MAX_ORDER.html).
+    UnsupportedOrder(u8),
+    /// [`generate_fast`](struct.Sudoku.html#method.generate_fast) has no
+    /// template for the requested order.
+    NoTemplate(u8),
+    /// No uniquely solvable puzzle of the given order has as few clues as
+    /// were requested.
+    TooFewClues {
+        /// The requested order.
+        order: u8,
+        /// The requested number of clues.
+        clues: usize,
+        /// The fewest clues a puzzle of this order is known to need.
+        minimum: usize,
+    },
+    /// No puzzle with the requested number of clues could be produced
+    /// (though one may exist).
+    UnreachableClues(usize),
+    /// The source of randomness failed (e.g. `Math.random` was replaced with
+    /// something that doesn't return numbers).
+    EntropyFailed,
+    /// No puzzle meeting the requested
+    /// [options](struct.GenerateOptions.html) could be produced.
+    OptionsUnsatisfied,
+    /// The deadline in the requested [options](struct.GenerateOptions.html)
+    /// passed before a puzzle was produced.
+    DeadlineExceeded,
+    /// Every puzzle produced had already been seen.
+    NoNovelPuzzle,
 }
 
-fn recurse(puzzle: Sudoku) -> Option<Sudoku> {
-    let map: PossibilityMap = puzzle.clone().into();
-    match map.next() {
-        (None, _) => {
-            if puzzle.is_complete() {
-                Some(puzzle)
-            } else {
-                None
-            }
-        }
-        (Some(index), Some(set)) => {
-            let mut possibilities = (1..=(puzzle.order as usize).pow(2))
-                .filter(|v| set.contains(*v))
-                .collect::<Vec<_>>();
-            while let Some(candidate) = take_random(&mut possibilities) {
-                let mut puzzle = puzzle.clone();
-                puzzle.substitute(index, Some(Element(candidate as u8)));
-                let solution = recurse(puzzle);
-                if solution.is_some() {
-                    return solution;
-                }
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidDate => write!(f, "the requested date doesn't exist"),
+            Error::NoGrid => write!(f, "no complete grid could be constructed"),
+            Error::Unsolvable => write!(f, "the generated puzzle has no unique solution"),
+            Error::Unplayable => write!(f, "no playable puzzle could be produced"),
+            Error::TooBranchy => write!(f, "no puzzle within the branching limit was found"),
+            Error::TooSimple => write!(f, "no puzzle requiring the technique was found"),
+            Error::SolversDisagree => write!(f, "the solvers disagree about the puzzle"),
+            Error::InconsistentGivens => write!(f, "the required clues are inconsistent"),
+            Error::UnreachableDistribution => {
+                write!(f, "the requested distribution couldn't be filled")
             }
-            None
+            Error::UnsupportedOrder(order) => write!(f, "order {} isn't supported", order),
+            Error::NoTemplate(order) => write!(f, "there's no template for order {}", order),
+            Error::TooFewClues {
+                order,
+                clues,
+                minimum,
+            } => write!(
+                f,
+                "no order-{} puzzle has a unique solution with {} clues (at least {} are needed)",
+                order, clues, minimum
+            ),
+            Error::UnreachableClues(clues) => {
+                write!(f, "no puzzle with {} clues could be produced", clues)
+            }
+            Error::EntropyFailed => write!(f, "the source of randomness failed"),
+            Error::OptionsUnsatisfied => write!(f, "no puzzle meeting the options was found"),
+            Error::DeadlineExceeded => write!(f, "the deadline passed before a puzzle was found"),
+            Error::NoNovelPuzzle => write!(f, "no puzzle that hadn't been seen was found"),
         }
-        _ => unreachable!(),
+    }
+}
+
+impl From<OrderError> for Error {
+    fn from(error: OrderError) -> Self {
+        Error::UnsupportedOrder(error.0)
+    }
+}
+
+/// How a complete grid is filled in before clues are removed from it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FillMethod {
+    /// Backtracking with randomly-ordered candidates (the default).
+    #[default]
+    Random,
+    /// The boxes along the main diagonal (which don't constrain one another)
+    /// are filled in ascending order, and the rest of the grid is filled by
+    /// backtracking with ascending candidates (two dimensions only).
+    Diagonal,
+    /// Each row is the row above it shifted left by the order, with one
+    /// extra shift at the start of each band (two dimensions only).
+    Shift,
+}
+
+fn take_random<T, R: Source>(values: &mut Vec<T>, source: &mut R) -> Option<T> {
+    if values.is_empty() {
+        None
+    } else {
+        let index = source.below(values.len());
+        Some(values.remove(index))
+    }
+}
+
+/// Tries candidates in a random order drawn from the given source.
+struct Random<'a, R: Source>(&'a mut R);
+
+impl<'a, R: Source> CandidateOrder for Random<'a, R> {
+    fn take(&mut self, candidates: &mut Vec<usize>) -> Option<usize> {
+        take_random(candidates, self.0)
     }
 }
 
 /// Creates a randomized sudoku grid of the specified order.
 #[allow(clippy::needless_range_loop)]
-fn grid(order: u8) -> Option<Sudoku> {
+pub(crate) fn grid<R: Source>(order: u8, source: &mut R) -> Option<Sudoku> {
     let mut puzzle = Sudoku::new(order);
     // TODO(#14): Revisit this block when NLL lands.
     {
         let mut first_box = (1..=order.pow(2))
             .map(|v| Some(Element(v)))
             .collect::<Vec<_>>();
-        shuffle(&mut first_box);
+        source.shuffle(&mut first_box);
         let order = order as usize;
         let axis = order.pow(2);
         for i in 0..axis {
@@ -92,69 +256,1651 @@ fn grid(order: u8) -> Option<Sudoku> {
         }
         // TODO(#13): Reduce the number of cells that are filled with backtracking.
         // The rest
-        recurse(puzzle)
+        backtrack(&puzzle, 1, false, &mut Random(source)).solution
+    }
+}
+
+/// Creates a deterministic grid with the diagonal boxes filled in order.
+#[cfg(feature = "2D")]
+fn diagonal_grid(order: u8) -> Option<Sudoku> {
+    let mut puzzle = Sudoku::new(order);
+    let order = order as usize;
+    let axis = order.pow(2);
+    for b in 0..order {
+        for i in 0..axis {
+            let (x, y) = (b * order + i % order, b * order + i / order);
+            puzzle.elements[y * axis + x] = Some(Element(i as u8 + 1));
+        }
+    }
+    backtrack(&puzzle, 1, false, &mut Ascending).solution
+}
+
+/// Diagonal grids are only defined in two dimensions.
+#[cfg(not(feature = "2D"))]
+fn diagonal_grid(_order: u8) -> Option<Sudoku> {
+    None
+}
+
+/// Creates the shifted-rows grid of the given order.
+#[cfg(feature = "2D")]
+fn shift_grid(order: u8) -> Option<Sudoku> {
+    let mut puzzle = Sudoku::new(order);
+    let order = order as usize;
+    let axis = order.pow(2);
+    for y in 0..axis {
+        for x in 0..axis {
+            let value = (order * (y % order) + y / order + x) % axis + 1;
+            puzzle.elements[y * axis + x] = Some(Element(value as u8));
+        }
+    }
+    Some(puzzle)
+}
+
+/// Shifted grids are only defined in two dimensions.
+#[cfg(not(feature = "2D"))]
+fn shift_grid(_order: u8) -> Option<Sudoku> {
+    None
+}
+
+/// Creates a complete grid of the specified order using the given method.
+fn fill<R: Source>(order: u8, method: FillMethod, source: &mut R) -> Option<Sudoku> {
+    match method {
+        FillMethod::Random => grid(order, source),
+        FillMethod::Diagonal => diagonal_grid(order),
+        FillMethod::Shift => shift_grid(order),
+    }
+}
+
+/// The share of cells (in percent) the
+/// [fast path](fn.quick_remove.html) removes up front when generating a puzzle
+/// of the given difficulty, or `None` if the difficulty has no fast path.
+///
+/// These are tuned so that, at orders 2 and 3, the batch usually leaves a
+/// unique puzzle at or below the difficulty's band; the harder difficulties
+/// need removals chosen one at a time.
+fn quick_removal_percent(difficulty: Difficulty) -> Option<usize> {
+    match difficulty {
+        Difficulty::Beginner => Some(35),
+        Difficulty::Easy => Some(45),
+        _ => None,
+    }
+}
+
+/// Removes a random batch of clues from the complete grid in one go, sized by
+/// [`quick_removal_percent`](fn.quick_removal_percent.html), returning the
+/// puzzle and its difficulty. The clues at the points in `keep` are never
+/// removed.
+///
+/// Clues are removed in pairs that are symmetric under a half turn. The
+/// batch is checked for uniqueness and scored once; if the difficulty has no
+/// fast path, or the batch admits a second solution or overshoots the target,
+/// this returns `None` and the caller should harden the full grid instead.
+fn quick_remove<R: Source>(
+    grid: &Sudoku,
+    target: Difficulty,
+    keep: &[Point],
+    source: &mut R,
+    cache: Option<&mut UniquenessCache>,
+) -> Option<(Sudoku, Difficulty)> {
+    let count = grid.elements.len() * quick_removal_percent(target)? / 100;
+    let last = grid.axis().saturating_sub(1);
+    let mut points = grid.points();
+    source.shuffle(&mut points);
+    let mut puzzle = grid.clone();
+    let mut removed = vec![];
+    for point in points {
+        if removed.len() >= count {
+            break;
+        }
+        let mut rotated = point;
+        for i in 0..DIMENSIONS {
+            rotated[i] = last - point[i];
+        }
+        if puzzle[point].is_none() || keep.contains(&point) || keep.contains(&rotated) {
+            continue;
+        }
+        for cell in &[point, rotated] {
+            if puzzle[*cell].is_some() {
+                puzzle.substitute(*cell, None);
+                removed.push(*cell);
+            }
+        }
+    }
+    if removed.is_empty() || other_solution(&puzzle, grid, &removed).0 {
+        return None;
     }
+    let difficulty =
+        Difficulty::from_score_with_boxes(cache::score(&puzzle, cache)?, puzzle.box_shape());
+    if difficulty > target {
+        return None;
+    }
+    Some((puzzle, difficulty))
+}
+
+/// Why a [hardening](fn.harden.html) search stopped.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum HardenReport {
+    /// The puzzle reached the target difficulty.
+    TargetReached,
+    /// Every sequence of removals was tried without reaching the target (or
+    /// the puzzle couldn't be scored to begin with).
+    NoProgress,
+    /// The search scored
+    /// [`MAX_HARDEN_EVALUATIONS`](constant.MAX_HARDEN_EVALUATIONS.html)
+    /// puzzles without reaching the target.
+    IterationCap,
 }
 
-/// Makes the sudoku harder to the desired level, modifying it in-place.
+/// Makes the sudoku harder to the desired level, modifying it in-place, and
+/// reports why it stopped. The clues at the points in `keep` are never
+/// removed.
+///
+/// This is a depth-first search over clue removals: at each step, the
+/// remaining clues are tried in a random order, and the first whose removal
+/// keeps the puzzle uniquely solvable without overshooting the target is
+/// removed before going deeper. Removals that would overshoot are skipped
+/// rather than ending the search, and if no clue can be removed from a
+/// puzzle, the search backs up and tries a different removal one level up.
+/// The search gives up after
+/// [`MAX_HARDEN_EVALUATIONS`](constant.MAX_HARDEN_EVALUATIONS.html) scores
+/// (or once every removal has been tried), leaving the deepest puzzle it
+/// found. The search path is kept on an explicit stack rather than the call
+/// stack, so large puzzles can't overflow it.
+///
+/// Every puzzle on the search path has the same unique solution, so a
+/// removal can only admit a second solution that differs at the removed
+/// cell; removals are checked for that before being scored.
+///
+/// Stripping clues greedily to the hardest reachable puzzle and then adding
+/// clues back until it lands in the target band would also work, but adding
+/// a clue back doesn't always lower the score (which depends on where the
+/// solver happens to branch), so landing in the band would take a second
+/// search over add-backs, and every puzzle scored past the band is wasted.
+/// Never overshooting keeps every scored puzzle a step towards the target,
+/// which converges more often within the same budget.
 ///
 /// # Notes
 /// No validation is performed on the passed puzzle.
-fn harden(mut sudoku: &mut Sudoku, target: Difficulty) -> Result<(), ()> {
-    let current = sudoku.score().unwrap();
-    let mut points = sudoku.points();
-    for _ in 0..MAX_HARDEN_ITERATIONS {
-        if let (Some(one), Some(two)) = (take_random(&mut points), take_random(&mut points)) {
-            let (one, two) = (one.fold(sudoku.order), two.fold(sudoku.order));
-            let mut puzzle = sudoku.clone();
-            // Faster than substituting twice.
-            puzzle.elements[one] = None;
-            puzzle.elements[two] = None;
-            if let Some(score) = puzzle.score() {
-                if score > current {
-                    let difficulty: Difficulty = score.into();
-                    if difficulty > target {
-                        // We overshot the target difficulty
-                        continue;
-                    }
-                    sudoku.elements[one] = None;
-                    sudoku.elements[two] = None;
-                    return if difficulty == target {
-                        Ok(())
-                    } else {
-                        harden(&mut sudoku, target)
-                    };
+fn harden<R: Source>(
+    sudoku: &mut Sudoku,
+    target: Difficulty,
+    keep: &[Point],
+    source: &mut R,
+    mut cache: Option<&mut UniquenessCache>,
+    attempts: &mut Attempts,
+) -> HardenReport {
+    let score = match cache::score(sudoku, cache.as_deref_mut()) {
+        Some(score) => score,
+        None => return HardenReport::NoProgress,
+    };
+    if Difficulty::from_score_with_boxes(score, sudoku.box_shape()) == target {
+        return HardenReport::TargetReached;
+    }
+    let solution = match sudoku.solution() {
+        Ok(solution) => solution,
+        Err(_) => return HardenReport::NoProgress,
+    };
+    let clues = sudoku
+        .points()
+        .into_iter()
+        .filter(|p| sudoku[*p].is_some() && !keep.contains(p))
+        .collect::<Vec<_>>();
+    let mut search = Search {
+        target,
+        solution,
+        source,
+        cache,
+        budget: MAX_HARDEN_EVALUATIONS,
+        deepest: (0, sudoku.clone()),
+    };
+    let report = search.run(sudoku, clues);
+    attempts.evaluations += MAX_HARDEN_EVALUATIONS - search.budget;
+    if report != HardenReport::TargetReached {
+        *sudoku = search.deepest.1;
+    }
+    report
+}
+
+/// The work done by a run of the generation pipeline.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct Attempts {
+    /// The number of complete grids constructed.
+    pub(crate) grids: usize,
+    /// The number of puzzles scored by [hardening](fn.harden.html) searches.
+    pub(crate) evaluations: usize,
+}
+
+/// The state of a [hardening](fn.harden.html) search.
+struct Search<'a, 'b, R: Source> {
+    target: Difficulty,
+    // The unique solution of every puzzle on the search path.
+    solution: Sudoku,
+    source: &'a mut R,
+    cache: Option<&'b mut UniquenessCache>,
+    // The number of puzzles that may still be scored.
+    budget: usize,
+    // The puzzle with the most clues removed so far (and how many).
+    deepest: (usize, Sudoku),
+}
+
+/// A level of the [hardening](fn.harden.html) search path.
+struct Level {
+    // The clues that may still be removed at this level, in the order
+    // they're tried.
+    clues: Vec<Point>,
+    // The index of the next clue to try.
+    next: usize,
+    // The removal that led to this level, to be undone when backing up.
+    removed: Option<(Point, Option<Element>)>,
+}
+
+impl<'a, 'b, R: Source> Search<'a, 'b, R> {
+    /// Searches for removals from the given clues that bring the puzzle to
+    /// the target (in which case `sudoku` holds the result).
+    fn run(&mut self, sudoku: &mut Sudoku, clues: Vec<Point>) -> HardenReport {
+        let mut path = vec![];
+        self.enter(&mut path, sudoku, clues, None);
+        while let Some(level) = path.last_mut() {
+            if level.next == level.clues.len() {
+                // Every removal at this level was tried; back up.
+                if let Some((point, value)) = level.removed {
+                    sudoku.substitute(point, value);
+                }
+                let _ = path.pop();
+                continue;
+            }
+            if self.budget == 0 {
+                return HardenReport::IterationCap;
+            }
+            self.budget -= 1;
+            let i = level.next;
+            level.next += 1;
+            let point = level.clues[i];
+            let value = sudoku[point];
+            sudoku.substitute(point, None);
+            if other_solution(sudoku, &self.solution, &[point]).0 {
+                sudoku.substitute(point, value);
+                continue;
+            }
+            if let Some(score) = cache::score(sudoku, self.cache.as_deref_mut()) {
+                let difficulty = Difficulty::from_score_with_boxes(score, sudoku.box_shape());
+                if difficulty == self.target {
+                    return HardenReport::TargetReached;
+                }
+                if difficulty < self.target {
+                    let mut rest = level.clues.clone();
+                    let _ = rest.swap_remove(i);
+                    self.enter(&mut path, sudoku, rest, Some((point, value)));
+                    continue;
                 }
             }
+            sudoku.substitute(point, value);
+        }
+        HardenReport::NoProgress
+    }
+
+    /// Pushes a new level onto the search path.
+    fn enter(
+        &mut self,
+        path: &mut Vec<Level>,
+        sudoku: &Sudoku,
+        mut clues: Vec<Point>,
+        removed: Option<(Point, Option<Element>)>,
+    ) {
+        if path.len() > self.deepest.0 {
+            self.deepest = (path.len(), sudoku.clone());
+        }
+        self.source.shuffle(&mut clues);
+        path.push(Level {
+            clues,
+            next: 0,
+            removed,
+        });
+    }
+}
+
+/// Runs the full generation pipeline against the given source.
+///
+/// Unplayable targets are treated as beginner targets, and unplayable results
+/// are discarded in favor of a fresh grid.
+pub(crate) fn generate_from<R: Source>(
+    order: u8,
+    difficulty: Difficulty,
+    method: FillMethod,
+    source: &mut R,
+    cache: Option<&mut UniquenessCache>,
+) -> Result<Generated, Error> {
+    generate_counted(
+        order,
+        difficulty,
+        method,
+        source,
+        cache,
+        &mut Attempts::default(),
+    )
+}
+
+/// Runs the full generation pipeline against the given source (like
+/// [`generate_from`](fn.generate_from.html)), adding the work it does to the
+/// given attempts, whether or not it succeeds.
+pub(crate) fn generate_counted<R: Source>(
+    order: u8,
+    difficulty: Difficulty,
+    method: FillMethod,
+    source: &mut R,
+    cache: Option<&mut UniquenessCache>,
+    attempts: &mut Attempts,
+) -> Result<Generated, Error> {
+    let puzzle = Sudoku::try_new(order)?;
+    generate_around(
+        &puzzle, difficulty, method, source, cache, None, false, attempts,
+    )
+}
+
+/// Runs the given generation against a source seeded from entropy, recording
+/// the seed in the result.
+///
+/// Entropy only ever provides the seed, so that every unseeded puzzle can
+/// be regenerated from it.
+fn from_entropy<F>(generate: F) -> Result<Generated, Error>
+where
+    F: FnOnce(&mut SeededSource) -> Result<Generated, Error>,
+{
+    from_seed(entropy_seed().ok_or(Error::EntropyFailed)?, generate)
+}
+
+/// Runs the given generation against a source with the given seed,
+/// recording the seed in the result.
+pub(crate) fn from_seed<F>(seed: u64, generate: F) -> Result<Generated, Error>
+where
+    F: FnOnce(&mut SeededSource) -> Result<Generated, Error>,
+{
+    let generated = generate(&mut SeededSource::new(seed))?;
+    Ok(Generated {
+        seed: Some(seed),
+        ..generated
+    })
+}
+
+/// A source seeded from entropy, for generation that doesn't record its
+/// seed.
+fn entropy_source() -> Result<SeededSource, Error> {
+    entropy_seed()
+        .map(SeededSource::new)
+        .ok_or(Error::EntropyFailed)
+}
+
+/// Runs the full generation pipeline against the given source, keeping the
+/// clues of the given puzzle.
+///
+/// If the puzzle has no clues and square boxes, grids are taken from the
+/// given pool, or else filled using the given method; otherwise, they're
+/// completed from the puzzle by backtracking with random candidates.
+///
+/// If `quick` is set, low difficulties first try the
+/// [fast path](fn.quick_remove.html): a batch that lands in the target's band
+/// is returned as is, and one that falls short is hardened from there. Only
+/// if the batch misses altogether is the full grid hardened.
+#[allow(clippy::too_many_arguments)]
+fn generate_around<R: Source>(
+    givens: &Sudoku,
+    difficulty: Difficulty,
+    method: FillMethod,
+    source: &mut R,
+    mut cache: Option<&mut UniquenessCache>,
+    mut pool: Option<&mut GridPool>,
+    quick: bool,
+    attempts: &mut Attempts,
+) -> Result<Generated, Error> {
+    let order = givens.order;
+    let target = if difficulty.is_playable() {
+        difficulty
+    } else {
+        Difficulty::Beginner
+    };
+    let keep = givens
+        .points()
+        .into_iter()
+        .filter(|p| givens[*p].is_some())
+        .collect::<Vec<_>>();
+    let (rows, columns) = givens.box_shape();
+    for _ in 0..MAX_GENERATE_ATTEMPTS {
+        let grid = if keep.is_empty() && rows == columns {
+            match pool.as_deref_mut() {
+                Some(pool) => pool.grid(order, source),
+                None => fill(order, method, source),
+            }
+        } else {
+            backtrack(givens, 1, false, &mut Random(source)).solution
+        };
+        let grid = grid.ok_or(Error::NoGrid)?;
+        attempts.grids += 1;
+        let batch = if quick {
+            quick_remove(&grid, target, &keep, source, cache.as_deref_mut())
+        } else {
+            None
+        };
+        let mut puzzle = match batch {
+            Some((puzzle, difficulty)) if difficulty == target => {
+                return Ok(Generated {
+                    puzzle,
+                    solution: grid,
+                    difficulty,
+                    seed: None,
+                });
+            }
+            Some((puzzle, _)) => puzzle,
+            None => grid,
+        };
+        let _ = harden(
+            &mut puzzle,
+            target,
+            &keep,
+            source,
+            cache.as_deref_mut(),
+            attempts,
+        );
+        // A grid with no clues removed isn't a puzzle, whatever it scores.
+        if puzzle.is_complete() {
+            continue;
+        }
+        let score = cache::score(&puzzle, cache.as_deref_mut()).ok_or(Error::Unsolvable)?;
+        let difficulty = Difficulty::from_score_with_boxes(score, puzzle.box_shape());
+        if !difficulty.is_playable() {
+            continue;
         }
+        let solution = puzzle.solution().map_err(|_| Error::Unsolvable)?;
+        return Ok(Generated {
+            puzzle,
+            solution,
+            difficulty,
+            seed: None,
+        });
     }
-    Err(())
+    Err(Error::Unplayable)
+}
+
+/// The fewest clues a uniquely solvable puzzle of the given order can have,
+/// as far as is known.
+///
+/// The order-2 and order-3 values are exact (the latter is the proven
+/// 17-clue minimum); beyond those, this is the general bound that all but
+/// one value must appear somewhere (else the two missing values could be
+/// swapped in any solution).
+pub(crate) fn minimum_clues(order: u8) -> usize {
+    match (DIMENSIONS, order) {
+        (2, 2) => 4,
+        (2, 3) => 17,
+        _ => usize::from(order).pow(2).saturating_sub(1),
+    }
+}
+
+/// Generates a puzzle with the given number of clues against the given
+/// source (see
+/// [`generate_with_clues`](struct.Sudoku.html#method.generate_with_clues)).
+fn generate_with_clues_from<R: Source>(
+    order: u8,
+    clues: usize,
+    source: &mut R,
+) -> Result<Sudoku, Error> {
+    validate_order(order)?;
+    let minimum = minimum_clues(order);
+    if clues < minimum {
+        return Err(Error::TooFewClues {
+            order,
+            clues,
+            minimum,
+        });
+    }
+    for _ in 0..MAX_CLUE_ATTEMPTS {
+        let grid = grid(order, source).ok_or(Error::NoGrid)?;
+        let mut puzzle = grid.clone();
+        let mut remaining = puzzle.elements.len();
+        // Asking for at least as many clues as there are cells means the
+        // full grid.
+        let clues = clues.min(remaining);
+        let mut points = puzzle.points();
+        source.shuffle(&mut points);
+        for point in points {
+            if remaining <= clues {
+                break;
+            }
+            puzzle.substitute(point, None);
+            if other_solution(&puzzle, &grid, &[point]).0 {
+                puzzle.substitute(point, grid[point]);
+            } else {
+                remaining -= 1;
+            }
+        }
+        if remaining == clues {
+            return Ok(puzzle);
+        }
+    }
+    Err(Error::UnreachableClues(clues))
+}
+
+/// Generates one of the puzzles a regenerating entry point tries, from a
+/// seed drawn from the given source (and recorded, so that
+/// [`generate_seeded`](struct.Sudoku.html#method.generate_seeded)
+/// reproduces the puzzle it settles on).
+fn generate_attempt<R: Source>(
+    order: u8,
+    difficulty: Difficulty,
+    source: &mut R,
+) -> Result<Generated, Error> {
+    Sudoku::generate_seeded(order, difficulty, source.next_u64())
+}
+
+/// Generates a puzzle whose solve path never branches more than
+/// `max_branch_factor` ways against the given source (see
+/// [`generate_smooth`](struct.Sudoku.html#method.generate_smooth)).
+fn generate_smooth_from<R: Source>(
+    order: u8,
+    difficulty: Difficulty,
+    max_branch_factor: usize,
+    source: &mut R,
+) -> Result<Generated, Error> {
+    for _ in 0..MAX_SMOOTH_ATTEMPTS {
+        let generated = generate_attempt(order, difficulty, source)?;
+        let branch_factor = generated
+            .puzzle
+            .solve_path()
+            .ok_or(Error::Unsolvable)?
+            .max_branch_factor();
+        if branch_factor <= max_branch_factor {
+            return Ok(generated);
+        }
+    }
+    Err(Error::TooBranchy)
+}
+
+/// Generates a puzzle with the given star rating against the given source
+/// (see [`generate_stars`](struct.Sudoku.html#method.generate_stars)).
+fn generate_stars_from<R: Source>(
+    order: u8,
+    stars: StarRating,
+    source: &mut R,
+) -> Result<Generated, Error> {
+    let target = stars.difficulty();
+    let mut generated = generate_attempt(order, target, source)?;
+    for _ in 1..MAX_STAR_ATTEMPTS {
+        if generated.difficulty == target {
+            break;
+        }
+        generated = generate_attempt(order, target, source)?;
+    }
+    Ok(generated)
+}
+
+/// Generates a puzzle whose fingerprint isn't in `seen` against the given
+/// source (see
+/// [`generate_novel`](struct.Sudoku.html#method.generate_novel)).
+fn generate_novel_from<R: Source>(
+    order: u8,
+    difficulty: Difficulty,
+    seen: &HashSet<u64>,
+    source: &mut R,
+) -> Result<Sudoku, Error> {
+    for _ in 0..MAX_NOVEL_ATTEMPTS {
+        let puzzle = generate_from(order, difficulty, FillMethod::Random, source, None)?.puzzle;
+        if !seen.contains(&puzzle.fingerprint()) {
+            return Ok(puzzle);
+        }
+    }
+    Err(Error::NoNovelPuzzle)
+}
+
+/// Generates a batch with the given number of puzzles of each difficulty
+/// against the given source (see
+/// [`generate_distribution`](struct.Sudoku.html#method.generate_distribution)).
+fn generate_distribution_from<R: Source>(
+    order: u8,
+    counts: &[(Difficulty, usize)],
+    source: &mut R,
+) -> Result<Vec<Sudoku>, Error> {
+    validate_order(order)?;
+    let mut bands: Vec<(Difficulty, usize, Vec<Sudoku>)> = vec![];
+    for &(difficulty, count) in counts {
+        match bands.iter_mut().find(|band| band.0 == difficulty) {
+            Some(band) => band.1 += count,
+            None => bands.push((difficulty, count, vec![])),
+        }
+    }
+    let total = bands.iter().map(|band| band.1).sum::<usize>();
+    let mut seen = BTreeSet::new();
+    let mut attempts = total * MAX_DISTRIBUTION_ATTEMPTS;
+    while let Some(target) = bands
+        .iter()
+        .find(|band| band.2.len() < band.1)
+        .map(|band| band.0)
+    {
+        if attempts == 0 || !target.is_playable() {
+            return Err(Error::UnreachableDistribution);
+        }
+        attempts -= 1;
+        let generated = generate_from(order, target, FillMethod::Random, source, None)?;
+        let band = bands
+            .iter_mut()
+            .find(|band| band.0 == generated.difficulty && band.2.len() < band.1);
+        if let Some(band) = band {
+            if seen.insert(generated.puzzle.elements.clone()) {
+                band.2.push(generated.puzzle);
+            }
+        }
+    }
+    Ok(bands.into_iter().flat_map(|band| band.2).collect())
 }
 
 impl Generate for Sudoku {
     fn generate(order: u8, difficulty: Difficulty) -> Self {
-        let mut puzzle = grid(order).unwrap();
-        let _ = harden(&mut puzzle, difficulty);
-        puzzle
+        from_entropy(|source| generate_from(order, difficulty, FillMethod::Random, source, None))
+            .unwrap()
+            .puzzle
+    }
+}
+
+/// Whether the given date exists in the proleptic Gregorian calendar.
+fn is_valid_date(year: i32, month: u8, day: u8) -> bool {
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    day >= 1 && day <= days
+}
+
+/// A stable numeric code for each difficulty, used for seed derivation.
+///
+/// These values are frozen; they deliberately do not depend on the enum's
+/// discriminants.
+fn difficulty_code(difficulty: Difficulty) -> u8 {
+    use crate::Difficulty::*;
+    match difficulty {
+        Unplayable => 0,
+        Beginner => 1,
+        Easy => 2,
+        Intermediate => 3,
+        Difficult => 4,
+        Advanced => 5,
+    }
+}
+
+/// Derives the seed of the puzzle of the day.
+///
+/// The seed is the 64-bit FNV-1a hash of the year (four bytes,
+/// little-endian), month, day, order, and difficulty code, in that order.
+fn daily_seed(date: (i32, u8, u8), order: u8, difficulty: Difficulty) -> u64 {
+    let (year, month, day) = date;
+    let mut bytes = year.to_le_bytes().to_vec();
+    bytes.extend_from_slice(&[month, day, order, difficulty_code(difficulty)]);
+    fnv1a(&bytes)
+}
+
+impl Sudoku {
+    /// Creates a complete, valid grid of the specified order using the given
+    /// method.
+    ///
+    /// Returns `None` if no grid could be constructed (including when the
+    /// order is unsupported, or when the method only works in two
+    /// dimensions).
+    pub fn seed_full_grid(order: u8, method: FillMethod) -> Option<Sudoku> {
+        validate_order(order).ok()?;
+        match method {
+            FillMethod::Random => grid(order, &mut entropy_source().ok()?),
+            FillMethod::Diagonal => diagonal_grid(order),
+            FillMethod::Shift => shift_grid(order),
+        }
+    }
+
+    /// Completes this partial grid to a full, valid grid by backtracking with
+    /// randomly-ordered candidates, drawn from the given seed (if any) or
+    /// from entropy.
+    ///
+    /// This is the completion step of generation, for building other
+    /// generators or puzzle tools: the puzzle needn't be uniquely solvable,
+    /// and any completion may be returned. With a seed, the same puzzle is
+    /// always completed the same way. Returns `None` if the filled cells
+    /// break the rules or can't be completed.
+    pub fn complete_randomly(&self, seed: Option<u64>) -> Option<Sudoku> {
+        if !self.is_valid() {
+            return None;
+        }
+        let seed = seed.or_else(entropy_seed)?;
+        backtrack(self, 1, false, &mut Random(&mut SeededSource::new(seed))).solution
+    }
+
+    /// Generates a puzzle of the desired order and difficulty, starting from
+    /// a complete grid filled using the given method.
+    ///
+    /// The [`Diagonal`](enum.FillMethod.html#variant.Diagonal) and
+    /// [`Shift`](enum.FillMethod.html#variant.Shift) methods always start from
+    /// the same grid, so only the choice of clues varies between puzzles.
+    pub fn generate_with(
+        order: u8,
+        difficulty: Difficulty,
+        method: FillMethod,
+    ) -> Result<Generated, Error> {
+        from_entropy(|source| generate_from(order, difficulty, method, source, None))
+    }
+
+    /// Generates a puzzle of the desired order and difficulty that includes
+    /// the given clues (e.g. to spell something out).
+    ///
+    /// The puzzle is hardened around the givens without ever removing one,
+    /// so many givens limit how hard it can get (check the result's
+    /// `difficulty`). Givens that conflict with one another (including two
+    /// different values for the same cell), lie outside the grid or can't be
+    /// completed to a full grid are rejected with
+    /// [`InconsistentGivens`](enum.GenerateError.html#variant.InconsistentGivens).
+    pub fn generate_with_givens(
+        order: u8,
+        difficulty: Difficulty,
+        givens: &[(Point, Element)],
+    ) -> Result<Generated, Error> {
+        let axis = usize::from(order).pow(2);
+        let mut puzzle = Sudoku::try_new(order)?;
+        for &(point, value) in givens {
+            if point.0.iter().any(|c| usize::from(*c) >= axis) {
+                return Err(Error::InconsistentGivens);
+            }
+            if puzzle[point].is_some_and(|existing| existing != value) {
+                return Err(Error::InconsistentGivens);
+            }
+            puzzle.substitute(point, Some(value));
+        }
+        if !puzzle.is_valid()
+            || backtrack(&puzzle, 1, false, &mut Ascending)
+                .solution
+                .is_none()
+        {
+            return Err(Error::InconsistentGivens);
+        }
+        from_entropy(|source| {
+            generate_around(
+                &puzzle,
+                difficulty,
+                FillMethod::Random,
+                source,
+                None,
+                None,
+                false,
+                &mut Attempts::default(),
+            )
+        })
+    }
+
+    /// Generates a puzzle of the desired difficulty with boxes `box_rows`
+    /// cells tall and `box_cols` cells wide (see
+    /// [`new_rect`](#method.new_rect), which panics on the same shapes this
+    /// does).
+    ///
+    /// Like [`generate_with`](#method.generate_with), this may settle for an
+    /// easier puzzle if the difficulty can't be reached (check the result's
+    /// `difficulty`).
+    #[cfg(feature = "2D")]
+    pub fn generate_rect(
+        box_rows: u8,
+        box_cols: u8,
+        difficulty: Difficulty,
+    ) -> Result<Generated, Error> {
+        from_entropy(|source| {
+            generate_around(
+                &Sudoku::new_rect(box_rows, box_cols),
+                difficulty,
+                FillMethod::Random,
+                source,
+                None,
+                None,
+                false,
+                &mut Attempts::default(),
+            )
+        })
+    }
+
+    /// Generates a puzzle of the desired order and difficulty whose
+    /// [solve path](struct.SolvePath.html) never branches more than
+    /// `max_branch_factor` ways, regenerating until one is found.
+    ///
+    /// Puzzles that only ever require small guesses feel smoother to solve.
+    /// Note that the harder difficulties can't be reached without some
+    /// larger branches.
+    pub fn generate_smooth(
+        order: u8,
+        difficulty: Difficulty,
+        max_branch_factor: usize,
+    ) -> Result<Generated, Error> {
+        generate_smooth_from(order, difficulty, max_branch_factor, &mut entropy_source()?)
+    }
+
+    /// Generates a puzzle of the desired order and difficulty that can't be
+    /// solved by deduction without the given technique (or a later one on
+    /// the [ladder](enum.Technique.html#associatedconstant.LADDER)),
+    /// regenerating until one is found.
+    ///
+    /// For example, requiring
+    /// [`HiddenSingle`](enum.Technique.html#variant.HiddenSingle) rules out
+    /// puzzles that fall to naked singles alone, which feel mindless however
+    /// few clues they have. Some puzzles need more than the ladder offers;
+    /// those count as requiring every technique.
+    pub fn generate_requiring(
+        order: u8,
+        difficulty: Difficulty,
+        technique: Technique,
+    ) -> Result<Generated, Error> {
+        let simpler = Technique::LADDER
+            .iter()
+            .take_while(|t| **t != technique)
+            .cloned()
+            .collect::<Vec<_>>();
+        for _ in 0..MAX_TECHNIQUE_ATTEMPTS {
+            let generated = Self::generate_with(order, difficulty, FillMethod::Random)?;
+            if !generated.puzzle.deduce(&simpler).is_solved() {
+                return Ok(generated);
+            }
+        }
+        Err(Error::TooSimple)
+    }
+
+    /// Generates a puzzle of the desired order and difficulty whose
+    /// uniqueness is confirmed by two independent solvers: the backtracking
+    /// solver behind [`solution`](trait.Solve.html#tymethod.solution) and a
+    /// constraint-propagation solver.
+    ///
+    /// Puzzles the solvers disagree about (which would indicate a bug in one
+    /// of them) are discarded in favor of fresh ones.
+    pub fn generate_cross_checked(order: u8, difficulty: Difficulty) -> Result<Generated, Error> {
+        for _ in 0..MAX_CROSS_CHECK_ATTEMPTS {
+            let generated = Self::generate_with(order, difficulty, FillMethod::Random)?;
+            if propagating_solutions(&generated.puzzle, 2) == [generated.solution.clone()] {
+                return Ok(generated);
+            }
+        }
+        Err(Error::SolversDisagree)
+    }
+
+    /// Generates a puzzle of the desired order with the given
+    /// [star rating](struct.StarRating.html), regenerating until the rating
+    /// is hit.
+    ///
+    /// Like [`generate_with`](#method.generate_with), this may settle for an
+    /// easier puzzle if the rating can't be reached (check the result's
+    /// `difficulty`).
+    pub fn generate_stars(order: u8, stars: StarRating) -> Result<Generated, Error> {
+        generate_stars_from(order, stars, &mut entropy_source()?)
+    }
+
+    /// Generates a batch of distinct puzzles of the desired order with
+    /// exactly the requested number of puzzles of each difficulty (e.g. for
+    /// a pack that's mostly easy, with a few hard puzzles).
+    ///
+    /// The puzzles are grouped by difficulty, in the order the difficulties
+    /// are first requested; a difficulty requested more than once gets the
+    /// sum of its counts. Puzzles that miss their target but land in another
+    /// requested difficulty are kept for it. If some difficulty can't be
+    /// filled (including [`Unplayable`](enum.Difficulty.html#variant.Unplayable)),
+    /// this fails with
+    /// [`UnreachableDistribution`](enum.GenerateError.html#variant.UnreachableDistribution).
+    pub fn generate_distribution(
+        order: u8,
+        counts: &[(Difficulty, usize)],
+    ) -> Result<Vec<Sudoku>, Error> {
+        generate_distribution_from(order, counts, &mut entropy_source()?)
+    }
+
+    /// Generates a uniquely solvable puzzle of the desired order with exactly
+    /// the requested number of clues, whatever its difficulty.
+    ///
+    /// Clues are removed from a random grid one at a time, in a random
+    /// order, as long as the puzzle stays uniquely solvable; if that gets
+    /// stuck above the requested count, a fresh grid is tried. Counts below
+    /// the known minimum for the order (17 for order 3) are rejected up
+    /// front with [`TooFewClues`](enum.GenerateError.html#variant.TooFewClues),
+    /// and counts just above it, which random removal rarely reaches, fail
+    /// with [`UnreachableClues`](enum.GenerateError.html#variant.UnreachableClues)
+    /// after a bounded number of grids. Counts at or above the number of
+    /// cells yield a full grid.
+    pub fn generate_with_clues(order: u8, clues: usize) -> Result<Sudoku, Error> {
+        generate_with_clues_from(order, clues, &mut entropy_source()?)
+    }
+
+    /// Generates a puzzle of the desired order and difficulty whose
+    /// [fingerprint](#method.fingerprint) isn't in `seen` (the fingerprints
+    /// of puzzles already served, say).
+    ///
+    /// Puzzles are regenerated until one hasn't been seen, failing with
+    /// [`NoNovelPuzzle`](enum.GenerateError.html#variant.NoNovelPuzzle)
+    /// after a bounded number of attempts (which only happens when `seen`
+    /// covers most puzzles of the order and difficulty).
+    pub fn generate_novel(
+        order: u8,
+        difficulty: Difficulty,
+        seen: &HashSet<u64>,
+    ) -> Result<Sudoku, Error> {
+        generate_novel_from(order, difficulty, seen, &mut entropy_source()?)
+    }
+
+    /// Generates a puzzle of the desired order and difficulty from the given
+    /// seed.
+    ///
+    /// The same seed always produces the same puzzle, regardless of the
+    /// platform or randomness configuration.
+    pub fn generate_seeded(
+        order: u8,
+        difficulty: Difficulty,
+        seed: u64,
+    ) -> Result<Generated, Error> {
+        from_seed(seed, |source| {
+            generate_from(order, difficulty, FillMethod::Random, source, None)
+        })
+    }
+
+    /// Generates a puzzle of the desired order and difficulty, trying a
+    /// faster path first for beginner and easy puzzles: a random batch of
+    /// clues is removed in one go, and the puzzle is only hardened one clue at
+    /// a time if the batch falls short of the difficulty.
+    ///
+    /// With a seed, the same seed always produces the same puzzle; without
+    /// one, the seed drawn from entropy is recorded in the result. The batch
+    /// draws on the seed, so a seed produces a different puzzle here than
+    /// with [`generate_seeded`](#method.generate_seeded); this is why the
+    /// fast path is opt-in, leaving seeded puzzles (and
+    /// [puzzles of the day](#method.daily)) as they were.
+    pub fn generate_quick(
+        order: u8,
+        difficulty: Difficulty,
+        seed: Option<u64>,
+    ) -> Result<Generated, Error> {
+        let seed = seed.or_else(entropy_seed).ok_or(Error::EntropyFailed)?;
+        from_seed(seed, |source| {
+            generate_around(
+                &Sudoku::try_new(order)?,
+                difficulty,
+                FillMethod::Random,
+                source,
+                None,
+                None,
+                true,
+                &mut Attempts::default(),
+            )
+        })
+    }
+
+    /// Generates a puzzle from the given seed (like
+    /// [`generate_seeded`](#method.generate_seeded)), reusing and filling the
+    /// given cache.
+    ///
+    /// The result is the same as without the cache; only the number of
+    /// solves differs.
+    pub fn generate_seeded_with_cache(
+        order: u8,
+        difficulty: Difficulty,
+        seed: u64,
+        cache: &mut UniquenessCache,
+    ) -> Result<Generated, Error> {
+        from_seed(seed, |source| {
+            generate_from(order, difficulty, FillMethod::Random, source, Some(cache))
+        })
+    }
+
+    /// Generates a puzzle of the desired order and difficulty, taking its
+    /// grid from the given pool (see [`GridPool`](struct.GridPool.html))
+    /// rather than building a fresh one each time.
+    ///
+    /// This is for generating many puzzles (e.g. on a server): once the
+    /// pool is full, generation skips grid construction entirely.
+    pub fn generate_pooled(
+        order: u8,
+        difficulty: Difficulty,
+        pool: &mut GridPool,
+    ) -> Result<Generated, Error> {
+        generate_around(
+            &Sudoku::try_new(order)?,
+            difficulty,
+            FillMethod::Random,
+            &mut entropy_source()?,
+            None,
+            Some(pool),
+            false,
+            &mut Attempts::default(),
+        )
+    }
+
+    /// Generates the puzzle of the day for the given `(year, month, day)`.
+    ///
+    /// Every caller asking for the same date, order, and difficulty receives
+    /// the same puzzle, without any coordination.
+    pub fn daily(
+        date: (i32, u8, u8),
+        order: u8,
+        difficulty: Difficulty,
+    ) -> Result<Generated, Error> {
+        let (year, month, day) = date;
+        if !is_valid_date(year, month, day) {
+            return Err(Error::InvalidDate);
+        }
+        Self::generate_seeded(order, difficulty, daily_seed(date, order, difficulty))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::gen;
+    #[cfg(feature = "2D")]
+    use crate::cache::UniquenessCache;
+    #[cfg(feature = "2D")]
+    use crate::gen::{
+        self, generate_distribution_from, generate_novel_from, generate_smooth_from,
+        generate_stars_from, generate_with_clues_from, quick_remove, take_random, Attempts,
+        HardenReport,
+    };
+    use crate::gen::{daily_seed, is_valid_date, Error, FillMethod};
+    #[cfg(feature = "2D")]
+    use crate::logic::propagating_solutions;
+    #[cfg(feature = "2D")]
+    use crate::pool::GridPool;
+    #[cfg(feature = "2D")]
+    use crate::random::{SeededSource, Source};
+    #[cfg(feature = "2D")]
+    use crate::sol::{backtrack, other_solution, Ascending};
+    use crate::Difficulty;
+    #[cfg(feature = "2D")]
+    use crate::Element;
+    #[cfg(feature = "2D")]
+    use crate::Generate;
+    #[cfg(feature = "2D")]
+    use crate::Grid;
+    #[cfg(feature = "2D")]
+    use crate::Point;
+    #[cfg(feature = "2D")]
+    use crate::Score;
+    #[cfg(feature = "2D")]
     use crate::Solve;
+    #[cfg(feature = "2D")]
+    use crate::StarRating;
+    use crate::Sudoku;
+    #[cfg(feature = "2D")]
+    use crate::Technique;
+
+    #[cfg(feature = "2D")]
+    use std::collections::HashSet;
     #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
     fn test_grid() {
-        let grid = gen::grid(3);
+        let grid = gen::grid(3, &mut SeededSource::new(0));
         let grid = grid.unwrap();
-        assert!(grid.is_complete());
+        assert!(grid.is_solved());
         assert!(grid.is_uniquely_solvable());
     }
     #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
     fn test_grid_hang() {
-        for _ in 0..100 {
-            let grid = gen::grid(3).unwrap();
-            assert!(grid.is_complete());
+        for seed in 0..100 {
+            let grid = gen::grid(3, &mut SeededSource::new(seed)).unwrap();
+            assert!(grid.is_solved());
+        }
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_grid_varied() {
+        let grids = (0..5)
+            .map(|seed| gen::grid(3, &mut SeededSource::new(seed)).unwrap())
+            .collect::<Vec<_>>();
+        for (i, one) in grids.iter().enumerate() {
+            for two in grids.iter().skip(i + 1) {
+                assert_ne!(one, two);
+            }
+        }
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_seed_full_grid() {
+        for method in &[FillMethod::Random, FillMethod::Diagonal, FillMethod::Shift] {
+            for order in 1..=3 {
+                let grid = Sudoku::seed_full_grid(order, *method).unwrap();
+                assert!(grid.is_solved());
+            }
+        }
+        let one = Sudoku::seed_full_grid(3, FillMethod::Shift);
+        assert_eq!(one, Sudoku::seed_full_grid(3, FillMethod::Shift));
+        let one = Sudoku::seed_full_grid(3, FillMethod::Diagonal);
+        assert_eq!(one, Sudoku::seed_full_grid(3, FillMethod::Diagonal));
+    }
+    #[cfg_attr(not(feature = "2D"), test)]
+    #[cfg(not(feature = "2D"))]
+    fn test_seed_full_grid_3d() {
+        // The fixed fills lay out rows and columns, so they're 2D-only.
+        for method in &[FillMethod::Diagonal, FillMethod::Shift] {
+            for order in 1..=3 {
+                assert_eq!(Sudoku::seed_full_grid(order, *method), None);
+            }
+        }
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_complete_randomly() {
+        for order in 1..=3 {
+            let grid = Sudoku::new(order).complete_randomly(None).unwrap();
+            assert!(grid.is_solved());
+        }
+        let mut partial = Sudoku::new(3);
+        partial.substitute(Point([4, 4]), Some(Element(5)));
+        let grid = partial.complete_randomly(Some(7)).unwrap();
+        assert!(grid.is_solved());
+        assert_eq!(grid[Point([4, 4])], Some(Element(5)));
+        assert_eq!(partial.complete_randomly(Some(7)), Some(grid));
+        // Two fives in a row can't be completed.
+        partial.substitute(Point([0, 4]), Some(Element(5)));
+        assert_eq!(partial.complete_randomly(None), None);
+        // Neither can a cell left without candidates.
+        let mut stuck = Sudoku::new(2);
+        for (x, value) in (1..4).enumerate() {
+            stuck.substitute(Point([x as u8, 0]), Some(Element(value)));
+        }
+        stuck.substitute(Point([3, 1]), Some(Element(4)));
+        assert_eq!(stuck.complete_randomly(Some(0)), None);
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_with() {
+        for method in &[FillMethod::Diagonal, FillMethod::Shift] {
+            let generated = Sudoku::generate_with(3, Difficulty::Beginner, *method).unwrap();
+            assert!(generated.puzzle.is_uniquely_solvable());
+            assert!(generated.difficulty.is_playable());
+        }
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_smooth() {
+        for order in 2..=3 {
+            let generated =
+                generate_smooth_from(order, Difficulty::Easy, 2, &mut SeededSource::new(0))
+                    .unwrap();
+            let path = generated.puzzle.solve_path().unwrap();
+            assert!(path.iter().all(|(_, _, branch_factor)| *branch_factor <= 2));
+            // The recorded seed regenerates the puzzle.
+            let seed = generated.seed.unwrap();
+            let regenerated = Sudoku::generate_seeded(order, Difficulty::Easy, seed).unwrap();
+            assert_eq!(regenerated.puzzle, generated.puzzle);
+        }
+        // Every step has at least one candidate.
+        assert_eq!(
+            generate_smooth_from(2, Difficulty::Beginner, 0, &mut SeededSource::new(0)),
+            Err(Error::TooBranchy)
+        );
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_requiring() {
+        for _ in 0..3 {
+            let generated =
+                Sudoku::generate_requiring(3, Difficulty::Easy, Technique::HiddenSingle).unwrap();
+            assert!(generated.puzzle.is_uniquely_solvable());
+            let singles = generated.puzzle.deduce(&[Technique::NakedSingle]);
+            assert!(!singles.is_solved());
         }
+        // Every generated puzzle has something left to deduce.
+        let generated =
+            Sudoku::generate_requiring(2, Difficulty::Beginner, Technique::NakedSingle).unwrap();
+        assert!(!generated.puzzle.is_complete());
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_seeded_with_cache() {
+        let mut cache = UniquenessCache::new();
+        for seed in 0..2 {
+            let cached =
+                Sudoku::generate_seeded_with_cache(3, Difficulty::Beginner, seed, &mut cache)
+                    .unwrap();
+            assert_eq!(
+                cached,
+                Sudoku::generate_seeded(3, Difficulty::Beginner, seed).unwrap()
+            );
+        }
+        assert!(cache.hits() > 0);
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generated_seed() {
+        // Unseeded puzzles record the seed they were drawn from, which
+        // regenerates them.
+        let one = Sudoku::generate_with(3, Difficulty::Easy, FillMethod::Random).unwrap();
+        let seed = one.seed.unwrap();
+        assert_eq!(
+            Sudoku::generate_seeded(3, Difficulty::Easy, seed).unwrap(),
+            one
+        );
+        let two = Sudoku::generate_smooth(3, Difficulty::Easy, 9).unwrap();
+        assert_ne!(two.seed, one.seed);
+        assert_eq!(
+            Sudoku::generate_seeded(3, Difficulty::Easy, two.seed.unwrap()).unwrap(),
+            two
+        );
+        let daily = Sudoku::daily((2024, 6, 1), 2, Difficulty::Beginner).unwrap();
+        assert_eq!(
+            daily.seed,
+            Some(daily_seed((2024, 6, 1), 2, Difficulty::Beginner))
+        );
+        // Puzzles that don't depend on a seed alone record none.
+        assert_eq!(
+            Sudoku::generate_fast(3, Difficulty::Easy).unwrap().seed,
+            None
+        );
+        let mut pool = GridPool::with_capacity(1);
+        let pooled = Sudoku::generate_pooled(2, Difficulty::Beginner, &mut pool).unwrap();
+        assert_eq!(pooled.seed, None);
+    }
+    // The previous hardening algorithm (randomly removing pairs of clues
+    // until the target is hit), kept as the baseline for
+    // `test_harden_convergence`.
+    #[cfg(feature = "2D")]
+    fn legacy_harden<R: Source>(
+        sudoku: &mut Sudoku,
+        target: Difficulty,
+        source: &mut R,
+    ) -> Result<(), ()> {
+        let current = sudoku.score().unwrap();
+        let mut points = sudoku.points();
+        for _ in 0..20 {
+            if let (Some(one), Some(two)) = (
+                take_random(&mut points, source),
+                take_random(&mut points, source),
+            ) {
+                let (one, two) = (one.fold(sudoku.order), two.fold(sudoku.order));
+                let mut puzzle = sudoku.clone();
+                puzzle.elements[one] = None;
+                puzzle.elements[two] = None;
+                if let Some(score) = puzzle.score() {
+                    if score > current {
+                        let difficulty =
+                            Difficulty::from_score_with_boxes(score, sudoku.box_shape());
+                        if difficulty > target {
+                            continue;
+                        }
+                        sudoku.elements[one] = None;
+                        sudoku.elements[two] = None;
+                        return if difficulty == target {
+                            Ok(())
+                        } else {
+                            legacy_harden(sudoku, target, source)
+                        };
+                    }
+                }
+            }
+        }
+        Err(())
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_harden_convergence() {
+        // Order-2 puzzles score quickly enough to run many trials; the
+        // difficult band is where the two algorithms part ways.
+        let targets = [
+            Difficulty::Easy,
+            Difficulty::Intermediate,
+            Difficulty::Difficult,
+        ];
+        for target in &targets {
+            let (mut legacy, mut current) = (0, 0);
+            for seed in 0..50 {
+                let mut source = SeededSource::new(seed);
+                let grid = gen::grid(2, &mut source).unwrap();
+                let mut puzzle = grid.clone();
+                if legacy_harden(&mut puzzle, *target, &mut source.clone()).is_ok() {
+                    legacy += 1;
+                }
+                let mut puzzle = grid;
+                if gen::harden(
+                    &mut puzzle,
+                    *target,
+                    &[],
+                    &mut source,
+                    None,
+                    &mut Attempts::default(),
+                ) == HardenReport::TargetReached
+                {
+                    current += 1;
+                    assert_eq!(puzzle.difficulty(), Some(*target));
+                }
+                assert!(puzzle.is_uniquely_solvable());
+            }
+            assert!(
+                current >= legacy,
+                "{:?}: {} vs. {} of 50",
+                target,
+                current,
+                legacy
+            );
+            if *target == Difficulty::Difficult {
+                assert!(current > legacy, "{} vs. {} of 50", current, legacy);
+            }
+        }
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_harden_unreachable() {
+        // No order-2 puzzle is advanced, so the search must give up rather
+        // than loop, leaving a harder (but still unique) puzzle.
+        for seed in 0..5 {
+            let mut source = SeededSource::new(seed);
+            let mut puzzle = gen::grid(2, &mut source).unwrap();
+            let report = gen::harden(
+                &mut puzzle,
+                Difficulty::Advanced,
+                &[],
+                &mut source,
+                None,
+                &mut Attempts::default(),
+            );
+            assert_ne!(report, HardenReport::TargetReached);
+            assert!(puzzle.clue_count() < 16);
+            assert!(puzzle.is_uniquely_solvable());
+        }
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_other_solution_matches_general_search() {
+        // Replays clue removals from seeded grids, comparing each decision
+        // with a general two-solution search.
+        let (mut general_nodes, mut known_nodes) = (0, 0);
+        for seed in 0..3 {
+            let mut source = SeededSource::new(seed);
+            let solution = gen::grid(3, &mut source).unwrap();
+            let mut puzzle = solution.clone();
+            let mut clues = puzzle.points();
+            source.shuffle(&mut clues);
+            for point in clues.into_iter().take(50) {
+                puzzle.substitute(point, None);
+                let general = backtrack(&puzzle, 2, false, &mut Ascending);
+                let (other, nodes) = other_solution(&puzzle, &solution, &[point]);
+                assert_eq!(other, general.count > 1);
+                general_nodes += general.nodes;
+                known_nodes += nodes;
+                if other {
+                    puzzle.substitute(point, solution[point]);
+                }
+            }
+        }
+        assert!(
+            known_nodes * 2 < general_nodes,
+            "{} vs. {} nodes",
+            known_nodes,
+            general_nodes
+        );
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_quick_remove() {
+        let mut hits = 0;
+        for seed in 0..10 {
+            for order in 2..=3 {
+                for target in &[Difficulty::Beginner, Difficulty::Easy] {
+                    let mut source = SeededSource::new(seed);
+                    let grid = gen::grid(order, &mut source).unwrap();
+                    let quick = quick_remove(&grid, *target, &[], &mut source, None);
+                    let (puzzle, difficulty) = match quick {
+                        Some(quick) => quick,
+                        None => continue,
+                    };
+                    assert!(!puzzle.is_complete());
+                    assert_eq!(puzzle.solution().unwrap(), grid);
+                    assert_eq!(puzzle.difficulty(), Some(difficulty));
+                    assert!(difficulty <= *target);
+                    if difficulty == *target {
+                        hits += 1;
+                    }
+                }
+            }
+        }
+        assert!(hits > 0);
+        // The harder difficulties have no fast path.
+        let mut source = SeededSource::new(0);
+        let grid = gen::grid(3, &mut source).unwrap();
+        let quick = quick_remove(&grid, Difficulty::Intermediate, &[], &mut source, None);
+        assert_eq!(quick, None);
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_quick() {
+        for difficulty in &[Difficulty::Beginner, Difficulty::Easy] {
+            let generated = Sudoku::generate_quick(3, *difficulty, Some(1)).unwrap();
+            assert_eq!(generated.seed, Some(1));
+            assert_eq!(generated.puzzle.difficulty(), Some(generated.difficulty));
+            assert_eq!(generated.puzzle.solution().unwrap(), generated.solution);
+            let again = Sudoku::generate_quick(3, *difficulty, Some(1)).unwrap();
+            assert_eq!(again.puzzle, generated.puzzle);
+        }
+        let generated = Sudoku::generate_quick(2, Difficulty::Beginner, None).unwrap();
+        let seed = generated.seed.unwrap();
+        let again = Sudoku::generate_quick(2, Difficulty::Beginner, Some(seed)).unwrap();
+        assert_eq!(again.puzzle, generated.puzzle);
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_quick_remove_keeps_givens() {
+        let mut source = SeededSource::new(0);
+        let grid = gen::grid(2, &mut source).unwrap();
+        let keep = grid.points().into_iter().step_by(3).collect::<Vec<_>>();
+        for _ in 0..10 {
+            let quick = quick_remove(&grid, Difficulty::Easy, &keep, &mut source, None);
+            if let Some((puzzle, _)) = quick {
+                assert!(keep.iter().all(|point| puzzle[*point] == grid[*point]));
+            }
+        }
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_rect() {
+        for &(rows, columns) in &[(2, 3), (3, 2)] {
+            for difficulty in &[Difficulty::Beginner, Difficulty::Intermediate] {
+                let generated = Sudoku::generate_rect(rows, columns, *difficulty).unwrap();
+                let puzzle = &generated.puzzle;
+                assert_eq!(puzzle.box_shape(), (rows, columns));
+                assert!(!puzzle.is_complete());
+                assert!(puzzle.is_valid());
+                assert!(puzzle.is_uniquely_solvable());
+                assert_eq!(puzzle.solution().unwrap(), generated.solution);
+                assert!(generated.solution.is_solved());
+                assert_eq!(puzzle.difficulty(), Some(generated.difficulty));
+            }
+        }
+    }
+    #[test]
+    fn test_is_valid_date() {
+        assert!(is_valid_date(2024, 6, 1));
+        assert!(is_valid_date(2024, 2, 29));
+        assert!(is_valid_date(2000, 2, 29));
+        assert!(!is_valid_date(1900, 2, 29));
+        assert!(!is_valid_date(2023, 2, 29));
+        assert!(!is_valid_date(2024, 4, 31));
+        assert!(!is_valid_date(2024, 13, 1));
+        assert!(!is_valid_date(2024, 1, 0));
+    }
+    #[test]
+    fn test_daily_seed_vector() {
+        assert_eq!(
+            daily_seed((2024, 6, 1), 3, Difficulty::Beginner),
+            0x60A6_1F8B_2285_6A5D
+        );
+    }
+    #[test]
+    fn test_daily_invalid_date() {
+        let daily = Sudoku::daily((2023, 2, 29), 3, Difficulty::Beginner);
+        assert_eq!(daily, Err(Error::InvalidDate));
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_order_2() {
+        for _ in 0..20 {
+            let puzzle = Sudoku::generate(2, Difficulty::Beginner);
+            assert!(!puzzle.is_complete());
+            assert!(puzzle.is_uniquely_solvable());
+            assert_eq!(puzzle.difficulty(), Some(Difficulty::Beginner));
+        }
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_lowest_difficulty() {
+        for seed in 0..3 {
+            let generated = Sudoku::generate_seeded(3, Difficulty::Beginner, seed).unwrap();
+            assert_eq!(generated.difficulty, Difficulty::Beginner);
+            let generated = Sudoku::generate_seeded(3, Difficulty::Unplayable, seed).unwrap();
+            assert!(generated.difficulty.is_playable());
+            assert_eq!(generated.puzzle.difficulty(), Some(generated.difficulty));
+        }
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_cross_checked() {
+        let generated = Sudoku::generate_cross_checked(3, Difficulty::Easy).unwrap();
+        assert_eq!(generated.puzzle.solution().unwrap(), generated.solution);
+        assert_eq!(
+            propagating_solutions(&generated.puzzle, 2),
+            vec![generated.solution]
+        );
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_with_givens() {
+        let givens = [
+            (Point([0, 0]), Element(1)),
+            (Point([4, 4]), Element(2)),
+            (Point([8, 8]), Element(3)),
+            (Point([1, 4]), Element(3)),
+        ];
+        let generated = Sudoku::generate_with_givens(3, Difficulty::Easy, &givens).unwrap();
+        for (point, value) in &givens {
+            assert_eq!(generated.puzzle[*point], Some(*value));
+        }
+        assert!(generated.puzzle.is_uniquely_solvable());
+        assert!(!generated.puzzle.is_complete());
+        let conflicting = [(Point([0, 0]), Element(1)), (Point([5, 0]), Element(1))];
+        assert_eq!(
+            Sudoku::generate_with_givens(3, Difficulty::Easy, &conflicting),
+            Err(Error::InconsistentGivens)
+        );
+        let overwritten = [(Point([0, 0]), Element(1)), (Point([0, 0]), Element(2))];
+        assert_eq!(
+            Sudoku::generate_with_givens(3, Difficulty::Easy, &overwritten),
+            Err(Error::InconsistentGivens)
+        );
+        let outside = [(Point([9, 0]), Element(1))];
+        assert_eq!(
+            Sudoku::generate_with_givens(3, Difficulty::Easy, &outside),
+            Err(Error::InconsistentGivens)
+        );
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_stars() {
+        for stars in 1..=2 {
+            let rating = StarRating::new(stars).unwrap();
+            let generated = generate_stars_from(3, rating, &mut SeededSource::new(0)).unwrap();
+            assert_eq!(generated.difficulty.stars(), stars);
+            assert_eq!(generated.puzzle.difficulty(), Some(rating.difficulty()));
+        }
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_daily_repeatable() {
+        let one = Sudoku::daily((2024, 6, 1), 3, Difficulty::Beginner).unwrap();
+        let two = Sudoku::daily((2024, 6, 1), 3, Difficulty::Beginner).unwrap();
+        assert_eq!(one, two);
+        assert_eq!(one.puzzle.solution().unwrap(), one.solution);
+        let next = Sudoku::daily((2024, 6, 2), 3, Difficulty::Beginner).unwrap();
+        assert_ne!(one.puzzle, next.puzzle);
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_distribution() {
+        let counts = [
+            (Difficulty::Easy, 1),
+            (Difficulty::Beginner, 2),
+            (Difficulty::Easy, 1),
+        ];
+        let batch = generate_distribution_from(3, &counts, &mut SeededSource::new(0)).unwrap();
+        let difficulties = batch
+            .iter()
+            .map(|puzzle| puzzle.difficulty().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            difficulties,
+            vec![
+                Difficulty::Easy,
+                Difficulty::Easy,
+                Difficulty::Beginner,
+                Difficulty::Beginner,
+            ]
+        );
+        for (i, one) in batch.iter().enumerate() {
+            assert!(one.is_uniquely_solvable());
+            assert!(batch.iter().skip(i + 1).all(|two| one != two));
+        }
+        // No order-2 puzzle is advanced.
+        assert_eq!(
+            Sudoku::generate_distribution(2, &[(Difficulty::Advanced, 1)]),
+            Err(Error::UnreachableDistribution)
+        );
+        assert_eq!(Sudoku::generate_distribution(2, &[]), Ok(vec![]));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_with_clues() {
+        let puzzle = generate_with_clues_from(3, 30, &mut SeededSource::new(0)).unwrap();
+        assert_eq!(puzzle.clue_count(), 30);
+        assert!(puzzle.is_uniquely_solvable());
+        let puzzle = generate_with_clues_from(2, 6, &mut SeededSource::new(0)).unwrap();
+        assert_eq!(puzzle.clue_count(), 6);
+        assert!(puzzle.is_uniquely_solvable());
+        for clues in &[16, 100] {
+            let grid = generate_with_clues_from(2, *clues, &mut SeededSource::new(0)).unwrap();
+            assert!(grid.is_complete() && grid.is_solved());
+        }
+        // Below the proven minimum, this fails straight away rather than
+        // stripping grids in vain.
+        let error = Sudoku::generate_with_clues(3, 16).unwrap_err();
+        assert_eq!(
+            error,
+            Error::TooFewClues {
+                order: 3,
+                clues: 16,
+                minimum: 17
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "no order-3 puzzle has a unique solution with 16 clues (at least 17 are needed)"
+        );
+        assert!(matches!(
+            Sudoku::generate_with_clues(2, 3),
+            Err(Error::TooFewClues { minimum: 4, .. })
+        ));
+        assert_eq!(
+            Sudoku::generate_with_clues(0, 0),
+            Err(Error::UnsupportedOrder(0))
+        );
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_novel() {
+        let mut seen = HashSet::new();
+        let first =
+            generate_novel_from(2, Difficulty::Beginner, &seen, &mut SeededSource::new(0)).unwrap();
+        assert!(seen.insert(first.fingerprint()));
+        // The same seed now has to move past its first puzzle.
+        let novel =
+            generate_novel_from(2, Difficulty::Beginner, &seen, &mut SeededSource::new(0)).unwrap();
+        assert!(!seen.contains(&novel.fingerprint()));
+        assert_ne!(novel, first);
+        assert!(novel.is_uniquely_solvable());
+        let novel = Sudoku::generate_novel(2, Difficulty::Beginner, &seen).unwrap();
+        assert!(!seen.contains(&novel.fingerprint()));
+        assert_eq!(
+            Sudoku::generate_novel(0, Difficulty::Beginner, &seen),
+            Err(Error::UnsupportedOrder(0))
+        );
     }
 }
@@ -0,0 +1,370 @@
+//! A generator that does its work in small steps, for driving from an event
+//! loop (such as the browser's) without blocking it.
+//!
+//! # Failure model
+//! Every error inside a step (an order that isn't supported, a source of
+//! randomness that fails, a puzzle that unexpectedly can't be scored, or a
+//! search that runs out of budget without producing a playable puzzle) moves
+//! the generator to [`Failed`](enum.GeneratorStatus.html#variant.Failed) for
+//! good: later steps return the same status without doing any work. Nothing
+//! in a step panics on these errors, and the grid and search state are
+//! dropped as soon as the generator finishes, keeping only the best puzzle
+//! so far for [`abort`](struct.Generator.html#method.abort).
+use crate::dimensions::validate_order;
+use crate::gen::{grid, Error, MAX_HARDEN_EVALUATIONS};
+use crate::random::{entropy_seed, SeededSource, Source};
+use crate::sol::other_solution;
+use crate::Difficulty;
+use crate::Generated;
+use crate::Grid;
+use crate::Point;
+use crate::Score;
+use crate::Sudoku;
+
+use std::mem;
+
+/// Where a [`Generator`](struct.Generator.html) stands after a step.
+///
+/// More states may be added in future releases, so matches outside this
+/// crate need a wildcard arm.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum GeneratorStatus {
+    /// The generator needs more steps.
+    Working,
+    /// The generator finished with a puzzle.
+    Done(Generated),
+    /// The generator failed, and won't do any more work.
+    Failed(Error),
+}
+
+/// Generates a puzzle one step at a time.
+///
+/// The first step fills a complete grid, and each later step tries to remove
+/// one clue from it (keeping the puzzle uniquely solvable without
+/// overshooting the target difficulty), until the target is reached or every
+/// clue has been tried. Each removal that's kept is scored, and the search
+/// stops early once it has scored as many puzzles as its budget allows.
+///
+/// As with [`Generate`](trait.Generate.html), asking for an
+/// [`Unplayable`](enum.Difficulty.html#variant.Unplayable) puzzle yields a
+/// [`Beginner`](enum.Difficulty.html#variant.Beginner) one.
+#[derive(Clone, Debug)]
+pub struct Generator {
+    order: u8,
+    target: Difficulty,
+    // The number of puzzles the search may still score.
+    budget: usize,
+    source: Randomness,
+    // The seed the source started from, recorded with the finished puzzle.
+    seed: Option<u64>,
+    phase: Phase,
+    // The hardest puzzle with at least one clue removed so far.
+    best: Option<(Sudoku, Difficulty)>,
+}
+
+#[derive(Clone, Debug)]
+enum Phase {
+    Filling,
+    Hardening {
+        grid: Sudoku,
+        puzzle: Sudoku,
+        // The clues not yet tried.
+        pending: Vec<Point>,
+    },
+    Finished(GeneratorStatus),
+}
+
+/// A source of randomness that remembers whether it has failed, rather than
+/// panicking.
+#[derive(Clone, Debug)]
+struct Randomness {
+    kind: Kind,
+    failed: bool,
+}
+
+#[derive(Clone, Debug)]
+enum Kind {
+    Seeded(SeededSource),
+    // No seed could be drawn from entropy, so every draw fails.
+    Unseeded,
+    // Fails after the given number of draws.
+    #[cfg(all(test, feature = "2D"))]
+    Faulty(SeededSource, usize),
+}
+
+impl Source for Randomness {
+    fn next_u64(&mut self) -> u64 {
+        let next = match &mut self.kind {
+            Kind::Seeded(source) => source.try_next_u64(),
+            Kind::Unseeded => None,
+            #[cfg(all(test, feature = "2D"))]
+            Kind::Faulty(source, remaining) => {
+                if *remaining == 0 {
+                    None
+                } else {
+                    *remaining -= 1;
+                    source.try_next_u64()
+                }
+            }
+        };
+        next.unwrap_or_else(|| {
+            self.failed = true;
+            0
+        })
+    }
+}
+
+impl Generator {
+    /// Creates a generator for a puzzle of the desired order and difficulty,
+    /// seeded from entropy.
+    ///
+    /// The seed is recorded with the finished puzzle, and
+    /// [`seeded`](#method.seeded) takes it to the same puzzle again.
+    pub fn new(order: u8, difficulty: Difficulty) -> Self {
+        match entropy_seed() {
+            Some(seed) => Self::seeded(order, difficulty, seed),
+            None => Self::with_kind(order, difficulty, Kind::Unseeded),
+        }
+    }
+
+    /// Creates a generator for a puzzle of the desired order and difficulty,
+    /// drawing from the given seed.
+    ///
+    /// The same seed always takes the same steps to the same puzzle.
+    pub fn seeded(order: u8, difficulty: Difficulty, seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            ..Self::with_kind(order, difficulty, Kind::Seeded(SeededSource::new(seed)))
+        }
+    }
+
+    fn with_kind(order: u8, difficulty: Difficulty, kind: Kind) -> Self {
+        Self {
+            order,
+            target: difficulty.max(Difficulty::Beginner),
+            budget: MAX_HARDEN_EVALUATIONS,
+            source: Randomness {
+                kind,
+                failed: false,
+            },
+            seed: None,
+            phase: Phase::Filling,
+            best: None,
+        }
+    }
+
+    /// Limits the number of puzzles the search may score (200 by default).
+    pub fn with_budget(mut self, evaluations: usize) -> Self {
+        self.budget = evaluations;
+        self
+    }
+
+    /// Does the next piece of work, returning where the generator stands.
+    ///
+    /// Once the generator has finished, this returns the same status without
+    /// doing any more work.
+    pub fn step(&mut self) -> GeneratorStatus {
+        let phase = mem::replace(&mut self.phase, Phase::Filling);
+        self.phase = match phase {
+            Phase::Filling => self.fill(),
+            Phase::Hardening {
+                grid,
+                puzzle,
+                pending,
+            } => self.harden(grid, puzzle, pending),
+            finished @ Phase::Finished(_) => finished,
+        };
+        match &self.phase {
+            Phase::Finished(status) => status.clone(),
+            _ => GeneratorStatus::Working,
+        }
+    }
+
+    /// Stops the generator, returning the best puzzle it produced (the
+    /// finished puzzle, or the hardest it reached before stopping), if any.
+    ///
+    /// The puzzle always has a unique solution, but may fall short of the
+    /// target difficulty (or even be unplayable). A generator stopped before
+    /// removing any clues returns `None`.
+    pub fn abort(self) -> Option<Sudoku> {
+        match self.phase {
+            Phase::Finished(GeneratorStatus::Done(generated)) => Some(generated.puzzle),
+            _ => self.best.map(|(puzzle, _)| puzzle),
+        }
+    }
+
+    fn fill(&mut self) -> Phase {
+        if let Err(error) = validate_order(self.order) {
+            return Phase::Finished(GeneratorStatus::Failed(error.into()));
+        }
+        let grid = grid(self.order, &mut self.source);
+        if self.source.failed {
+            return Phase::Finished(GeneratorStatus::Failed(Error::EntropyFailed));
+        }
+        match grid {
+            Some(grid) => Phase::Hardening {
+                puzzle: grid.clone(),
+                pending: grid.points(),
+                grid,
+            },
+            None => Phase::Finished(GeneratorStatus::Failed(Error::NoGrid)),
+        }
+    }
+
+    fn harden(&mut self, grid: Sudoku, mut puzzle: Sudoku, mut pending: Vec<Point>) -> Phase {
+        if pending.is_empty() || self.budget == 0 {
+            return self.finish(grid);
+        }
+        let point = pending.swap_remove(self.source.below(pending.len()));
+        if self.source.failed {
+            return Phase::Finished(GeneratorStatus::Failed(Error::EntropyFailed));
+        }
+        puzzle.substitute(point, None);
+        if other_solution(&puzzle, &grid, &[point]).0 {
+            puzzle.substitute(point, grid[point]);
+        } else {
+            self.budget -= 1;
+            let score = match puzzle.score() {
+                Some(score) => score,
+                None => return Phase::Finished(GeneratorStatus::Failed(Error::Unsolvable)),
+            };
+            let difficulty = Difficulty::from_score(score, self.order);
+            if difficulty > self.target {
+                puzzle.substitute(point, grid[point]);
+            } else {
+                self.best = Some((puzzle.clone(), difficulty));
+                if difficulty == self.target {
+                    return self.finish(grid);
+                }
+            }
+        }
+        Phase::Hardening {
+            grid,
+            puzzle,
+            pending,
+        }
+    }
+
+    fn finish(&mut self, solution: Sudoku) -> Phase {
+        Phase::Finished(match &self.best {
+            Some((puzzle, difficulty)) if difficulty.is_playable() => {
+                GeneratorStatus::Done(Generated {
+                    puzzle: puzzle.clone(),
+                    solution,
+                    difficulty: *difficulty,
+                    seed: self.seed,
+                })
+            }
+            _ => GeneratorStatus::Failed(Error::Unplayable),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "2D")]
+    use crate::gen::Error;
+    #[cfg(feature = "2D")]
+    use crate::incremental::{Generator, GeneratorStatus, Kind};
+    #[cfg(feature = "2D")]
+    use crate::random::SeededSource;
+    #[cfg(feature = "2D")]
+    use crate::Difficulty;
+    #[cfg(feature = "2D")]
+    use crate::Solve;
+
+    #[cfg(feature = "2D")]
+    fn faulty(draws: usize) -> Generator {
+        Generator::with_kind(
+            3,
+            Difficulty::Beginner,
+            Kind::Faulty(SeededSource::new(0), draws),
+        )
+    }
+
+    /// Steps the generator until it finishes.
+    #[cfg(feature = "2D")]
+    fn run(generator: &mut Generator) -> GeneratorStatus {
+        for _ in 0..1000 {
+            match generator.step() {
+                GeneratorStatus::Working => {}
+                status => return status,
+            }
+        }
+        panic!("the generator didn't finish");
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generator() {
+        let mut generator = Generator::seeded(3, Difficulty::Beginner, 0);
+        let generated = match run(&mut generator) {
+            GeneratorStatus::Done(generated) => generated,
+            other => panic!("unexpected status: {:?}", other),
+        };
+        assert_eq!(generated.difficulty, Difficulty::Beginner);
+        assert_eq!(generated.puzzle.solution().unwrap(), generated.solution);
+        assert_eq!(generated.seed, Some(0));
+        // Finished generators stay finished.
+        assert_eq!(generator.step(), GeneratorStatus::Done(generated.clone()));
+        assert_eq!(generator.abort(), Some(generated.puzzle));
+        let mut generator = Generator::seeded(0, Difficulty::Beginner, 0);
+        assert_eq!(
+            generator.step(),
+            GeneratorStatus::Failed(Error::UnsupportedOrder(0))
+        );
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generator_entropy_failure() {
+        // Failing while filling the grid.
+        let mut generator = faulty(0);
+        let failed = GeneratorStatus::Failed(Error::EntropyFailed);
+        assert_eq!(generator.step(), failed);
+        assert_eq!(generator.step(), failed);
+        assert_eq!(generator.abort(), None);
+        // Failing while hardening: count the draws filling takes, and allow
+        // a few more.
+        let mut generator = faulty(usize::MAX);
+        assert_eq!(generator.step(), GeneratorStatus::Working);
+        let fill = match generator.source.kind {
+            Kind::Faulty(_, remaining) => usize::MAX - remaining,
+            _ => unreachable!(),
+        };
+        let mut generator = faulty(fill + 10);
+        assert_eq!(run(&mut generator), failed);
+        assert_eq!(generator.step(), failed);
+        let partial = generator.abort().unwrap();
+        assert!(partial.clue_count() < 81);
+        assert!(partial.is_uniquely_solvable());
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generator_budget() {
+        // No budget to score anything: nothing playable comes of it.
+        let mut generator = Generator::seeded(3, Difficulty::Beginner, 0).with_budget(0);
+        assert_eq!(generator.step(), GeneratorStatus::Working);
+        assert_eq!(generator.step(), GeneratorStatus::Failed(Error::Unplayable));
+        assert_eq!(generator.abort(), None);
+        // Running out mid-search still leaves a partly hardened puzzle.
+        let mut generator = Generator::seeded(3, Difficulty::Advanced, 0).with_budget(5);
+        assert_eq!(
+            run(&mut generator),
+            GeneratorStatus::Failed(Error::Unplayable)
+        );
+        let partial = generator.abort().unwrap();
+        assert_eq!(partial.clue_count(), 76);
+        assert!(partial.is_uniquely_solvable());
+        // As does stopping the generator on the way.
+        let mut generator = Generator::seeded(3, Difficulty::Advanced, 0);
+        for _ in 0..20 {
+            assert_eq!(generator.step(), GeneratorStatus::Working);
+        }
+        let partial = generator.abort().unwrap();
+        assert!(partial.clue_count() < 81);
+        assert!(partial.is_uniquely_solvable());
+    }
+}
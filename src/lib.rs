@@ -15,20 +15,79 @@
 #[cfg(all(feature = "use_stdweb", feature = "use_rand"))]
 compile_error!("use_stdweb and use_rand are mutually exclusive.");
 
+#[cfg(feature = "2D")]
+mod alphabet;
+pub mod analysis;
+#[cfg(feature = "rayon")]
+pub mod bulk;
+mod bytes;
+mod cache;
+mod capabilities;
+#[cfg(feature = "2D")]
+mod cellref;
+#[cfg(all(feature = "2D", any(test, feature = "bench-corpus")))]
+pub mod corpus;
+#[cfg(feature = "2D")]
+mod diff;
 mod dimensions;
+#[cfg(feature = "2D")]
+mod fast;
 mod gen;
+mod incremental;
+mod links;
+#[cfg(feature = "2D")]
+mod load;
+mod locked;
+mod logic;
+mod meta;
+mod minimize;
+mod options;
+mod pool;
 mod puzzle;
+mod random;
+#[cfg(feature = "2D")]
+mod sample;
 mod sol;
 mod sudoku;
+mod table;
 
-#[cfg(feature = "ui")]
-extern crate num_traits;
 #[cfg(feature = "ui")]
 pub mod ui;
 
-pub use crate::gen::Generate;
+#[cfg(feature = "2D")]
+pub use crate::alphabet::{Alphabet, Error as AlphabetError};
+pub use crate::bytes::Error as DecodeError;
+pub use crate::cache::{ScoreCache, ScoredSudoku, SharedUniquenessCache, UniquenessCache};
+pub use crate::capabilities::{capabilities, Capabilities, SCORING_VERSION};
+#[cfg(feature = "2D")]
+pub use crate::cellref::{Error as RefParseError, RefStyle};
+#[cfg(feature = "2D")]
+pub use crate::diff::{DiffRenderOptions, Error as DiffError};
+pub use crate::gen::{Error as GenerateError, FillMethod, Generate, Generated};
+pub use crate::incremental::{Generator, GeneratorStatus};
+pub use crate::links::Links;
+#[cfg(feature = "2D")]
+pub use crate::load::{Error as LoadError, PuzzleSource, MAX_INPUT_LEN};
+pub use crate::locked::{Error as LockError, LockedSudoku};
+pub use crate::logic::{
+    Deductions, Elimination, ParseTechniqueError, Placement, Reduced, Technique,
+};
+pub use crate::meta::{PuzzleMeta, Symmetry};
+pub use crate::minimize::ClueImpact;
+pub use crate::options::{GenerateOptions, GeneratePreset, ParsePresetError};
+pub use crate::pool::GridPool;
 pub use crate::puzzle::Puzzle;
-pub use crate::sol::{Difficulty, Error as SolveError, Score, Solve};
-pub use crate::sudoku::{Element, Grid, Group, ParseError, Point, Sudoku};
+#[cfg(feature = "2D")]
+pub use crate::sample::{Provenance, Sampled, Sampler, SamplerSource};
+pub use crate::sol::{
+    BacktrackStrategy, Difficulty, Error as SolveError, FilterDifficulty, ParseDifficultyError,
+    Score, Solve, SolvePath, StarRating, SudokuIterExt,
+};
+pub use crate::sudoku::{
+    Element, Grid, Group, GroupKind, ParseElementError, ParseError, Point, Sudoku, Violation,
+};
+
+#[doc(hidden)]
+pub use crate::table::{__table_order, __table_sudoku};
 
-pub use crate::dimensions::DIMENSIONS;
+pub use crate::dimensions::{OrderError, DIMENSIONS, MAX_ORDER, MIN_ORDER};
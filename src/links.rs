@@ -0,0 +1,331 @@
+//! Links between the cells that can hold a value, the building block of
+//! techniques like simple coloring and (eventually) chains.
+use crate::sol::PossibilityMap;
+use crate::sudoku::Shape;
+use crate::Grid;
+use crate::Point;
+use crate::Sudoku;
+use crate::DIMENSIONS;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The candidates of each cell of a puzzle, as bitmasks (bit `v - 1` is set
+/// if `v` is a candidate).
+///
+/// Filled cells have no candidates.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Candidates {
+    pub(crate) shape: Shape,
+    pub(crate) masks: Vec<u64>,
+}
+
+impl Candidates {
+    /// Finds the candidates of each empty cell of the puzzle (the values not
+    /// yet used in any of its groups).
+    pub(crate) fn of(sudoku: &Sudoku) -> Self {
+        let map = PossibilityMap::from(sudoku.clone());
+        let masks = sudoku
+            .points()
+            .into_iter()
+            .map(|point| map[point].map_or(0, |set| set.values))
+            .collect();
+        Self {
+            shape: sudoku.shape(),
+            masks,
+        }
+    }
+
+    /// Whether the value is a candidate for the cell.
+    pub(crate) fn contains(&self, point: Point, value: u8) -> bool {
+        (1..=64).contains(&value) && self.masks[self.index(point)] & (1 << (value - 1)) != 0
+    }
+
+    /// Removes the value from the cell's candidates, returning whether it
+    /// was one.
+    pub(crate) fn remove(&mut self, point: Point, value: u8) -> bool {
+        let had = self.contains(point, value);
+        let index = self.index(point);
+        self.masks[index] &= !(1 << (value - 1));
+        had
+    }
+
+    /// The index of the cell's mask.
+    pub(crate) fn index(&self, point: Point) -> usize {
+        self.shape.fold(point).expect("point lies outside the grid")
+    }
+}
+
+/// Returns every group of a grid of the given shape, as lists of points:
+/// the boxes, then the columns, then the rows, then the lines along each
+/// further axis.
+///
+/// This order is part of the crate's contract (deductions, and so hints,
+/// depend on it): within each kind, groups are ordered by the
+/// [fold](struct.Point.html#method.fold) of their first cell, and the cells
+/// of each group are in fold order.
+pub(crate) fn all_groups(shape: Shape) -> Vec<Vec<Point>> {
+    let mut boxes = BTreeMap::new();
+    let mut lines = BTreeMap::new();
+    for point in shape.points() {
+        boxes
+            .entry(shape.fold(shape.snap(point)))
+            .or_insert_with(Vec::new)
+            .push(point);
+        for (rank, axis) in line_axes().enumerate() {
+            let mut key = point;
+            key[axis] = 0;
+            lines
+                .entry((rank, shape.fold(key)))
+                .or_insert_with(Vec::new)
+                .push(point);
+        }
+    }
+    boxes.into_values().chain(lines.into_values()).collect()
+}
+
+/// The axes lines run along, in contract order: columns (along y), rows
+/// (along x), then each further axis.
+fn line_axes() -> impl Iterator<Item = usize> {
+    (0..DIMENSIONS).map(|i| match i {
+        0 => 1,
+        1 => 0,
+        _ => i,
+    })
+}
+
+/// The links between the cells that can hold a given value.
+///
+/// Two cells are *weakly* linked if they share a group and both can hold the
+/// value (so at most one of them does), and *strongly* linked if they are
+/// the only two cells of some group that can hold the value (so exactly one
+/// of them does). Every strong link is also a weak link.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Links {
+    value: u8,
+    strong: BTreeMap<Point, BTreeSet<Point>>,
+    weak: BTreeMap<Point, BTreeSet<Point>>,
+}
+
+impl Links {
+    /// Computes the links for the value from the given candidates.
+    pub(crate) fn new(candidates: &Candidates, value: u8) -> Self {
+        let mut links = Self {
+            value,
+            strong: BTreeMap::new(),
+            weak: BTreeMap::new(),
+        };
+        for group in all_groups(candidates.shape) {
+            let cells = group
+                .into_iter()
+                .filter(|point| candidates.contains(*point, value))
+                .collect::<Vec<_>>();
+            for (i, a) in cells.iter().enumerate() {
+                for b in &cells[i + 1..] {
+                    Self::connect(&mut links.weak, *a, *b);
+                }
+            }
+            if let [a, b] = cells[..] {
+                Self::connect(&mut links.strong, a, b);
+            }
+        }
+        links
+    }
+
+    fn connect(links: &mut BTreeMap<Point, BTreeSet<Point>>, a: Point, b: Point) {
+        let _ = links.entry(a).or_default().insert(b);
+        let _ = links.entry(b).or_default().insert(a);
+    }
+
+    /// Returns the value the links are for.
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+
+    /// Returns the cells that can hold the value and share a group with
+    /// another such cell, in ascending order.
+    pub fn cells(&self) -> Vec<Point> {
+        self.weak.keys().cloned().collect()
+    }
+
+    /// Whether the cells are strongly linked.
+    pub fn is_strong(&self, a: Point, b: Point) -> bool {
+        self.strong.get(&a).is_some_and(|set| set.contains(&b))
+    }
+
+    /// Whether the cells are weakly linked.
+    pub fn is_weak(&self, a: Point, b: Point) -> bool {
+        self.weak.get(&a).is_some_and(|set| set.contains(&b))
+    }
+
+    /// Returns the cells strongly linked to the cell, in ascending order.
+    pub fn strong_neighbors(&self, point: Point) -> Vec<Point> {
+        self.strong
+            .get(&point)
+            .map_or_else(Vec::new, |set| set.iter().cloned().collect())
+    }
+
+    /// Returns the cells weakly linked to the cell, in ascending order.
+    pub fn weak_neighbors(&self, point: Point) -> Vec<Point> {
+        self.weak
+            .get(&point)
+            .map_or_else(Vec::new, |set| set.iter().cloned().collect())
+    }
+
+    /// Iterates over the strong links, each once (with the smaller point
+    /// first), in ascending order.
+    pub fn strong(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        Self::pairs(&self.strong)
+    }
+
+    /// Iterates over the weak links, each once (with the smaller point
+    /// first), in ascending order.
+    pub fn weak(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        Self::pairs(&self.weak)
+    }
+
+    fn pairs(
+        links: &BTreeMap<Point, BTreeSet<Point>>,
+    ) -> impl Iterator<Item = (Point, Point)> + '_ {
+        links
+            .iter()
+            .flat_map(|(a, set)| set.iter().filter(move |b| *b > a).map(move |b| (*a, *b)))
+    }
+
+    /// Splits the cells with strong links into connected components (by
+    /// strong links alone), each listed in ascending order.
+    ///
+    /// Components are listed in order of their smallest cell.
+    pub fn strong_components(&self) -> Vec<Vec<Point>> {
+        let mut seen = BTreeSet::new();
+        let mut components = vec![];
+        for start in self.strong.keys() {
+            if !seen.insert(*start) {
+                continue;
+            }
+            let mut component = vec![*start];
+            let mut next = 0;
+            while next < component.len() {
+                for neighbor in &self.strong[&component[next]] {
+                    if seen.insert(*neighbor) {
+                        component.push(*neighbor);
+                    }
+                }
+                next += 1;
+            }
+            component.sort();
+            components.push(component);
+        }
+        components
+    }
+}
+
+impl Sudoku {
+    /// Computes the links between the empty cells that can hold the value
+    /// (see [`Links`](struct.Links.html)).
+    ///
+    /// A cell can hold a value if it isn't used anywhere in the cell's
+    /// groups.
+    pub fn links_for(&self, value: u8) -> Links {
+        Links::new(&Candidates::of(self), value)
+    }
+
+    /// Splits the empty cells that can hold the value into the connected
+    /// components of its strong links (see
+    /// [`Links::strong_components`](struct.Links.html#method.strong_components)).
+    ///
+    /// Within a component, the cells alternate between holding the value and
+    /// not, which is the basis of simple coloring.
+    pub fn strong_link_components(&self, value: u8) -> Vec<Vec<Point>> {
+        self.links_for(value).strong_components()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::links::all_groups;
+    use crate::sudoku::Shape;
+    #[cfg(feature = "2D")]
+    use crate::Point;
+    #[cfg(feature = "2D")]
+    use crate::Sudoku;
+    use crate::DIMENSIONS;
+
+    #[test]
+    fn test_all_groups() {
+        let groups = all_groups(Shape::square(2));
+        // The boxes, then the lines along each axis.
+        let dimensions = DIMENSIONS as u32;
+        assert_eq!(
+            groups.len(),
+            2usize.pow(dimensions) + DIMENSIONS * 4usize.pow(dimensions - 1)
+        );
+        let boxes = 2usize.pow(dimensions);
+        assert!(groups[..boxes].iter().all(|group| group.len() == boxes));
+        assert!(groups[boxes..].iter().all(|group| group.len() == 4));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_all_groups_order_contract() {
+        // Groups of an order-2 grid by the folds of their cells: the boxes,
+        // then the columns, then the rows.
+        let expected = vec![
+            vec![0, 1, 4, 5],
+            vec![2, 3, 6, 7],
+            vec![8, 9, 12, 13],
+            vec![10, 11, 14, 15],
+            vec![0, 4, 8, 12],
+            vec![1, 5, 9, 13],
+            vec![2, 6, 10, 14],
+            vec![3, 7, 11, 15],
+            vec![0, 1, 2, 3],
+            vec![4, 5, 6, 7],
+            vec![8, 9, 10, 11],
+            vec![12, 13, 14, 15],
+        ];
+        let groups = all_groups(Shape::square(2))
+            .into_iter()
+            .map(|group| group.into_iter().map(|p| p.fold(2)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(groups, expected);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_links() {
+        // Row 1 can only hold 1 in its first two cells, and the top-right
+        // box only in its bottom two.
+        let puzzle: Sudoku = "_ _ 2 3\n\
+                              _ 3 _ _\n\
+                              _ _ _ _\n\
+                              _ _ _ _\n"
+            .parse()
+            .unwrap();
+        let links = puzzle.links_for(1);
+        assert_eq!(links.value(), 1);
+        assert!(links.is_strong(Point([0, 0]), Point([1, 0])));
+        assert!(links.is_weak(Point([0, 0]), Point([1, 0])));
+        assert!(links.is_strong(Point([2, 1]), Point([3, 1])));
+        // The first column has four cells that can hold 1.
+        assert!(links.is_weak(Point([0, 0]), Point([0, 3])));
+        assert!(!links.is_strong(Point([0, 0]), Point([0, 3])));
+        assert!(!links.is_weak(Point([0, 0]), Point([3, 3])));
+        assert_eq!(links.strong_neighbors(Point([2, 1])), vec![Point([3, 1])]);
+        assert_eq!(links.weak_neighbors(Point([3, 3])).len(), 6);
+        assert!(links.strong().all(|(a, b)| a < b && links.is_weak(a, b)));
+        assert_eq!(
+            links.weak().count(),
+            links
+                .weak()
+                .collect::<std::collections::BTreeSet<_>>()
+                .len()
+        );
+        assert_eq!(
+            puzzle.strong_link_components(1),
+            vec![
+                vec![Point([0, 0]), Point([1, 0])],
+                vec![Point([2, 1]), Point([3, 1])],
+            ]
+        );
+    }
+}
@@ -0,0 +1,688 @@
+//! Loading puzzles from strings, files, readers, and the bundled fixtures.
+//!
+//! The textual format is detected automatically; see
+//! [`PuzzleSource::load`](enum.PuzzleSource.html#method.load) for the formats
+//! understood.
+use crate::dimensions::validate_order;
+use crate::sudoku::{infer_shape, parse_boxes_header, BOXES_HEADER};
+use crate::Element;
+use crate::ParseError;
+use crate::Point;
+use crate::Sudoku;
+
+use std::{
+    fmt,
+    fs::File,
+    io::{Error as IoError, Read},
+    path::PathBuf,
+};
+
+/// The largest input (in bytes) that will be read from a single source.
+///
+/// This comfortably fits a collection of several hundred order-3 puzzles
+/// (or a single grid of the largest order), while keeping a runaway reader
+/// from exhausting memory.
+pub const MAX_INPUT_LEN: usize = 1 << 18;
+
+// The puzzles bundled with the crate, by name.
+const EMBEDDED: &[(&str, &str)] = &[
+    ("2D-O3", include_str!("../tests/sudokus/solvable/2D-O3.txt")),
+    ("2D-O4", include_str!("../tests/sudokus/solvable/2D-O4.txt")),
+];
+
+/// Somewhere to get puzzles from.
+pub enum PuzzleSource<'a> {
+    /// A borrowed string.
+    Str(&'a str),
+    /// An owned string.
+    String(String),
+    /// A file on disk.
+    Path(PathBuf),
+    /// Anything readable (e.g. standard input).
+    Reader(Box<dyn Read + 'a>),
+    /// One of the puzzles bundled with the crate, by name (e.g. `"2D-O3"`).
+    Embedded(&'a str),
+}
+
+/// Encodes errors encountered while loading a puzzle.
+///
+/// The first associated value of each variant describes the source (e.g. the
+/// file path).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The source couldn't be read.
+    Io(String, IoError),
+    /// The source is longer than [`MAX_INPUT_LEN`](constant.MAX_INPUT_LEN.html).
+    TooLarge(String),
+    /// The source's contents couldn't be parsed.
+    Parse(String, ParseError),
+    /// No bundled puzzle has the given name.
+    UnknownEmbedded(String),
+}
+
+// The textual formats we understand.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    // Rows of space-separated values, with `_` for empty cells (the format
+    // produced by `Display`).
+    Grid,
+    // The whole puzzle on one line, one character per cell.
+    Line,
+    // SadMan Sudoku: optional `#` comment lines, then one line of characters
+    // per row.
+    Sdk,
+    // Any run of digits and blanks, ignoring everything else.
+    Lenient,
+}
+
+impl<'a> PuzzleSource<'a> {
+    /// Loads a single puzzle from the source.
+    ///
+    /// The following formats are detected:
+    ///
+    /// - the grid format produced by `Display` (rows of space-separated
+    ///   values, `_` for empty cells);
+    /// - a single line with one character per cell (`1`–`9`, then `A`
+    ///   onward for larger values; `.` or `0` for empty cells);
+    /// - the SadMan Sudoku (`.sdk`) format: `#` comment lines followed by one
+    ///   line of such characters per row.
+    ///
+    /// In every format, a `#boxes: RxC` line (as written by `Display` for
+    /// grids with rectangular boxes) gives the box shape; without one, the
+    /// shape is inferred from the axis length as by `FromStr`.
+    pub fn load(self) -> Result<Sudoku, Error> {
+        self.load_with(false)
+    }
+
+    /// Loads a single puzzle from the source, falling back to picking out
+    /// digits and blanks (`.`, `_` or `0`) and ignoring everything else if
+    /// the input isn't in any of the formats understood by
+    /// [`load`](#method.load).
+    ///
+    /// This accepts hand-drawn grids with borders, at the cost of accepting
+    /// some garbage too.
+    pub fn load_lenient(self) -> Result<Sudoku, Error> {
+        self.load_with(true)
+    }
+
+    /// Loads every puzzle from the source.
+    ///
+    /// Puzzles are separated by blank lines; single-line puzzles may also
+    /// simply be listed one per line.
+    ///
+    /// A block of lines without spaces that is as tall as it is wide could be
+    /// either one grid or a list of single-line puzzles. If both readings
+    /// fit (e.g. sixteen lines of sixteen characters), this fails with
+    /// [`InferenceAmbiguous`](enum.ParseError.html#variant.InferenceAmbiguous);
+    /// use [`load_collection_with_order`](#method.load_collection_with_order)
+    /// to choose.
+    pub fn load_collection(self) -> Result<Vec<Sudoku>, Error> {
+        self.collection_with(None)
+    }
+
+    /// Loads a single puzzle of the given order from the source (like
+    /// [`load`](#method.load)), failing with
+    /// [`OrderMismatch`](enum.ParseError.html#variant.OrderMismatch) if it
+    /// has another order.
+    pub fn load_with_order(self, order: u8) -> Result<Sudoku, Error> {
+        let (name, text) = self.read()?;
+        parse_hinted(&text, false, Some(order)).map_err(|error| Error::Parse(name, error))
+    }
+
+    /// Loads every puzzle from the source (like
+    /// [`load_collection`](#method.load_collection)), reading blocks that
+    /// could be either one grid or a list of single-line puzzles in
+    /// whichever way gives puzzles of the given order.
+    ///
+    /// Fails with [`OrderMismatch`](enum.ParseError.html#variant.OrderMismatch)
+    /// if any puzzle has another order.
+    pub fn load_collection_with_order(self, order: u8) -> Result<Vec<Sudoku>, Error> {
+        self.collection_with(Some(order))
+    }
+
+    fn collection_with(self, order: Option<u8>) -> Result<Vec<Sudoku>, Error> {
+        let (name, text) = self.read()?;
+        if let Some(order) = order {
+            validate_order(order).map_err(|error| Error::Parse(name.clone(), error.into()))?;
+        }
+        let mut puzzles = vec![];
+        for block in blocks(&text) {
+            let lines = block.lines().map(str::trim).collect::<Vec<_>>();
+            let rows = lines.iter().filter(|l| !l.starts_with('#')).count();
+            let boxes = boxes(&lines).map_err(|e| Error::Parse(name.clone(), e))?;
+            let grid = is_single_grid(&lines);
+            let list = detect(block) == Format::Sdk
+                && match order {
+                    Some(order) => !grid || rows != usize::from(order).pow(2),
+                    None if grid && is_line_list(&lines) => {
+                        return Err(Error::Parse(name, ParseError::InferenceAmbiguous));
+                    }
+                    None => !grid,
+                };
+            let parsed = if list {
+                lines
+                    .iter()
+                    .filter(|l| !l.starts_with('#'))
+                    .map(|line| parse_line(line, boxes))
+                    .collect::<Result<Vec<_>, _>>()
+            } else {
+                parse(block, detect(block)).map(|puzzle| vec![puzzle])
+            };
+            for puzzle in parsed.map_err(|e| Error::Parse(name.clone(), e))? {
+                match order {
+                    Some(order) if puzzle.order != order => {
+                        return Err(Error::Parse(name, ParseError::OrderMismatch(puzzle.order)));
+                    }
+                    _ => puzzles.push(puzzle),
+                }
+            }
+        }
+        Ok(puzzles)
+    }
+
+    fn load_with(self, lenient: bool) -> Result<Sudoku, Error> {
+        let (name, text) = self.read()?;
+        parse_hinted(&text, lenient, None).map_err(|error| Error::Parse(name, error))
+    }
+
+    // Returns a description of the source.
+    fn name(&self) -> String {
+        match self {
+            PuzzleSource::Str(_) | PuzzleSource::String(_) => "<string>".to_string(),
+            PuzzleSource::Path(path) => path.display().to_string(),
+            PuzzleSource::Reader(_) => "<reader>".to_string(),
+            PuzzleSource::Embedded(name) => name.to_string(),
+        }
+    }
+
+    // Reads the whole source, returning its description and contents.
+    fn read(self) -> Result<(String, String), Error> {
+        let name = self.name();
+        let text = match self {
+            PuzzleSource::Str(text) => text.to_string(),
+            PuzzleSource::String(text) => text,
+            PuzzleSource::Path(path) => match File::open(&path) {
+                Ok(file) => read_capped(&name, file)?,
+                Err(error) => return Err(Error::Io(name, error)),
+            },
+            PuzzleSource::Reader(reader) => read_capped(&name, reader)?,
+            PuzzleSource::Embedded(key) => match EMBEDDED.iter().find(|(k, _)| *k == key) {
+                Some((_, text)) => text.to_string(),
+                None => return Err(Error::UnknownEmbedded(name)),
+            },
+        };
+        if text.len() > MAX_INPUT_LEN {
+            return Err(Error::TooLarge(name));
+        }
+        Ok((name, text))
+    }
+}
+
+impl<'a> fmt::Debug for PuzzleSource<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PuzzleSource({})", self.name())
+    }
+}
+
+// Reads at most one byte more than the cap, so that oversized input is
+// detected without reading all of it.
+fn read_capped<R: Read>(name: &str, reader: R) -> Result<String, Error> {
+    let mut text = String::new();
+    match reader
+        .take(MAX_INPUT_LEN as u64 + 1)
+        .read_to_string(&mut text)
+    {
+        Ok(_) if text.len() > MAX_INPUT_LEN => Err(Error::TooLarge(name.to_string())),
+        Ok(_) => Ok(text),
+        Err(error) => Err(Error::Io(name.to_string(), error)),
+    }
+}
+
+// Splits the text into blank-line-separated blocks.
+fn blocks(text: &str) -> Vec<&str> {
+    let mut blocks = vec![];
+    let mut start = None;
+    let mut end = 0;
+    let mut offset = 0;
+    for line in text.split('\n') {
+        if line.trim().is_empty() {
+            if let Some(s) = start.take() {
+                blocks.push(&text[s..end]);
+            }
+        } else {
+            if start.is_none() {
+                start = Some(offset);
+            }
+            end = offset + line.len();
+        }
+        offset += line.len() + 1;
+    }
+    if let Some(s) = start {
+        blocks.push(&text[s..end]);
+    }
+    blocks
+}
+
+// Whether the lines form a single square grid (rather than a list of
+// single-line puzzles).
+fn is_single_grid(lines: &[&str]) -> bool {
+    let rows = lines
+        .iter()
+        .filter(|l| !l.starts_with('#'))
+        .map(|l| l.chars().count())
+        .collect::<Vec<_>>();
+    rows.len() > 1 && rows.iter().all(|r| *r == rows.len())
+}
+
+// Whether every line (other than comments) is a whole single-line puzzle.
+fn is_line_list(lines: &[&str]) -> bool {
+    lines
+        .iter()
+        .filter(|l| !l.starts_with('#'))
+        .all(|l| fits_line(l.chars().count()))
+}
+
+// Whether a line of the given length holds a whole puzzle.
+fn fits_line(len: usize) -> bool {
+    let axis = (len as f64).sqrt() as usize;
+    len > 0 && axis * axis == len && infer_shape(axis, None).is_ok()
+}
+
+// Whether the line gives the box shape (rather than being a comment).
+fn is_boxes_header(line: &str) -> bool {
+    line.trim().starts_with(BOXES_HEADER.trim_end())
+}
+
+// Finds the box shape given by a header among the lines, if any.
+fn boxes(lines: &[&str]) -> Result<Option<(u8, u8)>, ParseError> {
+    lines
+        .iter()
+        .find(|l| is_boxes_header(l))
+        .map(|l| parse_boxes_header(l))
+        .transpose()
+}
+
+fn detect(text: &str) -> Format {
+    let lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !is_boxes_header(l))
+        .collect::<Vec<_>>();
+    if lines.iter().any(|l| l.starts_with('#')) {
+        Format::Sdk
+    } else if lines.iter().any(|l| l.contains(char::is_whitespace)) {
+        Format::Grid
+    } else if lines.len() == 1 {
+        Format::Line
+    } else {
+        Format::Sdk
+    }
+}
+
+fn parse(text: &str, format: Format) -> Result<Sudoku, ParseError> {
+    match format {
+        Format::Grid => text.trim_end().parse(),
+        Format::Line => {
+            let lines = text.lines().collect::<Vec<_>>();
+            let line = lines.iter().filter(|l| !is_boxes_header(l)).copied();
+            parse_line(&line.collect::<String>(), boxes(&lines)?)
+        }
+        Format::Sdk => {
+            let lines = text.lines().map(str::trim).collect::<Vec<_>>();
+            let rows = lines
+                .iter()
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(|l| l.chars().collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            from_rows(
+                &rows.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                boxes(&lines)?,
+            )
+        }
+        Format::Lenient => {
+            let lines = text.lines().collect::<Vec<_>>();
+            let cells = lines
+                .iter()
+                .filter(|l| !is_boxes_header(l))
+                .flat_map(|l| l.chars())
+                .filter(|c| c.is_ascii_digit() || *c == '.' || *c == '_')
+                .collect::<String>();
+            parse_line(&cells, boxes(&lines)?)
+        }
+    }
+}
+
+// Parses a whole puzzle written on one line, with the given box shape (if
+// any).
+fn parse_line(text: &str, boxes: Option<(u8, u8)>) -> Result<Sudoku, ParseError> {
+    let cells = text.trim().chars().collect::<Vec<_>>();
+    let axis = (cells.len() as f64).sqrt() as usize;
+    if axis * axis != cells.len() {
+        return Err(ParseError::NonSquareAxis);
+    }
+    from_rows(&cells.chunks(axis.max(1)).collect::<Vec<_>>(), boxes)
+}
+
+// Parses a single puzzle in whichever format the text is in (falling back
+// to picking out digits if lenient), checking its order against the hint.
+fn parse_hinted(text: &str, lenient: bool, order: Option<u8>) -> Result<Sudoku, ParseError> {
+    if let Some(order) = order {
+        validate_order(order)?;
+    }
+    let puzzle = parse(text, detect(text)).or_else(|error| {
+        if lenient {
+            parse(text, Format::Lenient)
+        } else {
+            Err(error)
+        }
+    })?;
+    match order {
+        Some(order) if puzzle.order != order => Err(ParseError::OrderMismatch(puzzle.order)),
+        _ => Ok(puzzle),
+    }
+}
+
+// Builds a sudoku from rows of single-character cells, with the given box
+// shape (if any).
+fn from_rows(rows: &[&[char]], boxes: Option<(u8, u8)>) -> Result<Sudoku, ParseError> {
+    let axis = rows.len();
+    let shape = infer_shape(axis, boxes)?;
+    let mut elements = Vec::with_capacity(axis.pow(2));
+    for (y, row) in rows.iter().enumerate() {
+        if row.len() != axis {
+            return Err(ParseError::UnequalDimensions);
+        }
+        for (x, c) in row.iter().enumerate() {
+            elements.push(cell(*c, axis, Point([x as u8, y as u8]))?);
+        }
+    }
+    Ok(Sudoku::from_parts(shape, elements))
+}
+
+// Parses a single-character cell at the given point of a grid with the given
+// axial length.
+fn cell(c: char, axis: usize, point: Point) -> Result<Option<Element>, ParseError> {
+    match c {
+        '.' | '_' | '0' => Ok(None),
+        c => match c.to_digit(36) {
+            Some(value) if value as usize > axis => Err(ParseError::LargeValue(value as u8, point)),
+            Some(value) => Ok(Some(Element(value as u8))),
+            None => Err(ParseError::UnexpectedCharacter(c, point)),
+        },
+    }
+}
+
+impl Sudoku {
+    /// Parses a puzzle of the given order in any format understood by
+    /// [`PuzzleSource::load_lenient`](enum.PuzzleSource.html#method.load_lenient)
+    /// (the grid format, a single line, `.sdk`, or digits and blanks amid
+    /// anything else).
+    ///
+    /// Input that parses, but as a puzzle of another order, is rejected with
+    /// [`OrderMismatch`](enum.ParseError.html#variant.OrderMismatch).
+    pub fn parse_with_order(s: &str, order: u8) -> Result<Sudoku, ParseError> {
+        parse_hinted(s, true, Some(order))
+    }
+
+    /// Parses a puzzle written on a single line, one byte per cell, without
+    /// first validating the input as UTF-8.
+    ///
+    /// Each byte is an ASCII digit (`1`–`9`, then `A` onward for larger
+    /// values) or an empty-cell marker (`.`, `_` or `0`); surrounding
+    /// whitespace is ignored. This is the fast path for reading large
+    /// collections of puzzles in the single-line format understood by
+    /// [`PuzzleSource::load`](enum.PuzzleSource.html#method.load).
+    pub fn from_ascii(bytes: &[u8]) -> Result<Sudoku, ParseError> {
+        let start = bytes
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(bytes.len());
+        let end = bytes
+            .iter()
+            .rposition(|b| !b.is_ascii_whitespace())
+            .map_or(start, |i| i + 1);
+        let bytes = &bytes[start..end];
+        let axis = (bytes.len() as f64).sqrt() as usize;
+        if axis * axis != bytes.len() {
+            return Err(ParseError::NonSquareAxis);
+        }
+        let shape = infer_shape(axis, None)?;
+        let mut elements = Vec::with_capacity(bytes.len());
+        for (i, byte) in bytes.iter().enumerate() {
+            let point = Point([(i % axis) as u8, (i / axis) as u8]);
+            elements.push(cell(char::from(*byte), axis, point)?);
+        }
+        Ok(Sudoku::from_parts(shape, elements))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::load::{Error, PuzzleSource, MAX_INPUT_LEN};
+    use crate::ParseError;
+    use crate::Point;
+    use crate::Sudoku;
+
+    use std::io::{repeat, Cursor, Read};
+    use std::path::PathBuf;
+
+    const GRID: &str = include_str!("../tests/sudokus/solvable/2D-O3.txt");
+    const LINE: &str =
+        "5...9316......1...36.8........6..9..68..7..52..2..8........2.96...5......4391...8";
+
+    fn fixture() -> Sudoku {
+        GRID.parse().unwrap()
+    }
+
+    #[test]
+    fn test_load_sources() {
+        assert_eq!(PuzzleSource::Str(GRID).load().unwrap(), fixture());
+        assert_eq!(
+            PuzzleSource::String(GRID.to_string()).load().unwrap(),
+            fixture()
+        );
+        let reader = Box::new(Cursor::new(GRID.as_bytes()));
+        assert_eq!(PuzzleSource::Reader(reader).load().unwrap(), fixture());
+        let path =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/sudokus/solvable/2D-O3.txt");
+        assert_eq!(PuzzleSource::Path(path).load().unwrap(), fixture());
+        assert_eq!(PuzzleSource::Embedded("2D-O3").load().unwrap(), fixture());
+    }
+
+    #[test]
+    fn test_load_errors() {
+        match PuzzleSource::Path(PathBuf::from("/nonexistent/puzzle.txt")).load() {
+            Err(Error::Io(name, _)) => assert_eq!(name, "/nonexistent/puzzle.txt"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match PuzzleSource::Embedded("2D-O9").load() {
+            Err(Error::UnknownEmbedded(name)) => assert_eq!(name, "2D-O9"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match PuzzleSource::Str("12?4............").load() {
+            Err(Error::Parse(_, ParseError::UnexpectedCharacter('?', _))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_load_too_large() {
+        let reader = Box::new(repeat(b'.').take(MAX_INPUT_LEN as u64 + 1));
+        match PuzzleSource::Reader(reader).load() {
+            Err(Error::TooLarge(name)) => assert_eq!(name, "<reader>"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // Endless input is cut off rather than read forever.
+        let reader = Box::new(repeat(b'.'));
+        assert!(PuzzleSource::Reader(reader).load().is_err());
+        let text = ".".repeat(MAX_INPUT_LEN + 1);
+        match PuzzleSource::Str(&text).load() {
+            Err(Error::TooLarge(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_load_formats() {
+        assert_eq!(PuzzleSource::Str(LINE).load().unwrap(), fixture());
+        let zeroes = LINE.replace('.', "0");
+        assert_eq!(PuzzleSource::Str(&zeroes).load().unwrap(), fixture());
+        let sdk = LINE
+            .as_bytes()
+            .chunks(9)
+            .map(|row| String::from_utf8(row.to_vec()).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(PuzzleSource::Str(&sdk).load().unwrap(), fixture());
+        let commented = format!("#A Somebody\n#D A test puzzle\n{}\n", sdk);
+        assert_eq!(PuzzleSource::Str(&commented).load().unwrap(), fixture());
+        assert_eq!(
+            PuzzleSource::Str("1.3.\n..1.\n.1..\n4..3")
+                .load()
+                .unwrap()
+                .order,
+            2
+        );
+    }
+
+    #[test]
+    fn test_from_ascii() {
+        assert_eq!(Sudoku::from_ascii(LINE.as_bytes()).unwrap(), fixture());
+        assert_eq!(
+            Sudoku::from_ascii(format!("  {}\r\n", LINE).as_bytes()).unwrap(),
+            PuzzleSource::Str(LINE).load().unwrap()
+        );
+        let mut bytes = LINE.as_bytes().to_vec();
+        bytes[1] = 0xE9;
+        match Sudoku::from_ascii(&bytes) {
+            Err(ParseError::UnexpectedCharacter(_, point)) => assert_eq!(point, Point([1, 0])),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match Sudoku::from_ascii(&LINE.as_bytes()[1..]) {
+            Err(ParseError::NonSquareAxis) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(Sudoku::from_ascii(b"").is_err());
+    }
+
+    #[test]
+    fn test_load_lenient() {
+        let bordered = LINE
+            .as_bytes()
+            .chunks(9)
+            .map(|row| {
+                let row = String::from_utf8(row.to_vec()).unwrap();
+                format!("|{}|{}|{}|", &row[..3], &row[3..6], &row[6..])
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(PuzzleSource::Str(&bordered).load().is_err());
+        assert_eq!(
+            PuzzleSource::Str(&bordered).load_lenient().unwrap(),
+            fixture()
+        );
+    }
+
+    #[test]
+    fn test_load_collection() {
+        let text = format!("{}\n{}\n\n{}\n", LINE, LINE, GRID);
+        let puzzles = PuzzleSource::Str(&text).load_collection().unwrap();
+        assert_eq!(puzzles, vec![fixture(), fixture(), fixture()]);
+        let sdk = "1.3.\n..1.\n.1..\n4..3\n\n1.3.\n..1.\n.1..\n4..3\n";
+        assert_eq!(PuzzleSource::Str(sdk).load_collection().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_order_hints() {
+        // Sixteen lines of sixteen characters: one order-4 grid, or sixteen
+        // order-2 puzzles.
+        let block = vec!["1234341221434321"; 16].join("\n");
+        match PuzzleSource::Str(&block).load_collection() {
+            Err(Error::Parse(_, ParseError::InferenceAmbiguous)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let grids = PuzzleSource::Str(&block)
+            .load_collection_with_order(4)
+            .unwrap();
+        assert_eq!(grids.len(), 1);
+        assert_eq!(grids[0].order, 4);
+        let lines = PuzzleSource::Str(&block)
+            .load_collection_with_order(2)
+            .unwrap();
+        assert_eq!(lines.len(), 16);
+        assert!(lines.iter().all(|puzzle| puzzle.order == 2));
+        match PuzzleSource::Str(&block).load_collection_with_order(3) {
+            Err(Error::Parse(_, ParseError::OrderMismatch(2))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // A single puzzle can only be the grid.
+        assert_eq!(PuzzleSource::Str(&block).load().unwrap(), grids[0]);
+        assert_eq!(
+            PuzzleSource::Str(&block).load_with_order(4).unwrap(),
+            grids[0]
+        );
+        match PuzzleSource::Str(&block).load_with_order(2) {
+            Err(Error::Parse(_, ParseError::OrderMismatch(4))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_load_rect() {
+        let grid = include_str!("../tests/sudokus/solvable/2D-R2x3.txt");
+        let puzzle = PuzzleSource::Str(grid).load().unwrap();
+        assert_eq!(puzzle, grid.parse::<Sudoku>().unwrap());
+        assert_eq!(puzzle.box_shape(), (2, 3));
+        let line = ".2......61.....56.....313....564....";
+        assert_eq!(PuzzleSource::Str(line).load().unwrap(), puzzle);
+        let sdk = line
+            .as_bytes()
+            .chunks(6)
+            .map(|row| String::from_utf8(row.to_vec()).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(PuzzleSource::Str(&sdk).load().unwrap(), puzzle);
+        // The header gives the shape, whatever the format.
+        let tall = PuzzleSource::Str(&format!("#boxes: 3x2\n#C A comment\n{}", sdk))
+            .load()
+            .unwrap();
+        assert_eq!(tall.box_shape(), (3, 2));
+        let listed = PuzzleSource::Str(&format!("#boxes: 3x2\n{}\n{}\n", line, line))
+            .load_collection()
+            .unwrap();
+        assert_eq!(listed, vec![tall.clone(), tall]);
+        let text = format!("{}\n{}", grid, grid);
+        assert_eq!(
+            PuzzleSource::Str(&text).load_collection().unwrap(),
+            vec![puzzle.clone(), puzzle.clone()]
+        );
+        assert_eq!(
+            PuzzleSource::Str(grid).load_lenient().unwrap(),
+            PuzzleSource::Str(grid).load().unwrap()
+        );
+        assert_eq!(Sudoku::from_ascii(line.as_bytes()).unwrap(), puzzle);
+        match PuzzleSource::Str(&format!("#boxes: 2x2\n{}", line)).load() {
+            Err(Error::Parse(_, ParseError::InvalidBoxes)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_with_order() {
+        assert_eq!(Sudoku::parse_with_order(GRID, 3).unwrap(), fixture());
+        assert_eq!(Sudoku::parse_with_order(LINE, 3).unwrap(), fixture());
+        let bordered = format!("|{}|", LINE);
+        assert_eq!(Sudoku::parse_with_order(&bordered, 3).unwrap(), fixture());
+        for text in &[GRID, LINE] {
+            match Sudoku::parse_with_order(text, 4) {
+                Err(ParseError::OrderMismatch(3)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+        match Sudoku::parse_with_order("12?4", 2) {
+            Err(ParseError::NonSquareAxis) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Sudokus whose givens can't be overwritten.
+use crate::Element;
+use crate::Point;
+use crate::Sudoku;
+
+use std::{fmt, ops::Deref};
+
+/// A sudoku with a mask of frozen cells, which [`set`](#method.set) refuses
+/// to change.
+///
+/// The wrapped sudoku can be read through `Deref`, but can only be written
+/// through [`set`](#method.set), so the givens of a puzzle can't be
+/// overwritten by accident.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockedSudoku {
+    sudoku: Sudoku,
+    frozen: Vec<bool>,
+}
+
+/// Encodes errors encountered while writing to a locked sudoku.
+///
+/// More variants may be added in future releases, so matches outside this
+/// crate need a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The cell at the given point is frozen.
+    Frozen(Point),
+    /// The given point lies outside the grid.
+    OutOfBounds(Point),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Frozen(point) => write!(f, "the cell at {} is frozen", point),
+            Error::OutOfBounds(point) => write!(f, "{} is outside the grid", point),
+        }
+    }
+}
+
+impl LockedSudoku {
+    /// Wraps a sudoku, freezing every cell that's filled in (its givens).
+    pub fn new(sudoku: Sudoku) -> Self {
+        let frozen = sudoku.elements.iter().map(Option::is_some).collect();
+        Self { sudoku, frozen }
+    }
+
+    /// Wraps a sudoku with the given mask of frozen cells, in
+    /// [`points`](trait.Grid.html#tymethod.points) order.
+    pub(crate) fn with_frozen(sudoku: Sudoku, frozen: Vec<bool>) -> Self {
+        debug_assert_eq!(frozen.len(), sudoku.elements.len());
+        Self { sudoku, frozen }
+    }
+
+    /// Returns whether the cell at the given point is frozen.
+    ///
+    /// Points outside the grid are never frozen.
+    pub fn is_frozen(&self, point: Point) -> bool {
+        self.sudoku
+            .shape()
+            .fold(point)
+            .is_some_and(|index| self.frozen[index])
+    }
+
+    /// Writes the value to the cell at the given point, unless that cell is
+    /// frozen.
+    pub fn set(&mut self, point: Point, value: Option<Element>) -> Result<(), Error> {
+        let index = self
+            .sudoku
+            .shape()
+            .fold(point)
+            .ok_or(Error::OutOfBounds(point))?;
+        if self.frozen[index] {
+            return Err(Error::Frozen(point));
+        }
+        self.sudoku.elements[index] = value;
+        Ok(())
+    }
+
+    /// Unwraps the sudoku, discarding the mask.
+    pub fn into_inner(self) -> Sudoku {
+        self.sudoku
+    }
+}
+
+impl Deref for LockedSudoku {
+    type Target = Sudoku;
+
+    fn deref(&self) -> &Sudoku {
+        &self.sudoku
+    }
+}
+
+impl From<Sudoku> for LockedSudoku {
+    fn from(sudoku: Sudoku) -> Self {
+        Self::new(sudoku)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "2D")]
+    use crate::locked::Error;
+    #[cfg(feature = "2D")]
+    use crate::Element;
+    #[cfg(feature = "2D")]
+    use crate::LockedSudoku;
+    #[cfg(feature = "2D")]
+    use crate::Point;
+    #[cfg(feature = "2D")]
+    use crate::Sudoku;
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_locked_set() {
+        let mut puzzle = Sudoku::new(2);
+        let (given, free) = (Point([0, 0]), Point([1, 0]));
+        puzzle.substitute(given, Some(Element(1)));
+        let mut locked = LockedSudoku::new(puzzle);
+        assert!(locked.is_frozen(given));
+        assert!(!locked.is_frozen(free));
+        assert_eq!(
+            locked.set(given, Some(Element(2))),
+            Err(Error::Frozen(given))
+        );
+        assert_eq!(locked.set(given, None), Err(Error::Frozen(given)));
+        assert_eq!(locked[given], Some(Element(1)));
+        assert_eq!(locked.set(free, Some(Element(2))), Ok(()));
+        assert_eq!(locked[free], Some(Element(2)));
+        // Filling in a free cell doesn't freeze it.
+        assert!(!locked.is_frozen(free));
+        assert_eq!(locked.set(free, None), Ok(()));
+        let outside = Point([4, 0]);
+        assert!(!locked.is_frozen(outside));
+        assert_eq!(locked.set(outside, None), Err(Error::OutOfBounds(outside)));
+        assert_eq!(locked.into_inner().elements[1], None);
+    }
+}
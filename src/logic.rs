@@ -0,0 +1,604 @@
+//! Solving by deduction, the way a person would, rather than by search.
+//!
+//! Deductions are made by a ladder of [techniques](enum.Technique.html),
+//! applied in order: each step uses the first technique that makes
+//! progress, then starts again from the bottom of the ladder.
+use crate::links::{all_groups, Candidates, Links};
+use crate::Element;
+use crate::Grid;
+use crate::Point;
+use crate::SolveError;
+use crate::Sudoku;
+use crate::Violation;
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// A deduction technique.
+///
+/// More techniques may be added in future releases, so matches outside this
+/// crate need a wildcard arm.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum Technique {
+    /// Fills a cell that has only one candidate left.
+    NakedSingle,
+    /// Fills the only cell of a group that can hold a value.
+    HiddenSingle,
+    /// Colors each chain of strong links for a value with two alternating
+    /// colors, exactly one of which holds the value, then eliminates the
+    /// value from:
+    ///
+    /// - every cell of a color that appears twice in one group (that color
+    ///   must be the false one), and
+    /// - every other cell that shares a group with cells of both colors.
+    Coloring,
+}
+
+impl Technique {
+    /// Every technique, in the order the ladder tries them (simplest first).
+    pub const LADDER: [Technique; 3] = [
+        Technique::NakedSingle,
+        Technique::HiddenSingle,
+        Technique::Coloring,
+    ];
+
+    /// Returns the technique's name, in kebab case (e.g. `hidden-single`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "naked-single",
+            Technique::HiddenSingle => "hidden-single",
+            Technique::Coloring => "coloring",
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Represents a failure to parse a [`Technique`](enum.Technique.html) from
+/// its name.
+///
+/// The associated value is the unrecognized name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseTechniqueError(pub String);
+
+impl fmt::Display for ParseTechniqueError {
+    /// Names the unrecognized technique and lists the valid ones.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = Technique::LADDER
+            .iter()
+            .map(|technique| technique.name())
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "unknown technique \"{}\" (expected one of: {})",
+            self.0,
+            names.join(", ")
+        )
+    }
+}
+
+impl FromStr for Technique {
+    type Err = ParseTechniqueError;
+    /// Parses a technique from its name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::LADDER
+            .iter()
+            .find(|technique| technique.name().eq_ignore_ascii_case(s.trim()))
+            .cloned()
+            .ok_or_else(|| ParseTechniqueError(s.to_string()))
+    }
+}
+
+/// A candidate ruled out by a technique.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Elimination {
+    /// The cell the candidate was removed from.
+    pub point: Point,
+    /// The candidate removed.
+    pub value: Element,
+    /// The technique that ruled it out.
+    pub technique: Technique,
+}
+
+/// A cell filled by a technique.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Placement {
+    /// The cell filled.
+    pub point: Point,
+    /// The value placed.
+    pub value: Element,
+    /// The technique that found it.
+    pub technique: Technique,
+}
+
+/// The outcome of solving by deduction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deductions {
+    /// The grid with every deduced value filled in; this is only complete if
+    /// the techniques sufficed to solve the puzzle.
+    pub grid: Sudoku,
+    /// The cells filled, in the order they were found.
+    pub placements: Vec<Placement>,
+    /// The candidates eliminated (other than by placing values), in the
+    /// order they were found.
+    pub eliminations: Vec<Elimination>,
+}
+
+impl Deductions {
+    /// Whether the techniques sufficed to solve the puzzle.
+    pub fn is_solved(&self) -> bool {
+        self.grid.is_complete()
+    }
+}
+
+impl Sudoku {
+    /// Solves the puzzle by deduction alone, using the given techniques (in
+    /// the given order) until none makes progress.
+    ///
+    /// Pass [`Technique::LADDER`](enum.Technique.html#associatedconstant.LADDER)
+    /// to use every technique.
+    pub fn deduce(&self, techniques: &[Technique]) -> Deductions {
+        let mut state = State::new(self);
+        while techniques.iter().any(|technique| state.apply(*technique)) {}
+        state.deductions.grid = state.grid;
+        state.deductions
+    }
+
+    /// Fills in every cell the techniques of the
+    /// [ladder](enum.Technique.html#associatedconstant.LADDER) up to and
+    /// including `upto` can deduce, stopping short of the harder ones (e.g.
+    /// to hand a student the puzzle with its singles already placed).
+    ///
+    /// A puzzle none of those techniques makes progress on is returned
+    /// unchanged, with no steps. Puzzles that break the rules, or that the
+    /// deductions show to have no solution, are rejected.
+    pub fn reduce_by_techniques(&self, upto: Technique) -> Result<Reduced, SolveError> {
+        if let Some(violation) = self.diagnostics().first() {
+            return Err(match *violation {
+                Violation::ValueOutOfRange(point, value) => {
+                    SolveError::ValueOutOfRange(point, value)
+                }
+                _ => SolveError::Unknown,
+            });
+        }
+        let rungs = Technique::LADDER
+            .iter()
+            .position(|technique| *technique == upto)
+            .map_or(Technique::LADDER.len(), |i| i + 1);
+        let techniques = &Technique::LADDER[..rungs];
+        let mut state = State::new(self);
+        while techniques.iter().any(|technique| state.apply(*technique)) {}
+        if state.is_dead() {
+            return Err(SolveError::Unknown);
+        }
+        let remaining = state.grid.elements.iter().filter(|e| e.is_none()).count();
+        Ok(Reduced {
+            grid: state.grid,
+            steps: state.deductions.placements,
+            remaining,
+        })
+    }
+
+    /// Finds the candidates simple coloring eliminates in the current
+    /// position (without applying them or any other technique).
+    pub fn coloring_eliminations(&self) -> Vec<Elimination> {
+        coloring(&Candidates::of(self))
+    }
+}
+
+/// A puzzle partly solved by deduction (see
+/// [`reduce_by_techniques`](struct.Sudoku.html#method.reduce_by_techniques)).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reduced {
+    /// The puzzle with every deduced value filled in.
+    pub grid: Sudoku,
+    /// The cells filled, in the order they were found.
+    pub steps: Vec<Placement>,
+    /// The number of cells left empty.
+    pub remaining: usize,
+}
+
+/// The working state of a deduction.
+#[derive(Clone)]
+struct State {
+    grid: Sudoku,
+    candidates: Candidates,
+    groups: Vec<Vec<Point>>,
+    deductions: Deductions,
+}
+
+impl State {
+    fn new(puzzle: &Sudoku) -> Self {
+        Self {
+            grid: puzzle.clone(),
+            candidates: Candidates::of(puzzle),
+            groups: all_groups(puzzle.shape()),
+            deductions: Deductions {
+                grid: puzzle.clone(),
+                placements: vec![],
+                eliminations: vec![],
+            },
+        }
+    }
+
+    /// Whether the position has no solution because some empty cell has no
+    /// candidates, or some group has nowhere left for a value.
+    fn is_dead(&self) -> bool {
+        let stuck = self.grid.points().into_iter().any(|point| {
+            self.grid[point].is_none() && self.candidates.masks[self.candidates.index(point)] == 0
+        });
+        stuck
+            || self.groups.iter().any(|group| {
+                (1..=self.grid.axis()).any(|value| {
+                    group.iter().all(|point| {
+                        self.grid[*point] != Some(Element(value))
+                            && !self.candidates.contains(*point, value)
+                    })
+                })
+            })
+    }
+
+    /// Applies the technique once, returning whether it made progress.
+    fn apply(&mut self, technique: Technique) -> bool {
+        match technique {
+            Technique::NakedSingle => {
+                let single = self.grid.points().into_iter().find_map(|point| {
+                    let mask = self.candidates.masks[self.candidates.index(point)];
+                    if mask.count_ones() == 1 {
+                        Some((point, mask.trailing_zeros() as u8 + 1))
+                    } else {
+                        None
+                    }
+                });
+                single.is_some_and(|(point, value)| self.place(point, value, technique))
+            }
+            Technique::HiddenSingle => {
+                let axis = self.grid.axis();
+                let single = self.groups.iter().find_map(|group| {
+                    (1..=axis).find_map(|value| {
+                        let mut cells = group
+                            .iter()
+                            .filter(|point| self.candidates.contains(**point, value));
+                        match (cells.next(), cells.next()) {
+                            (Some(point), None) => Some((*point, value)),
+                            _ => None,
+                        }
+                    })
+                });
+                single.is_some_and(|(point, value)| self.place(point, value, technique))
+            }
+            Technique::Coloring => {
+                let eliminations = coloring(&self.candidates);
+                for elimination in &eliminations {
+                    let _ = self
+                        .candidates
+                        .remove(elimination.point, elimination.value.0);
+                }
+                self.deductions.eliminations.extend(eliminations.iter());
+                !eliminations.is_empty()
+            }
+        }
+    }
+
+    /// Fills the cell, removing the value from its peers' candidates.
+    fn place(&mut self, point: Point, value: u8, technique: Technique) -> bool {
+        self.grid.substitute(point, Some(Element(value)));
+        let index = self.candidates.index(point);
+        self.candidates.masks[index] = 0;
+        for peer in self.grid.group_indices(point) {
+            let _ = self.candidates.remove(peer, value);
+        }
+        self.deductions.placements.push(Placement {
+            point,
+            value: Element(value),
+            technique,
+        });
+        true
+    }
+}
+
+/// Finds up to `limit` solutions of the puzzle by constraint propagation
+/// (filling in singles) and search, for cross-checking the backtracking
+/// solver.
+///
+/// This shares no code with the backtracking solver (other than computing
+/// the initial candidates), so the two make independent checks.
+pub(crate) fn propagating_solutions(puzzle: &Sudoku, limit: usize) -> Vec<Sudoku> {
+    let mut solutions = vec![];
+    if puzzle.is_valid() {
+        propagate(State::new(puzzle), limit, &mut solutions);
+    }
+    solutions
+}
+
+fn propagate(mut state: State, limit: usize, solutions: &mut Vec<Sudoku>) {
+    while state.apply(Technique::NakedSingle) || state.apply(Technique::HiddenSingle) {}
+    if state.is_dead() {
+        return;
+    }
+    let branch = state
+        .grid
+        .points()
+        .into_iter()
+        .filter(|point| state.grid[*point].is_none())
+        .min_by_key(|point| state.candidates.masks[state.candidates.index(*point)].count_ones());
+    let point = match branch {
+        Some(point) => point,
+        None => {
+            if state.grid.is_solved() {
+                solutions.push(state.grid);
+            }
+            return;
+        }
+    };
+    for value in 1..=state.grid.axis() {
+        if solutions.len() >= limit {
+            return;
+        }
+        if state.candidates.contains(point, value) {
+            let mut next = state.clone();
+            let _ = next.place(point, value, Technique::NakedSingle);
+            propagate(next, limit, solutions);
+        }
+    }
+}
+
+/// Finds the candidates simple coloring eliminates, for every value.
+fn coloring(candidates: &Candidates) -> Vec<Elimination> {
+    let mut eliminations = vec![];
+    for value in 1..=candidates.shape.axis() {
+        let links = Links::new(candidates, value);
+        for component in links.strong_components() {
+            let colors = match two_color(&links, &component) {
+                Some(colors) => colors,
+                None => continue,
+            };
+            let mut eliminated = vec![];
+            // A color appearing twice in a group is false everywhere.
+            for color in [false, true] {
+                let cells = component.iter().filter(|point| colors[*point] == color);
+                let clash = cells.clone().any(|a| {
+                    links
+                        .weak_neighbors(*a)
+                        .iter()
+                        .any(|b| colors.get(b) == Some(&color))
+                });
+                if clash {
+                    eliminated.extend(cells.cloned());
+                }
+            }
+            // A cell seeing both colors can't hold the value.
+            if eliminated.is_empty() {
+                for point in links.cells() {
+                    if colors.contains_key(&point) {
+                        continue;
+                    }
+                    let neighbors = links.weak_neighbors(point);
+                    let sees = |color| neighbors.iter().any(|n| colors.get(n) == Some(&color));
+                    if sees(false) && sees(true) {
+                        eliminated.push(point);
+                    }
+                }
+            }
+            eliminations.extend(eliminated.into_iter().map(|point| Elimination {
+                point,
+                value: Element(value),
+                technique: Technique::Coloring,
+            }));
+        }
+    }
+    eliminations.sort_by_key(|elimination| (elimination.value, elimination.point));
+    eliminations.dedup();
+    eliminations
+}
+
+/// Colors the component's cells so that strongly linked cells differ,
+/// returning `None` if that's impossible (which only happens in a broken
+/// position).
+fn two_color(links: &Links, component: &[Point]) -> Option<BTreeMap<Point, bool>> {
+    let mut colors = BTreeMap::new();
+    let mut queue = vec![component[0]];
+    let _ = colors.insert(component[0], false);
+    while let Some(point) = queue.pop() {
+        let color = colors[&point];
+        for neighbor in links.strong_neighbors(point) {
+            match colors.get(&neighbor) {
+                Some(other) if *other == color => return None,
+                Some(_) => {}
+                None => {
+                    let _ = colors.insert(neighbor, !color);
+                    queue.push(neighbor);
+                }
+            }
+        }
+    }
+    Some(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "2D")]
+    use crate::links::Candidates;
+    #[cfg(feature = "2D")]
+    use crate::logic::{coloring, propagating_solutions, Elimination};
+    use crate::logic::{ParseTechniqueError, Technique};
+    #[cfg(feature = "2D")]
+    use crate::sudoku::Shape;
+    #[cfg(feature = "2D")]
+    use crate::Element;
+    #[cfg(feature = "2D")]
+    use crate::Point;
+    #[cfg(feature = "2D")]
+    use crate::Solve;
+    #[cfg(feature = "2D")]
+    use crate::SolveError;
+    #[cfg(feature = "2D")]
+    use crate::Sudoku;
+
+    // An order-3 position in which only the given cells have a candidate
+    // (namely 1).
+    #[cfg(feature = "2D")]
+    fn ones(cells: &[[u8; 2]]) -> Candidates {
+        let mut candidates = Candidates {
+            shape: Shape::square(3),
+            masks: vec![0; 81],
+        };
+        for cell in cells {
+            candidates.masks[Point(*cell).fold(3)] = 1;
+        }
+        candidates
+    }
+
+    #[cfg(feature = "2D")]
+    fn eliminated(cells: &[[u8; 2]]) -> Vec<Elimination> {
+        cells
+            .iter()
+            .map(|cell| Elimination {
+                point: Point(*cell),
+                value: Element(1),
+                technique: Technique::Coloring,
+            })
+            .collect()
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_coloring_wrap() {
+        // The chain (0, 0) → (0, 4) → (4, 4) → (4, 1) → (1, 1) colors both
+        // ends alike, but they share the top-left box (along with (2, 2), so
+        // that they aren't strongly linked themselves); that color is false.
+        let candidates = ones(&[[0, 0], [0, 4], [4, 4], [4, 1], [1, 1], [2, 2]]);
+        assert_eq!(coloring(&candidates), eliminated(&[[0, 0], [1, 1], [4, 4]]));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_coloring_trap() {
+        // The chain (0, 0) → (0, 4) → (4, 4) → (5, 5) colors its ends
+        // differently, so (5, 0), which sees both (along the top row and
+        // along x = 5), can't hold 1. (8, 0) and (5, 8) keep those lines from
+        // being strong links, and each only sees one color.
+        let candidates = ones(&[[0, 0], [0, 4], [4, 4], [5, 5], [5, 0], [8, 0], [5, 8]]);
+        assert_eq!(coloring(&candidates), eliminated(&[[5, 0]]));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_coloring_unlocks_fixture() {
+        let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let solution = puzzle.solution().unwrap();
+        let singles = puzzle.deduce(&[Technique::NakedSingle, Technique::HiddenSingle]);
+        assert!(!singles.is_solved());
+        assert!(singles.eliminations.is_empty());
+        assert!(!singles.grid.coloring_eliminations().is_empty());
+        let deductions = puzzle.deduce(&Technique::LADDER);
+        assert!(deductions.is_solved());
+        assert_eq!(deductions.grid, solution);
+        for elimination in &deductions.eliminations {
+            assert_eq!(elimination.technique, Technique::Coloring);
+            assert_ne!(solution[elimination.point], Some(elimination.value));
+        }
+        for placement in &deductions.placements {
+            assert_eq!(solution[placement.point], Some(placement.value));
+        }
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_propagating_solutions() {
+        let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let solution = puzzle.solution().unwrap();
+        assert_eq!(propagating_solutions(&puzzle, 2), vec![solution]);
+        assert_eq!(propagating_solutions(&Sudoku::new(2), 2).len(), 2);
+        let mut invalid = Sudoku::new(2);
+        invalid.substitute(Point([0, 0]), Some(Element(1)));
+        invalid.substitute(Point([3, 0]), Some(Element(1)));
+        assert!(propagating_solutions(&invalid, 2).is_empty());
+    }
+
+    #[test]
+    fn test_technique_names() {
+        for technique in Technique::LADDER.iter() {
+            assert_eq!(technique.to_string().parse(), Ok(*technique));
+        }
+        assert_eq!("Hidden-Single".parse(), Ok(Technique::HiddenSingle));
+        let error = "x-wing".parse::<Technique>().unwrap_err();
+        assert_eq!(error, ParseTechniqueError("x-wing".to_string()));
+        assert_eq!(
+            error.to_string(),
+            "unknown technique \"x-wing\" (expected one of: naked-single, hidden-single, coloring)"
+        );
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_reduce_by_techniques() {
+        let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        // The fixture with its singles placed (checked against its
+        // solution, and for the absence of any further single).
+        let expected: Sudoku = "5 _ 8 _ 9 3 1 6 _\n\
+                                4 _ 9 _ 6 1 8 3 5\n\
+                                3 6 1 8 _ _ _ _ 9\n\
+                                _ 3 _ 6 2 _ 9 8 _\n\
+                                6 8 4 1 7 9 3 5 2\n\
+                                _ _ 2 _ _ 8 6 _ _\n\
+                                _ _ _ _ _ 2 _ 9 6\n\
+                                _ _ 6 5 _ _ _ 1 3\n\
+                                _ 4 3 9 1 6 5 _ 8"
+            .parse()
+            .unwrap();
+        let reduced = puzzle
+            .reduce_by_techniques(Technique::HiddenSingle)
+            .unwrap();
+        assert_eq!(reduced.grid, expected);
+        assert_eq!((reduced.steps.len(), reduced.remaining), (22, 32));
+        let solution = puzzle.solution().unwrap();
+        for step in &reduced.steps {
+            assert_eq!(solution[step.point], Some(step.value));
+        }
+        // Reducing again changes nothing.
+        let again = expected
+            .reduce_by_techniques(Technique::HiddenSingle)
+            .unwrap();
+        assert_eq!((again.grid, again.steps.len()), (expected, 0));
+        // The fixture has no naked singles to begin with.
+        let naked = puzzle.reduce_by_techniques(Technique::NakedSingle).unwrap();
+        assert_eq!((naked.grid, naked.steps.len()), (puzzle.clone(), 0));
+        let full = puzzle.reduce_by_techniques(Technique::Coloring).unwrap();
+        assert_eq!((full.grid, full.remaining), (solution, 0));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_reduce_contradictions() {
+        let duplicate: Sudoku = "1 _ _ 1\n_ _ _ _\n_ _ _ _\n_ _ _ _".parse().unwrap();
+        match duplicate.reduce_by_techniques(Technique::HiddenSingle) {
+            Err(SolveError::Unknown) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let mut out_of_range = Sudoku::new(2);
+        out_of_range.substitute(Point([1, 1]), Some(Element(5)));
+        match out_of_range.reduce_by_techniques(Technique::HiddenSingle) {
+            Err(SolveError::ValueOutOfRange(point, Element(5))) => {
+                assert_eq!(point, Point([1, 1]))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // (3, 0) sees 1 and 2 along its row and 3 and 4 down its column.
+        let dead: Sudoku = "1 2 _ _\n_ _ _ _\n_ _ _ 3\n_ _ _ 4".parse().unwrap();
+        match dead.reduce_by_techniques(Technique::NakedSingle) {
+            Err(SolveError::Unknown) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -3,22 +3,41 @@ extern crate clap;
 extern crate sudoku;
 
 use std::{
-    fs::File,
-    io::{stdin, Error as IoError, Read},
+    fs,
+    io::{stdin, Error as IoError},
+    path::PathBuf,
+    process,
+    time::Duration,
 };
 
-use sudoku::{Difficulty, Generate, ParseError, Score, Solve, SolveError, Sudoku};
+#[cfg(feature = "ui")]
+use sudoku::ui::book::{BookOptions, Collection};
 
+use sudoku::analysis::{self, DifficultyComparison};
+use sudoku::{
+    Difficulty, Generate, GenerateError, GeneratePreset, LoadError, OrderError, PuzzleSource,
+    Score, Solve, SolveError, Sudoku, MAX_ORDER, MIN_ORDER,
+};
+
+// The payloads are only ever read through `Debug` when `main` returns.
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Error {
     Solve(SolveError),
-    Parse(ParseError),
+    Load(LoadError),
+    Generate(GenerateError),
     Io(IoError),
 }
 
-impl From<ParseError> for Error {
-    fn from(error: ParseError) -> Self {
-        Error::Parse(error)
+impl From<IoError> for Error {
+    fn from(error: IoError) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<LoadError> for Error {
+    fn from(error: LoadError) -> Self {
+        Error::Load(error)
     }
 }
 
@@ -28,62 +47,341 @@ impl From<SolveError> for Error {
     }
 }
 
-impl From<IoError> for Error {
-    fn from(error: IoError) -> Self {
-        Error::Io(error)
+impl From<GenerateError> for Error {
+    fn from(error: GenerateError) -> Self {
+        Error::Generate(error)
     }
 }
 
 fn puzzle(matches: &clap::ArgMatches) -> Result<Sudoku, Error> {
-    let mut reader: Box<Read> = if matches.is_present("INPUT") {
-        Box::new(File::open(matches.value_of("INPUT").unwrap()).expect("File not found."))
-    } else {
-        Box::new(stdin())
+    let source = match matches.value_of("INPUT") {
+        Some(path) => PuzzleSource::Path(PathBuf::from(path)),
+        None => PuzzleSource::Reader(Box::new(stdin())),
     };
-    let mut puzzle = String::new();
-    reader.read_to_string(&mut puzzle)?;
-    puzzle.parse().map_err(Into::into)
+    match order(matches) {
+        Some(order) => source.load_with_order(order),
+        None => source.load(),
+    }
+    .map_err(Into::into)
+}
+
+/// The order given with `--order` (or as `generate`'s argument), if any.
+fn order(matches: &clap::ArgMatches) -> Option<u8> {
+    // Validated by clap.
+    matches.value_of("ORDER").and_then(|s| s.parse().ok())
 }
 
 #[rustfmt::skip]
 fn main() -> Result<(), Error> {
-    let matches = clap_app!(ku =>
+    let app = clap_app!(ku =>
         (setting: clap::AppSettings::ArgRequiredElseHelp)
         (setting: clap::AppSettings::VersionlessSubcommands)
         (about: "A sudoku generator/solver/manipulator.")
+        (@arg CAPABILITIES: --capabilities "Prints the version and capabilities of the solver.")
         (@subcommand solve =>
             (about: "Solves the given sudoku.")
             (@arg INPUT: "Sets the input file (defaults to stdin).")
+            (@arg ORDER: --order +takes_value {is_order} "Sets the order of the puzzle, rather than inferring it.")
+            (@arg TIMEOUT: --timeout +takes_value conflicts_with[MAX_NODES] {is_timeout} "Gives up after the given number of seconds.")
+            (@arg MAX_NODES: --("max-nodes") +takes_value {is_count} "Gives up after visiting the given number of search nodes.")
         )
         (@subcommand score =>
             (about: "Scores the given sudoku.")
             (@arg INPUT: "Sets the input file (defaults to stdin).")
+            (@arg ORDER: --order +takes_value {is_order} "Sets the order of the puzzle, rather than inferring it.")
+        )
+        (@subcommand reduce =>
+            (about: "Fills in what the techniques up to the given one can deduce.")
+            (@arg INPUT: "Sets the input file (defaults to stdin).")
+            (@arg ORDER: --order +takes_value {is_order} "Sets the order of the puzzle, rather than inferring it.")
+            (@arg UPTO: --upto +takes_value +required "The hardest technique to use (e.g. hidden-single).")
+            (@arg STEPS: --steps "Also lists the values placed, in order.")
         )
         (@subcommand generate =>
             (about: "Generates a sudoku.")
-            (@arg ORDER: "The order of sudoku to be generated (defaults to 3).")
+            (@arg ORDER: {is_order} "The order of sudoku to be generated (defaults to 3).")
+            (@arg DAILY: --daily +takes_value "Generates the puzzle of the day for the given date (YYYY-MM-DD).")
+            (@arg DIFFICULTY: --difficulty +takes_value "The difficulty of sudoku to be generated (defaults to beginner).")
+            (@arg PRESET: --preset +takes_value conflicts_with[ORDER DAILY DIFFICULTY] "Generates a sudoku from a named bundle of options (e.g. newspaper-easy).")
         )
-    ).get_matches();
-    if let Some(matches) = matches.subcommand_matches("solve") {
-        let solution = solve(&matches)?;
-        println!("{}", solution);
+        (@subcommand rate =>
+            (about: "Prints the fingerprint, difficulty and score of each sudoku in a collection.")
+            (@arg INPUT: +required "Sets the collection file.")
+            (@arg ORDER: --order +takes_value {is_order} "Sets the order of the puzzles, rather than inferring it.")
+            (@arg COMPARE: --compare +takes_value "Compares the difficulties with the labels in the given CSV file (of fingerprint,label lines).")
+            (@arg SCALE: --scale +takes_value requires[COMPARE] "The labels, easiest first and separated by commas (defaults to numbers).")
+        )
+    );
+    #[cfg(feature = "ui")]
+    let app = app.subcommand(clap_app!(book =>
+        (about: "Typesets a collection of sudokus as SVG pages.")
+        (@arg FROM: --from +takes_value +required "Sets the collection file.")
+        (@arg OUT: --out +takes_value "Sets the directory to write pages to (defaults to the current one).")
+        (@arg PER_PAGE: --("per-page") +takes_value {is_positive} "The number of puzzles on each page (defaults to 4).")
+        (@arg TITLE: --title +takes_value "The title atop each page (defaults to the collection's file name).")
+        (@arg SOLUTIONS: --("with-solutions") "Ends the book with the puzzles' solutions.")
+        (@arg ORDER: --order +takes_value {is_order} "Sets the order of the puzzles, rather than inferring it.")
+    ));
+    let matches = app.get_matches();
+    if matches.is_present("CAPABILITIES") {
+        print!("{}", sudoku::capabilities());
+    } else if let Some(matches) = matches.subcommand_matches("solve") {
+        match solve(matches) {
+            Ok(solution) => println!("{}", solution),
+            Err(Error::Solve(error)) => {
+                eprintln!("Couldn't solve puzzle: {}.", error);
+                process::exit(1);
+            }
+            Err(error) => return Err(error),
+        }
     } else if let Some(matches) = matches.subcommand_matches("score") {
-        if let Some(score) = score(&matches) {
+        if let Some(score) = score(matches) {
             println!("Score: {}", score);
         } else {
             println!("Couldn't score puzzle.");
         }
+    } else if let Some(matches) = matches.subcommand_matches("reduce") {
+        let upto = match matches.value_of("UPTO").unwrap_or_default().parse() {
+            Ok(upto) => upto,
+            Err(error) => {
+                eprintln!("Invalid technique: {}.", error);
+                process::exit(1);
+            }
+        };
+        match puzzle(matches)?.reduce_by_techniques(upto) {
+            Ok(reduced) => {
+                println!("{}", reduced.grid);
+                if matches.is_present("STEPS") {
+                    for step in reduced.steps {
+                        println!("{} at {} ({})", step.value.0, step.point, step.technique);
+                    }
+                }
+            }
+            Err(error) => {
+                eprintln!("Couldn't reduce puzzle: {}.", error);
+                process::exit(1);
+            }
+        }
     } else if let Some(matches) = matches.subcommand_matches("generate") {
-        let order = matches.value_of("ORDER").and_then(|s: &str| s.parse().ok()).unwrap_or(3);
-        println!("{:X}", Sudoku::generate(order, Difficulty::Beginner));
+        let order = order(matches).unwrap_or(3);
+        let difficulty = match matches.value_of("DIFFICULTY").map(str::parse) {
+            Some(Ok(difficulty)) => difficulty,
+            Some(Err(error)) => {
+                eprintln!("Invalid difficulty: {}.", error);
+                process::exit(1);
+            }
+            None => Difficulty::Beginner,
+        };
+        if let Some(preset) = matches.value_of("PRESET") {
+            let preset = match GeneratePreset::parse(preset) {
+                Ok(preset) => preset,
+                Err(error) => {
+                    eprintln!("Invalid preset: {}.", error);
+                    process::exit(1);
+                }
+            };
+            println!("{:X}", Sudoku::generate_with_options(&preset.options(), None)?.puzzle);
+        } else if let Some(date) = matches.value_of("DAILY") {
+            let date = parse_date(date).ok_or(GenerateError::InvalidDate)?;
+            println!("{:X}", Sudoku::daily(date, order, difficulty)?.puzzle);
+        } else {
+            println!("{:X}", Sudoku::generate(order, difficulty));
+        }
+    } else if let Some(matches) = matches.subcommand_matches("rate") {
+        rate(matches)?;
+    } else if let Some(matches) = matches.subcommand_matches("book") {
+        book(matches)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "ui")]
+fn book(matches: &clap::ArgMatches) -> Result<(), Error> {
+    let from = PathBuf::from(matches.value_of("FROM").unwrap_or_default());
+    let title = match matches.value_of("TITLE") {
+        Some(title) => title.to_string(),
+        None => from
+            .file_stem()
+            .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned()),
+    };
+    let source = PuzzleSource::Path(from);
+    let puzzles = match order(matches) {
+        Some(order) => source.load_collection_with_order(order)?,
+        None => source.load_collection()?,
+    };
+    let mut options = BookOptions {
+        with_solutions: matches.is_present("SOLUTIONS"),
+        ..BookOptions::default()
+    };
+    // Validated by clap.
+    if let Some(per_page) = matches.value_of("PER_PAGE").and_then(|s| s.parse().ok()) {
+        options.per_page = per_page;
+    }
+    let out = PathBuf::from(matches.value_of("OUT").unwrap_or("."));
+    fs::create_dir_all(&out)?;
+    let pages = Collection::new(&title, puzzles).render_book(&options);
+    for (i, page) in pages.iter().enumerate() {
+        fs::write(out.join(page_name(i + 1, pages.len())), page)?;
+    }
+    Ok(())
+}
+
+// The subcommand is only registered with the `ui` feature.
+#[cfg(not(feature = "ui"))]
+fn book(_: &clap::ArgMatches) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Names the given (1-based) page of a book, zero-padding the number so the
+/// pages sort in order (e.g. `page-007.svg` of 120).
+#[cfg(feature = "ui")]
+fn page_name(page: usize, pages: usize) -> String {
+    let width = pages.to_string().len().max(3);
+    format!("page-{:0width$}.svg", page, width = width)
+}
+
+fn rate(matches: &clap::ArgMatches) -> Result<(), Error> {
+    let source = PuzzleSource::Path(PathBuf::from(matches.value_of("INPUT").unwrap_or_default()));
+    let puzzles = match order(matches) {
+        Some(order) => source.load_collection_with_order(order)?,
+        None => source.load_collection()?,
+    };
+    let path = match matches.value_of("COMPARE") {
+        Some(path) => path,
+        None => {
+            for puzzle in &puzzles {
+                match puzzle.score() {
+                    Some(score) => {
+                        let difficulty =
+                            Difficulty::from_score_with_boxes(score, puzzle.box_shape());
+                        println!("{},{},{}", analysis::fingerprint(puzzle), difficulty, score);
+                    }
+                    None => println!("{},,", analysis::fingerprint(puzzle)),
+                }
+            }
+            return Ok(());
+        }
+    };
+    let scale = matches
+        .value_of("SCALE")
+        .map_or_else(Vec::new, |scale| scale.split(',').map(str::trim).collect());
+    let labeled = analysis::parse_labels(&fs::read_to_string(path)?, &scale)
+        .and_then(|labels| analysis::label(&puzzles, &labels));
+    match labeled {
+        Ok(labeled) => print!("{}", DifficultyComparison::compute(&labeled)),
+        Err(error) => {
+            eprintln!("Couldn't read labels: {}.", error);
+            process::exit(1);
+        }
     }
     Ok(())
 }
 
 fn solve(matches: &clap::ArgMatches) -> Result<Sudoku, Error> {
-    puzzle(matches).and_then(|p| p.solution().map_err(Into::into))
+    let puzzle = puzzle(matches)?;
+    // Both limits were validated by clap, and they conflict, so at most one
+    // is given.
+    let timeout = matches.value_of("TIMEOUT").and_then(parse_timeout);
+    let max_nodes = matches.value_of("MAX_NODES").and_then(|s| s.parse().ok());
+    let solution = match (timeout, max_nodes) {
+        (Some(timeout), _) => puzzle.solution_within(timeout),
+        (None, Some(max_nodes)) => puzzle.solution_within_nodes(max_nodes),
+        (None, None) => puzzle.solution(),
+    };
+    solution.map_err(Into::into)
 }
 
 fn score(matches: &clap::ArgMatches) -> Option<usize> {
     puzzle(matches).ok().and_then(|p| p.score())
 }
+
+/// Parses a timeout given in (possibly fractional) seconds, rejecting
+/// negative, non-finite and unrepresentably long ones.
+fn parse_timeout(seconds: &str) -> Option<Duration> {
+    let seconds = seconds.parse().ok()?;
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Checks a `--timeout` value for clap.
+fn is_timeout(value: String) -> Result<(), String> {
+    match parse_timeout(&value) {
+        Some(_) => Ok(()),
+        None => Err(format!("\"{}\" isn't a number of seconds", value)),
+    }
+}
+
+/// Checks a value for clap that must be a count (a non-negative integer).
+fn is_count(value: String) -> Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("\"{}\" isn't a whole number", value)),
+    }
+}
+
+/// Checks a value for clap that must be a positive count.
+#[cfg(feature = "ui")]
+fn is_positive(value: String) -> Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(()),
+        _ => Err(format!("\"{}\" isn't a positive whole number", value)),
+    }
+}
+
+/// Checks a value for clap that must be an order supported by the compiled
+/// dimensionality.
+fn is_order(value: String) -> Result<(), String> {
+    match value.parse() {
+        Ok(order) if (MIN_ORDER..=MAX_ORDER).contains(&order) => Ok(()),
+        Ok(order) => Err(OrderError(order).to_string()),
+        Err(_) => Err(format!("\"{}\" isn't an order", value)),
+    }
+}
+
+/// Parses a date of the form `YYYY-MM-DD` into a `(year, month, day)` triple.
+///
+/// Only the format is checked here; the library validates the date itself.
+fn parse_date(date: &str) -> Option<(i32, u8, u8)> {
+    let mut parts = date.splitn(3, '-');
+    let year = parts.next()?;
+    let month = parts.next()?;
+    let day = parts.next()?;
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    Some((year.parse().ok()?, month.parse().ok()?, day.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "ui")]
+    use crate::page_name;
+    use crate::{parse_date, parse_timeout};
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("1.5"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_timeout("0"), Some(Duration::from_secs(0)));
+        for invalid in &["-1", "NaN", "inf", "1e300", "soon", ""] {
+            assert_eq!(parse_timeout(invalid), None);
+        }
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2024-06-01"), Some((2024, 6, 1)));
+        assert_eq!(parse_date("2024-6-1"), None);
+        assert_eq!(parse_date("2024-06"), None);
+        assert_eq!(parse_date("2024-06-01-01"), None);
+        assert_eq!(parse_date("year-06-01"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "ui")]
+    fn test_page_name() {
+        assert_eq!(page_name(7, 120), "page-007.svg");
+        assert_eq!(page_name(1, 1), "page-001.svg");
+        assert_eq!(page_name(42, 1200), "page-0042.svg");
+    }
+}
@@ -0,0 +1,253 @@
+//! Metadata describing a generated puzzle.
+use crate::gen::{generate_counted, Attempts};
+use crate::random::{entropy_seed, SeededSource};
+use crate::Difficulty;
+use crate::FillMethod;
+use crate::GenerateError;
+use crate::Grid;
+use crate::Point;
+use crate::Score;
+use crate::Sudoku;
+use crate::DIMENSIONS;
+
+use std::time::{Duration, Instant};
+
+/// The symmetry of a puzzle's pattern of clues (ignoring their values).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Symmetry {
+    /// The pattern is unchanged by a half turn (reversing every axis).
+    HalfTurn,
+    /// The pattern is unchanged by mirroring it left to right (reversing the
+    /// x-axis).
+    Mirror,
+    /// The pattern has neither of the above symmetries.
+    Asymmetric,
+}
+
+impl Symmetry {
+    /// Finds the symmetry of the puzzle's clue pattern.
+    ///
+    /// A pattern with both symmetries is reported as
+    /// [`HalfTurn`](#variant.HalfTurn).
+    pub fn of(puzzle: &Sudoku) -> Self {
+        if Symmetry::HalfTurn.holds(puzzle) {
+            Symmetry::HalfTurn
+        } else if Symmetry::Mirror.holds(puzzle) {
+            Symmetry::Mirror
+        } else {
+            Symmetry::Asymmetric
+        }
+    }
+
+    /// The share of the puzzle's clues (not counting those this symmetry
+    /// maps to themselves) whose images under this symmetry are also clues.
+    ///
+    /// This is 1 for a pattern with this symmetry, and 0 for one without a
+    /// single symmetric pair of clues (or for
+    /// [`Asymmetric`](#variant.Asymmetric)).
+    pub fn degree(self, puzzle: &Sudoku) -> f64 {
+        let (mut clues, mut paired) = (0, 0);
+        for point in puzzle.points() {
+            let image = match self.image(puzzle, point) {
+                Some(image) if image != point => image,
+                _ => continue,
+            };
+            if puzzle[point].is_some() {
+                clues += 1;
+                if puzzle[image].is_some() {
+                    paired += 1;
+                }
+            }
+        }
+        if clues == 0 {
+            0.0
+        } else {
+            f64::from(paired) / f64::from(clues)
+        }
+    }
+
+    /// Whether the puzzle's clue pattern is unchanged by this symmetry
+    /// (every pattern is unchanged by [`Asymmetric`](#variant.Asymmetric)).
+    pub(crate) fn holds(self, puzzle: &Sudoku) -> bool {
+        puzzle.points().into_iter().all(|point| {
+            self.image(puzzle, point)
+                .is_none_or(|image| puzzle[point].is_some() == puzzle[image].is_some())
+        })
+    }
+
+    /// The image of the point under this symmetry, if it has one.
+    pub(crate) fn image(self, puzzle: &Sudoku, mut point: Point) -> Option<Point> {
+        let last = puzzle.axis().saturating_sub(1);
+        match self {
+            Symmetry::HalfTurn => {
+                for i in 0..DIMENSIONS {
+                    point[i] = last - point[i];
+                }
+                Some(point)
+            }
+            Symmetry::Mirror => {
+                point[0] = last - point[0];
+                Some(point)
+            }
+            Symmetry::Asymmetric => None,
+        }
+    }
+}
+
+impl Sudoku {
+    /// How nearly symmetric the puzzle's clue pattern is: the larger
+    /// [degree](enum.Symmetry.html#method.degree) of its half-turn and
+    /// mirror symmetries, from 0 to 1.
+    pub fn symmetry_score(&self) -> f64 {
+        Symmetry::HalfTurn
+            .degree(self)
+            .max(Symmetry::Mirror.degree(self))
+    }
+}
+
+/// Everything worth recording about a generated puzzle, in one place.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PuzzleMeta {
+    /// The puzzle's order.
+    pub order: u8,
+    /// The puzzle's difficulty.
+    pub difficulty: Difficulty,
+    /// The puzzle's score.
+    pub score: usize,
+    /// The number of clues given.
+    pub clue_count: usize,
+    /// The symmetry of the clue pattern.
+    pub symmetry: Symmetry,
+    /// The seed the puzzle was generated from (drawn from entropy if none
+    /// was given), which
+    /// [`generate_seeded`](struct.Sudoku.html#method.generate_seeded) turns
+    /// back into the same puzzle.
+    pub seed: u64,
+    /// The number of complete grids the generator constructed, including
+    /// those it discarded before settling on this puzzle's.
+    pub grid_attempts: usize,
+    /// The number of candidate puzzles the hardening searches scored while
+    /// removing clues (zero if the fast path hit the target outright).
+    pub harden_attempts: usize,
+    /// How long generation took.
+    pub elapsed: Duration,
+}
+
+impl Sudoku {
+    /// Generates a puzzle of the desired order and difficulty (from the
+    /// given seed, if any), along with its metadata.
+    ///
+    /// The puzzle is the one [`generate_seeded`](#method.generate_seeded)
+    /// produces from the seed recorded in the metadata.
+    pub fn generate_with_meta(
+        order: u8,
+        difficulty: Difficulty,
+        seed: Option<u64>,
+    ) -> Result<(Sudoku, PuzzleMeta), GenerateError> {
+        let start = Instant::now();
+        let mut attempts = Attempts::default();
+        let method = FillMethod::Random;
+        let seed = seed
+            .or_else(entropy_seed)
+            .ok_or(GenerateError::EntropyFailed)?;
+        let generated = generate_counted(
+            order,
+            difficulty,
+            method,
+            &mut SeededSource::new(seed),
+            None,
+            &mut attempts,
+        )?;
+        let elapsed = start.elapsed();
+        let puzzle = generated.puzzle;
+        let score = puzzle.score().ok_or(GenerateError::Unsolvable)?;
+        let meta = PuzzleMeta {
+            order,
+            difficulty: generated.difficulty,
+            score,
+            clue_count: puzzle.clue_count(),
+            symmetry: Symmetry::of(&puzzle),
+            seed,
+            grid_attempts: attempts.grids,
+            harden_attempts: attempts.evaluations,
+            elapsed,
+        };
+        Ok((puzzle, meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::meta::Symmetry;
+    #[cfg(feature = "2D")]
+    use crate::Difficulty;
+    use crate::Element;
+    use crate::Grid;
+    #[cfg(feature = "2D")]
+    use crate::Score;
+    use crate::Sudoku;
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_with_meta() {
+        for seed in 0..3 {
+            let (puzzle, meta) =
+                Sudoku::generate_with_meta(2, Difficulty::Beginner, Some(seed)).unwrap();
+            let givens = puzzle.elements.iter().filter(|e| e.is_some()).count();
+            assert_eq!(meta.clue_count, givens);
+            assert_eq!(puzzle.score(), Some(meta.score));
+            assert_eq!(puzzle.difficulty(), Some(meta.difficulty));
+            assert_eq!(meta.symmetry, Symmetry::of(&puzzle));
+            assert_eq!((meta.order, meta.seed), (2, seed));
+            let seeded = Sudoku::generate_seeded(2, Difficulty::Beginner, seed).unwrap();
+            assert_eq!(seeded.puzzle, puzzle);
+        }
+        // Without a seed, the one drawn is recorded, and regenerates the
+        // puzzle.
+        let (puzzle, meta) = Sudoku::generate_with_meta(2, Difficulty::Beginner, None).unwrap();
+        let seeded = Sudoku::generate_seeded(2, Difficulty::Beginner, meta.seed).unwrap();
+        assert_eq!(seeded.puzzle, puzzle);
+        assert!(meta.grid_attempts >= 1);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_with_meta_attempts() {
+        // Any single run may get lucky, but over several seeds hitting a
+        // beginner target takes less work than an advanced one.
+        let total = |difficulty| {
+            (0..3)
+                .map(|seed| {
+                    let (_, meta) = Sudoku::generate_with_meta(3, difficulty, Some(seed)).unwrap();
+                    assert!(meta.grid_attempts >= 1);
+                    meta.grid_attempts + meta.harden_attempts
+                })
+                .sum::<usize>()
+        };
+        let (beginner, advanced) = (total(Difficulty::Beginner), total(Difficulty::Advanced));
+        assert!(beginner < advanced, "{} vs. {}", beginner, advanced);
+    }
+
+    #[test]
+    fn test_symmetry() {
+        let mut puzzle = Sudoku::new(2);
+        assert_eq!(Symmetry::of(&puzzle), Symmetry::HalfTurn);
+        let points = puzzle.points();
+        let (first, last) = (points[0], points[points.len() - 1]);
+        puzzle.substitute(first, Some(Element(1)));
+        assert_eq!(Symmetry::of(&puzzle), Symmetry::Asymmetric);
+        puzzle.substitute(last, Some(Element(1)));
+        assert_eq!(Symmetry::of(&puzzle), Symmetry::HalfTurn);
+        puzzle.substitute(last, None);
+        let mut mirrored = first;
+        mirrored[0] = 3;
+        puzzle.substitute(mirrored, Some(Element(2)));
+        assert_eq!(Symmetry::of(&puzzle), Symmetry::Mirror);
+        assert_eq!(Symmetry::Mirror.degree(&puzzle), 1.0);
+        assert_eq!(Symmetry::HalfTurn.degree(&puzzle), 0.0);
+        assert_eq!(puzzle.symmetry_score(), 1.0);
+        puzzle.substitute(last, Some(Element(1)));
+        assert!((Symmetry::HalfTurn.degree(&puzzle) - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(Symmetry::Asymmetric.degree(&puzzle), 0.0);
+    }
+}
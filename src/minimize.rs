@@ -0,0 +1,296 @@
+//! Clue analysis and puzzle minimization, optionally spread across threads.
+//!
+//! Both are deterministic: candidates are split into contiguous chunks whose
+//! results are combined in a fixed order, so the output depends only on the
+//! input (and seed, if any), never on the number of threads or on how they
+//! happen to be scheduled.
+use crate::dimensions::validate_order;
+use crate::gen::{grid, Error as GenerateError};
+use crate::random::{entropy_seed, SeededSource, Source};
+use crate::sol::other_solution;
+use crate::Element;
+use crate::Grid;
+use crate::Point;
+use crate::Score;
+use crate::Solve;
+use crate::Sudoku;
+
+use std::thread;
+
+/// The effect of removing a single clue from a puzzle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClueImpact {
+    /// The clue's location.
+    pub point: Point,
+    /// The clue's value.
+    pub element: Element,
+    /// The score of the puzzle without the clue, or `None` if removing it
+    /// leaves the puzzle without a unique solution.
+    pub score_without: Option<usize>,
+}
+
+impl Sudoku {
+    /// Reports the effect of removing each clue, in the order of
+    /// [`points`](trait.Grid.html#tymethod.points).
+    ///
+    /// The clues are checked using up to `threads` threads (at least one);
+    /// the report is the same for any number of threads.
+    pub fn clue_impact(&self, threads: usize) -> Vec<ClueImpact> {
+        let clues = self
+            .points()
+            .into_iter()
+            .filter_map(|point| self[point].map(|element| (point, element)))
+            .collect::<Vec<_>>();
+        map_chunked(&clues, threads, |(point, element)| {
+            let mut puzzle = self.clone();
+            puzzle.substitute(*point, None);
+            ClueImpact {
+                point: *point,
+                element: *element,
+                score_without: puzzle.score(),
+            }
+        })
+    }
+
+    /// Removes clues until every remaining clue is needed for the solution to
+    /// be unique, trying them in the order of
+    /// [`points`](trait.Grid.html#tymethod.points).
+    ///
+    /// The result is the same for any number of `threads` (at least one is
+    /// used), and is exactly the puzzle a one-at-a-time pass would produce.
+    /// A puzzle that isn't uniquely solvable is returned unchanged.
+    pub fn minimize(&self, threads: usize) -> Sudoku {
+        let clues = self
+            .points()
+            .into_iter()
+            .filter(|point| self[*point].is_some())
+            .collect();
+        minimize_in_order(self, clues, threads)
+    }
+
+    /// Removes clues the puzzle doesn't need, in place, returning how many
+    /// were removed.
+    ///
+    /// This is a single greedy pass in the order of
+    /// [`points`](trait.Grid.html#tymethod.points) (see
+    /// [`minimize`](#method.minimize)), so the result is tighter, but not
+    /// necessarily the smallest possible. A puzzle that isn't uniquely
+    /// solvable is left unchanged.
+    pub fn remove_redundant_clues(&mut self) -> usize {
+        let before = self.clue_count();
+        *self = self.minimize(1);
+        before - self.clue_count()
+    }
+
+    /// Like [`minimize`](#method.minimize), but tries the clues in an order
+    /// determined by the seed.
+    ///
+    /// The result depends only on the puzzle and the seed.
+    pub fn minimize_seeded(&self, seed: u64, threads: usize) -> Sudoku {
+        let mut clues = self
+            .points()
+            .into_iter()
+            .filter(|point| self[*point].is_some())
+            .collect::<Vec<_>>();
+        SeededSource::new(seed).shuffle(&mut clues);
+        minimize_in_order(self, clues, threads)
+    }
+
+    /// Generates a minimal puzzle of the given order (one from which no clue
+    /// can be removed), returning it with its number of clues, randomly from
+    /// the given seed (if any) or from entropy.
+    ///
+    /// The clues of a random grid are removed in a random order (see
+    /// [`minimize`](#method.minimize)), so the count reached is typically
+    /// well above the known minimum for the order (17 for order 3); comparing
+    /// the two shows how tight the puzzle is. With a seed, the same order
+    /// always produces the same puzzle.
+    pub fn generate_minimal(
+        order: u8,
+        seed: Option<u64>,
+    ) -> Result<(Sudoku, usize), GenerateError> {
+        let seed = seed
+            .or_else(entropy_seed)
+            .ok_or(GenerateError::EntropyFailed)?;
+        generate_minimal_from(order, &mut SeededSource::new(seed))
+    }
+}
+
+fn generate_minimal_from<R: Source>(
+    order: u8,
+    source: &mut R,
+) -> Result<(Sudoku, usize), GenerateError> {
+    validate_order(order)?;
+    let grid = grid(order, source).ok_or(GenerateError::NoGrid)?;
+    let mut clues = grid.points();
+    source.shuffle(&mut clues);
+    let puzzle = minimize_in_order(&grid, clues, 1);
+    let clues = puzzle.clue_count();
+    Ok((puzzle, clues))
+}
+
+/// Removes each candidate in turn if the puzzle stays uniquely solvable.
+///
+/// Each pass checks the next `threads` candidates against the current puzzle
+/// at once. A removal that fails can never succeed later (removing more clues
+/// only admits more solutions), so failures are dropped for good. Only the
+/// first success of the pass is applied; any later successes were checked
+/// against a puzzle that has since changed, so they're checked again in the
+/// next pass. This makes the result identical to checking the candidates one
+/// at a time, in order.
+fn minimize_in_order(puzzle: &Sudoku, candidates: Vec<Point>, threads: usize) -> Sudoku {
+    let mut puzzle = puzzle.clone();
+    let solution = match puzzle.solution() {
+        Ok(solution) => solution,
+        Err(_) => return puzzle,
+    };
+    let window = threads.max(1);
+    let mut pending = candidates;
+    while !pending.is_empty() {
+        let end = window.min(pending.len());
+        // Every puzzle along the way has the same unique solution, so a
+        // removal can only admit a second one that differs at that cell.
+        let removable = map_chunked(&pending[..end], window, |point| {
+            let mut candidate = puzzle.clone();
+            candidate.substitute(*point, None);
+            !other_solution(&candidate, &solution, &[*point]).0
+        });
+        let mut retry = vec![];
+        if let Some(first) = removable.iter().position(|r| *r) {
+            puzzle.substitute(pending[first], None);
+            retry.extend(
+                (first + 1..end)
+                    .filter(|i| removable[*i])
+                    .map(|i| pending[i]),
+            );
+        }
+        retry.extend_from_slice(&pending[end..]);
+        pending = retry;
+    }
+    puzzle
+}
+
+/// Maps `f` over the items using up to `threads` threads, returning the
+/// results in the same order as the items.
+///
+/// Each thread handles one contiguous chunk. With a single thread (or a
+/// single item), everything runs on the calling thread.
+fn map_chunked<T, U, F>(items: &[T], threads: usize, f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync,
+{
+    let threads = threads.max(1).min(items.len());
+    if threads <= 1 {
+        return items.iter().map(&f).collect();
+    }
+    let chunk = items.len().div_ceil(threads);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("worker thread panicked"))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::minimize::map_chunked;
+    #[cfg(feature = "2D")]
+    use crate::FillMethod;
+    #[cfg(feature = "2D")]
+    use crate::Grid;
+    #[cfg(feature = "2D")]
+    use crate::Score;
+    use crate::Sudoku;
+
+    #[test]
+    fn test_map_chunked_order() {
+        let items = (0..37).collect::<Vec<usize>>();
+        let expected = items.iter().map(|i| i * i).collect::<Vec<_>>();
+        for threads in 0..6 {
+            assert_eq!(map_chunked(&items, threads, |i| i * i), expected);
+        }
+        assert!(map_chunked::<usize, usize, _>(&[], 4, |i| *i).is_empty());
+    }
+
+    #[test]
+    fn test_minimize_not_unique() {
+        let sudoku = Sudoku::new(2);
+        assert_eq!(sudoku.minimize(2), sudoku);
+        let mut copy = sudoku.clone();
+        assert_eq!(copy.remove_redundant_clues(), 0);
+        assert_eq!(copy, sudoku);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_remove_redundant_clues() {
+        let grid = Sudoku::seed_full_grid(2, FillMethod::Shift).unwrap();
+        let mut puzzle = grid.clone();
+        let removed = puzzle.remove_redundant_clues();
+        assert!(removed > 0);
+        assert_eq!(puzzle.clue_count() + removed, grid.clue_count());
+        assert!(puzzle.score().is_some());
+        assert_eq!(puzzle.remove_redundant_clues(), 0);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_minimize_thread_independent() {
+        let grid = Sudoku::seed_full_grid(2, FillMethod::Shift).unwrap();
+        for seed in 0..4 {
+            let serial = grid.minimize_seeded(seed, 1);
+            assert!(serial.score().is_some());
+            // Every remaining clue is needed.
+            assert!(serial
+                .clue_impact(1)
+                .iter()
+                .all(|impact| impact.score_without.is_none()));
+            for threads in 2..=5 {
+                let parallel = grid.minimize_seeded(seed, threads);
+                assert_eq!(parallel.elements, serial.elements);
+            }
+        }
+        assert_eq!(grid.minimize(1), grid.minimize(3));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_clue_impact_thread_independent() {
+        let mut puzzle = Sudoku::seed_full_grid(3, FillMethod::Shift).unwrap();
+        for point in puzzle.points().into_iter().step_by(2) {
+            puzzle.substitute(point, None);
+        }
+        let serial = puzzle.clue_impact(1);
+        assert_eq!(serial.len(), puzzle.clue_count());
+        for impact in &serial {
+            assert_eq!(puzzle[impact.point], Some(impact.element));
+        }
+        assert!(serial.iter().any(|impact| impact.score_without.is_some()));
+        assert_eq!(puzzle.clue_impact(4), serial);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_minimal() {
+        use crate::Solve;
+        let (puzzle, clues) = Sudoku::generate_minimal(3, Some(0)).unwrap();
+        assert_eq!(clues, puzzle.clue_count());
+        assert!(clues >= 17);
+        assert!(puzzle.solution().is_ok());
+        // Every remaining clue is needed.
+        assert_eq!(puzzle.minimize(1), puzzle);
+        assert_eq!(
+            Sudoku::generate_minimal(3, Some(0)).unwrap(),
+            (puzzle, clues)
+        );
+        assert!(Sudoku::generate_minimal(crate::MAX_ORDER + 1, None).is_err());
+    }
+}
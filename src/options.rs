@@ -0,0 +1,604 @@
+//! Generation with every constraint spelled out, and named bundles of them.
+use crate::dimensions::validate_order;
+use crate::gen::{from_seed, generate_from, grid, Error};
+use crate::random::{entropy_seed, Source};
+use crate::sol::{other_solution, PossibilityMap};
+use crate::Difficulty;
+use crate::FillMethod;
+use crate::Generated;
+use crate::Grid;
+use crate::Point;
+use crate::Score;
+use crate::Solve;
+use crate::Sudoku;
+use crate::Symmetry;
+use crate::Technique;
+
+use std::{
+    fmt,
+    ops::RangeInclusive,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// The maximum number of grids
+/// [`generate_with_options`](struct.Sudoku.html#method.generate_with_options)
+/// will start from before giving up on meeting the options.
+const MAX_OPTIONS_ATTEMPTS: usize = 50;
+
+/// The number of orders each starting puzzle is carved in before moving on
+/// to a new one.
+const CARVES_PER_START: usize = 4;
+
+/// The highest [symmetry score](struct.Sudoku.html#method.symmetry_score)
+/// a puzzle generated with
+/// [`avoid_symmetry`](struct.GenerateOptions.html#structfield.avoid_symmetry)
+/// may have.
+const MAX_SYMMETRY_SCORE: f64 = 0.1;
+
+/// Everything that shapes a generated puzzle.
+///
+/// See [`GeneratePreset`](enum.GeneratePreset.html) for named bundles of
+/// options.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenerateOptions {
+    /// The order of the puzzle.
+    pub order: u8,
+    /// The difficulty the puzzle must grade as.
+    pub difficulty: Difficulty,
+    /// The symmetry the clue pattern must have;
+    /// [`Asymmetric`](enum.Symmetry.html#variant.Asymmetric) imposes none.
+    pub symmetry: Symmetry,
+    /// Whether the clue pattern must be far from symmetric: at most a tenth of
+    /// the clues may have their images under a half turn (or under the
+    /// mirror) also be clues, leaving no near-symmetry to exploit. This only
+    /// makes sense without a required `symmetry`.
+    pub avoid_symmetry: bool,
+    /// The range the number of clues must lie in.
+    pub givens: RangeInclusive<usize>,
+    /// Whether the puzzle must be solvable by deduction alone (using every
+    /// [technique](enum.Technique.html#associatedconstant.LADDER)).
+    pub logic_solvable: bool,
+    /// Whether every clue must be needed (removing any would admit another
+    /// solution).
+    pub minimal: bool,
+    /// Whether no empty cell may be a naked single in the starting position
+    /// (every one must have at least two candidates), so that even the first
+    /// move takes more than a glance. Few easy puzzles qualify, so this suits
+    /// the harder difficulties.
+    pub no_opening_singles: bool,
+    /// How long generation may take before giving up, if limited.
+    pub deadline: Option<Duration>,
+}
+
+impl GenerateOptions {
+    /// Options for a puzzle of the given order and difficulty, with no other
+    /// constraints.
+    pub fn new(order: u8, difficulty: Difficulty) -> Self {
+        Self {
+            order,
+            difficulty,
+            symmetry: Symmetry::Asymmetric,
+            avoid_symmetry: false,
+            givens: 0..=usize::MAX,
+            logic_solvable: false,
+            minimal: false,
+            no_opening_singles: false,
+            deadline: None,
+        }
+    }
+
+    /// Whether the puzzle meets every constraint (other than the deadline).
+    ///
+    /// The puzzle must have a unique solution.
+    pub fn accepts(&self, puzzle: &Sudoku) -> bool {
+        let clues = puzzle.clue_count();
+        puzzle.order == self.order
+            && self.givens.contains(&clues)
+            && self.symmetry.holds(puzzle)
+            && (!self.avoid_symmetry || puzzle.symmetry_score() <= MAX_SYMMETRY_SCORE)
+            && puzzle.difficulty() == Some(self.difficulty)
+            && (!self.logic_solvable || is_logic_solvable(puzzle))
+            && (!self.minimal || is_minimal(puzzle))
+            && (!self.no_opening_singles || !has_opening_single(puzzle))
+    }
+}
+
+/// A named bundle of [options](struct.GenerateOptions.html).
+///
+/// More presets may be added in future releases, so matches outside this
+/// crate need a wildcard arm.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum GeneratePreset {
+    /// Easy, symmetric (under a half turn) puzzles that can be solved by
+    /// deduction alone, as printed in newspapers.
+    NewspaperEasy,
+    /// Beginner 4×4 puzzles.
+    Kids,
+    /// Advanced puzzles without a single spare clue.
+    Expert,
+    /// Easy puzzles with plenty of clues, for large print.
+    LargePrint,
+}
+
+impl GeneratePreset {
+    /// Every preset.
+    pub const ALL: [GeneratePreset; 4] = [
+        GeneratePreset::NewspaperEasy,
+        GeneratePreset::Kids,
+        GeneratePreset::Expert,
+        GeneratePreset::LargePrint,
+    ];
+
+    /// Returns the preset's name, in kebab case (e.g. `newspaper-easy`).
+    pub fn name(self) -> &'static str {
+        match self {
+            GeneratePreset::NewspaperEasy => "newspaper-easy",
+            GeneratePreset::Kids => "kids",
+            GeneratePreset::Expert => "expert",
+            GeneratePreset::LargePrint => "large-print",
+        }
+    }
+
+    /// Parses a preset from its name, ignoring case.
+    pub fn parse(s: &str) -> Result<Self, ParsePresetError> {
+        s.parse()
+    }
+
+    /// Expands the preset to the options it stands for.
+    pub fn options(self) -> GenerateOptions {
+        match self {
+            GeneratePreset::NewspaperEasy => GenerateOptions {
+                order: 3,
+                difficulty: Difficulty::Easy,
+                symmetry: Symmetry::HalfTurn,
+                avoid_symmetry: false,
+                givens: 0..=usize::MAX,
+                logic_solvable: true,
+                minimal: false,
+                no_opening_singles: false,
+                deadline: None,
+            },
+            GeneratePreset::Kids => GenerateOptions {
+                order: 2,
+                difficulty: Difficulty::Beginner,
+                symmetry: Symmetry::Asymmetric,
+                avoid_symmetry: false,
+                givens: 0..=usize::MAX,
+                logic_solvable: false,
+                minimal: false,
+                no_opening_singles: false,
+                deadline: None,
+            },
+            GeneratePreset::Expert => GenerateOptions {
+                order: 3,
+                difficulty: Difficulty::Advanced,
+                symmetry: Symmetry::Asymmetric,
+                avoid_symmetry: false,
+                givens: 0..=usize::MAX,
+                logic_solvable: false,
+                minimal: true,
+                no_opening_singles: false,
+                deadline: None,
+            },
+            GeneratePreset::LargePrint => GenerateOptions {
+                order: 3,
+                difficulty: Difficulty::Easy,
+                symmetry: Symmetry::Asymmetric,
+                avoid_symmetry: false,
+                givens: 32..=usize::MAX,
+                logic_solvable: false,
+                minimal: false,
+                no_opening_singles: false,
+                deadline: None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for GeneratePreset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Represents a failure to parse a
+/// [`GeneratePreset`](enum.GeneratePreset.html) from its name.
+///
+/// The associated value is the unrecognized name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParsePresetError(pub String);
+
+impl fmt::Display for ParsePresetError {
+    /// Names the unrecognized preset and lists the valid ones.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = GeneratePreset::ALL
+            .iter()
+            .map(|preset| preset.name())
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "unknown preset \"{}\" (expected one of: {})",
+            self.0,
+            names.join(", ")
+        )
+    }
+}
+
+impl FromStr for GeneratePreset {
+    type Err = ParsePresetError;
+    /// Parses a preset from its name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|preset| preset.name().eq_ignore_ascii_case(s.trim()))
+            .cloned()
+            .ok_or_else(|| ParsePresetError(s.to_string()))
+    }
+}
+
+impl Sudoku {
+    /// Generates a puzzle meeting the given options, randomly from the given
+    /// seed (if any) or from entropy.
+    ///
+    /// Clues are removed from a random grid in a random order (together
+    /// with their images under the required symmetry, or, when avoiding
+    /// symmetry, preferring clues whose images are still clues), skipping
+    /// removals
+    /// that would admit a second solution, defeat deduction (if required),
+    /// drop below the fewest givens allowed or overshoot the difficulty,
+    /// until the puzzle meets the options. (Minimal puzzles without other
+    /// constraints start from a puzzle the generator has already hardened
+    /// to the difficulty, rather than a full grid.) Grids that can't be carved into
+    /// a match are discarded, and after a bounded number of them this fails
+    /// with [`OptionsUnsatisfied`](enum.GenerateError.html#variant.OptionsUnsatisfied)
+    /// (or with [`DeadlineExceeded`](enum.GenerateError.html#variant.DeadlineExceeded)
+    /// if the deadline passes first).
+    ///
+    /// With a seed, the same options always produce the same puzzle; without
+    /// one, the seed drawn from entropy is recorded in the result.
+    pub fn generate_with_options(
+        options: &GenerateOptions,
+        seed: Option<u64>,
+    ) -> Result<Generated, Error> {
+        let seed = seed.or_else(entropy_seed).ok_or(Error::EntropyFailed)?;
+        from_seed(seed, |source| generate_with_options_from(options, source))
+    }
+}
+
+fn generate_with_options_from<R: Source>(
+    options: &GenerateOptions,
+    source: &mut R,
+) -> Result<Generated, Error> {
+    validate_order(options.order)?;
+    // A deadline too far off to represent is no deadline at all.
+    let deadline = options
+        .deadline
+        .and_then(|deadline| Instant::now().checked_add(deadline));
+    let expired = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+    // A single pass of removals rarely lands a minimal puzzle on its target,
+    // so minimal puzzles start from the generator's hardened ones when
+    // nothing else constrains which clues go.
+    let harden_first =
+        options.minimal && options.symmetry == Symmetry::Asymmetric && !options.logic_solvable;
+    for _ in 0..MAX_OPTIONS_ATTEMPTS {
+        if expired() {
+            return Err(Error::DeadlineExceeded);
+        }
+        let (start, solution) = if harden_first {
+            let generated = generate_from(
+                options.order,
+                options.difficulty,
+                FillMethod::Random,
+                source,
+                None,
+            )?;
+            (generated.puzzle, generated.solution)
+        } else {
+            let grid = grid(options.order, source).ok_or(Error::NoGrid)?;
+            (grid.clone(), grid)
+        };
+        for _ in 0..CARVES_PER_START {
+            let puzzle = carve(start.clone(), &solution, options, source, &expired);
+            if options.accepts(&puzzle) {
+                return Ok(Generated {
+                    puzzle,
+                    solution,
+                    difficulty: options.difficulty,
+                    seed: None,
+                });
+            }
+        }
+    }
+    Err(Error::OptionsUnsatisfied)
+}
+
+/// Removes clues from the puzzle (whose solution is given), an orbit under
+/// the required symmetry at a time, as far as the options allow.
+fn carve<R: Source>(
+    mut puzzle: Sudoku,
+    solution: &Sudoku,
+    options: &GenerateOptions,
+    source: &mut R,
+    expired: &dyn Fn() -> bool,
+) -> Sudoku {
+    let mut orbits = orbits(&puzzle, options.symmetry);
+    source.shuffle(&mut orbits);
+    let mut clues = puzzle.clue_count();
+    while !orbits.is_empty() {
+        if expired() {
+            break;
+        }
+        // Breaking up symmetric pairs first, in the shuffled order otherwise.
+        let next = if options.avoid_symmetry {
+            (0..orbits.len())
+                .rev()
+                .max_by_key(|i| paired(&puzzle, &orbits[*i]))
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        let orbit = orbits.remove(next);
+        if orbit.iter().any(|point| puzzle[*point].is_none())
+            || clues < orbit.len() + options.givens.start()
+        {
+            continue;
+        }
+        for point in &orbit {
+            puzzle.substitute(*point, None);
+        }
+        let difficulty = if other_solution(&puzzle, solution, &orbit).0
+            || (options.logic_solvable && !is_logic_solvable(&puzzle))
+        {
+            None
+        } else {
+            puzzle
+                .difficulty()
+                .filter(|difficulty| *difficulty <= options.difficulty)
+        };
+        let difficulty = match difficulty {
+            Some(difficulty) => difficulty,
+            None => {
+                for point in &orbit {
+                    puzzle.substitute(*point, solution[*point]);
+                }
+                continue;
+            }
+        };
+        clues -= orbit.len();
+        if !options.minimal
+            && difficulty == options.difficulty
+            && options.givens.contains(&clues)
+            && (!options.avoid_symmetry || puzzle.symmetry_score() <= MAX_SYMMETRY_SCORE)
+            && (!options.no_opening_singles || !has_opening_single(&puzzle))
+        {
+            break;
+        }
+    }
+    puzzle
+}
+
+/// Splits the grid's cells into the orbits of the given symmetry (each cell
+/// with its images), in `points` order.
+fn orbits(grid: &Sudoku, symmetry: Symmetry) -> Vec<Vec<Point>> {
+    let mut seen = vec![false; grid.elements.len()];
+    let mut orbits = vec![];
+    for point in grid.points() {
+        let index = grid.shape().fold(point).unwrap_or_default();
+        if seen[index] {
+            continue;
+        }
+        let mut orbit = vec![point];
+        if let Some(image) = symmetry.image(grid, point) {
+            if image != point {
+                orbit.push(image);
+            }
+        }
+        for cell in &orbit {
+            if let Some(index) = grid.shape().fold(*cell) {
+                seen[index] = true;
+            }
+        }
+        orbits.push(orbit);
+    }
+    orbits
+}
+
+/// The number of symmetries under which the orbit's first cell is paired
+/// with another clue.
+fn paired(puzzle: &Sudoku, orbit: &[Point]) -> usize {
+    let point = orbit[0];
+    [Symmetry::HalfTurn, Symmetry::Mirror]
+        .iter()
+        .filter_map(|symmetry| symmetry.image(puzzle, point))
+        .filter(|image| *image != point && puzzle[*image].is_some())
+        .count()
+}
+
+/// Whether some empty cell has a single candidate, given the clues alone.
+///
+/// Removing clues only ever adds candidates, so once a carved puzzle has no
+/// such cell, it never regains one.
+fn has_opening_single(puzzle: &Sudoku) -> bool {
+    let map = PossibilityMap::from(puzzle.clone());
+    map.points()
+        .into_iter()
+        .any(|point| map[point].is_some_and(|set| set.freedom() == 1))
+}
+
+fn is_logic_solvable(puzzle: &Sudoku) -> bool {
+    puzzle.deduce(&Technique::LADDER).is_solved()
+}
+
+/// Whether removing any one clue would admit another solution.
+fn is_minimal(puzzle: &Sudoku) -> bool {
+    let solution = match puzzle.solution() {
+        Ok(solution) => solution,
+        Err(_) => return false,
+    };
+    puzzle
+        .points()
+        .into_iter()
+        .filter(|point| puzzle[*point].is_some())
+        .all(|point| {
+            // Any other solution differs from this one at the removed clue.
+            let mut fewer = puzzle.clone();
+            fewer.substitute(point, None);
+            other_solution(&fewer, &solution, &[point]).0
+        })
+}
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "2D")]
+    use crate::gen::Error;
+    use crate::options::GeneratePreset;
+    #[cfg(feature = "2D")]
+    use crate::options::{has_opening_single, GenerateOptions, MAX_SYMMETRY_SCORE};
+    #[cfg(feature = "2D")]
+    use crate::sol::PossibilityMap;
+    #[cfg(feature = "2D")]
+    use crate::Difficulty;
+    #[cfg(feature = "2D")]
+    use crate::Grid;
+    #[cfg(feature = "2D")]
+    use crate::Score;
+    #[cfg(feature = "2D")]
+    use crate::Solve;
+    #[cfg(feature = "2D")]
+    use crate::Sudoku;
+    #[cfg(feature = "2D")]
+    use crate::Symmetry;
+    #[cfg(feature = "2D")]
+    use crate::Technique;
+
+    #[cfg(feature = "2D")]
+    use std::time::Duration;
+
+    /// Generates a puzzle from the preset and checks every promise it makes,
+    /// independently of the generator's own checks.
+    #[cfg(feature = "2D")]
+    fn check(preset: GeneratePreset, seed: u64) {
+        let options = preset.options();
+        let generated = Sudoku::generate_with_options(&options, Some(seed)).unwrap();
+        let puzzle = &generated.puzzle;
+        assert_eq!(puzzle.order, options.order);
+        assert_eq!(puzzle.solution().unwrap(), generated.solution);
+        assert_eq!(puzzle.difficulty(), Some(options.difficulty));
+        assert_eq!(generated.difficulty, options.difficulty);
+        assert!(options.givens.contains(&puzzle.clue_count()));
+        if options.symmetry != Symmetry::Asymmetric {
+            assert_eq!(Symmetry::of(puzzle), options.symmetry);
+        }
+        if options.logic_solvable {
+            assert!(puzzle.deduce(&Technique::LADDER).is_solved());
+        }
+        if options.no_opening_singles {
+            assert!(!has_opening_single(puzzle));
+        }
+        if options.minimal {
+            for point in puzzle.points() {
+                if puzzle[point].is_some() {
+                    let mut fewer = puzzle.clone();
+                    fewer.substitute(point, None);
+                    assert!(!fewer.is_uniquely_solvable());
+                }
+            }
+        }
+    }
+
+    /// A seed for which the preset generates quickly, even in debug builds.
+    ///
+    /// The match is exhaustive so that every new preset gets a seed (and so
+    /// gets tested).
+    #[cfg(feature = "2D")]
+    fn quick_seed(preset: GeneratePreset) -> u64 {
+        match preset {
+            GeneratePreset::NewspaperEasy => 2,
+            GeneratePreset::Kids => 0,
+            GeneratePreset::Expert => 28,
+            GeneratePreset::LargePrint => 3,
+        }
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_presets() {
+        for preset in GeneratePreset::ALL.iter() {
+            check(*preset, quick_seed(*preset));
+        }
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_avoid_symmetry() {
+        let mut options = GenerateOptions::new(3, Difficulty::Easy);
+        let plain = Sudoku::generate_with_options(&options, Some(0)).unwrap();
+        assert!(plain.puzzle.symmetry_score() > MAX_SYMMETRY_SCORE);
+        options.avoid_symmetry = true;
+        let generated = Sudoku::generate_with_options(&options, Some(0)).unwrap();
+        let puzzle = &generated.puzzle;
+        assert!(puzzle.symmetry_score() <= MAX_SYMMETRY_SCORE);
+        assert_eq!(Symmetry::of(puzzle), Symmetry::Asymmetric);
+        assert_eq!(puzzle.difficulty(), Some(Difficulty::Easy));
+        assert_eq!(puzzle.solution().unwrap(), generated.solution);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_no_opening_singles() {
+        let mut options = GenerateOptions::new(3, Difficulty::Intermediate);
+        options.no_opening_singles = true;
+        let generated = Sudoku::generate_with_options(&options, Some(0)).unwrap();
+        let puzzle = &generated.puzzle;
+        let map = PossibilityMap::from(puzzle.clone());
+        for point in puzzle.points() {
+            match map[point] {
+                Some(set) => assert!(set.freedom() >= 2, "{} is a naked single", point),
+                None => assert!(puzzle[point].is_some()),
+            }
+        }
+        assert_eq!(puzzle.difficulty(), Some(Difficulty::Intermediate));
+        assert_eq!(puzzle.solution().unwrap(), generated.solution);
+    }
+
+    #[test]
+    fn test_preset_parse() {
+        for preset in GeneratePreset::ALL.iter() {
+            assert_eq!(GeneratePreset::parse(preset.name()), Ok(*preset));
+        }
+        assert_eq!("Newspaper-Easy".parse(), Ok(GeneratePreset::NewspaperEasy));
+        let error = GeneratePreset::parse("tabloid").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown preset \"tabloid\" (expected one of: newspaper-easy, kids, expert, \
+             large-print)"
+        );
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_with_options_errors() {
+        let mut options = GenerateOptions::new(2, Difficulty::Beginner);
+        options.givens = 0..=3;
+        assert_eq!(
+            Sudoku::generate_with_options(&options, Some(0)),
+            Err(Error::OptionsUnsatisfied)
+        );
+        options.deadline = Some(Duration::from_secs(0));
+        assert_eq!(
+            Sudoku::generate_with_options(&options, Some(0)),
+            Err(Error::DeadlineExceeded)
+        );
+        // A deadline too far off to represent never passes.
+        options.deadline = Some(Duration::MAX);
+        assert_eq!(
+            Sudoku::generate_with_options(&options, Some(0)),
+            Err(Error::OptionsUnsatisfied)
+        );
+        assert_eq!(
+            Sudoku::generate_with_options(&GenerateOptions::new(0, Difficulty::Easy), None),
+            Err(Error::UnsupportedOrder(0))
+        );
+    }
+}
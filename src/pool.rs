@@ -0,0 +1,172 @@
+//! A pool of complete grids, reused (in disguise) across generations.
+use crate::gen::grid;
+use crate::random::Source;
+use crate::Element;
+use crate::Grid;
+use crate::Sudoku;
+use crate::DIMENSIONS;
+
+use std::collections::HashMap;
+
+/// The number of grids a [`GridPool`](struct.GridPool.html) keeps per order
+/// by default.
+const DEFAULT_GRIDS_PER_ORDER: usize = 4;
+
+/// A pool of complete grids, so that generating many puzzles (see
+/// [`Sudoku::generate_pooled`](struct.Sudoku.html#method.generate_pooled))
+/// doesn't construct a fresh grid every time.
+///
+/// Grids are built lazily, as they're asked for, until the pool holds its
+/// capacity for the order. From then on, each request reuses a kept grid at
+/// random, disguised by a random transformation that keeps it valid: the
+/// values are relabeled, and along each axis, the bands are shuffled, as are
+/// the lines within each band.
+#[derive(Clone, Debug)]
+pub struct GridPool {
+    grids: HashMap<u8, Vec<Sudoku>>,
+    grids_per_order: usize,
+    hits: usize,
+    misses: usize,
+}
+
+impl GridPool {
+    /// Creates an empty pool, keeping up to four grids per order.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_GRIDS_PER_ORDER)
+    }
+
+    /// Creates an empty pool keeping up to the given number of grids (at
+    /// least one) per order.
+    ///
+    /// More grids take longer to build, but disguise reuse better.
+    pub fn with_capacity(grids_per_order: usize) -> Self {
+        Self {
+            grids: HashMap::new(),
+            grids_per_order: grids_per_order.max(1),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns a complete grid of the given order, built fresh if the pool
+    /// isn't yet full for the order, and otherwise transformed from a kept
+    /// grid.
+    pub(crate) fn grid<R: Source>(&mut self, order: u8, source: &mut R) -> Option<Sudoku> {
+        let kept = self.grids.entry(order).or_default();
+        if kept.len() < self.grids_per_order {
+            let fresh = grid(order, source)?;
+            kept.push(fresh.clone());
+            self.misses += 1;
+            Some(fresh)
+        } else {
+            self.hits += 1;
+            let index = source.below(kept.len());
+            Some(transform(&kept[index], source))
+        }
+    }
+
+    /// Returns the number of grids reused from the pool.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Returns the number of grids built (and kept) by the pool.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Returns the number of grids kept, across all orders.
+    pub fn len(&self) -> usize {
+        self.grids.values().map(Vec::len).sum()
+    }
+
+    /// Whether the pool keeps no grids.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every kept grid (but not the counts).
+    pub fn clear(&mut self) {
+        self.grids.clear();
+    }
+}
+
+impl Default for GridPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Relabels the grid's values and shuffles its bands (and the lines within
+/// them) along each axis, which maps every group onto a group.
+fn transform<R: Source>(grid: &Sudoku, source: &mut R) -> Sudoku {
+    let order = usize::from(grid.order);
+    let mut values = (1..=order.pow(2) as u8).collect::<Vec<_>>();
+    source.shuffle(&mut values);
+    let mut lines = Vec::with_capacity(DIMENSIONS);
+    for _ in 0..DIMENSIONS {
+        let mut bands = (0..order).collect::<Vec<_>>();
+        source.shuffle(&mut bands);
+        let mut axis = Vec::with_capacity(order.pow(2));
+        for band in bands {
+            let mut within = (0..order).map(|i| band * order + i).collect::<Vec<_>>();
+            source.shuffle(&mut within);
+            axis.extend(within);
+        }
+        lines.push(axis);
+    }
+    let mut transformed = grid.clone();
+    for point in grid.points() {
+        let mut image = point;
+        for (i, axis) in lines.iter().enumerate() {
+            image[i] = axis[usize::from(point[i])] as u8;
+        }
+        let value = grid[point].map(|Element(value)| Element(values[usize::from(value) - 1]));
+        transformed.substitute(image, value);
+    }
+    transformed
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "2D")]
+    use crate::pool::{transform, GridPool};
+    #[cfg(feature = "2D")]
+    use crate::random::SeededSource;
+    #[cfg(feature = "2D")]
+    use crate::Sudoku;
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_transform() {
+        let mut source = SeededSource::new(0);
+        for order in 2..=3 {
+            let mut pool = GridPool::with_capacity(1);
+            let grid = pool.grid(order, &mut source).unwrap();
+            let transformed = transform(&grid, &mut source);
+            assert!(transformed.is_solved());
+            assert_ne!(transformed, grid);
+        }
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_generate_pooled() {
+        use crate::{Difficulty, Solve};
+        let mut pool = GridPool::with_capacity(1);
+        let first = Sudoku::generate_pooled(3, Difficulty::Beginner, &mut pool).unwrap();
+        assert_eq!((pool.misses(), pool.len()), (1, 1));
+        let hits = pool.hits();
+        let second = Sudoku::generate_pooled(3, Difficulty::Beginner, &mut pool).unwrap();
+        assert_eq!(pool.misses(), 1);
+        assert!(pool.hits() > hits);
+        assert_ne!(first.puzzle, second.puzzle);
+        assert_ne!(first.solution, second.solution);
+        for generated in &[first, second] {
+            assert!(generated.solution.is_solved());
+            assert_eq!(generated.puzzle.solution().unwrap(), generated.solution);
+        }
+        pool.clear();
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,385 @@
+//! Drawing random puzzles of a given difficulty from stored sets, topped up
+//! by the generator.
+use crate::random::{entropy_seed, SeededSource, Source};
+use crate::Difficulty;
+use crate::Element;
+use crate::Point;
+use crate::Score;
+use crate::Sudoku;
+
+/// How many seeds a generator source is given to produce a puzzle within one
+/// band of the requested difficulty before the sampler gives up topping up.
+const MAX_GENERATION_ATTEMPTS: usize = 4;
+
+/// How many shuffles of a stored puzzle are graded in search of one as hard
+/// as the puzzle itself.
+const MAX_VARIANT_ATTEMPTS: usize = 8;
+
+/// A place for a [`Sampler`](struct.Sampler.html) to get puzzles from.
+///
+/// More kinds of source may be added in future releases, so matches outside
+/// this crate need a wildcard arm.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum SamplerSource {
+    /// A fixed set of puzzles, each drawn at most once.
+    Stored(Vec<Sudoku>),
+    /// The generator, producing puzzles of the given order on demand.
+    Generator {
+        /// The order of the puzzles to generate.
+        order: u8,
+    },
+}
+
+#[cfg(feature = "ui")]
+impl From<crate::ui::book::Collection> for SamplerSource {
+    fn from(collection: crate::ui::book::Collection) -> Self {
+        SamplerSource::Stored(collection.puzzles)
+    }
+}
+
+/// Where a sampled puzzle came from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Provenance {
+    /// A variant of a stored puzzle.
+    Stored {
+        /// The index of the source among those the sampler was given.
+        source: usize,
+        /// The index of the puzzle within the source.
+        index: usize,
+    },
+    /// A freshly generated puzzle.
+    Generated {
+        /// The index of the source among those the sampler was given.
+        source: usize,
+        /// The seed the puzzle was generated from (see
+        /// [`Sudoku::generate_seeded`](struct.Sudoku.html#method.generate_seeded)).
+        seed: u64,
+    },
+}
+
+/// A puzzle drawn by a [`Sampler`](struct.Sampler.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sampled {
+    /// The puzzle.
+    pub puzzle: Sudoku,
+    /// The puzzle's grade.
+    pub difficulty: Difficulty,
+    /// Where the puzzle came from.
+    pub provenance: Provenance,
+}
+
+/// Draws random puzzles of a given difficulty, preferring stored puzzles to
+/// generating new ones.
+///
+/// Stored puzzles are graded once, when the sampler is created, and each is
+/// drawn at most once over the sampler's lifetime (puzzles without a unique
+/// solution are never drawn). Rather than the stored puzzle itself, the
+/// sampler hands out a variant of it of the same difficulty, with its bands,
+/// stacks, rows within a band, columns within a stack and values shuffled,
+/// so the same puzzle drawn by two samplers needn't look the same.
+///
+/// Only once the stored puzzles of the requested difficulty run out does the
+/// sampler turn to its generator sources, in turn.
+#[derive(Clone, Debug)]
+pub struct Sampler {
+    pools: Vec<Pool>,
+    // The generator pool to try first when next topping up.
+    next_generator: usize,
+}
+
+#[derive(Clone, Debug)]
+enum Pool {
+    Stored(Vec<Entry>),
+    Generator(u8),
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    puzzle: Sudoku,
+    difficulty: Option<Difficulty>,
+    drawn: bool,
+}
+
+impl Sampler {
+    /// Creates a sampler drawing from the given sources, grading every stored
+    /// puzzle.
+    pub fn new(sources: Vec<SamplerSource>) -> Self {
+        let pools = sources
+            .into_iter()
+            .map(|source| match source {
+                SamplerSource::Stored(puzzles) => Pool::Stored(
+                    puzzles
+                        .into_iter()
+                        .map(|puzzle| Entry {
+                            difficulty: puzzle.difficulty(),
+                            puzzle,
+                            drawn: false,
+                        })
+                        .collect(),
+                ),
+                SamplerSource::Generator { order } => Pool::Generator(order),
+            })
+            .collect();
+        Self {
+            pools,
+            next_generator: 0,
+        }
+    }
+
+    /// The number of stored puzzles of the given difficulty not yet drawn.
+    pub fn remaining(&self, difficulty: Difficulty) -> usize {
+        self.candidates(difficulty).len()
+    }
+
+    /// Draws `k` puzzles of the given difficulty, randomly from the given
+    /// seed (if any) or from entropy.
+    ///
+    /// Stored puzzles of exactly that difficulty come first, in random order;
+    /// if there aren't `k` of them left, the rest are generated, each within
+    /// one band of the difficulty. Fewer than `k` puzzles are returned only
+    /// if the sampler has no generator sources, or they repeatedly fail to
+    /// produce a close enough puzzle (and none at all if no seed was given
+    /// and the source of entropy fails).
+    ///
+    /// With a seed, a sampler created from the same sources and asked for the
+    /// same puzzles always draws the same ones.
+    pub fn sample(&mut self, difficulty: Difficulty, k: usize, seed: Option<u64>) -> Vec<Sampled> {
+        match seed.or_else(entropy_seed) {
+            Some(seed) => self.sample_from(difficulty, k, &mut SeededSource::new(seed)),
+            None => vec![],
+        }
+    }
+
+    fn sample_from<S: Source>(
+        &mut self,
+        difficulty: Difficulty,
+        k: usize,
+        source: &mut S,
+    ) -> Vec<Sampled> {
+        let mut candidates = self.candidates(difficulty);
+        source.shuffle(&mut candidates);
+        let mut sampled = Vec::with_capacity(k);
+        for (pool, index) in candidates.into_iter().take(k) {
+            if let Pool::Stored(entries) = &mut self.pools[pool] {
+                let entry = &mut entries[index];
+                entry.drawn = true;
+                sampled.push(Sampled {
+                    puzzle: variant(&entry.puzzle, difficulty, source),
+                    difficulty,
+                    provenance: Provenance::Stored {
+                        source: pool,
+                        index,
+                    },
+                });
+            }
+        }
+        while sampled.len() < k {
+            match self.generate(difficulty, source) {
+                Some(generated) => sampled.push(generated),
+                None => break,
+            }
+        }
+        sampled
+    }
+
+    /// The stored puzzles of the given difficulty not yet drawn, as (pool,
+    /// index) pairs.
+    fn candidates(&self, difficulty: Difficulty) -> Vec<(usize, usize)> {
+        self.pools
+            .iter()
+            .enumerate()
+            .filter_map(|(pool, source)| match source {
+                Pool::Stored(entries) => Some((pool, entries)),
+                Pool::Generator(_) => None,
+            })
+            .flat_map(|(pool, entries)| {
+                entries
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, entry)| !entry.drawn && entry.difficulty == Some(difficulty))
+                    .map(move |(index, _)| (pool, index))
+            })
+            .collect()
+    }
+
+    /// Generates a puzzle within one band of the given difficulty, trying the
+    /// generator pools in turn.
+    fn generate<S: Source>(&mut self, difficulty: Difficulty, source: &mut S) -> Option<Sampled> {
+        let generators = self
+            .pools
+            .iter()
+            .enumerate()
+            .filter_map(|(pool, source)| match source {
+                Pool::Generator(order) => Some((pool, *order)),
+                Pool::Stored(_) => None,
+            })
+            .collect::<Vec<_>>();
+        if generators.is_empty() {
+            return None;
+        }
+        for _ in 0..MAX_GENERATION_ATTEMPTS {
+            let (pool, order) = generators[self.next_generator % generators.len()];
+            self.next_generator = self.next_generator.wrapping_add(1);
+            let seed = source.next_u64();
+            if let Ok(generated) = Sudoku::generate_seeded(order, difficulty, seed) {
+                if bands_apart(generated.difficulty, difficulty) <= 1 {
+                    return Some(Sampled {
+                        puzzle: generated.puzzle,
+                        difficulty: generated.difficulty,
+                        provenance: Provenance::Generated { source: pool, seed },
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+fn bands_apart(a: Difficulty, b: Difficulty) -> u8 {
+    let (a, b) = (a.stars(), b.stars());
+    a.max(b) - a.min(b)
+}
+
+/// Shuffles the puzzle's bands, stacks, rows within each band, columns
+/// within each stack and values, none of which changes whether it has a
+/// unique solution.
+///
+/// The solver's tie-breaking can still grade the result differently, so the
+/// shuffle is retried until it grades as the given difficulty, falling back
+/// to the puzzle itself.
+fn variant<S: Source>(puzzle: &Sudoku, difficulty: Difficulty, source: &mut S) -> Sudoku {
+    let (rows, columns) = puzzle.box_shape();
+    let axis = puzzle.axis();
+    for _ in 0..MAX_VARIANT_ATTEMPTS {
+        // A band is `rows` rows tall, so there are `columns` bands (and
+        // vice versa for stacks).
+        let row_map = lines(rows, columns, source);
+        let column_map = lines(columns, rows, source);
+        let mut values = (1..=axis).collect::<Vec<_>>();
+        source.shuffle(&mut values);
+        let mut shuffled = puzzle.clone();
+        for y in 0..axis {
+            for x in 0..axis {
+                let value = puzzle[Point([column_map[x as usize], row_map[y as usize]])]
+                    .map(|Element(value)| Element(values[value as usize - 1]));
+                shuffled.substitute(Point([x, y]), value);
+            }
+        }
+        if shuffled.difficulty() == Some(difficulty) {
+            return shuffled;
+        }
+    }
+    puzzle.clone()
+}
+
+/// A random permutation of `width * count` lines that keeps each of the
+/// `count` groups of `width` consecutive lines together.
+fn lines<S: Source>(width: u8, count: u8, source: &mut S) -> Vec<u8> {
+    let mut groups = (0..count).collect::<Vec<_>>();
+    source.shuffle(&mut groups);
+    let mut lines = Vec::with_capacity(width as usize * count as usize);
+    for group in groups {
+        let mut offsets = (0..width).collect::<Vec<_>>();
+        source.shuffle(&mut offsets);
+        lines.extend(offsets.into_iter().map(|offset| group * width + offset));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::corpus;
+    #[cfg(feature = "2D")]
+    use crate::random::SeededSource;
+    #[cfg(feature = "2D")]
+    use crate::sample::{bands_apart, lines, variant, Provenance};
+    use crate::sample::{Sampler, SamplerSource};
+    #[cfg(feature = "2D")]
+    use crate::Difficulty;
+    #[cfg(feature = "2D")]
+    use crate::Score;
+    #[cfg(feature = "2D")]
+    use crate::Solve;
+
+    fn sampler() -> Sampler {
+        Sampler::new(vec![
+            SamplerSource::Stored(corpus::by_tag("order-3")),
+            SamplerSource::Generator { order: 3 },
+        ])
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_variant() {
+        let puzzle = corpus::get("2D-O3").unwrap();
+        let difficulty = puzzle.difficulty().unwrap();
+        let mut source = SeededSource::new(7);
+        let shuffled = variant(&puzzle, difficulty, &mut source);
+        assert_ne!(shuffled, puzzle);
+        assert_eq!(shuffled.clue_count(), puzzle.clue_count());
+        assert!(shuffled.is_uniquely_solvable());
+        assert_eq!(shuffled.difficulty(), Some(difficulty));
+        let mut lines = lines(3, 3, &mut source);
+        lines.sort_unstable();
+        assert_eq!(lines, (0..9).collect::<Vec<_>>());
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_sample_exhaustion() {
+        let mut sampler = sampler();
+        let stored = sampler.remaining(Difficulty::Beginner);
+        assert!(stored > 0);
+        let sampled = sampler.sample(Difficulty::Beginner, stored + 1, Some(1));
+        assert_eq!(sampled.len(), stored + 1);
+        let (from_store, generated) = sampled.split_at(stored);
+        assert!(from_store
+            .iter()
+            .all(|s| matches!(s.provenance, Provenance::Stored { source: 0, .. })));
+        assert!(matches!(
+            generated[0].provenance,
+            Provenance::Generated { source: 1, .. }
+        ));
+        assert_eq!(sampler.remaining(Difficulty::Beginner), 0);
+        // Drawn puzzles stay drawn across calls.
+        let sampled = sampler.sample(Difficulty::Beginner, 1, Some(2));
+        assert!(matches!(
+            sampled[0].provenance,
+            Provenance::Generated { .. }
+        ));
+        // Without a generator, the supply simply runs out.
+        let mut stored_only = Sampler::new(vec![SamplerSource::Stored(corpus::by_tag("order-3"))]);
+        assert_eq!(
+            stored_only
+                .sample(Difficulty::Beginner, stored + 1, Some(1))
+                .len(),
+            stored
+        );
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_sample_deterministic() {
+        let k = sampler().remaining(Difficulty::Beginner) + 1;
+        let first = sampler().sample(Difficulty::Beginner, k, Some(5));
+        let second = sampler().sample(Difficulty::Beginner, k, Some(5));
+        assert_eq!(first, second);
+        let other = sampler().sample(Difficulty::Beginner, k, Some(6));
+        assert_ne!(first, other);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_sample_difficulty() {
+        let mut sampler = sampler();
+        for difficulty in [Difficulty::Beginner, Difficulty::Easy].iter() {
+            let k = sampler.remaining(*difficulty) + 1;
+            for sampled in sampler.sample(*difficulty, k, Some(3)) {
+                let graded = sampled.puzzle.difficulty().unwrap();
+                assert_eq!(graded, sampled.difficulty);
+                assert!(bands_apart(graded, *difficulty) <= 1);
+            }
+        }
+    }
+}
@@ -23,19 +23,47 @@
 //! The final difficulty score is given by `D = S * C + E`, where `C` is the
 //! first power of 10 greater than the number of elements and `E` is the number
 //! of empty elements.
-use crate::sudoku::Grid;
+use crate::random::fnv1a;
+use crate::sudoku::{Grid, Shape};
 use crate::Element;
 use crate::Point;
 use crate::Sudoku;
+use crate::Technique;
 use crate::DIMENSIONS;
 
-use std::ops::{Index, IndexMut};
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::{Index, IndexMut, RangeInclusive},
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+/// The names of the techniques the solver uses, in the order it applies
+/// them: the logical [ladder](enum.Technique.html#associatedconstant.LADDER),
+/// then backtracking for whatever logic leaves open.
+pub(crate) fn techniques() -> Vec<&'static str> {
+    Technique::LADDER
+        .iter()
+        .map(|technique| technique.name())
+        .chain(Some("backtracking"))
+        .collect()
+}
 
 /// Represents the difficulty of a puzzle.
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+///
+/// More grades may be added in future releases, so matches outside this crate
+/// need a wildcard arm.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
 pub enum Difficulty {
-    #[doc(hidden)]
-    /// Filler
+    /// Trivially easy grids that don't constitute a real puzzle (e.g. a grid
+    /// with only a handful of cells removed).
+    ///
+    /// Scores are graded as unplayable so that every score has a grade, but
+    /// the generator never targets or returns unplayable puzzles; asking it
+    /// for this difficulty yields a [`Beginner`](#variant.Beginner) puzzle.
     Unplayable,
     /// Very easy puzzles, ideal for learning a new game.
     Beginner,
@@ -53,27 +81,265 @@ impl From<usize> for Difficulty {
     fn from(score: usize) -> Self {
         use crate::Difficulty::*;
         match score {
-            0...49 => Unplayable,
-            50...150 => Beginner,
-            151...250 => Easy,
-            251...400 => Intermediate,
-            401...550 => Difficult,
+            0..=49 => Unplayable,
+            50..=150 => Beginner,
+            151..=250 => Easy,
+            251..=400 => Intermediate,
+            401..=550 => Difficult,
             _ => Advanced,
         }
     }
 }
 
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use crate::Difficulty::*;
+        let name = match self {
+            Unplayable => "Unplayable",
+            Beginner => "Beginner",
+            Easy => "Easy",
+            Intermediate => "Intermediate",
+            Difficult => "Difficult",
+            Advanced => "Advanced",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Represents a failure to parse a [`Difficulty`](enum.Difficulty.html) from
+/// its name.
+///
+/// The associated value is the unrecognized name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseDifficultyError(pub String);
+
+impl fmt::Display for ParseDifficultyError {
+    /// Names the unrecognized difficulty and lists the valid ones.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = Difficulty::ALL
+            .iter()
+            .map(|difficulty| difficulty.to_string().to_lowercase())
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "unknown difficulty \"{}\" (expected one of: {})",
+            self.0,
+            names.join(", ")
+        )
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = ParseDifficultyError;
+    /// Parses a difficulty from its name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|difficulty| difficulty.to_string().eq_ignore_ascii_case(s.trim()))
+            .cloned()
+            .ok_or_else(|| ParseDifficultyError(s.to_string()))
+    }
+}
+
+impl Difficulty {
+    /// Every difficulty, from easiest to hardest.
+    ///
+    /// This includes [`Unplayable`](#variant.Unplayable); use
+    /// [`is_playable`](#method.is_playable) to filter it out.
+    pub const ALL: [Difficulty; 6] = [
+        Difficulty::Unplayable,
+        Difficulty::Beginner,
+        Difficulty::Easy,
+        Difficulty::Intermediate,
+        Difficulty::Difficult,
+        Difficulty::Advanced,
+    ];
+
+    /// Whether this difficulty denotes a real puzzle (that is, whether it is
+    /// anything other than [`Unplayable`](#variant.Unplayable)).
+    pub fn is_playable(self) -> bool {
+        self != Difficulty::Unplayable
+    }
+
+    /// The range of raw scores graded as this difficulty by the standard
+    /// bands (orders 3 and above).
+    pub fn score_range(self) -> RangeInclusive<usize> {
+        use crate::Difficulty::*;
+        match self {
+            Unplayable => 0..=49,
+            Beginner => 50..=150,
+            Easy => 151..=250,
+            Intermediate => 251..=400,
+            Difficult => 401..=550,
+            Advanced => 551..=usize::MAX,
+        }
+    }
+
+    /// The number of stars this difficulty is rated (see
+    /// [`StarRating`](struct.StarRating.html)).
+    ///
+    /// [`Unplayable`](#variant.Unplayable) puzzles are rated 0 stars.
+    pub fn stars(self) -> u8 {
+        use crate::Difficulty::*;
+        match self {
+            Unplayable => 0,
+            Beginner => 1,
+            Easy => 2,
+            Intermediate => 3,
+            Difficult => 4,
+            Advanced => 5,
+        }
+    }
+
+    /// Grades a raw score for a puzzle of the given order.
+    ///
+    /// Orders of 3 and above use the standard bands (see the `From<usize>`
+    /// implementation). Order-2 puzzles have too few cells for those bands to
+    /// mean anything, so they are graded by the number of empty cells `E`
+    /// (0–3: unplayable, 4–7: beginner, 8–9: easy, 10–11: intermediate, 12+:
+    /// difficult), moving up one band if the branch-difficulty score `S`
+    /// exceeds 1 (see [Scoring](index.html#scoring)).
+    ///
+    /// Use [`from_score_with_boxes`](#method.from_score_with_boxes) for
+    /// puzzles with rectangular boxes.
+    pub fn from_score(score: usize, order: u8) -> Self {
+        Self::from_score_with_boxes(score, (order, order))
+    }
+
+    /// Grades a raw score for a puzzle whose boxes are `rows` cells tall and
+    /// `columns` wide (see [`Sudoku::box_shape`](struct.Sudoku.html#method.box_shape)).
+    ///
+    /// This matches [`from_score`](#method.from_score) for square boxes.
+    /// Rectangular boxes (whose order is always 2) are graded by their empty
+    /// cells too, with the bands scaled to the size of the grid (so a 6×6
+    /// grid needs 9–17 empty cells to count as a beginner puzzle).
+    pub fn from_score_with_boxes(score: usize, boxes: (u8, u8)) -> Self {
+        use crate::Difficulty::*;
+        let order = boxes.0.min(boxes.1);
+        if order != 2 {
+            return score.into();
+        }
+        // The bands for a 4×4 grid, as fractions of the cells in a layer.
+        let cells = (usize::from(boxes.0) * usize::from(boxes.1)).pow(2);
+        let c = c_for_order(order);
+        let (s, e) = (score / c, score % c);
+        let difficulty = if e * 4 < cells {
+            Unplayable
+        } else if e * 2 < cells {
+            Beginner
+        } else if e * 8 < cells * 5 {
+            Easy
+        } else if e * 4 < cells * 3 {
+            Intermediate
+        } else {
+            Difficult
+        };
+        if s > 1 && difficulty != Unplayable {
+            match difficulty {
+                Beginner => Easy,
+                Easy => Intermediate,
+                Intermediate => Difficult,
+                _ => Advanced,
+            }
+        } else {
+            difficulty
+        }
+    }
+}
+
+/// A difficulty expressed as a number of stars, from 1 (the easiest) to 5
+/// (the hardest), as shown by apps that rate puzzles with stars.
+///
+/// Each star count corresponds to one playable
+/// [`Difficulty`](enum.Difficulty.html): one star is
+/// [`Beginner`](enum.Difficulty.html#variant.Beginner), two
+/// [`Easy`](enum.Difficulty.html#variant.Easy), three
+/// [`Intermediate`](enum.Difficulty.html#variant.Intermediate), four
+/// [`Difficult`](enum.Difficulty.html#variant.Difficult), and five
+/// [`Advanced`](enum.Difficulty.html#variant.Advanced).
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct StarRating(u8);
+
+impl StarRating {
+    /// The fewest stars a puzzle can be rated.
+    pub const MIN: u8 = 1;
+    /// The most stars a puzzle can be rated.
+    pub const MAX: u8 = 5;
+
+    /// Creates a rating of the given number of stars, if it lies within
+    /// `MIN..=MAX`.
+    pub fn new(stars: u8) -> Option<Self> {
+        if (Self::MIN..=Self::MAX).contains(&stars) {
+            Some(StarRating(stars))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of stars.
+    pub fn stars(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the difficulty this rating corresponds to.
+    pub fn difficulty(self) -> Difficulty {
+        Difficulty::ALL[usize::from(self.0)]
+    }
+}
+
 /// Encodes errors encountered while attempting a puzzle solution.
+///
+/// More variants may be added in future releases, so matches outside this
+/// crate need a wildcard arm:
+///
+/// ```
+/// # use sudoku::{Solve, SolveError, Sudoku};
+/// let sudoku = Sudoku::new(3);
+/// let message = match sudoku.solution() {
+///     Ok(_) => "solved",
+///     Err(SolveError::MultipleSolutions) => "more than one solution",
+///     Err(SolveError::ValueOutOfRange(..)) => "malformed puzzle",
+///     Err(_) => "couldn't solve",
+/// };
+/// assert_eq!(message, "more than one solution");
+/// ```
 #[derive(Clone, Debug)]
 #[allow(missing_copy_implementations)] // This is an error type.
+#[non_exhaustive]
 pub enum Error {
     /// A mere placeholder; this will be replaced by proper errors in a future
     /// revision.
     Unknown,
+    /// The puzzle has more than one solution.
+    MultipleSolutions,
+    /// The puzzle contains a value outside `1..=order²` at the given location.
+    ValueOutOfRange(Point, Element),
+    /// The search ran past its deadline before finishing.
+    Timeout,
+    /// The search was cancelled before finishing.
+    Cancelled,
+    /// The search visited its maximum number of nodes before finishing.
+    Exhausted,
     #[doc(hidden)]
     __TestOther,
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unknown => write!(f, "the puzzle has no solution"),
+            Error::MultipleSolutions => write!(f, "the puzzle has more than one solution"),
+            Error::ValueOutOfRange(point, Element(value)) => {
+                write!(f, "the value {} at {} is out of range", value, point)
+            }
+            Error::Timeout => write!(f, "the search timed out"),
+            Error::Cancelled => write!(f, "the search was cancelled"),
+            Error::Exhausted => write!(f, "the search reached its node limit"),
+            Error::__TestOther => write!(f, "test error"),
+        }
+    }
+}
+
 /// Trait defining a solvable puzzle.
 pub trait Solve: Sized {
     /// Returns the puzzle's unique solution if it exists.
@@ -94,6 +360,46 @@ pub trait Score: Solve {
     }
 }
 
+/// Extension trait for iterators of sudokus.
+pub trait SudokuIterExt: Iterator<Item = Sudoku> + Sized {
+    /// Grades each puzzle, keeping those whose difficulty lies in the given
+    /// range.
+    ///
+    /// Puzzles that can't be graded (those without a unique solution) are
+    /// dropped.
+    fn filter_difficulty(self, range: RangeInclusive<Difficulty>) -> FilterDifficulty<Self> {
+        FilterDifficulty { inner: self, range }
+    }
+}
+
+impl<I: Iterator<Item = Sudoku>> SudokuIterExt for I {}
+
+/// An iterator over the puzzles of another whose difficulty lies in a range.
+///
+/// Returned by [`SudokuIterExt::filter_difficulty`](trait.SudokuIterExt.html#method.filter_difficulty).
+#[derive(Clone, Debug)]
+pub struct FilterDifficulty<I> {
+    inner: I,
+    range: RangeInclusive<Difficulty>,
+}
+
+impl<I: Iterator<Item = Sudoku>> Iterator for FilterDifficulty<I> {
+    type Item = Sudoku;
+
+    fn next(&mut self) -> Option<Sudoku> {
+        let range = &self.range;
+        self.inner.by_ref().find(|sudoku| {
+            sudoku
+                .difficulty()
+                .is_some_and(|difficulty| range.contains(&difficulty))
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
 // TODO(#12): Allow higher orders (u128?)
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct PossibilitySet {
@@ -102,15 +408,39 @@ pub struct PossibilitySet {
 
 impl PossibilitySet {
     /// Creates a new set full of possibilities.
-    pub fn new(order: u8) -> Self {
+    #[cfg(test)]
+    pub const fn new(order: u8) -> Self {
+        Self::full(order.pow(2))
+    }
+    /// Creates a set holding exactly the given values, or `None` if there are
+    /// none or any lies outside `1..=order²`.
+    #[cfg(test)]
+    pub fn from_values(values: &[usize], order: u8) -> Option<Self> {
+        let axis = usize::from(order).pow(2).min(64);
+        let mut set = 0;
+        for value in values {
+            if !(1..=axis).contains(value) {
+                return None;
+            }
+            set |= 1 << (value - 1);
+        }
+        match set {
+            0 => None,
+            values => Some(Self { values }),
+        }
+    }
+    /// Creates a new set holding every value from 1 to `axis`.
+    pub const fn full(axis: u8) -> Self {
         let mut values = 0;
-        for i in 1..=order.pow(2) as usize {
-            values |= 1 << (i - 1);
+        let mut i = 0;
+        while i < axis {
+            values |= 1 << i;
+            i += 1;
         }
         Self { values }
     }
     /// Elminates the given possible value from the set and returns the result.
-    pub fn eliminate(self, value: usize) -> Option<Self> {
+    pub const fn eliminate(self, value: usize) -> Option<Self> {
         let values = self.values & !(1 << (value - 1));
         match values {
             0 => None,
@@ -118,7 +448,7 @@ impl PossibilitySet {
         }
     }
     /// The number of possible values in this set.
-    pub fn freedom(self) -> usize {
+    pub const fn freedom(self) -> usize {
         let mut x = self.values;
         let mut n = 0;
         while x > 0 {
@@ -128,7 +458,7 @@ impl PossibilitySet {
         n
     }
     /// Whether the set contains the given possibility.
-    pub fn contains(self, value: usize) -> bool {
+    pub const fn contains(self, value: usize) -> bool {
         self.values | (1 << (value - 1)) == self.values
     }
 }
@@ -136,19 +466,22 @@ impl PossibilitySet {
 #[derive(Debug)]
 pub struct PossibilityMap {
     possibilities: Vec<Option<PossibilitySet>>,
-    order: u8,
+    shape: Shape,
     parent: Option<Sudoku>,
 }
 
 impl PossibilityMap {
     /// Constructs a blank possibilitiy map of the given order.
+    #[cfg(test)]
     pub fn new(order: u8) -> Self {
+        Self::with_shape(Shape::square(order))
+    }
+
+    /// Constructs a blank possibility map of the given shape.
+    fn with_shape(shape: Shape) -> Self {
         Self {
-            possibilities: vec![
-                Some(PossibilitySet::new(order));
-                (order as usize).pow(2 + DIMENSIONS as u32)
-            ],
-            order,
+            possibilities: vec![Some(PossibilitySet::full(shape.axis())); shape.len()],
+            shape,
             parent: None,
         }
     }
@@ -161,17 +494,31 @@ impl PossibilityMap {
         self[index] = self[index].and_then(|e| e.eliminate(value));
     }
 
-    // Returns the next easiest index to solve and its corresponding value.
-    pub fn next(&self) -> (Option<Point>, Option<PossibilitySet>) {
+    // Returns the next easiest index to solve and its corresponding value,
+    // breaking ties between the cells with the fewest possibilities as the
+    // strategy asks (by default, the first such cell wins).
+    pub fn next(&self, strategy: BacktrackStrategy) -> (Option<Point>, Option<PossibilitySet>) {
         let mut best = None;
         let mut best_index = None;
         let mut best_score = None;
+        // The degree of the best cell, computed only once there's a tie.
+        let mut best_degree = None;
         for index in self.points() {
             if let Some(element) = self[index] {
                 if best_score.is_none() || best_score.unwrap() > element.freedom() {
                     best = Some(element);
                     best_index = Some(index);
                     best_score = Some(element.freedom());
+                    best_degree = None;
+                } else if strategy.degree_tiebreak && best_score == Some(element.freedom()) {
+                    let current =
+                        *best_degree.get_or_insert_with(|| self.degree(best_index.unwrap()));
+                    let degree = self.degree(index);
+                    if degree > current {
+                        best = Some(element);
+                        best_index = Some(index);
+                        best_degree = Some(degree);
+                    }
                 }
             } else if let Some(ref parent) = self.parent {
                 if parent[index].is_none() {
@@ -184,46 +531,123 @@ impl PossibilityMap {
     }
 }
 
+impl PossibilityMap {
+    /// Hashes the solver state made up of the grid and this map (which must
+    /// be the grid's), for spotting states that have been searched before.
+    ///
+    /// The hash is the 64-bit FNV-1a hash of the grid's empty cells (as a
+    /// bitmap in [`points`](trait.Grid.html#tymethod.points) order, eight
+    /// cells to a byte, least significant bit first) followed by the
+    /// [compact snapshot](#method.compact_snapshot) (each word
+    /// little-endian). The values of the filled cells are left out on
+    /// purpose: the rest of the search only sees them through the
+    /// candidates, so states that differ only there have exactly the same
+    /// completions, and hash the same. The hash is stable within a process
+    /// run (and in fact across runs), but isn't part of any format.
+    pub fn state_hash(&self, grid: &Sudoku) -> u64 {
+        let mut bytes = vec![0; grid.elements.len().div_ceil(8)];
+        for (i, element) in grid.elements.iter().enumerate() {
+            if element.is_none() {
+                bytes[i / 8] |= 1 << (i % 8);
+            }
+        }
+        for word in self.compact_snapshot().iter() {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        fnv1a(&bytes)
+    }
+
+    /// Packs the candidates of every cell into as few words as possible.
+    ///
+    /// Each cell takes `axis` bits (bit `v - 1` standing for the value `v`),
+    /// in [`points`](trait.Grid.html#tymethod.points) order, filling each
+    /// word from its least significant bit; a cell may straddle two words.
+    /// Cells without candidates (filled ones, or dead ends) are all zeros.
+    pub fn compact_snapshot(&self) -> Box<[u64]> {
+        let width = usize::from(self.shape.axis());
+        let mut words = vec![0_u64; (self.possibilities.len() * width).div_ceil(64)];
+        for (i, set) in self.possibilities.iter().enumerate() {
+            let values = set.map_or(0, |set| set.values);
+            let (word, shift) = ((i * width) / 64, (i * width) % 64);
+            words[word] |= values << shift;
+            if shift + width > 64 {
+                words[word + 1] |= values >> (64 - shift);
+            }
+        }
+        words.into_boxed_slice()
+    }
+
+    // Returns the number of empty cells sharing both a group and a
+    // possibility with the point.
+    fn degree(&self, point: Point) -> usize {
+        let shape = self.shape;
+        let corner = shape.snap(point);
+        let own = self[point].map_or(0, |set| set.values);
+        let shares = |set: Option<PossibilitySet>| set.is_some_and(|set| set.values & own != 0);
+        let mut degree = 0;
+        // The box, walking the offsets from its corner like digits whose
+        // bases are the box's extents.
+        for offset in 0..usize::from(shape.axis()) {
+            let mut peer = corner;
+            let mut rest = offset;
+            for i in 0..DIMENSIONS {
+                let extent = usize::from(shape.extent(i));
+                peer[i] += (rest % extent) as u8;
+                rest /= extent;
+            }
+            if peer != point && shares(self[peer]) {
+                degree += 1;
+            }
+        }
+        // The lines through the point, outside the box.
+        for i in 0..DIMENSIONS {
+            let extent = shape.extent(i);
+            for coordinate in 0..shape.axis() {
+                let mut peer = point;
+                peer[i] = coordinate;
+                if coordinate / extent != point[i] / extent && shares(self[peer]) {
+                    degree += 1;
+                }
+            }
+        }
+        degree
+    }
+}
+
 impl Index<Point> for PossibilityMap {
     type Output = Option<PossibilitySet>;
 
     fn index(&self, index: Point) -> &Self::Output {
-        let index = index.fold(self.order);
+        let index = self.shape.fold(index).expect("point lies outside the grid");
         &self.possibilities[index]
     }
 }
 
 impl IndexMut<Point> for PossibilityMap {
     fn index_mut(&mut self, index: Point) -> &mut Option<PossibilitySet> {
-        let index = index.fold(self.order);
+        let index = self.shape.fold(index).expect("point lies outside the grid");
         &mut self.possibilities[index]
     }
 }
 
 impl Grid for PossibilityMap {
     fn points(&self) -> Vec<Point> {
-        (0..(self.order as usize).pow(2 + DIMENSIONS as u32))
-            .map(|p| Point::unfold(p, self.order))
-            .collect()
+        self.shape.points()
     }
 }
 
 impl From<Sudoku> for PossibilityMap {
     fn from(sudoku: Sudoku) -> Self {
-        let order = sudoku.order;
-        let mut map = PossibilityMap::new(order);
-        for i in 0..(sudoku.order as usize).pow(2 + DIMENSIONS as u32) {
-            let point = Point::unfold(i, order);
+        let shape = sudoku.shape();
+        let mut map = PossibilityMap::with_shape(shape);
+        for point in shape.points() {
             if sudoku[point].is_some() {
                 map[point] = None;
             } else {
                 let groups = sudoku.groups(point);
                 for group in &groups {
-                    let elements = group.elements();
-                    for element in elements {
-                        if let Some(Element(value)) = element {
-                            map.eliminate(point, value as usize);
-                        }
+                    for Element(value) in group.as_slice().iter().flatten() {
+                        map.eliminate(point, *value as usize);
                     }
                 }
             }
@@ -233,66 +657,436 @@ impl From<Sudoku> for PossibilityMap {
     }
 }
 
+/// Options for the backtracking search behind
+/// [`Sudoku::solution_with`](struct.Sudoku.html#method.solution_with).
+///
+/// At each step, the search fills the empty cell with the fewest candidates.
+/// By default, ties go to the first such cell in
+/// [`points`](trait.Grid.html#tymethod.points) order; this is what scoring
+/// uses, so that scores stay stable.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BacktrackStrategy {
+    degree_tiebreak: bool,
+    memo: Option<usize>,
+}
+
+impl BacktrackStrategy {
+    /// A strategy that breaks ties between the cells with the fewest
+    /// candidates by the degree heuristic: the cell constraining the most
+    /// empty cells (those sharing a group and a candidate with it) goes
+    /// first, since filling it prunes the most of the rest of the search.
+    ///
+    /// This usually explores fewer nodes than the default, but it's a
+    /// heuristic, so it isn't better on every puzzle.
+    pub fn with_degree_tiebreak() -> Self {
+        Self {
+            degree_tiebreak: true,
+            memo: None,
+        }
+    }
+
+    /// This strategy, also keeping a transposition table of up to
+    /// `capacity` states known to have no solution, so the search can skip
+    /// them when they come up again.
+    ///
+    /// States are compared by their empty cells and candidates (see
+    /// [`PossibilityMap::state_hash`](struct.PossibilityMap.html#method.state_hash)),
+    /// so a state can come up again with different values in its filled
+    /// cells. A state is only recorded once every branch below it has been
+    /// searched without finding a solution; a branch cut short by the
+    /// two-solution cap (or a bound) has either found a solution or not
+    /// been searched in full, so it's never recorded. Skipping a recorded
+    /// state therefore never skips a solution, and the solutions found, the
+    /// uniqueness verdict and the score all stay the same. Hashes are
+    /// confirmed against the stored snapshot before a state is skipped, so
+    /// collisions can't cause wrong answers either.
+    pub fn with_memo(self, capacity: usize) -> Self {
+        Self {
+            memo: Some(capacity),
+            ..self
+        }
+    }
+}
+
+/// The sequence of assignments made along the search path from the puzzle to
+/// its solution.
+///
+/// Each step records the point filled, the value placed there, and the
+/// branching factor `B` (the number of candidates the point had when it was
+/// chosen) that contributed to the puzzle's score (see
+/// [Scoring](index.html#scoring)).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolvePath(pub Vec<(Point, Element, usize)>);
+
+impl SolvePath {
+    /// The number of steps in the path.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// Whether the path is empty (the puzzle was already complete).
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Iterates over the steps of the path in order.
+    pub fn iter(&self) -> impl Iterator<Item = &(Point, Element, usize)> {
+        self.0.iter()
+    }
+    /// The largest branching factor encountered along the path.
+    pub fn max_branch_factor(&self) -> usize {
+        self.iter().map(|(_, _, b)| *b).max().unwrap_or_default()
+    }
+}
+
 pub fn solve(puzzle: &Sudoku) -> Result<Sudoku, Error> {
     solve_and_score(puzzle).map(|(sol, _)| sol)
 }
 
 pub fn solve_and_score(puzzle: &Sudoku) -> Result<(Sudoku, usize), Error> {
-    let mut context = Context {
-        problem: puzzle.clone(),
-        count: 0,
-        solution: None,
-        branch_score: 0,
-    };
-    recurse(&mut context, 0);
+    search(puzzle, false).map(|(sol, score, _)| (sol, score))
+}
+
+/// Returns the number of times the solver backtracked on its way to the
+/// puzzle's unique solution, if it exists.
+pub fn backtrack_count(puzzle: &Sudoku) -> Option<usize> {
+    verify(puzzle).ok()?;
+    let context = backtrack(puzzle, 2, false, &mut Ascending);
+    if context.count == 1 {
+        Some(context.backtracks)
+    } else {
+        None
+    }
+}
+
+/// Returns the path the solver took to the puzzle's unique solution.
+pub fn solve_path(puzzle: &Sudoku) -> Option<SolvePath> {
+    search(puzzle, true).ok().and_then(|(_, _, path)| path)
+}
+
+/// Whether the puzzle has a solution other than `known` (which must be one of
+/// its solutions).
+pub fn has_other_solution(puzzle: &Sudoku, known: &Sudoku) -> bool {
+    let empty = puzzle
+        .points()
+        .into_iter()
+        .filter(|point| puzzle[*point].is_none())
+        .collect::<Vec<_>>();
+    other_solution(puzzle, known, &empty).0
+}
+
+/// Searches for a solution of the puzzle that differs from `known` in at
+/// least one of the given cells, returning whether one exists along with the
+/// number of search nodes visited.
+///
+/// The cells are taken one at a time, and each value other than the known
+/// one is placed and searched for a single solution. Once a cell has no
+/// alternative, every remaining solution agrees with `known` there, so it's
+/// filled in before moving on; the searches get easier as they go.
+pub(crate) fn other_solution(puzzle: &Sudoku, known: &Sudoku, cells: &[Point]) -> (bool, usize) {
+    debug_assert!(known.is_solved(), "the known solution isn't a solution");
+    let axis = usize::from(puzzle.axis());
+    let mut puzzle = puzzle.clone();
+    let mut nodes = 0;
+    for cell in cells {
+        puzzle.substitute(*cell, None);
+        let map: PossibilityMap = puzzle.clone().into();
+        nodes += 1;
+        let candidates = match map[*cell] {
+            Some(set) => (1..=axis).filter(|v| set.contains(*v)).collect(),
+            None => vec![],
+        };
+        for value in candidates {
+            let element = Some(Element(value as u8));
+            if element == known[*cell] {
+                continue;
+            }
+            puzzle.substitute(*cell, element);
+            let context = backtrack(&puzzle, 1, false, &mut Ascending);
+            nodes += context.nodes;
+            if context.count > 0 {
+                return (true, nodes);
+            }
+        }
+        puzzle.substitute(*cell, known[*cell]);
+    }
+    (false, nodes)
+}
+
+/// Searches for the puzzle's unique solution using the given strategy.
+pub fn solve_with(puzzle: &Sudoku, strategy: BacktrackStrategy) -> Result<Sudoku, Error> {
+    verify(puzzle)?;
+    let context = backtrack_with(puzzle, 2, false, &mut Ascending, strategy);
+    if context.count > 1 {
+        return Err(Error::MultipleSolutions);
+    }
+    context.solution.ok_or(Error::Unknown)
+}
+
+/// Searches for the puzzle's unique solution, returning it along with its
+/// score and (if requested) the path taken to it.
+fn search(puzzle: &Sudoku, record_path: bool) -> Result<(Sudoku, usize, Option<SolvePath>), Error> {
+    verify(puzzle)?;
+    // Two solutions are enough to know that the puzzle isn't uniquely
+    // solvable.
+    let context = backtrack(puzzle, 2, record_path, &mut Ascending);
+    if context.count > 1 {
+        return Err(Error::MultipleSolutions);
+    }
     let s = context.branch_score;
     let c = calculate_c(puzzle) as isize;
     let e = count_empty(puzzle) as isize;
+    let path = context.solution_path.map(SolvePath);
     context
         .solution
         .ok_or(Error::Unknown)
-        .map(|sol| (sol, (s * c + e) as usize))
+        .map(|sol| (sol, (s * c + e) as usize, path))
+}
+
+/// Checks the puzzle for malformed data before searching.
+fn verify(puzzle: &Sudoku) -> Result<(), Error> {
+    match puzzle.out_of_range().first() {
+        Some((point, element)) => Err(Error::ValueOutOfRange(*point, *element)),
+        None => Ok(()),
+    }
 }
 
-struct Context {
+/// Chooses the order in which a cell's candidates are tried during a
+/// [backtracking search](fn.backtrack.html).
+pub(crate) trait CandidateOrder {
+    /// Removes and returns the next candidate to try, if any remain.
+    fn take(&mut self, candidates: &mut Vec<usize>) -> Option<usize>;
+}
+
+/// Tries candidates in ascending order (used by the solver, so that scores
+/// are deterministic).
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Ascending;
+
+impl CandidateOrder for Ascending {
+    fn take(&mut self, candidates: &mut Vec<usize>) -> Option<usize> {
+        if candidates.is_empty() {
+            None
+        } else {
+            Some(candidates.remove(0))
+        }
+    }
+}
+
+/// Limits on a backtracking search, which stops with the corresponding
+/// error as soon as any is reached.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Bounds<'a> {
+    // The most nodes to visit.
+    pub(crate) max_nodes: Option<usize>,
+    // When to give up.
+    pub(crate) deadline: Option<Instant>,
+    // Set (by another thread) to give up.
+    pub(crate) cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> Bounds<'a> {
+    /// The number of nodes between checks of the clock.
+    const CLOCK_INTERVAL: usize = 64;
+
+    /// Returns why a search that has visited the given number of nodes
+    /// should stop, if it should.
+    fn check(&self, nodes: usize) -> Option<Error> {
+        if self.max_nodes.is_some_and(|max| nodes >= max) {
+            Some(Error::Exhausted)
+        } else if self.cancel.is_some_and(|c| c.load(Ordering::Relaxed)) {
+            Some(Error::Cancelled)
+        } else if nodes.is_multiple_of(Self::CLOCK_INTERVAL)
+            && self.deadline.is_some_and(|d| Instant::now() >= d)
+        {
+            Some(Error::Timeout)
+        } else {
+            None
+        }
+    }
+}
+
+/// The state and results of a backtracking search.
+pub(crate) struct Context<'a> {
     problem: Sudoku,
-    count: usize,
-    solution: Option<Sudoku>,
+    strategy: BacktrackStrategy,
+    // The search stops once this many solutions have been found.
+    limit: usize,
+    pub(crate) count: usize,
+    pub(crate) solution: Option<Sudoku>,
+    // The number of nodes visited, for measuring the cost of a search.
+    pub(crate) nodes: usize,
+    // The limits on the search, and the error for the one reached (if any).
+    bounds: Bounds<'a>,
+    stopped: Option<Error>,
     branch_score: isize,
+    // The number of candidates tried (and undone) without finding a solution
+    // before the first solution was reached.
+    pub(crate) backtracks: usize,
+    // The assignments leading to the current node, if we're recording them.
+    path: Option<Vec<(Point, Element, usize)>>,
+    solution_path: Option<Vec<(Point, Element, usize)>>,
+    // The states known to have no solution, by their hashes (if the
+    // strategy keeps a memo).
+    memo: Option<HashMap<u64, Box<[u64]>>>,
 }
 
-fn recurse(mut context: &mut Context, difficulty: isize) {
+/// Performs a backtracking search from the given puzzle.
+///
+/// At each node, the empty cell with the fewest candidates is filled with
+/// each of its candidates in the order chosen by `order`. The search stops
+/// after `limit` solutions have been found (or the tree is exhausted); the
+/// first solution found is kept, along with its branch-difficulty score.
+pub(crate) fn backtrack<O: CandidateOrder>(
+    puzzle: &Sudoku,
+    limit: usize,
+    record_path: bool,
+    order: &mut O,
+) -> Context<'static> {
+    backtrack_with(
+        puzzle,
+        limit,
+        record_path,
+        order,
+        BacktrackStrategy::default(),
+    )
+}
+
+/// Performs a [backtracking search](fn.backtrack.html), choosing cells as the
+/// strategy asks.
+pub(crate) fn backtrack_with<O: CandidateOrder>(
+    puzzle: &Sudoku,
+    limit: usize,
+    record_path: bool,
+    order: &mut O,
+    strategy: BacktrackStrategy,
+) -> Context<'static> {
+    let mut context = Context::new(puzzle, limit, record_path, strategy);
+    recurse(&mut context, 0, order);
+    context
+}
+
+impl<'a> Context<'a> {
+    fn new(puzzle: &Sudoku, limit: usize, record_path: bool, strategy: BacktrackStrategy) -> Self {
+        Self {
+            problem: puzzle.clone(),
+            strategy,
+            limit,
+            count: 0,
+            solution: None,
+            nodes: 0,
+            bounds: Bounds::default(),
+            stopped: None,
+            branch_score: 0,
+            backtracks: 0,
+            path: if record_path { Some(vec![]) } else { None },
+            solution_path: None,
+            memo: strategy.memo.map(|_| HashMap::new()),
+        }
+    }
+}
+
+/// Searches for the puzzle's unique solution within the given bounds,
+/// failing with [`Exhausted`](enum.Error.html#variant.Exhausted),
+/// [`Cancelled`](enum.Error.html#variant.Cancelled) or
+/// [`Timeout`](enum.Error.html#variant.Timeout) if one is reached first.
+pub(crate) fn solve_bounded(puzzle: &Sudoku, bounds: Bounds) -> Result<Sudoku, Error> {
+    solve_and_score_bounded(puzzle, bounds).map(|(sol, _)| sol)
+}
+
+/// Searches for the puzzle's unique solution within the given bounds (as
+/// [`solve_bounded`](fn.solve_bounded.html) does), scoring it along the
+/// way. The search is the one [`solve_and_score`](fn.solve_and_score.html)
+/// makes, so the scores agree.
+pub(crate) fn solve_and_score_bounded(
+    puzzle: &Sudoku,
+    bounds: Bounds,
+) -> Result<(Sudoku, usize), Error> {
+    verify(puzzle)?;
+    let mut context = Context::new(puzzle, 2, false, BacktrackStrategy::default());
+    context.bounds = bounds;
+    recurse(&mut context, 0, &mut Ascending);
+    if context.count > 1 {
+        return Err(Error::MultipleSolutions);
+    }
+    if let Some(error) = context.stopped {
+        return Err(error);
+    }
+    let s = context.branch_score;
+    let c = calculate_c(puzzle) as isize;
+    let e = count_empty(puzzle) as isize;
+    context
+        .solution
+        .ok_or(Error::Unknown)
+        .map(|sol| (sol, (s * c + e) as usize))
+}
+
+fn recurse<O: CandidateOrder>(context: &mut Context, difficulty: isize, order: &mut O) {
+    if context.stopped.is_some() {
+        return;
+    }
+    context.stopped = context.bounds.check(context.nodes);
+    if context.stopped.is_some() {
+        return;
+    }
+    context.nodes += 1;
     let problem = context.problem.clone();
     let map: PossibilityMap = problem.into();
-    match map.next() {
+    match map.next(context.strategy) {
         (None, _) => {
-            if context.problem.is_complete() {
+            // A full grid that breaks the rules isn't a solution.
+            if context.problem.is_solved() {
                 // We're done! Stash the solution and return.
                 if context.count == 0 {
                     context.branch_score = difficulty;
                     context.solution = Some(context.problem.clone());
+                    context.solution_path = context.path.clone();
                 }
                 context.count += 1;
             }
-            return;
         }
         (Some(index), Some(set)) => {
+            let key = context
+                .memo
+                .as_ref()
+                .map(|_| (map.state_hash(&context.problem), map.compact_snapshot()));
+            if let (Some(memo), Some((hash, snapshot))) = (&context.memo, &key) {
+                if memo.get(hash) == Some(snapshot) {
+                    return;
+                }
+            }
+            let count = context.count;
             let branch_factor = set.freedom() as isize - 1;
-            let possible = (1..=(context.problem.order as usize).pow(2))
+            let mut possible = (1..=usize::from(context.problem.axis()))
                 .filter(|v| set.contains(*v))
                 .collect::<Vec<_>>();
             let difficulty = difficulty + branch_factor.pow(DIMENSIONS as u32);
-            for value in possible {
-                context
-                    .problem
-                    .substitute(index, Some(Element(value as u8)));
-                recurse(&mut context, difficulty);
-                if context.count > 1 {
-                    // There are multiple solutions; abort.
+            while let Some(value) = order.take(&mut possible) {
+                let element = Element(value as u8);
+                context.problem.substitute(index, Some(element));
+                if let Some(ref mut path) = context.path {
+                    path.push((index, element, set.freedom()));
+                }
+                recurse(context, difficulty, order);
+                if let Some(ref mut path) = context.path {
+                    let _ = path.pop();
+                }
+                if context.count == 0 {
+                    context.backtracks += 1;
+                }
+                if context.count >= context.limit || context.stopped.is_some() {
+                    // We've found as many solutions as we were asked for (or
+                    // reached a limit).
                     return;
                 }
             }
             context.problem.substitute(index, None);
+            // Every branch was searched in full, so if none held a solution,
+            // neither does this state.
+            let capacity = context.strategy.memo.unwrap_or(0);
+            if let (Some(memo), Some((hash, snapshot))) = (&mut context.memo, key) {
+                if context.count == count && memo.len() < capacity {
+                    let _ = memo.entry(hash).or_insert(snapshot);
+                }
+            }
         }
         _ => unreachable!(),
     }
@@ -307,35 +1101,56 @@ fn count_empty(sudoku: &Sudoku) -> usize {
 
 /// Calculates the value of `C`, as discussed in [Scoring](#Scoring).
 fn calculate_c(sudoku: &Sudoku) -> usize {
-    let order = sudoku.order;
+    c_for_order(sudoku.order)
+}
+
+/// Calculates the value of `C` for a puzzle of the given order.
+fn c_for_order(order: u8) -> usize {
     10.0_f64.powf(f64::from(order).powf(4.0).log10().ceil()) as usize
 }
 
 /// Scores the passed, if it's solvable.
 pub fn score(sudoku: &Sudoku) -> Option<usize> {
-    solve_and_score(&sudoku).ok().map(|(_, s)| s)
+    solve_and_score(sudoku).ok().map(|(_, s)| s)
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::sol::{calculate_c, Error, PossibilityMap, PossibilitySet, Solve};
+    #[cfg(feature = "2D")]
+    use crate::corpus;
+    #[cfg(feature = "2D")]
+    use crate::sol::Score;
+    #[cfg(feature = "2D")]
+    use crate::sol::SudokuIterExt;
+    use crate::sol::{
+        calculate_c, Error, ParseDifficultyError, PossibilityMap, PossibilitySet, Solve, StarRating,
+    };
+    #[cfg(feature = "2D")]
+    use crate::sol::{solve_and_score, solve_and_score_bounded, Bounds};
+    use crate::Difficulty;
+    use crate::Element;
+    #[cfg(feature = "2D")]
+    use crate::Grid;
     use crate::Point;
     use crate::Sudoku;
     use crate::DIMENSIONS;
 
+    #[cfg(feature = "2D")]
+    use std::{sync::atomic::AtomicBool, time::Duration};
+
     struct DummyPuzzle(bool);
 
     impl DummyPuzzle {
         fn new(solvable: bool) -> Self {
-            Self { 0: solvable }
+            Self(solvable)
         }
     }
 
     impl Solve for DummyPuzzle {
         fn solution(&self) -> Result<Self, Error> {
             if self.0 {
-                Ok(Self { 0: true })
+                Ok(Self(true))
             } else {
                 Err(Error::__TestOther)
             }
@@ -345,9 +1160,215 @@ mod tests {
     #[test]
     fn test_is_uniquely_solvable() {
         let solvable = DummyPuzzle::new(true);
-        assert_eq!(solvable.is_uniquely_solvable(), true);
+        assert!(solvable.is_uniquely_solvable());
         let unsolvable = DummyPuzzle::new(false);
-        assert_eq!(unsolvable.is_uniquely_solvable(), false);
+        assert!(!unsolvable.is_uniquely_solvable());
+    }
+
+    #[test]
+    fn test_difficulty_unplayable() {
+        assert_eq!(Difficulty::from(10), Difficulty::Unplayable);
+        assert_eq!(Difficulty::from_score(10, 3), Difficulty::Unplayable);
+        assert!(!Difficulty::Unplayable.is_playable());
+        assert!(Difficulty::Unplayable.score_range().contains(&10));
+        assert_eq!(Difficulty::Unplayable.to_string(), "Unplayable");
+        assert_eq!("unplayable".parse(), Ok(Difficulty::Unplayable));
+    }
+
+    #[test]
+    fn test_difficulty_bands_consistent() {
+        for difficulty in Difficulty::ALL.iter() {
+            let range = difficulty.score_range();
+            assert_eq!(Difficulty::from(*range.start()), *difficulty);
+            assert_eq!(Difficulty::from(*range.end()), *difficulty);
+            assert_eq!(difficulty.to_string().parse(), Ok(*difficulty));
+        }
+        assert_eq!(
+            "impossible".parse::<Difficulty>(),
+            Err(ParseDifficultyError("impossible".to_string()))
+        );
+        assert_eq!(
+            ParseDifficultyError("impossible".to_string()).to_string(),
+            "unknown difficulty \"impossible\" (expected one of: unplayable, beginner, easy, \
+             intermediate, difficult, advanced)"
+        );
+    }
+
+    #[test]
+    fn test_star_rating() {
+        // Every band has its own star count.
+        let stars = Difficulty::ALL
+            .iter()
+            .map(|difficulty| difficulty.stars())
+            .collect::<Vec<_>>();
+        assert_eq!(stars, vec![0, 1, 2, 3, 4, 5]);
+        for difficulty in Difficulty::ALL.iter().filter(|d| d.is_playable()) {
+            let rating = StarRating::new(difficulty.stars()).unwrap();
+            assert_eq!(rating.difficulty(), *difficulty);
+        }
+        assert_eq!(StarRating::new(0), None);
+        assert_eq!(StarRating::new(6), None);
+        assert_eq!(StarRating::new(3).map(StarRating::stars), Some(3));
+    }
+
+    #[test]
+    fn test_difficulty_from_score() {
+        assert_eq!(Difficulty::from_score(120, 3), Difficulty::Beginner);
+        assert_eq!(Difficulty::from_score(2, 2), Difficulty::Unplayable);
+        assert_eq!(Difficulty::from_score(105, 2), Difficulty::Beginner);
+        assert_eq!(Difficulty::from_score(209, 2), Difficulty::Intermediate);
+        assert_eq!(Difficulty::from_score(312, 2), Difficulty::Advanced);
+        assert_eq!(
+            Difficulty::from_score_with_boxes(8, (2, 3)),
+            Difficulty::Unplayable
+        );
+        assert_eq!(
+            Difficulty::from_score_with_boxes(25, (2, 3)),
+            Difficulty::Intermediate
+        );
+        assert_eq!(
+            Difficulty::from_score_with_boxes(225, (3, 2)),
+            Difficulty::Difficult
+        );
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_filter_difficulty() {
+        let puzzles = corpus::by_tag("order-3");
+        let graded = puzzles
+            .iter()
+            .map(|puzzle| puzzle.difficulty().unwrap())
+            .collect::<Vec<_>>();
+        let band = Difficulty::Easy..=Difficulty::Intermediate;
+        let expected = graded.iter().filter(|d| band.contains(d)).count();
+        assert!(expected > 0 && expected < puzzles.len());
+        let filtered = puzzles
+            .into_iter()
+            .filter_difficulty(band.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(filtered.len(), expected);
+        for puzzle in filtered {
+            assert!(band.contains(&puzzle.difficulty().unwrap()));
+        }
+        let unsolvable = vec![Sudoku::new(2)];
+        let everything = Difficulty::Unplayable..=Difficulty::Advanced;
+        assert_eq!(
+            unsolvable.into_iter().filter_difficulty(everything).count(),
+            0
+        );
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_solve_multiple_solutions() {
+        match Sudoku::new(2).solution() {
+            Err(Error::MultipleSolutions) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(Sudoku::new(2).solve_path().is_none());
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_backtrack_count() {
+        // Scored 50: only forced cells, so nothing to undo.
+        let singles = corpus::get("seeded-beginner-0").unwrap();
+        assert_eq!(singles.score(), Some(50));
+        assert_eq!(singles.backtrack_count(), Some(0));
+        let hard = corpus::get("seeded-advanced-0").unwrap();
+        assert!(hard.backtrack_count().unwrap() > 0);
+        assert_eq!(Sudoku::new(2).backtrack_count(), None);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_solve_complete_invalid() {
+        let mut sudoku = Sudoku::new(2);
+        for point in sudoku.points() {
+            sudoku.substitute(point, Some(Element(1)));
+        }
+        assert!(sudoku.is_complete());
+        assert!(sudoku.solution().is_err());
+        assert_eq!(sudoku.score(), None);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_has_other_solution() {
+        let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let solution = puzzle.solution().unwrap();
+        assert!(!puzzle.has_other_solution(&solution));
+        assert!(!solution.has_other_solution(&solution));
+        let mut ambiguous = puzzle.clone();
+        for point in ambiguous.points().into_iter().take(40) {
+            ambiguous.substitute(point, None);
+        }
+        assert!(ambiguous.has_other_solution(&solution));
+        assert!(Sudoku::new(2).has_other_solution(&solution_of_order_2()));
+    }
+
+    #[cfg(feature = "2D")]
+    fn solution_of_order_2() -> Sudoku {
+        "1 2 3 4\n3 4 1 2\n2 1 4 3\n4 3 2 1".parse().unwrap()
+    }
+
+    #[test]
+    fn test_solve_out_of_range() {
+        let mut sudoku = Sudoku::new(3);
+        let point = Point::unfold(3, 3);
+        sudoku.substitute(point, Some(Element(42)));
+        match sudoku.solution() {
+            Err(Error::ValueOutOfRange(p, e)) => assert_eq!((p, e), (point, Element(42))),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_bounded_solves() {
+        let puzzle: Sudoku = corpus::get("2D-O3").unwrap();
+        let solution = puzzle.solution().unwrap();
+        match puzzle.solution_within_nodes(10) {
+            Err(Error::Exhausted) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(puzzle.solution_within_nodes(10_000).unwrap(), solution);
+        match puzzle.solution_within(Duration::from_secs(0)) {
+            Err(Error::Timeout) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            puzzle.solution_within(Duration::from_secs(60)).unwrap(),
+            solution
+        );
+        assert_eq!(puzzle.solution_within(Duration::MAX).unwrap(), solution);
+        // The bounded search scores the puzzle just as the unbounded one does.
+        assert_eq!(
+            solve_and_score_bounded(&puzzle, Bounds::default()).unwrap(),
+            solve_and_score(&puzzle).unwrap()
+        );
+        match puzzle.solution_cancellable(&AtomicBool::new(true)) {
+            Err(Error::Cancelled) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            puzzle
+                .solution_cancellable(&AtomicBool::new(false))
+                .unwrap(),
+            solution
+        );
+        // A bound doesn't hide other errors.
+        match Sudoku::new(2).solution_within_nodes(10_000) {
+            Err(Error::MultipleSolutions) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            Error::Exhausted.to_string(),
+            "the search reached its node limit"
+        );
     }
 
     #[test]
@@ -366,7 +1387,7 @@ mod tests {
     fn test_map_new() {
         for order in 1..6 {
             let map = PossibilityMap::new(order);
-            for i in 0..(order as usize).pow(DIMENSIONS as u32 + 2) {
+            for i in 0..(order as usize).pow(2 * DIMENSIONS as u32) {
                 let index = Point::unfold(i, order);
                 let set = PossibilitySet::new(order);
                 assert_eq!(map[index], Some(set));
@@ -392,6 +1413,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_set_from_values() {
+        let set = PossibilitySet::from_values(&[1, 5, 9], 3).unwrap();
+        for i in 1..10 {
+            assert_eq!(set.contains(i), [1, 5, 9].contains(&i));
+        }
+        assert_eq!(set.freedom(), 3);
+        assert_eq!(
+            PossibilitySet::from_values(&[5, 5], 3).unwrap().freedom(),
+            1
+        );
+        assert_eq!(PossibilitySet::from_values(&[], 3), None);
+        assert_eq!(PossibilitySet::from_values(&[1, 10], 3), None);
+        assert_eq!(PossibilitySet::from_values(&[0], 3), None);
+        assert_eq!(
+            PossibilitySet::from_values(&(1..10).collect::<Vec<_>>(), 3),
+            Some(PossibilitySet::new(3))
+        );
+    }
+
     #[test]
     fn test_set_eliminate() {
         let mut set = PossibilitySet::new(3);
@@ -410,4 +1451,105 @@ mod tests {
             assert_eq!(set.freedom(), 9 - i);
         }
     }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_degree_tiebreak() {
+        use crate::sol::{backtrack_with, Ascending, BacktrackStrategy};
+
+        let search = |puzzle: &Sudoku, strategy| {
+            let context = backtrack_with(puzzle, 2, false, &mut Ascending, strategy);
+            (context.solution, context.nodes)
+        };
+        // The order-3 fixture takes 266 nodes by default, but only 134 with
+        // the tie-break. (The order-4 fixture goes from 6303 to 4864, but
+        // takes too long to check routinely.)
+        let puzzle = corpus::get("2D-O3").unwrap();
+        let (_, default_nodes) = search(&puzzle, BacktrackStrategy::default());
+        let (_, degree_nodes) = search(&puzzle, BacktrackStrategy::with_degree_tiebreak());
+        assert_eq!((default_nodes, degree_nodes), (266, 134));
+        for puzzle in corpus::by_tag("order-3") {
+            let (default, _) = search(&puzzle, BacktrackStrategy::default());
+            let (degree, _) = search(&puzzle, BacktrackStrategy::with_degree_tiebreak());
+            assert_eq!(default, degree);
+        }
+        assert_eq!(
+            puzzle
+                .solution_with(BacktrackStrategy::with_degree_tiebreak())
+                .unwrap(),
+            puzzle.solution().unwrap()
+        );
+        match Sudoku::new(2).solution_with(BacktrackStrategy::with_degree_tiebreak()) {
+            Err(Error::MultipleSolutions) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_state_hash() {
+        // Filling the same cells in either order reaches the same state.
+        let puzzle = corpus::get("2D-O3").unwrap();
+        let solution = puzzle.solution().unwrap();
+        let empty = puzzle
+            .points()
+            .into_iter()
+            .filter(|p| puzzle[*p].is_none())
+            .collect::<Vec<_>>();
+        let (a, b) = (empty[0], empty[empty.len() - 1]);
+        let fill = |first: Point, second: Point| {
+            let mut grid = puzzle.clone();
+            grid.substitute(first, solution[first]);
+            grid.substitute(second, solution[second]);
+            let map: PossibilityMap = grid.clone().into();
+            (map.state_hash(&grid), map.compact_snapshot())
+        };
+        assert_eq!(fill(a, b), fill(b, a));
+        let map: PossibilityMap = puzzle.clone().into();
+        assert_ne!(fill(a, b).0, map.state_hash(&puzzle));
+        // Sixteen cells of four bits each fill a word.
+        let map: PossibilityMap = Sudoku::new(2).into();
+        assert_eq!(&*map.compact_snapshot(), &[u64::MAX]);
+        let map: PossibilityMap = Sudoku::new(3).into();
+        let snapshot = map.compact_snapshot();
+        assert_eq!(snapshot.len(), 12);
+        assert_eq!(snapshot[11], (1 << (81 * 9 - 11 * 64)) - 1);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_memo() {
+        use crate::sol::{backtrack_with, Ascending, BacktrackStrategy};
+
+        let memo = BacktrackStrategy::default().with_memo(10_000);
+        let search = |puzzle: &Sudoku, strategy| {
+            let context = backtrack_with(puzzle, 2, false, &mut Ascending, strategy);
+            (
+                context.solution,
+                context.count,
+                context.branch_score,
+                context.nodes,
+            )
+        };
+        for puzzle in corpus::by_tag("order-3") {
+            let (solution, count, score, nodes) = search(&puzzle, BacktrackStrategy::default());
+            let memoized = search(&puzzle, memo);
+            assert_eq!(
+                (solution, count, score),
+                (memoized.0, memoized.1, memoized.2)
+            );
+            assert!(memoized.3 <= nodes);
+        }
+        // The order-3 fixture takes 266 nodes without the memo, but only 206
+        // with it.
+        let puzzle = corpus::get("2D-O3").unwrap();
+        assert_eq!(search(&puzzle, memo).3, 206);
+        // A full memo stops recording, but still gives the same answers.
+        let (solution, ..) = search(&puzzle, BacktrackStrategy::default().with_memo(1));
+        assert_eq!(solution, Some(puzzle.solution().unwrap()));
+        match Sudoku::new(2).solution_with(memo) {
+            Err(Error::MultipleSolutions) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
@@ -1,13 +1,21 @@
-use crate::sol::{score, solve, Error as SolveError};
+use crate::dimensions::{validate_order, OrderError};
+use crate::sol::{
+    backtrack, backtrack_count, has_other_solution, score, solve, solve_bounded, solve_path,
+    solve_with, Ascending, BacktrackStrategy, Bounds, Error as SolveError, SolvePath,
+};
+use crate::Difficulty;
 use crate::Puzzle;
 use crate::Score;
 use crate::Solve;
 use crate::DIMENSIONS;
+use crate::MAX_ORDER;
 
 use std::{
     fmt,
     ops::{Index, IndexMut},
     str::FromStr,
+    sync::atomic::AtomicBool,
+    time::{Duration, Instant},
 };
 
 /// Represents a single sudoku "square."
@@ -48,16 +56,68 @@ pub enum Group {
     Band(Vec<Option<Element>>),
 }
 
+/// The kind of a [group](enum.Group.html).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GroupKind {
+    /// A box.
+    Box,
+    /// A stack (a column, in two dimensions).
+    Stack,
+    /// A band (a row, in two dimensions).
+    Band,
+}
+
 impl Group {
+    /// Constructs a group of the given kind from its elements.
+    ///
+    /// # Panics
+    /// Panics if the number of elements isn't a size a group of that kind
+    /// has for some order: `order.pow(DIMENSIONS)` for a box, `order²`
+    /// otherwise (an empty group is allowed). Groups of a grid with
+    /// [rectangular boxes](struct.Sudoku.html#method.new_rect) have as many
+    /// elements as the grid's axis, whatever their kind.
+    pub fn new(kind: GroupKind, elements: Vec<Option<Element>>) -> Self {
+        let len = elements.len();
+        let power = match kind {
+            GroupKind::Box => DIMENSIONS as u32,
+            GroupKind::Stack | GroupKind::Band => 2,
+        };
+        let order = (0usize..).find(|o| o.pow(power) >= len).unwrap();
+        assert!(
+            order.pow(power) == len || rect_boxes(len).is_some(),
+            "a {:?} of {} elements has no order",
+            kind,
+            len
+        );
+        match kind {
+            GroupKind::Box => Group::Box(elements),
+            GroupKind::Stack => Group::Stack(elements),
+            GroupKind::Band => Group::Band(elements),
+        }
+    }
+    /// Returns the kind of the group.
+    pub fn kind(&self) -> GroupKind {
+        match self {
+            Group::Box(_) => GroupKind::Box,
+            Group::Stack(_) => GroupKind::Stack,
+            Group::Band(_) => GroupKind::Band,
+        }
+    }
     /// Whether a group is valid (contains no errors).
     ///
     /// A group is considered valid if it contains only unique elements
-    /// (ignoring empty elements).
+    /// (ignoring empty elements), each of which lies within the group's value
+    /// range (see [`out_of_range`](#method.out_of_range)).
     pub fn is_valid(&self) -> bool {
-        let elements = self.elements();
-        let elements = elements.iter().filter(|e| e.is_some()).collect::<Vec<_>>();
+        if !self.out_of_range().is_empty() {
+            return false;
+        }
+        let mut elements = self
+            .as_slice()
+            .iter()
+            .filter(|e| e.is_some())
+            .collect::<Vec<_>>();
         let len = elements.len();
-        let mut elements = elements.into_iter().collect::<Vec<_>>();
         elements.sort();
         elements.dedup();
         elements.len() == len
@@ -67,21 +127,36 @@ impl Group {
     /// A group is considered complete if it contains every possible element
     /// value exactly once.
     pub fn is_complete(&self) -> bool {
-        let elements = self.elements();
+        let elements = self.as_slice();
         let len = elements.len();
-        let mut elements = elements
-            .into_iter()
-            .filter(Option::is_some)
-            .collect::<Vec<_>>();
+        let mut elements = elements.iter().filter(|e| e.is_some()).collect::<Vec<_>>();
         elements.sort();
         elements.dedup();
         elements.len() == len
     }
     /// Returns an owned copy of the group's constituent elements.
     pub fn elements(&self) -> Vec<Option<Element>> {
+        self.as_slice().to_vec()
+    }
+    /// Returns the elements whose values lie outside the group's value range,
+    /// along with their positions within the group.
+    ///
+    /// A group of *n* elements may only contain the values `1..=n`.
+    pub fn out_of_range(&self) -> Vec<(usize, Element)> {
+        let elements = self.as_slice();
+        let axis = elements.len();
+        elements
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| e.map(|e| (i, e)))
+            .filter(|(_, Element(value))| *value == 0 || usize::from(*value) > axis)
+            .collect()
+    }
+    /// Borrows the group's constituent elements.
+    pub fn as_slice(&self) -> &[Option<Element>] {
         use self::Group::*;
         match self {
-            Box(elements) | Stack(elements) | Band(elements) => elements.clone(),
+            Box(elements) | Stack(elements) | Band(elements) => elements,
         }
     }
 }
@@ -92,13 +167,176 @@ impl Default for Group {
     }
 }
 
+/// A violation of the sudoku rules found in a grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Violation {
+    /// The given value appears more than once in a group.
+    ///
+    /// The associated points are the two conflicting locations.
+    Duplicate(Element, Point, Point),
+    /// The value at the given location lies outside `1..=order²`.
+    ValueOutOfRange(Point, Element),
+}
+
 #[derive(Clone, Debug, PartialEq)]
 /// A (partial) grid of [elements](struct.Element.html).
+///
+/// Since the box shape is private, a sudoku can't be written as a struct
+/// literal; start from [`new`](#method.new) (or
+/// [`new_rect`](#method.new_rect)) and fill in `elements`, or parse one.
 pub struct Sudoku {
     /// The [order](trait.Puzzle.html#method.order) of this sudoku.
+    ///
+    /// For a sudoku with [rectangular boxes](#method.new_rect), this is the
+    /// smaller box dimension, which is what the puzzle is graded by.
     pub order: u8,
     /// The [elements](struct.Element.html) composing this sudoku.
     pub elements: Vec<Option<Element>>,
+    // The box shape as (rows, columns), if the boxes aren't `order` cells
+    // along every axis.
+    boxes: Option<(u8, u8)>,
+}
+
+/// The largest axis length a grid with rectangular boxes may have.
+///
+/// Rectangular grids are graded like order-2 grids, whose scores only leave
+/// room for fewer than 100 cells.
+#[cfg(feature = "2D")]
+const MAX_RECT_AXIS: usize = 9;
+
+/// Returns the box shape, as `(rows, columns)`, that a grid with the given
+/// (non-square) axis length is assumed to have: the most nearly square
+/// factorization, with fewer rows than columns.
+///
+/// Returns `None` for perfect squares (whose boxes are square) and for
+/// lengths no supported rectangular shape has.
+#[cfg(feature = "2D")]
+pub(crate) fn rect_boxes(axis: usize) -> Option<(u8, u8)> {
+    if axis > MAX_RECT_AXIS {
+        return None;
+    }
+    (2..axis)
+        .take_while(|rows| rows * rows < axis)
+        .filter(|rows| axis.is_multiple_of(*rows))
+        .last()
+        .map(|rows| (rows as u8, (axis / rows) as u8))
+}
+
+#[cfg(not(feature = "2D"))]
+pub(crate) fn rect_boxes(_axis: usize) -> Option<(u8, u8)> {
+    None
+}
+
+/// The geometry of a grid: the length of its axes and the extent of its
+/// boxes along each of them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Shape {
+    order: u8,
+    boxes: Option<(u8, u8)>,
+}
+
+impl Shape {
+    /// The shape of a grid of the given order, with square boxes.
+    pub(crate) fn square(order: u8) -> Self {
+        Self { order, boxes: None }
+    }
+
+    /// The shape of a two-dimensional grid with boxes `rows` cells tall and
+    /// `columns` wide, if those boxes aren't square and the grid isn't too
+    /// large.
+    #[cfg(feature = "2D")]
+    pub(crate) fn rect(rows: u8, columns: u8) -> Option<Self> {
+        let supported = rows != columns
+            && rows >= 2
+            && columns >= 2
+            && usize::from(rows) * usize::from(columns) <= MAX_RECT_AXIS;
+        if !supported {
+            return None;
+        }
+        Some(Self {
+            order: rows.min(columns),
+            boxes: Some((rows, columns)),
+        })
+    }
+
+    /// Rectangular boxes are only supported in two dimensions.
+    #[cfg(not(feature = "2D"))]
+    pub(crate) fn rect(_rows: u8, _columns: u8) -> Option<Self> {
+        None
+    }
+
+    /// The number of cells along each axis (and values in each group).
+    pub(crate) fn axis(self) -> u8 {
+        match self.boxes {
+            Some((rows, columns)) => rows * columns,
+            None => self.order.pow(2),
+        }
+    }
+
+    /// The number of cells a box spans along the given dimension.
+    pub(crate) fn extent(self, dimension: usize) -> u8 {
+        match (self.boxes, dimension) {
+            (Some((_, columns)), 0) => columns,
+            (Some((rows, _)), 1) => rows,
+            _ => self.order,
+        }
+    }
+
+    /// The number of cells in the grid.
+    pub(crate) fn len(self) -> usize {
+        usize::from(self.axis()).pow(DIMENSIONS as u32)
+    }
+
+    /// Folds the point into an index, returning `None` if it lies outside
+    /// the grid.
+    pub(crate) fn fold(self, point: Point) -> Option<usize> {
+        let axis = self.axis();
+        if point.0.iter().any(|c| *c >= axis) {
+            return None;
+        }
+        fold_coordinates(&point.0, usize::from(axis))
+    }
+
+    /// Unfolds an index into a point (the inverse of
+    /// [`fold`](#method.fold)).
+    pub(crate) fn unfold(self, index: usize) -> Point {
+        let axis = usize::from(self.axis());
+        let mut rest = index;
+        let mut point = [0; DIMENSIONS];
+        for coordinate in point.iter_mut() {
+            *coordinate = (rest % axis) as u8;
+            rest /= axis;
+        }
+        Point(point)
+    }
+
+    /// Returns the upper-left corner of the box containing the point.
+    pub(crate) fn snap(self, point: Point) -> Point {
+        let mut corner = point;
+        for i in 0..DIMENSIONS {
+            corner[i] -= point[i] % self.extent(i);
+        }
+        corner
+    }
+
+    /// Returns the index of the box containing the point, with boxes
+    /// numbered in fold order of their upper-left corners.
+    pub(crate) fn box_index(self, point: Point) -> usize {
+        let axis = usize::from(self.axis());
+        let mut index = 0;
+        let mut factor = 1;
+        for i in 0..DIMENSIONS {
+            let extent = usize::from(self.extent(i));
+            index += usize::from(point[i]) / extent * factor;
+            factor *= axis / extent;
+        }
+        index
+    }
+
+    /// Returns every point in the grid, in fold order.
+    pub(crate) fn points(self) -> Vec<Point> {
+        (0..self.len()).map(|i| self.unfold(i)).collect()
+    }
 }
 
 /// Specifies a sudoku element's location in space.
@@ -113,22 +351,60 @@ pub struct Sudoku {
 #[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct Point(pub [u8; DIMENSIONS]);
 impl Point {
+    /// Creates a point from its coordinates, in const contexts too (e.g.
+    /// for fixture tables).
+    pub const fn const_new(coordinates: [u8; DIMENSIONS]) -> Self {
+        Point(coordinates)
+    }
+
     /// Compresses an *n*-dimensional point to a single coordinate.
     ///
     /// Inverse of [`Point::unfold`](#method.unfold).
+    ///
+    /// # Panics
+    /// Panics if the point lies outside a grid of the given order, or if the
+    /// order is too large to fold (see [`MAX_ORDER`](constant.MAX_ORDER.html)).
+    /// Use [`checked_fold`](#method.checked_fold) for unvalidated input.
     pub fn fold(self, order: u8) -> usize {
+        self.checked_fold(order)
+            .unwrap_or_else(|| panic!("point {} lies outside a grid of order {}", self, order))
+    }
+
+    /// Compresses an *n*-dimensional point to a single coordinate, returning
+    /// `None` if the computation overflows.
+    pub fn try_fold(self, order: u8) -> Option<usize> {
+        fold_coordinates(&self.0, (order as usize).pow(2))
+    }
+
+    /// Compresses an *n*-dimensional point to a single coordinate, returning
+    /// `None` if the point lies outside a grid of the given order (or if the
+    /// computation overflows).
+    ///
+    /// Unlike [`fold`](#method.fold) and [`try_fold`](#method.try_fold), an
+    /// off-grid point never maps to the index of some other cell.
+    pub fn checked_fold(self, order: u8) -> Option<usize> {
         let axis = (order as usize).pow(2);
-        let mut sum = 0;
-        for i in 0..DIMENSIONS {
-            sum += usize::from(self[i]) * axis.pow(i as u32);
+        if self.0.iter().any(|c| usize::from(*c) >= axis) {
+            return None;
         }
-        sum
+        fold_coordinates(&self.0, axis)
     }
 
     /// Decompresses a single coordinate into an *n*-dimensional point.
     ///
     /// Inverse of [`Point::fold`](#method.fold).
+    ///
+    /// # Notes
+    /// The value is assumed to index a cell of the grid; this is checked only
+    /// in debug builds. Use [`try_unfold`](#method.try_unfold) for
+    /// unvalidated input.
     pub fn unfold(value: usize, order: u8) -> Self {
+        debug_assert!(
+            Self::try_unfold(value, order).is_some(),
+            "index {} lies outside a grid of order {}",
+            value,
+            order
+        );
         let mut total = value;
         let axis = (order as usize).pow(2);
         let mut point = [0; DIMENSIONS];
@@ -142,8 +418,30 @@ impl Point {
         Point(point)
     }
 
+    /// Decompresses a single coordinate into an *n*-dimensional point,
+    /// returning `None` if the value does not index a cell of a grid of the
+    /// given order (or if the grid's size overflows).
+    pub fn try_unfold(value: usize, order: u8) -> Option<Self> {
+        let axis = (order as usize).pow(2);
+        if axis == 0 || axis > usize::from(u8::MAX) + 1 {
+            return None;
+        }
+        let size = (0..DIMENSIONS).try_fold(1_usize, |total, _| total.checked_mul(axis))?;
+        if value >= size {
+            return None;
+        }
+        let mut total = value;
+        let mut point = [0; DIMENSIONS];
+        for coordinate in point.iter_mut() {
+            *coordinate = (total % axis) as u8;
+            total /= axis;
+        }
+        Some(Point(point))
+    }
+
     /// Snaps a point to the grid (returns the upper-left corner of the box).
     pub fn snap(self, order: u8) -> Self {
+        debug_assert!(order > 0, "cannot snap to a grid of order 0");
         let mut point = self;
         for i in 0..DIMENSIONS {
             point[i] = self[i] - self[i] % order;
@@ -182,6 +480,20 @@ impl Point {
     }
 }
 
+/// Folds the given coordinates along axes of the given length with checked
+/// arithmetic.
+pub(crate) fn fold_coordinates(coordinates: &[u8], axis: usize) -> Option<usize> {
+    let mut sum = 0_usize;
+    let mut factor = 1_usize;
+    for (i, coordinate) in coordinates.iter().enumerate() {
+        if i > 0 {
+            factor = factor.checked_mul(axis)?;
+        }
+        sum = sum.checked_add(usize::from(*coordinate).checked_mul(factor)?)?;
+    }
+    Some(sum)
+}
+
 impl Index<usize> for Point {
     type Output = u8;
     fn index(&self, index: usize) -> &Self::Output {
@@ -205,12 +517,82 @@ impl fmt::Display for Point {
     }
 }
 
+impl Element {
+    /// Creates an element holding the given value.
+    ///
+    /// This can be used in const contexts, where a value outside
+    /// `1..=MAX_ORDER²` fails to compile.
+    ///
+    /// # Panics
+    /// Panics if the value is zero, or too large for any supported order.
+    pub const fn new(value: u8) -> Self {
+        assert!(
+            value >= 1 && value as usize <= (MAX_ORDER as usize).pow(2),
+            "element values lie in 1..=MAX_ORDER²"
+        );
+        Element(value)
+    }
+
+    /// Writes the value compactly for a puzzle of the given order: as a
+    /// single glyph (`1`–`9`, then `A` onward, as the loaders accept) when
+    /// every value of the order fits in one, and in decimal otherwise.
+    ///
+    /// Values fit in a single glyph up to order 5 (whose largest value, 25,
+    /// is `P`).
+    pub fn to_string_radix(self, order: u8) -> String {
+        let axis = u32::from(order).pow(2);
+        match std::char::from_digit(u32::from(self.0), 36) {
+            Some(glyph) if axis < 36 => glyph.to_ascii_uppercase().to_string(),
+            _ => self.0.to_string(),
+        }
+    }
+}
+
+/// Writes the value in decimal.
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for Element {
+    type Err = ParseElementError;
+    /// Parses a value written in decimal, ignoring surrounding whitespace.
+    ///
+    /// Zero is rejected, since it marks an empty cell rather than a value.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().parse() {
+            Ok(0) | Err(_) => Err(ParseElementError(s.to_string())),
+            Ok(value) => Ok(Element(value)),
+        }
+    }
+}
+
+/// Represents a failure to parse an [`Element`](struct.Element.html).
+///
+/// The associated value is the unparsable text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseElementError(pub String);
+
+impl fmt::Display for ParseElementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"{}\" isn't a (nonzero) decimal value", self.0)
+    }
+}
+
 /// Represents an *n*-dimensional grid of values, indexable via
 /// [`Point`](struct.Point.html).
 pub trait Grid: Index<Point> {
     /// Returns all points in the grid.
     ///
     /// Useful for enumeration with `Iterator::zip`.
+    ///
+    /// # Order
+    /// Points are returned in [fold](struct.Point.html#method.fold) order:
+    /// the x-coordinate varies fastest, then y, then each further axis. This
+    /// order is part of the contract (the byte encoding and fingerprints
+    /// depend on it), so for an order-2 grid, the points run `(0, 0)`,
+    /// `(1, 0)`, `(2, 0)`, `(3, 0)`, `(0, 1)`, and so on.
     fn points(&self) -> Vec<Point>;
 }
 
@@ -223,14 +605,95 @@ impl Sudoku {
     /// This method **does not** generate a valid, uniquely solvable sudoku.
     /// If you wish to generate such a sudoku (which you likely do), use
     /// [`Sudoku::generate`](#method.generate).
+    ///
+    /// # Panics
+    /// Panics if the order is unsupported (see
+    /// [`try_new`](#method.try_new)).
     pub fn new(order: u8) -> Self {
-        Self {
+        Self::try_new(order).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Constructs a new sudoku of the specified order (like
+    /// [`new`](#method.new)), failing if the order lies outside
+    /// [`MIN_ORDER`](constant.MIN_ORDER.html)`..=`[`MAX_ORDER`](constant.MAX_ORDER.html).
+    pub fn try_new(order: u8) -> Result<Self, OrderError> {
+        validate_order(order)?;
+        Ok(Self {
             order,
-            elements: vec![None; (order as usize).pow(2 + DIMENSIONS as u32)],
+            elements: vec![None; (order as usize).pow(2 * DIMENSIONS as u32)],
+            boxes: None,
+        })
+    }
+
+    /// Constructs a new two-dimensional sudoku whose boxes are `box_rows`
+    /// cells tall and `box_cols` cells wide, such as the 6×6 puzzles with
+    /// 2×3 boxes common in newspapers.
+    ///
+    /// Each row, column, and box holds the values `1..=box_rows * box_cols`.
+    /// Square boxes give the same puzzle as [`new`](#method.new).
+    ///
+    /// # Panics
+    /// Panics if either dimension is less than 2, or if the grid would be
+    /// larger than 9×9 (rectangular grids are graded like order-2 grids,
+    /// which leaves room for fewer than 100 cells).
+    #[cfg(feature = "2D")]
+    pub fn new_rect(box_rows: u8, box_cols: u8) -> Self {
+        if box_rows == box_cols {
+            return Self::new(box_rows);
+        }
+        let shape = Shape::rect(box_rows, box_cols)
+            .unwrap_or_else(|| panic!("{}×{} boxes aren't supported", box_rows, box_cols));
+        Self {
+            order: shape.order,
+            elements: vec![None; shape.len()],
+            boxes: shape.boxes,
+        }
+    }
+
+    /// Returns the shape of the puzzle's boxes, as `(rows, columns)`.
+    ///
+    /// This is `(order, order)` unless the puzzle was created with
+    /// [rectangular boxes](#method.new_rect).
+    pub fn box_shape(&self) -> (u8, u8) {
+        self.boxes.unwrap_or((self.order, self.order))
+    }
+
+    /// Returns the number of cells along each axis, which is also the
+    /// largest value a cell may hold (`order²` for square boxes).
+    pub fn axis(&self) -> u8 {
+        self.shape().axis()
+    }
+
+    /// Snaps a point to the puzzle's boxes (returns the upper-left corner of
+    /// its box).
+    ///
+    /// Unlike [`Point::snap`](struct.Point.html#method.snap), this honors
+    /// [rectangular boxes](#method.new_rect).
+    pub fn snap(&self, point: Point) -> Point {
+        self.shape().snap(point)
+    }
+
+    pub(crate) fn shape(&self) -> Shape {
+        Shape {
+            order: self.order,
+            boxes: self.boxes,
+        }
+    }
+
+    /// Constructs a sudoku of the given shape from its elements.
+    pub(crate) fn from_parts(shape: Shape, elements: Vec<Option<Element>>) -> Self {
+        debug_assert_eq!(elements.len(), shape.len());
+        Self {
+            order: shape.order,
+            elements,
+            boxes: shape.boxes,
         }
     }
 
     /// Returns whether the puzzle is completely full of values.
+    ///
+    /// A complete grid may still break the rules; use
+    /// [`is_solved`](#method.is_solved) to check for a solution.
     pub fn is_complete(&self) -> bool {
         for point in self.points() {
             if self[point].is_none() {
@@ -240,35 +703,43 @@ impl Sudoku {
         true
     }
 
+    /// Returns whether the puzzle is solved: completely full of values, with
+    /// no rule violations.
+    ///
+    /// Unlike [`is_complete`](#method.is_complete), this rejects full grids
+    /// containing duplicates or out-of-range values.
+    pub fn is_solved(&self) -> bool {
+        self.is_complete() && self.is_valid()
+    }
+
     /// Returns the relevant groups for checking a given element in the grid.
     ///
     /// The number of groups is always equal to the number of dimensions plus
-    /// one.
+    /// one: the box, then the stack (along the y-axis), then a band along the
+    /// x-axis and one along each dimension past the second. The values of
+    /// each group are in [`points`](trait.Grid.html#tymethod.points) order.
+    /// Both orders are part of the contract.
     // This allow is here for higher dimensions
-    #[allow(clippy::reverse_range_loop)]
+    #[allow(clippy::reversed_empty_ranges)]
     pub fn groups(&self, pos: Point) -> [Group; DIMENSIONS + 1] {
+        let shape = self.shape();
         for i in 0..DIMENSIONS {
-            assert!(pos[i] < self.order.pow(2));
+            assert!(pos[i] < shape.axis());
         }
-        let top_left = pos.snap(self.order);
-        let order = i32::from(self.order);
+        let top_left = shape.snap(pos);
         let points = self.points();
         let b = points
             .iter()
             .zip(self.elements.iter())
             .filter(|(index, _)| {
-                let y = index[1];
-                let x = index[0];
-                let dy = i32::from(y) - i32::from(top_left[1]);
-                let dx = i32::from(x) - i32::from(top_left[0]);
-                if dy < 0 || dx < 0 || dy >= order || dx >= order {
-                    return false;
-                }
-                true
+                (0..DIMENSIONS).all(|i| {
+                    let delta = i32::from(index[i]) - i32::from(top_left[i]);
+                    delta >= 0 && delta < i32::from(shape.extent(i))
+                })
             })
             .map(|(_, v)| *v)
             .collect::<Vec<_>>();
-        let b = Group::Box(b);
+        let b = Group::new(GroupKind::Box, b);
 
         let s = points
             .iter()
@@ -286,11 +757,13 @@ impl Sudoku {
             })
             .map(|(_, v)| *v)
             .collect::<Vec<_>>();
-        let s = Group::Stack(s);
+        let s = Group::new(GroupKind::Stack, s);
         let bands = (1..DIMENSIONS)
             .map(|i| {
-                // The variant dimension
-                let dimension = i - 1;
+                // The variant dimension: the stack already covers the y-axis
+                // (dimension 1), so the first band runs along the x-axis and
+                // the rest along the higher dimensions.
+                let dimension = if i == 1 { 0 } else { i };
                 points
                     .iter()
                     .zip(self.elements.iter())
@@ -308,7 +781,7 @@ impl Sudoku {
                     .map(|(_, v)| *v)
                     .collect()
             })
-            .map(Group::Band)
+            .map(|band| Group::new(GroupKind::Band, band))
             .collect::<Vec<_>>();
         let mut g = bands;
         g.insert(0, s);
@@ -319,25 +792,21 @@ impl Sudoku {
 
     /// Returns the relevant group indices.
     // This allow is here for higher dimensions
-    #[allow(clippy::reverse_range_loop)]
+    #[allow(clippy::reversed_empty_ranges)]
     pub fn group_indices(&self, pos: Point) -> Vec<Point> {
+        let shape = self.shape();
         for i in 0..DIMENSIONS {
-            assert!(pos[i] < self.order.pow(2));
+            assert!(pos[i] < shape.axis());
         }
-        let top_left = pos.snap(self.order);
-        let order = i32::from(self.order);
+        let top_left = shape.snap(pos);
         let points = self.points();
         let b = points
             .iter()
             .filter(|index| {
-                let y = index[1];
-                let x = index[0];
-                let dy = i32::from(y) - i32::from(top_left[1]);
-                let dx = i32::from(x) - i32::from(top_left[0]);
-                if dy < 0 || dx < 0 || dy >= order || dx >= order {
-                    return false;
-                }
-                true
+                (0..DIMENSIONS).all(|i| {
+                    let delta = i32::from(index[i]) - i32::from(top_left[i]);
+                    delta >= 0 && delta < i32::from(shape.extent(i))
+                })
             })
             .cloned()
             .collect::<Vec<_>>();
@@ -360,8 +829,10 @@ impl Sudoku {
 
         let bands: Vec<Vec<Point>> = (1..DIMENSIONS)
             .map(|i| {
-                // The variant dimension
-                let dimension = i - 1;
+                // The variant dimension: the stack already covers the y-axis
+                // (dimension 1), so the first band runs along the x-axis and
+                // the rest along the higher dimensions.
+                let dimension = if i == 1 { 0 } else { i };
                 points
                     .iter()
                     .filter(|index| {
@@ -395,18 +866,278 @@ impl Sudoku {
         points
     }
 
+    /// Returns every group in the grid, with its kind and its index among
+    /// the groups of that kind.
+    ///
+    /// Bands (rows, in two dimensions) come first, then stacks (columns),
+    /// then boxes, each numbered from 0 in the
+    /// [`points`](trait.Grid.html#tymethod.points) order of their first
+    /// cells (reading order, in two dimensions). Bands along the dimensions
+    /// past the second follow those along the x-axis, continuing their
+    /// numbering. Each group's values are in the same order as in
+    /// [`groups`](#method.groups).
+    pub fn enumerate_groups(&self) -> impl Iterator<Item = (GroupKind, usize, Group)> + '_ {
+        let shape = self.shape();
+        let points = self.points();
+        // Each group is found from its first cell, along with its position in
+        // the result of `groups`.
+        let mut firsts = vec![];
+        let mut bands = 0;
+        for i in 1..DIMENSIONS {
+            let dimension = if i == 1 { 0 } else { i };
+            for point in points.iter().filter(|point| point[dimension] == 0) {
+                firsts.push((GroupKind::Band, bands, i + 1, *point));
+                bands += 1;
+            }
+        }
+        let stacks = points.iter().filter(|point| point[1] == 0);
+        firsts.extend(
+            stacks
+                .enumerate()
+                .map(|(index, point)| (GroupKind::Stack, index, 1, *point)),
+        );
+        let boxes = points.iter().filter(|point| shape.snap(**point) == **point);
+        firsts.extend(
+            boxes
+                .enumerate()
+                .map(|(index, point)| (GroupKind::Box, index, 0, *point)),
+        );
+        firsts
+            .into_iter()
+            .map(move |(kind, index, slot, point)| (kind, index, self.groups(point)[slot].clone()))
+    }
+
+    /// Returns the locations and values of any elements lying outside
+    /// `1..=axis` (see [`axis`](#method.axis)).
+    pub fn out_of_range(&self) -> Vec<(Point, Element)> {
+        let axis = usize::from(self.axis());
+        self.points()
+            .into_iter()
+            .zip(self.elements.iter())
+            .filter_map(|(p, e)| e.map(|e| (p, e)))
+            .filter(|(_, Element(value))| *value == 0 || usize::from(*value) > axis)
+            .collect()
+    }
+
+    /// Returns every rule violation in the grid.
+    ///
+    /// Out-of-range values are reported as
+    /// [`ValueOutOfRange`](enum.Violation.html#variant.ValueOutOfRange) and are
+    /// not additionally checked for duplication. Each duplicated pair is
+    /// reported once, with the points in ascending order.
+    pub fn diagnostics(&self) -> Vec<Violation> {
+        let out_of_range = self.out_of_range();
+        let mut violations = out_of_range
+            .iter()
+            .map(|(p, e)| Violation::ValueOutOfRange(*p, *e))
+            .collect::<Vec<_>>();
+        for point in self.points() {
+            let value = match self[point] {
+                Some(value) => value,
+                None => continue,
+            };
+            if out_of_range.iter().any(|(p, _)| *p == point) {
+                continue;
+            }
+            let mut peers = self.group_indices(point);
+            peers.sort();
+            peers.dedup();
+            for peer in peers {
+                if peer > point && self[peer] == Some(value) {
+                    violations.push(Violation::Duplicate(value, point, peer));
+                }
+            }
+        }
+        violations
+    }
+
+    /// Whether the grid obeys the sudoku rules (ignoring empty elements).
+    ///
+    /// See [`diagnostics`](#method.diagnostics) for details of any violations.
+    pub fn is_valid(&self) -> bool {
+        self.diagnostics().is_empty()
+    }
+
+    /// Returns the number of given values.
+    pub fn clue_count(&self) -> usize {
+        self.elements.iter().filter(|e| e.is_some()).count()
+    }
+
+    /// Returns the number of given values in each box.
+    ///
+    /// Boxes are listed in reading order (left to right, then top to bottom,
+    /// then along any further axes).
+    pub fn clues_per_box(&self) -> Vec<usize> {
+        let shape = self.shape();
+        let axis = usize::from(shape.axis());
+        let mut counts = vec![0; shape.len() / axis];
+        for (point, element) in self.points().into_iter().zip(self.elements.iter()) {
+            if element.is_some() {
+                counts[shape.box_index(point)] += 1;
+            }
+        }
+        counts
+    }
+
+    /// Writes the puzzle's unique solution into `out`, which must have the
+    /// puzzle's shape (its order and box shape).
+    ///
+    /// This lets a loop over many puzzles of the same shape keep a single
+    /// result grid, whose buffer is reused. The search itself still
+    /// allocates (including for the solution it finds), so this doesn't make
+    /// solving allocation-free. On error, `out` is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` doesn't have the puzzle's shape.
+    pub fn solve_into(&self, out: &mut Sudoku) -> Result<(), SolveError> {
+        assert!(
+            out.shape() == self.shape(),
+            "the output grid's shape doesn't match the puzzle's"
+        );
+        let solution = self.solution()?;
+        out.elements.copy_from_slice(&solution.elements);
+        Ok(())
+    }
+
+    /// Returns the sequence of assignments the solver made on its way to the
+    /// puzzle's unique solution, if it exists.
+    ///
+    /// This is the evidence behind the puzzle's [score](trait.Score.html):
+    /// each step carries the branching factor encountered at that point.
+    pub fn solve_path(&self) -> Option<SolvePath> {
+        solve_path(self)
+    }
+
+    /// Returns the number of times the solver backtracked (undid a guess
+    /// that led nowhere) on its way to the puzzle's unique solution, if it
+    /// exists.
+    ///
+    /// Unlike the [score](trait.Score.html), which weighs every branch by
+    /// its branching factor, this counts wrong guesses: a puzzle that can be
+    /// filled in one forced cell at a time has a count of 0.
+    pub fn backtrack_count(&self) -> Option<usize> {
+        backtrack_count(self)
+    }
+
+    /// Counts the puzzle's solutions, stopping once `limit` have been found
+    /// (so the count never exceeds `limit`).
+    ///
+    /// A puzzle whose givens already break the rules has no solutions.
+    pub fn solution_count(&self, limit: usize) -> usize {
+        if limit == 0 || !self.is_valid() {
+            return 0;
+        }
+        backtrack(self, limit, false, &mut Ascending).count
+    }
+
+    /// Whether the puzzle has at least one solution.
+    ///
+    /// Unlike [`is_uniquely_solvable`](trait.Solve.html#method.is_uniquely_solvable),
+    /// which is false both for puzzles with no solution and for those with
+    /// several, this tells the two apart: it's only false when the givens
+    /// contradict each other.
+    pub fn is_solvable(&self) -> bool {
+        self.solution_count(1) >= 1
+    }
+
+    /// Finds the puzzle's unique solution (like
+    /// [`Solve::solution`](trait.Solve.html#tymethod.solution)), searching
+    /// with the given strategy.
+    ///
+    /// Every strategy finds the same solution; they differ only in how much
+    /// of the search tree they explore along the way.
+    pub fn solution_with(&self, strategy: BacktrackStrategy) -> Result<Sudoku, SolveError> {
+        solve_with(self, strategy)
+    }
+
+    /// Finds the puzzle's unique solution, giving up with
+    /// [`SolveError::Exhausted`](enum.SolveError.html#variant.Exhausted)
+    /// after visiting `max_nodes` search nodes.
+    ///
+    /// Unlike a time limit, a node limit gives the same result on every
+    /// machine.
+    pub fn solution_within_nodes(&self, max_nodes: usize) -> Result<Sudoku, SolveError> {
+        solve_bounded(
+            self,
+            Bounds {
+                max_nodes: Some(max_nodes),
+                ..Bounds::default()
+            },
+        )
+    }
+
+    /// Finds the puzzle's unique solution, giving up with
+    /// [`SolveError::Timeout`](enum.SolveError.html#variant.Timeout) once
+    /// the given time has passed.
+    ///
+    /// The clock is only checked every so often, so the search may run
+    /// slightly over. A timeout too long to be represented as a point in
+    /// time (e.g. `Duration::MAX`) sets no deadline.
+    pub fn solution_within(&self, timeout: Duration) -> Result<Sudoku, SolveError> {
+        solve_bounded(
+            self,
+            Bounds {
+                deadline: Instant::now().checked_add(timeout),
+                ..Bounds::default()
+            },
+        )
+    }
+
+    /// Finds the puzzle's unique solution, giving up with
+    /// [`SolveError::Cancelled`](enum.SolveError.html#variant.Cancelled) as
+    /// soon as `cancel` is set (e.g. by another thread).
+    pub fn solution_cancellable(&self, cancel: &AtomicBool) -> Result<Sudoku, SolveError> {
+        solve_bounded(
+            self,
+            Bounds {
+                cancel: Some(cancel),
+                ..Bounds::default()
+            },
+        )
+    }
+
+    /// Whether the puzzle has a solution other than `known`, which must be
+    /// one of its solutions.
+    ///
+    /// This answers the same question as
+    /// [`is_uniquely_solvable`](trait.Solve.html#method.is_uniquely_solvable)
+    /// for a puzzle whose solution is already known, and is usually much
+    /// faster: rather than searching for any two solutions, it only looks for
+    /// one that departs from `known`.
+    ///
+    /// # Notes
+    /// If `known` isn't a solution of the puzzle, the result is meaningless.
+    pub fn has_other_solution(&self, known: &Sudoku) -> bool {
+        has_other_solution(self, known)
+    }
+
+    /// Returns the value (or lack thereof) at the given point, or `None` if
+    /// the point lies outside the grid.
+    pub fn get(&self, point: Point) -> Option<Option<Element>> {
+        self.shape()
+            .fold(point)
+            .and_then(|index| self.elements.get(index).cloned())
+    }
+
     /// Places the specified value (or lack thereof) at the specified index,
     /// modifying in-place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the point lies outside the grid.
     pub fn substitute(&mut self, index: Point, value: Option<Element>) {
-        self.elements[index.fold(self.order)] = value;
+        let index = self
+            .shape()
+            .fold(index)
+            .expect("point lies outside the grid");
+        self.elements[index] = value;
     }
 }
 
 impl Grid for Sudoku {
     fn points(&self) -> Vec<Point> {
-        (0..(self.order as usize).pow(2 + DIMENSIONS as u32))
-            .map(|p| Point::unfold(p, self.order))
-            .collect()
+        self.shape().points()
     }
 }
 
@@ -424,7 +1155,11 @@ where
 impl Index<Point> for Sudoku {
     type Output = Option<Element>;
     fn index(&self, index: Point) -> &Self::Output {
-        &self.elements[index.fold(self.order)]
+        let index = self
+            .shape()
+            .fold(index)
+            .expect("point lies outside the grid");
+        &self.elements[index]
     }
 }
 
@@ -444,26 +1179,38 @@ impl Score for Sudoku {
     fn score(&self) -> Option<usize> {
         score(self)
     }
+    fn difficulty(&self) -> Option<Difficulty> {
+        self.score()
+            .map(|score| Difficulty::from_score_with_boxes(score, self.box_shape()))
+    }
+}
+
+// Writes values in the hexadoku convention used by `UpperHex`: 10 as `0`,
+// and 11 through 16 as `A` through `F`.
+#[cfg(feature = "2D")]
+fn hexadoku(value: u8) -> u8 {
+    match value {
+        10 => 0,
+        value if value > 10 => value - 1,
+        value => value,
+    }
 }
 
 #[cfg(feature = "2D")]
 macro_rules! sudoku_fmt {
-    ($style:ident) => {
+    ($style:ident, $value:expr) => {
         impl fmt::$style for Sudoku {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                let order = self.order;
-                let axis = order.pow(2);
+                if let Some((rows, columns)) = self.boxes {
+                    writeln!(f, "{}{}x{}", BOXES_HEADER, rows, columns)?;
+                }
+                let axis = self.axis();
                 for y in 0..axis {
                     for x in 0..axis {
                         let element = self[Point([x, y])];
                         match element {
-                            Some(Element(mut value)) => {
-                                if value > 9 {
-                                    value -= 1;
-                                    if value == 9 {
-                                        value = 0;
-                                    }
-                                }
+                            Some(Element(value)) => {
+                                let value: u8 = $value(value);
                                 value.fmt(f)?;
                             }
                             None => {
@@ -484,14 +1231,18 @@ macro_rules! sudoku_fmt {
 
 #[cfg(not(feature = "2D"))]
 macro_rules! sudoku_fmt {
-    ($style:ident) => {};
+    ($style:ident, $value:expr) => {};
 }
 
-sudoku_fmt!(Display);
-sudoku_fmt!(UpperHex);
+sudoku_fmt!(Display, |value| value);
+sudoku_fmt!(UpperHex, hexadoku);
 
 /// Represents a deserialization error.
+///
+/// More variants may be added in future releases, so matches outside this
+/// crate need a wildcard arm.
 #[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
 pub enum ParseError {
     /// Represents a grid with differing width and height.
     UnequalDimensions,
@@ -503,32 +1254,139 @@ pub enum ParseError {
     LargeValue(u8, Point),
     /// Represents a grid with a non-perfect-square axial length.
     NonSquareAxis,
+    /// Represents a character that doesn't denote a value or an empty cell.
+    ///
+    /// The associated values are the character and its would-be location in
+    /// the puzzle.
+    UnexpectedCharacter(char, Point),
+    /// Represents input that fits puzzles of more than one order (e.g. a
+    /// block of sixteen lines of sixteen characters, which is either one
+    /// order-4 grid or sixteen order-2 puzzles).
+    ///
+    /// Giving the order (e.g. with
+    /// [`parse_with_order`](struct.Sudoku.html#method.parse_with_order))
+    /// resolves the ambiguity.
+    InferenceAmbiguous,
+    /// Represents input that doesn't fit a puzzle of the order it was said
+    /// to have.
+    ///
+    /// The associated value is the order the input does fit.
+    OrderMismatch(u8),
+    /// Represents a `#boxes: RxC` header that's malformed, describes an
+    /// unsupported shape, or doesn't fit the grid that follows.
+    InvalidBoxes,
+    /// Represents a grid (or a requested order) outside
+    /// [`MIN_ORDER`](constant.MIN_ORDER.html)`..=`[`MAX_ORDER`](constant.MAX_ORDER.html).
+    ///
+    /// The associated value is the unsupported order (saturated to
+    /// `u8::MAX`).
+    UnsupportedOrder(u8),
+}
+
+impl From<OrderError> for ParseError {
+    fn from(error: OrderError) -> Self {
+        ParseError::UnsupportedOrder(error.0)
+    }
+}
+
+/// Finds the order of a square grid with the given axis length, checking
+/// that it's supported.
+#[cfg(feature = "2D")]
+pub(crate) fn square_order(axis: usize) -> Result<u8, ParseError> {
+    let order = (axis as f64).sqrt() as usize;
+    if axis == 0 || order * order != axis {
+        return Err(ParseError::NonSquareAxis);
+    }
+    let order = order.min(usize::from(u8::MAX)) as u8;
+    validate_order(order)?;
+    Ok(order)
+}
+
+/// The prefix of the header line giving a grid's box shape (e.g.
+/// `#boxes: 2x3`).
+#[cfg(feature = "2D")]
+pub(crate) const BOXES_HEADER: &str = "#boxes: ";
+
+/// Parses the box shape out of a `#boxes: RxC` header line.
+#[cfg(feature = "2D")]
+pub(crate) fn parse_boxes_header(line: &str) -> Result<(u8, u8), ParseError> {
+    let shape = line
+        .trim()
+        .trim_start_matches(BOXES_HEADER.trim_end())
+        .trim();
+    let mut dimensions = shape.split('x').map(|d| d.trim().parse::<u8>());
+    match (dimensions.next(), dimensions.next(), dimensions.next()) {
+        (Some(Ok(rows)), Some(Ok(columns)), None) => Ok((rows, columns)),
+        _ => Err(ParseError::InvalidBoxes),
+    }
+}
+
+/// Finds the shape of a grid with the given axis length, honoring an
+/// explicit box shape if one was given.
+///
+/// Without one, the axis must be a perfect square or have a supported
+/// rectangular shape (see [`rect_boxes`](fn.rect_boxes.html)).
+#[cfg(feature = "2D")]
+pub(crate) fn infer_shape(axis: usize, boxes: Option<(u8, u8)>) -> Result<Shape, ParseError> {
+    match boxes {
+        Some((rows, columns)) => {
+            if usize::from(rows) * usize::from(columns) != axis {
+                return Err(ParseError::InvalidBoxes);
+            }
+            if rows == columns {
+                validate_order(rows)?;
+                return Ok(Shape::square(rows));
+            }
+            if rows < 2 || columns < 2 || axis > MAX_RECT_AXIS {
+                return Err(ParseError::InvalidBoxes);
+            }
+            Ok(Shape {
+                order: rows.min(columns),
+                boxes: Some((rows, columns)),
+            })
+        }
+        None => match square_order(axis) {
+            Err(ParseError::NonSquareAxis) if axis > 0 => rect_boxes(axis)
+                .map_or(Err(ParseError::NonSquareAxis), |boxes| {
+                    infer_shape(axis, Some(boxes))
+                }),
+            order => order.map(Shape::square),
+        },
+    }
 }
 
 // TODO((#7): Higher dimensions
 #[cfg(feature = "2D")]
 impl FromStr for Sudoku {
     type Err = ParseError;
+    /// Parses a grid of space-separated values, one row per line, with `_`
+    /// (or anything else that isn't a number) for empty cells.
+    ///
+    /// Grids whose axis length isn't a perfect square are given rectangular
+    /// boxes (see [`new_rect`](struct.Sudoku.html#method.new_rect)): a
+    /// first line like `#boxes: 2x3` gives the shape (rows by columns), and
+    /// without one, the most nearly square shape is assumed, with fewer rows
+    /// than columns.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut rows = s
-            .split('\n')
+        let mut lines = s.split('\n').collect::<Vec<_>>();
+        let boxes = if lines[0].starts_with(BOXES_HEADER.trim_end()) {
+            Some(parse_boxes_header(lines.remove(0))?)
+        } else {
+            None
+        };
+        if lines.len() > 1 && lines.last() == Some(&"") {
+            let _ = lines.pop();
+        }
+        let rows = lines
+            .iter()
             .map(|row| {
                 row.split(' ')
                     .map(|cell| cell.parse().ok().map(Element))
                     .collect::<Vec<_>>()
             })
             .collect::<Vec<_>>();
-        let order = (rows.len() as f64).sqrt() as usize;
-        if rows.len() == order * order + 1 {
-            let last = rows.pop().unwrap();
-            if last.len() != 1 || last[0] != None {
-                return Err(ParseError::NonSquareAxis);
-            }
-        }
         let axis = rows.len();
-        if order * order != axis {
-            return Err(ParseError::NonSquareAxis);
-        }
+        let shape = infer_shape(axis, boxes)?;
         let mut elements = Vec::with_capacity(axis.pow(2));
         for (j, row) in rows.iter().enumerate().take(axis) {
             if row.len() != axis {
@@ -543,21 +1401,113 @@ impl FromStr for Sudoku {
                 elements.push(*elem);
             }
         }
-        Ok(Sudoku {
-            order: order as u8,
-            elements,
-        })
+        Ok(Sudoku::from_parts(shape, elements))
+    }
+}
+
+#[cfg(feature = "2D")]
+impl Sudoku {
+    /// Returns the puzzle's values as a row-major matrix, with `None` for
+    /// empty cells.
+    pub fn as_matrix(&self) -> Vec<Vec<Option<u8>>> {
+        let axis = self.axis();
+        (0..axis)
+            .map(|y| {
+                (0..axis)
+                    .map(|x| self[Point([x, y])].map(|e| e.0))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Builds a puzzle from a row-major matrix of values, with `None` (or
+    /// `Some(0)`, as in the textual formats) for empty cells.
+    ///
+    /// This is the inverse of [`as_matrix`](#method.as_matrix), except
+    /// that a matrix whose size isn't a perfect square is assumed to have
+    /// the most nearly square boxes with fewer rows than columns (see
+    /// [`new_rect`](#method.new_rect)).
+    pub fn from_matrix(matrix: &[Vec<Option<u8>>]) -> Result<Self, ParseError> {
+        let axis = matrix.len();
+        let shape = infer_shape(axis, None)?;
+        let mut elements = Vec::with_capacity(axis.pow(2));
+        for (y, row) in matrix.iter().enumerate() {
+            if row.len() != axis {
+                return Err(ParseError::UnequalDimensions);
+            }
+            for (x, value) in row.iter().enumerate() {
+                match *value {
+                    Some(value) if usize::from(value) > axis => {
+                        return Err(ParseError::LargeValue(value, Point([x as u8, y as u8])));
+                    }
+                    Some(0) | None => elements.push(None),
+                    Some(value) => elements.push(Some(Element(value))),
+                }
+            }
+        }
+        Ok(Sudoku::from_parts(shape, elements))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::sudoku::{Element, Group, Point, Sudoku};
+    use crate::sudoku::{
+        fold_coordinates, Element, Grid, Group, GroupKind, ParseElementError, Point, Sudoku,
+        Violation,
+    };
     use crate::Puzzle;
     use crate::DIMENSIONS;
+    use crate::MAX_ORDER;
+    use crate::MIN_ORDER;
 
     // TODO(#9): Procedural macro-ify these tests
     // TODO(#8): Implement positive tests for Sudoku::groups
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_clues_per_box() {
+        let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let filled = puzzle.elements.iter().filter(|e| e.is_some()).count();
+        let counts = puzzle.clues_per_box();
+        assert_eq!(counts.len(), 9);
+        assert_eq!(counts.iter().sum::<usize>(), filled);
+        // Keep a pattern of cells that's symmetric under a half turn.
+        let mut symmetric = Sudoku::new(3);
+        for point in symmetric.points() {
+            let mut rotated = point;
+            rotated[0] = 8 - point[0];
+            rotated[1] = 8 - point[1];
+            if (point.fold(3) + rotated.fold(3)) % 7 < 3 || point.fold(3) % 5 == 0 {
+                symmetric.substitute(point, Some(Element(1)));
+                symmetric.substitute(rotated, Some(Element(1)));
+            }
+        }
+        let counts = symmetric.clues_per_box();
+        let mut reversed = counts.clone();
+        reversed.reverse();
+        assert_eq!(counts, reversed);
+        assert!(counts.iter().any(|c| *c != counts[0]));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_sudoku_is_solved() {
+        let mut sudoku = Sudoku::new(2);
+        assert!(!sudoku.is_complete());
+        assert!(!sudoku.is_solved());
+        for point in sudoku.points() {
+            sudoku.substitute(point, Some(Element(1)));
+        }
+        assert!(sudoku.is_complete());
+        assert!(!sudoku.is_solved());
+        for point in sudoku.points() {
+            let value = (2 * (point[1] % 2) + point[1] / 2 + point[0]) % 4 + 1;
+            sudoku.substitute(point, Some(Element(value)));
+        }
+        assert!(sudoku.is_solved());
+    }
+
     #[test]
     #[should_panic]
     fn test_sudoku_groups_index_x_3() {
@@ -604,11 +1554,231 @@ mod tests {
         assert_eq!(groups[2].elements().len(), 16);
     }
 
+    #[cfg_attr(feature = "3D", test)]
+    #[cfg(feature = "3D")]
+    fn test_sudoku_groups_length_3_3d() {
+        let sudoku = Sudoku::new(3);
+        let point = Point([4, 7, 2]);
+        let groups = sudoku.groups(point);
+        assert_eq!(groups[0].elements().len(), 27);
+        for group in &groups[1..] {
+            assert_eq!(group.elements().len(), 9);
+        }
+        // The box is the 3×3×3 cube around the point; every other relevant
+        // point lies on a line through it.
+        let in_box = |p: &Point| (0..3).all(|i| p[i] / 3 == point[i] / 3);
+        let on_line = |p: &Point| (0..3).filter(|i| p[*i] != point[*i]).count() <= 1;
+        let mut indices = sudoku.group_indices(point);
+        indices.sort();
+        indices.dedup();
+        assert_eq!(indices.iter().filter(|p| in_box(p)).count(), 27);
+        assert!(indices.iter().all(|p| in_box(p) || on_line(p)));
+    }
+
+    #[cfg_attr(feature = "3D", test)]
+    #[cfg(feature = "3D")]
+    fn test_sudoku_groups_members_3d() {
+        let empty = Sudoku::new(3);
+        let point = Point([4, 7, 2]);
+        let line = |dimension: usize| {
+            (0..9)
+                .map(|value| {
+                    let mut p = point;
+                    p[dimension] = value;
+                    p
+                })
+                .collect::<Vec<_>>()
+        };
+        let cube = empty
+            .points()
+            .into_iter()
+            .filter(|p| (0..3).all(|i| p[i] / 3 == point[i] / 3))
+            .collect::<Vec<_>>();
+        // Expected members, in the order `groups` returns them: the box, the
+        // stack (along y), then bands along x and z.
+        let expected = [cube, line(1), line(0), line(2)];
+        let groups = empty.groups(point);
+        for (i, members) in expected.iter().enumerate() {
+            // Mark exactly the expected members; the group should be those
+            // cells and nothing else.
+            let mut sudoku = empty.clone();
+            for p in members {
+                sudoku.substitute(*p, Some(Element(1)));
+            }
+            let groups_of_marked = sudoku.groups(point);
+            let group = &groups_of_marked[i];
+            assert_eq!(group.elements().len(), members.len(), "group {}", i);
+            assert!(group.elements().iter().all(Option::is_some), "group {}", i);
+            assert_eq!(groups[i].elements().len(), members.len());
+        }
+        match (&groups[1], &groups[2], &groups[3]) {
+            (Group::Stack(_), Group::Band(_), Group::Band(_)) => {}
+            other => panic!("unexpected group kinds: {:?}", other),
+        }
+        // group_indices covers the same cells.
+        let mut indices = empty.group_indices(point);
+        indices.sort();
+        indices.dedup();
+        let mut all = expected.concat();
+        all.sort();
+        all.dedup();
+        assert_eq!(indices, all);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_points_order_contract() {
+        #[rustfmt::skip]
+        let expected = [
+            [0, 0], [1, 0], [2, 0], [3, 0],
+            [0, 1], [1, 1], [2, 1], [3, 1],
+            [0, 2], [1, 2], [2, 2], [3, 2],
+            [0, 3], [1, 3], [2, 3], [3, 3],
+        ];
+        let expected = expected.iter().map(|p| Point(*p)).collect::<Vec<_>>();
+        assert_eq!(Sudoku::new(2).points(), expected);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_groups_order_contract() {
+        // Each cell holds one more than its fold, so the groups' values spell
+        // out which cells they hold, in order.
+        let mut sudoku = Sudoku::new(2);
+        for point in sudoku.points() {
+            sudoku.substitute(point, Some(Element(point.fold(2) as u8 + 1)));
+        }
+        let values = |group: &Group| {
+            group
+                .elements()
+                .iter()
+                .map(|e| e.unwrap().0)
+                .collect::<Vec<_>>()
+        };
+        let groups = sudoku.groups(Point([1, 2]));
+        match &groups {
+            [Group::Box(_), Group::Stack(_), Group::Band(_)] => {}
+            other => panic!("unexpected group kinds: {:?}", other),
+        }
+        assert_eq!(values(&groups[0]), vec![9, 10, 13, 14]);
+        assert_eq!(values(&groups[1]), vec![2, 6, 10, 14]);
+        assert_eq!(values(&groups[2]), vec![9, 10, 11, 12]);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_enumerate_groups() {
+        let sudoku: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let groups = sudoku.enumerate_groups().collect::<Vec<_>>();
+        assert_eq!(groups.len(), 3 * usize::from(sudoku.axis()));
+        for (i, kind) in [GroupKind::Band, GroupKind::Stack, GroupKind::Box]
+            .iter()
+            .enumerate()
+        {
+            for (index, (k, n, group)) in groups[9 * i..9 * (i + 1)].iter().enumerate() {
+                assert_eq!((k, *n, group.kind()), (kind, index, *kind));
+            }
+        }
+        // Row 4, column 2 and the middle-right box.
+        let row = sudoku.groups(Point([0, 4]))[2].elements();
+        assert_eq!(groups[4].2.elements(), row);
+        let column = sudoku.groups(Point([2, 0]))[1].elements();
+        assert_eq!(groups[9 + 2].2.elements(), column);
+        let cube = sudoku.groups(Point([7, 4]))[0].elements();
+        assert_eq!(groups[18 + 5].2.elements(), cube);
+        // Rectangular boxes count the same way.
+        let rect = Sudoku::new_rect(2, 3);
+        assert_eq!(rect.enumerate_groups().count(), 3 * 6);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_is_solvable() {
+        use crate::Solve;
+        // Several solutions: solvable, but not uniquely.
+        let mut open = Sudoku::new(2);
+        open.substitute(Point([0, 0]), Some(Element(1)));
+        assert!(open.is_solvable());
+        assert!(!open.is_uniquely_solvable());
+        assert_eq!(open.solution_count(2), 2);
+        assert_eq!(open.solution_count(0), 0);
+        // No candidates are left for the third cell of the first row, though
+        // no two givens conflict.
+        let stuck: Sudoku = "1 2 _ _\n\
+                             _ _ _ _\n\
+                             _ _ 3 _\n\
+                             _ _ 4 _"
+            .parse()
+            .unwrap();
+        assert!(stuck.is_valid());
+        assert!(!stuck.is_solvable());
+        assert_eq!(stuck.solution_count(2), 0);
+        // Nor is a puzzle whose givens conflict.
+        let mut conflicting = Sudoku::new(2);
+        conflicting.substitute(Point([0, 0]), Some(Element(1)));
+        conflicting.substitute(Point([3, 0]), Some(Element(1)));
+        assert!(!conflicting.is_solvable());
+        let unique: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        assert!(unique.is_solvable() && unique.is_uniquely_solvable());
+        assert_eq!(unique.solution_count(2), 1);
+    }
+
+    #[test]
+    fn test_element_fmt() {
+        for value in 1..=MAX_ORDER.pow(2) {
+            let element = Element(value);
+            assert_eq!(element.to_string(), value.to_string());
+            assert_eq!(element.to_string().parse(), Ok(element));
+        }
+        assert_eq!(" 12 ".parse(), Ok(Element(12)));
+        for bad in &["0", "", "-1", "256", "A"] {
+            assert_eq!(
+                bad.parse::<Element>(),
+                Err(ParseElementError(bad.to_string()))
+            );
+        }
+        // Order 4 runs from 1 to G, one glyph per value.
+        let glyphs = (1..=16)
+            .map(|value| Element(value).to_string_radix(4))
+            .collect::<String>();
+        assert_eq!(glyphs, "123456789ABCDEFG");
+        assert_eq!(Element(7).to_string_radix(3), "7");
+        assert_eq!(Element(25).to_string_radix(5), "P");
+        // Past order 5, some values need more than one glyph.
+        assert_eq!(Element(36).to_string_radix(6), "36");
+        assert_eq!(Element(7).to_string_radix(6), "7");
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_sudoku_fmt_large_values() {
+        // `Display` writes values above 9 in plain decimal, so the output
+        // parses back; `UpperHex` keeps the hexadoku convention.
+        let mut sudoku = Sudoku::new(4);
+        for (x, value) in (9..=16).enumerate() {
+            sudoku.substitute(Point([x as u8, 0]), Some(Element(value)));
+        }
+        let display = sudoku.to_string();
+        assert_eq!(
+            display.lines().next(),
+            Some("9 10 11 12 13 14 15 16 _ _ _ _ _ _ _ _")
+        );
+        assert_eq!(display.parse::<Sudoku>().unwrap(), sudoku);
+        assert_eq!(
+            format!("{:X}", sudoku).lines().next(),
+            Some("9 0 A B C D E F _ _ _ _ _ _ _ _")
+        );
+    }
+
     #[test]
     fn test_sudoku_new() {
-        for order in 2..10usize {
+        for order in 2..=MAX_ORDER as usize {
             let sudoku = Sudoku::new(order as u8);
-            assert_eq!(sudoku.elements.capacity(), order.pow(2 + DIMENSIONS as u32));
+            assert_eq!(sudoku.elements.capacity(), order.pow(2 * DIMENSIONS as u32));
         }
     }
 
@@ -620,9 +1790,64 @@ mod tests {
         assert!(!group.is_valid());
     }
 
+    #[test]
+    fn test_group_new() {
+        let line = vec![Some(Element(1)), None, Some(Element(3)), None];
+        let mut cube = vec![None; 2usize.pow(DIMENSIONS as u32)];
+        cube[1] = Some(Element(2));
+        for (kind, elements) in &[
+            (GroupKind::Box, cube),
+            (GroupKind::Stack, line.clone()),
+            (GroupKind::Band, line),
+        ] {
+            let group = Group::new(*kind, elements.clone());
+            assert_eq!(group.kind(), *kind);
+            assert_eq!(&group.elements(), elements);
+        }
+        assert_eq!(Group::new(GroupKind::Band, vec![]).kind(), GroupKind::Band);
+        assert_eq!(Group::Stack(vec![]).kind(), GroupKind::Stack);
+    }
+
+    #[test]
+    #[should_panic(expected = "a Band of 3 elements has no order")]
+    fn test_group_new_bad_length() {
+        let _ = Group::new(GroupKind::Band, vec![None; 3]);
+    }
+
+    #[test]
+    fn test_group_out_of_range() {
+        let group = Group::Box(vec![Some(Element(1)), Some(Element(42)), None]);
+        assert!(!group.is_valid());
+        assert_eq!(group.out_of_range(), vec![(1, Element(42))]);
+        let group = Group::Box(vec![Some(Element(0)), None]);
+        assert!(!group.is_valid());
+    }
+
+    #[test]
+    fn test_sudoku_diagnostics() {
+        let mut sudoku = Sudoku::new(3);
+        assert!(sudoku.is_valid());
+        let large = Point::unfold(10, 3);
+        sudoku.substitute(large, Some(Element(42)));
+        assert_eq!(sudoku.out_of_range(), vec![(large, Element(42))]);
+        assert_eq!(
+            sudoku.diagnostics(),
+            vec![Violation::ValueOutOfRange(large, Element(42))]
+        );
+        sudoku.substitute(large, None);
+        let (one, two) = (Point::unfold(0, 3), Point::unfold(4, 3));
+        sudoku.substitute(one, Some(Element(5)));
+        sudoku.substitute(two, Some(Element(5)));
+        assert!(!sudoku.is_valid());
+        assert_eq!(
+            sudoku.diagnostics(),
+            vec![Violation::Duplicate(Element(5), one, two)]
+        );
+    }
+
     #[test]
     fn test_group_is_complete() {
-        for vec in [vec![], vec![Some(Element(1)), Some(Element(2))]].into_iter() {
+        for vec in [vec![], vec![Some(Element(1)), Some(Element(2))]].iter() {
             let group = Group::Box(vec.clone());
             assert!(group.is_complete());
         }
@@ -632,15 +1857,109 @@ mod tests {
 
     #[test]
     fn test_group_elements() {
-        for vec in [vec![], vec![Some(Element(2)), Some(Element(6)), None]].into_iter() {
+        for vec in [vec![], vec![Some(Element(2)), Some(Element(6)), None]].iter() {
             let group = Group::Box(vec.clone());
             assert_eq!(&group.elements(), vec);
         }
     }
 
+    #[test]
+    fn test_group_as_slice() {
+        for vec in [vec![], vec![Some(Element(2)), Some(Element(6)), None]].iter() {
+            for group in [
+                Group::Box(vec.clone()),
+                Group::Stack(vec.clone()),
+                Group::Band(vec.clone()),
+            ]
+            .iter()
+            {
+                assert_eq!(group.as_slice(), &group.elements()[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sudoku_new_max_order() {
+        let sudoku = Sudoku::new(MAX_ORDER);
+        assert_eq!(sudoku.order, MAX_ORDER);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sudoku_new_past_max_order() {
+        let _ = Sudoku::new(MAX_ORDER + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "order 0 is unsupported")]
+    fn test_sudoku_new_below_min_order() {
+        let _ = Sudoku::new(MIN_ORDER - 1);
+    }
+
+    #[test]
+    fn test_fold_coordinates_overflow() {
+        assert_eq!(fold_coordinates(&[1, 2], 10), Some(21));
+        assert_eq!(fold_coordinates(&[0, 2], usize::MAX / 2 + 1), None);
+        assert_eq!(fold_coordinates(&[0, 0, 1], usize::MAX / 4), None);
+        assert_eq!(fold_coordinates(&[255, 1], usize::MAX - 254), None);
+    }
+
+    #[test]
+    fn test_point_try_unfold() {
+        assert_eq!(Point::try_unfold(0, 3), Some(Point::origin()));
+        assert_eq!(Point::try_unfold(9_usize.pow(DIMENSIONS as u32), 3), None);
+        assert_eq!(Point::try_unfold(0, 0), None);
+        assert_eq!(Point::try_unfold(0, 17), None);
+    }
+
+    #[test]
+    fn test_point_checked_fold() {
+        let mut last = Point::origin();
+        for i in 0..DIMENSIONS {
+            last[i] = 8;
+        }
+        assert_eq!(Point::origin().checked_fold(3), Some(0));
+        assert_eq!(
+            last.checked_fold(3),
+            Some(9_usize.pow(DIMENSIONS as u32) - 1)
+        );
+        // Each coordinate off the grid on its own, just past the edge and at
+        // the far end of its range.
+        for i in 0..DIMENSIONS {
+            for &coordinate in &[9, u8::MAX] {
+                let mut point = Point::origin();
+                point[i] = coordinate;
+                assert_eq!(point.checked_fold(3), None);
+                assert!(point.try_fold(3).is_some());
+                assert_eq!(Sudoku::new(3).get(point), None);
+            }
+        }
+        assert_eq!(Point::origin().checked_fold(0), None);
+        assert_eq!(Sudoku::new(3).get(last), Some(None));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_point_fold_off_grid() {
+        let _ = Point::with_x(9).fold(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "point lies outside the grid")]
+    fn test_sudoku_index_off_grid() {
+        let _ = Sudoku::new(2)[Point::with_x(4)];
+    }
+
+    #[test]
+    #[should_panic(expected = "point lies outside the grid")]
+    fn test_substitute_off_grid() {
+        // Folded unchecked, this would land on the cell below the origin.
+        Sudoku::new(2).substitute(Point::with_x(4), Some(Element(1)));
+    }
+
     #[test]
     fn test_sudoku_order() {
-        for order in 1..10 {
+        for order in 1..=MAX_ORDER {
             let sudoku = Sudoku::new(order);
             assert_eq!(sudoku.order(), order);
         }
@@ -663,6 +1982,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_point_fold_unfold_inverse() {
+        for order in 1..=MAX_ORDER {
+            let size = (order as usize).pow(2 * DIMENSIONS as u32);
+            // Small grids are checked exhaustively, larger ones by sampling.
+            let step = (size >> 16).max(1);
+            for index in (0..size).step_by(step).chain(Some(size - 1)) {
+                let point = Point::unfold(index, order);
+                assert_eq!(point.fold(order), index);
+                assert_eq!(point.try_fold(order), Some(index));
+                assert_eq!(point.checked_fold(order), Some(index));
+                assert_eq!(Point::try_unfold(index, order), Some(point));
+                assert_eq!(Point::unfold(point.fold(order), order), point);
+            }
+        }
+    }
+
+    #[test]
+    fn test_point_fold_axes() {
+        // Each successive coordinate is worth one more factor of the axis.
+        for i in 0..DIMENSIONS {
+            let mut point = Point::origin();
+            point[i] = 1;
+            assert_eq!(point.fold(3), 9_usize.pow(i as u32));
+            assert_eq!(Point::unfold(9_usize.pow(i as u32), 3), point);
+        }
+    }
+
     #[cfg_attr(feature = "2D", test)]
     #[cfg(feature = "2D")]
     fn test_point_index() {
@@ -694,15 +2041,15 @@ mod tests {
         for i in 0..9 {
             for j in 0..9 {
                 let x = match i {
-                    0...2 => 0,
-                    3...5 => 3,
-                    6...8 => 6,
+                    0..=2 => 0,
+                    3..=5 => 3,
+                    6..=8 => 6,
                     _ => unreachable!(),
                 };
                 let y = match j {
-                    0...2 => 0,
-                    3...5 => 3,
-                    6...8 => 6,
+                    0..=2 => 0,
+                    3..=5 => 3,
+                    6..=8 => 6,
                     _ => unreachable!(),
                 };
                 let point = Point([i, j]);
@@ -712,17 +2059,17 @@ mod tests {
         for i in 0..16 {
             for j in 0..16 {
                 let x = match i {
-                    0...3 => 0,
-                    4...7 => 4,
-                    8...11 => 8,
-                    12...15 => 12,
+                    0..=3 => 0,
+                    4..=7 => 4,
+                    8..=11 => 8,
+                    12..=15 => 12,
                     _ => unreachable!(),
                 };
                 let y = match j {
-                    0...3 => 0,
-                    4...7 => 4,
-                    8...11 => 8,
-                    12...15 => 12,
+                    0..=3 => 0,
+                    4..=7 => 4,
+                    8..=11 => 8,
+                    12..=15 => 12,
                     _ => unreachable!(),
                 };
                 let point = Point([i, j]);
@@ -758,4 +2105,113 @@ mod tests {
         assert!(puzzle.is_ok());
         assert_eq!(&format!("{}", puzzle.unwrap()), s);
     }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_sudoku_matrix_round_trip() {
+        let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let matrix = puzzle.as_matrix();
+        assert_eq!(matrix.len(), 9);
+        assert_eq!(matrix[0][..2], [Some(5), None]);
+        assert_eq!(Sudoku::from_matrix(&matrix).unwrap(), puzzle);
+        let mut large = matrix.clone();
+        large[1][2] = Some(10);
+        assert!(Sudoku::from_matrix(&large).is_err());
+        let mut ragged = matrix;
+        let _ = ragged[4].pop();
+        assert!(Sudoku::from_matrix(&ragged).is_err());
+        assert!(Sudoku::from_matrix(&[]).is_err());
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_sudoku_new_rect() {
+        let sudoku = Sudoku::new_rect(2, 3);
+        assert_eq!(sudoku.box_shape(), (2, 3));
+        assert_eq!(sudoku.axis(), 6);
+        assert_eq!(sudoku.order, 2);
+        assert_eq!(sudoku.elements.len(), 36);
+        assert_eq!(sudoku.points().len(), 36);
+        assert_eq!(Sudoku::new_rect(3, 3), Sudoku::new(3));
+        assert_eq!(Sudoku::new(3).box_shape(), (3, 3));
+        assert_eq!(Sudoku::new(3).axis(), 9);
+    }
+    #[test]
+    #[cfg(feature = "2D")]
+    #[should_panic(expected = "3×4 boxes aren't supported")]
+    fn test_sudoku_new_rect_too_large() {
+        let _ = Sudoku::new_rect(3, 4);
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_rect_boxes() {
+        use crate::sudoku::rect_boxes;
+        assert_eq!(rect_boxes(6), Some((2, 3)));
+        assert_eq!(rect_boxes(8), Some((2, 4)));
+        for axis in &[1, 4, 5, 7, 9, 12] {
+            assert_eq!(rect_boxes(*axis), None);
+        }
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_sudoku_rect_groups() {
+        let sudoku = Sudoku::new_rect(2, 3);
+        // The middle-left box spans rows 2 and 3, columns 0 through 2.
+        let point = Point([1, 3]);
+        assert_eq!(sudoku.snap(point), Point([0, 2]));
+        let boxed = sudoku
+            .group_indices(point)
+            .into_iter()
+            .take(6)
+            .collect::<Vec<_>>();
+        let expected = [[0, 2], [1, 2], [2, 2], [0, 3], [1, 3], [2, 3]];
+        assert_eq!(
+            boxed,
+            expected.iter().map(|c| Point(*c)).collect::<Vec<_>>()
+        );
+        for group in sudoku.groups(point).iter() {
+            assert_eq!(group.elements().len(), 6);
+        }
+        // A 3×2 box is the transpose.
+        let sudoku = Sudoku::new_rect(3, 2);
+        assert_eq!(sudoku.snap(point), Point([0, 3]));
+        let mut puzzle = Sudoku::new_rect(2, 3);
+        puzzle.substitute(Point([0, 0]), Some(Element(1)));
+        puzzle.substitute(Point([2, 1]), Some(Element(1)));
+        assert!(!puzzle.is_valid());
+        puzzle.substitute(Point([2, 1]), None);
+        puzzle.substitute(Point([3, 1]), Some(Element(1)));
+        assert!(puzzle.is_valid());
+        puzzle.substitute(Point([3, 1]), Some(Element(7)));
+        assert_eq!(
+            puzzle.diagnostics(),
+            vec![Violation::ValueOutOfRange(Point([3, 1]), Element(7))]
+        );
+        assert_eq!(puzzle.clues_per_box(), vec![1, 1, 0, 0, 0, 0]);
+    }
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_sudoku_from_str_rect() {
+        use crate::ParseError;
+        let s = include_str!("../tests/sudokus/solvable/2D-R2x3.txt");
+        let puzzle = s.parse::<Sudoku>().unwrap();
+        assert_eq!(puzzle.box_shape(), (2, 3));
+        assert_eq!(&format!("{}", puzzle), s);
+        // Without the header, the shape is inferred.
+        let (_, body) = s.split_once('\n').unwrap();
+        assert_eq!(body.parse::<Sudoku>().unwrap(), puzzle);
+        let transposed = format!("#boxes: 3x2\n{}", body).parse::<Sudoku>();
+        assert_eq!(transposed.unwrap().box_shape(), (3, 2));
+        for header in &["#boxes: 2x2", "#boxes: 1x6", "#boxes: two by three"] {
+            let parsed = format!("{}\n{}", header, body).parse::<Sudoku>();
+            assert!(matches!(parsed, Err(ParseError::InvalidBoxes)));
+        }
+        let matrix = puzzle.as_matrix();
+        assert_eq!(Sudoku::from_matrix(&matrix).unwrap(), puzzle);
+        let seven = "_ _ _ _ _ _ _\n".repeat(7);
+        assert!(matches!(
+            seven.parse::<Sudoku>(),
+            Err(ParseError::NonSquareAxis)
+        ));
+    }
 }
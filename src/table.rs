@@ -0,0 +1,145 @@
+//! Puzzles declared as tables of values, checked at compile time.
+use crate::sudoku::Shape;
+use crate::Element;
+use crate::Sudoku;
+use crate::DIMENSIONS;
+use crate::MAX_ORDER;
+use crate::MIN_ORDER;
+
+/// Builds a [`Sudoku`](struct.Sudoku.html) from a table of its values, in
+/// [`points`](trait.Grid.html#tymethod.points) order, with `0` for empty
+/// cells.
+///
+/// The order is inferred from the number of values (81 for an order-3
+/// grid). The table is checked at compile time, so a fixture with the wrong
+/// number of values, or a value too large for its order, fails to build
+/// rather than to parse at startup.
+///
+/// ```
+/// # #[macro_use] extern crate sudoku;
+/// # fn main() {
+/// let puzzle = sudoku_from_values![
+///     1, 0, 0, 0,
+///     0, 0, 1, 0,
+///     0, 1, 0, 0,
+///     0, 0, 0, 1,
+/// ];
+/// assert_eq!(puzzle.order, 2);
+/// # }
+/// ```
+///
+/// Fifteen values don't fill any grid:
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate sudoku;
+/// # fn main() {
+/// let puzzle = sudoku_from_values![
+///     1, 0, 0, 0,
+///     0, 0, 1, 0,
+///     0, 1, 0, 0,
+///     0, 0, 0,
+/// ];
+/// # }
+/// ```
+///
+/// Nor does an order-2 grid hold a 5:
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate sudoku;
+/// # fn main() {
+/// let puzzle = sudoku_from_values![
+///     1, 0, 0, 0,
+///     0, 0, 1, 0,
+///     0, 1, 0, 0,
+///     0, 0, 0, 5,
+/// ];
+/// # }
+/// ```
+#[macro_export]
+macro_rules! sudoku_from_values {
+    ($($value:expr),* $(,)?) => {{
+        const VALUES: &[u8] = &[$($value),*];
+        const ORDER: u8 = $crate::__table_order(VALUES);
+        $crate::__table_sudoku(ORDER, VALUES)
+    }};
+}
+
+/// Returns the order of the grid the values fill, panicking (which fails
+/// compilation, in a const context) if they fill none or don't fit it.
+#[doc(hidden)]
+pub const fn __table_order(values: &[u8]) -> u8 {
+    let mut order = MIN_ORDER;
+    while order <= MAX_ORDER && (order as usize).pow(2 * DIMENSIONS as u32) != values.len() {
+        order += 1;
+    }
+    assert!(
+        order <= MAX_ORDER,
+        "the number of values doesn't fill a grid of any supported order"
+    );
+    let axis = order * order;
+    let mut i = 0;
+    while i < values.len() {
+        assert!(values[i] <= axis, "a value is too large for the grid");
+        i += 1;
+    }
+    order
+}
+
+/// Builds the grid from values already checked by
+/// [`__table_order`](fn.__table_order.html).
+#[doc(hidden)]
+pub fn __table_sudoku(order: u8, values: &[u8]) -> Sudoku {
+    let elements = values
+        .iter()
+        .map(|value| match value {
+            0 => None,
+            value => Some(Element::new(*value)),
+        })
+        .collect();
+    Sudoku::from_parts(Shape::square(order), elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Element;
+    #[cfg(feature = "2D")]
+    use crate::Point;
+    #[cfg(feature = "2D")]
+    use crate::Sudoku;
+
+    // Checked when the crate's tests are built.
+    #[cfg(feature = "2D")]
+    const ONE: Element = Element::new(1);
+    #[cfg(feature = "2D")]
+    const ORIGIN: Point = Point::const_new([0, 0]);
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_sudoku_from_values() {
+        #[rustfmt::skip]
+        let puzzle = sudoku_from_values![
+            5, 0, 0, 0, 9, 3, 1, 6, 0,
+            0, 0, 0, 0, 0, 1, 0, 0, 0,
+            3, 6, 0, 8, 0, 0, 0, 0, 0,
+            0, 0, 0, 6, 0, 0, 9, 0, 0,
+            6, 8, 0, 0, 7, 0, 0, 5, 2,
+            0, 0, 2, 0, 0, 8, 0, 0, 0,
+            0, 0, 0, 0, 0, 2, 0, 9, 6,
+            0, 0, 0, 5, 0, 0, 0, 0, 0,
+            0, 4, 3, 9, 1, 0, 0, 0, 8,
+        ];
+        let parsed: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        assert_eq!(puzzle, parsed);
+        assert_eq!(puzzle[ORIGIN], Some(Element::new(5)));
+        assert_eq!(puzzle[Point([6, 0])], Some(ONE));
+        assert_eq!(puzzle[Point([8, 0])], None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_element_new_zero() {
+        let _ = Element::new(0);
+    }
+}
@@ -0,0 +1,504 @@
+//! Printable pages of puzzles (and, optionally, their solutions), as SVG.
+//!
+//! Everything here is in points (1/72 inch), with the origin in the top-left
+//! corner of the page. The layout is deterministic: the same collection and
+//! options always produce the same pages.
+
+use std::fmt::Write;
+
+use crate::analysis::fingerprint;
+use crate::ui::color::DigitPalette;
+use crate::ui::geometry::{GridGeometry, Rect};
+use crate::Grid;
+use crate::Score;
+use crate::Solve;
+use crate::Sudoku;
+
+/// The fraction of the page's smaller side left blank along each edge.
+const MARGIN_FRACTION: f64 = 0.06;
+
+/// The fraction of the page's height taken up by the header.
+const HEADER_FRACTION: f64 = 0.05;
+
+/// The fraction of a slot's height taken up by its caption.
+const CAPTION_FRACTION: f64 = 0.08;
+
+/// The fraction of a caption's (or the header's) height taken up by its text.
+const TEXT_FRACTION: f64 = 0.6;
+
+/// How many times as many solutions as puzzles fit on a page (so solutions
+/// are drawn at about half the size).
+const SOLUTION_DENSITY: usize = 4;
+
+/// A titled list of puzzles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collection {
+    /// The title printed atop each page.
+    pub title: String,
+    /// The puzzles, in order.
+    pub puzzles: Vec<Sudoku>,
+}
+
+/// Options for [rendering a book](struct.Collection.html#method.render_book).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BookOptions {
+    /// The number of puzzles on each page (at least one).
+    pub per_page: usize,
+    /// Whether to end the book with the puzzles' solutions.
+    pub with_solutions: bool,
+    /// The width of a page.
+    pub page_width: f64,
+    /// The height of a page.
+    pub page_height: f64,
+}
+
+impl Default for BookOptions {
+    /// Four puzzles to an A4 page, without solutions.
+    fn default() -> Self {
+        Self {
+            per_page: 4,
+            with_solutions: false,
+            page_width: 595.0,
+            page_height: 842.0,
+        }
+    }
+}
+
+/// The spot for one grid on a page.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Slot {
+    /// The area of the caption, directly above the grid.
+    caption: Rect,
+    /// The layout of the grid, already placed on the page (only its `grid`
+    /// is in page coordinates).
+    geometry: GridGeometry,
+}
+
+/// One grid to draw, with its caption.
+struct Entry<'a> {
+    caption: String,
+    grid: &'a Sudoku,
+    // Which cells were givens, to be set in bold (for solutions).
+    givens: Option<&'a Sudoku>,
+    // The colors to draw the values in (black otherwise).
+    palette: Option<&'a DigitPalette>,
+}
+
+impl Collection {
+    /// Creates a collection with the given title.
+    pub fn new(title: &str, puzzles: Vec<Sudoku>) -> Self {
+        Self {
+            title: title.to_string(),
+            puzzles,
+        }
+    }
+
+    /// Typesets the collection, returning one SVG document per page.
+    ///
+    /// Each puzzle is captioned with its number, difficulty and fingerprint
+    /// (a short hash of its clues), and each page is headed with the title
+    /// and page number. With solutions, the puzzles are followed by pages of
+    /// their solutions (givens in bold) at about half the size; puzzles
+    /// without a solution are left out of that section.
+    ///
+    /// # Notes
+    /// Only order 3 and 4 puzzles are guaranteed to be legible, and all
+    /// puzzles are assumed to be of the same order.
+    pub fn render_book(&self, options: &BookOptions) -> Vec<String> {
+        let per_page = options.per_page.max(1);
+        let puzzles = self
+            .puzzles
+            .iter()
+            .enumerate()
+            .map(|(i, puzzle)| {
+                let difficulty = puzzle
+                    .difficulty()
+                    .map_or_else(|| "Unscored".to_string(), |d| d.to_string());
+                Entry {
+                    caption: format!("#{} · {} · {}", i + 1, difficulty, fingerprint(puzzle)),
+                    grid: puzzle,
+                    givens: None,
+                    palette: None,
+                }
+            })
+            .collect::<Vec<_>>();
+        let solutions = if options.with_solutions {
+            self.puzzles
+                .iter()
+                .map(|puzzle| puzzle.solution().ok())
+                .collect::<Vec<_>>()
+        } else {
+            vec![]
+        };
+        let solutions = solutions
+            .iter()
+            .zip(&self.puzzles)
+            .enumerate()
+            .filter_map(|(i, (solution, puzzle))| {
+                solution.as_ref().map(|solution| Entry {
+                    caption: format!("#{}", i + 1),
+                    grid: solution,
+                    givens: Some(puzzle),
+                    palette: None,
+                })
+            })
+            .collect::<Vec<_>>();
+        let pages = puzzles
+            .chunks(per_page)
+            .map(|chunk| (self.title.clone(), per_page, chunk))
+            .chain(solutions.chunks(per_page * SOLUTION_DENSITY).map(|chunk| {
+                (
+                    format!("{} — Solutions", self.title),
+                    per_page * SOLUTION_DENSITY,
+                    chunk,
+                )
+            }))
+            .collect::<Vec<_>>();
+        let count = pages.len();
+        pages
+            .iter()
+            .enumerate()
+            .map(|(i, (title, slots, entries))| {
+                render_page(options, title, i + 1, count, *slots, entries)
+            })
+            .collect()
+    }
+}
+
+/// Draws a single puzzle as an SVG document the size of the geometry's
+/// canvas, with its values colored from the palette (e.g. for a preview
+/// image).
+pub fn render_preview(puzzle: &Sudoku, geometry: &GridGeometry, palette: &DigitPalette) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = geometry.width,
+        h = geometry.height
+    );
+    let entry = Entry {
+        caption: String::new(),
+        grid: puzzle,
+        givens: None,
+        palette: Some(palette),
+    };
+    render_grid(&mut svg, geometry, &entry);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Lays out `count` grids shaped like the given puzzle on a page, in reading
+/// order.
+///
+/// The grids are arranged in whichever number of columns makes them
+/// largest (preferring fewer columns), each in an equal slot below the
+/// header, with its caption directly above it.
+fn layout(puzzle: &Sudoku, count: usize, width: f64, height: f64) -> Vec<Slot> {
+    let count = count.max(1);
+    let margin = MARGIN_FRACTION * width.min(height);
+    let header = HEADER_FRACTION * height;
+    let area = Rect {
+        x: margin,
+        y: margin + header,
+        width: width - 2.0 * margin,
+        height: height - 2.0 * margin - header,
+    };
+    let slot_size = |columns: usize| {
+        let rows = count.div_ceil(columns);
+        (
+            area.width / columns as f64,
+            area.height / rows as f64 * (1.0 - CAPTION_FRACTION),
+        )
+    };
+    let mut columns = 1;
+    for candidate in 2..=count {
+        let (width, height) = slot_size(candidate);
+        let (best_width, best_height) = slot_size(columns);
+        if width.min(height) > best_width.min(best_height) {
+            columns = candidate;
+        }
+    }
+    let rows = count.div_ceil(columns);
+    let (slot_width, slot_height) = (area.width / columns as f64, area.height / rows as f64);
+    let caption = slot_height * CAPTION_FRACTION;
+    (0..count)
+        .map(|i| {
+            let x = area.x + (i % columns) as f64 * slot_width;
+            let y = area.y + (i / columns) as f64 * slot_height;
+            let mut geometry = GridGeometry::for_puzzle(puzzle, slot_width, slot_height - caption);
+            geometry.grid.x += x;
+            geometry.grid.y += y + caption;
+            Slot {
+                caption: Rect {
+                    x: geometry.grid.x,
+                    y: geometry.grid.y - caption,
+                    width: geometry.grid.width,
+                    height: caption,
+                },
+                geometry,
+            }
+        })
+        .collect()
+}
+
+/// Escapes text for inclusion in SVG.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Draws a page holding the given entries in a layout with `slots` spots.
+fn render_page(
+    options: &BookOptions,
+    title: &str,
+    page: usize,
+    pages: usize,
+    slots: usize,
+    entries: &[Entry],
+) -> String {
+    let (width, height) = (options.page_width, options.page_height);
+    let standard = Sudoku::new(3);
+    let puzzle = entries.first().map_or(&standard, |entry| entry.grid);
+    let margin = MARGIN_FRACTION * width.min(height);
+    let header = HEADER_FRACTION * height;
+    let font = header * TEXT_FRACTION;
+    let baseline = margin + header / 2.0;
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.2}" y="{:.2}" font-size="{:.2}" dominant-baseline="central">{}</text>"#,
+        margin,
+        baseline,
+        font,
+        escape(title)
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.2}" y="{:.2}" font-size="{:.2}" dominant-baseline="central" text-anchor="end">Page {} of {}</text>"#,
+        width - margin,
+        baseline,
+        font,
+        page,
+        pages
+    );
+    for (entry, slot) in entries.iter().zip(layout(puzzle, slots, width, height)) {
+        let caption = slot.caption;
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.2}" y="{:.2}" font-size="{:.2}" dominant-baseline="central">{}</text>"#,
+            caption.x,
+            caption.y + caption.height / 2.0,
+            caption.height * TEXT_FRACTION,
+            escape(&entry.caption)
+        );
+        render_grid(&mut svg, &slot.geometry, entry);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Draws a grid (its outline, cell and box lines, and values).
+fn render_grid(svg: &mut String, geometry: &GridGeometry, entry: &Entry) {
+    let grid = geometry.grid;
+    let size = geometry.cell_size();
+    let thin = (size / 40.0).max(0.25);
+    let _ = writeln!(
+        svg,
+        r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="none" stroke="black" stroke-width="{:.2}"/>"#,
+        grid.x,
+        grid.y,
+        grid.width,
+        grid.height,
+        thin * 3.0
+    );
+    let (rows, columns) = geometry.boxes;
+    let stroke = |i: u8, extent: u8| {
+        if i.is_multiple_of(extent) {
+            thin * 3.0
+        } else {
+            thin
+        }
+    };
+    for i in 1..geometry.axis() {
+        let offset = f64::from(i) * size;
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{x:.2}" y1="{:.2}" x2="{x:.2}" y2="{:.2}" stroke="black" stroke-width="{:.2}"/>"#,
+            grid.y,
+            grid.y + grid.height,
+            stroke(i, columns),
+            x = grid.x + offset
+        );
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{:.2}" y1="{y:.2}" x2="{:.2}" y2="{y:.2}" stroke="black" stroke-width="{:.2}"/>"#,
+            grid.x,
+            grid.x + grid.width,
+            stroke(i, rows),
+            y = grid.y + offset
+        );
+    }
+    let font = size * geometry.recommended_font_fraction(geometry.order);
+    for point in entry.grid.points() {
+        if let Some(value) = entry.grid[point] {
+            let (x, y) = geometry.cell_center(point);
+            let weight = match entry.givens {
+                Some(givens) if givens[point].is_some() => r#" font-weight="bold""#,
+                _ => "",
+            };
+            let fill = entry
+                .palette
+                .and_then(|palette| palette.css_for(value))
+                .map_or_else(String::new, |color| format!(r#" fill="{}""#, color));
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.2}" y="{:.2}" font-size="{:.2}"{}{} text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                x, y, font, weight, fill, value.0
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::fingerprint;
+    use crate::ui::book::{layout, render_preview, BookOptions, Collection};
+    use crate::ui::color::{DigitPalette, Theme};
+    use crate::ui::geometry::GridGeometry;
+    use crate::Element;
+    use crate::Sudoku;
+
+    fn fixture() -> Sudoku {
+        include_str!("../../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_layout_fits() {
+        for order in 3..=4 {
+            for count in 1..=16 {
+                let slots = layout(&Sudoku::new(order), count, 595.0, 842.0);
+                assert_eq!(slots.len(), count);
+                let mut areas = vec![];
+                for slot in &slots {
+                    let grid = slot.geometry.grid;
+                    assert!(grid.x >= 0.0 && grid.x + grid.width <= 595.0);
+                    assert!(grid.y >= 0.0 && grid.y + grid.height <= 842.0);
+                    areas.push(grid);
+                    areas.push(slot.caption);
+                }
+                for (i, area) in areas.iter().enumerate() {
+                    for other in &areas[i + 1..] {
+                        assert!(!area.overlaps(other), "{:?} overlaps {:?}", area, other);
+                    }
+                }
+            }
+        }
+        // Two grids on a portrait page go one above the other.
+        let slots = layout(&Sudoku::new(3), 2, 595.0, 842.0);
+        assert!(slots[0].geometry.grid.y < slots[1].geometry.grid.y);
+        assert_eq!(slots[0].geometry.grid.x, slots[1].geometry.grid.x);
+    }
+
+    #[test]
+    fn test_render_book_page() {
+        let puzzle = fixture();
+        let clues = puzzle.elements.iter().filter(|e| e.is_some()).count();
+        let collection = Collection::new("Daily <Two>", vec![puzzle.clone(), puzzle]);
+        let options = BookOptions {
+            per_page: 2,
+            ..BookOptions::default()
+        };
+        let pages = collection.render_book(&options);
+        assert_eq!(pages.len(), 1);
+        let page = &pages[0];
+        assert!(page.starts_with("<svg ") && page.ends_with("</svg>\n"));
+        // Two outlines, eight lines across and eight down each.
+        assert_eq!(page.matches("<rect ").count(), 2);
+        assert_eq!(page.matches("<line ").count(), 2 * 16);
+        // Title, page number, captions and clues.
+        assert_eq!(page.matches("<text ").count(), 2 + 2 + 2 * clues);
+        assert!(page.contains(">Daily &lt;Two&gt;</text>"));
+        assert!(page.contains(">Page 1 of 1</text>"));
+        assert!(page.contains(&format!("#2 · Difficult · {}", fingerprint(&fixture()))));
+        // The grids are where the layout puts them.
+        for slot in layout(&Sudoku::new(3), 2, 595.0, 842.0) {
+            let grid = slot.geometry.grid;
+            assert!(page.contains(&format!(
+                r#"<rect x="{:.2}" y="{:.2}" width="{:.2}""#,
+                grid.x, grid.y, grid.width
+            )));
+        }
+        assert!(!page.contains("Solutions"));
+    }
+
+    #[test]
+    fn test_render_book_solutions() {
+        let puzzles = vec![fixture(); 5];
+        let clues = puzzles[0].elements.iter().filter(|e| e.is_some()).count();
+        let collection = Collection::new("Five", puzzles);
+        let mut options = BookOptions {
+            per_page: 2,
+            ..BookOptions::default()
+        };
+        assert_eq!(collection.render_book(&options).len(), 3);
+        options.with_solutions = true;
+        let pages = collection.render_book(&options);
+        // Five solutions fit on one page at four times the density.
+        assert_eq!(pages.len(), 4);
+        assert!(pages[3].contains(">Five — Solutions</text>"));
+        assert!(pages[3].contains(">Page 4 of 4</text>"));
+        assert_eq!(pages[3].matches("<rect ").count(), 5);
+        assert_eq!(pages[3].matches(r#"font-weight="bold""#).count(), 5 * clues);
+        assert!(Collection::new("Empty", vec![])
+            .render_book(&options)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_render_preview() {
+        let puzzle = fixture();
+        let clues = puzzle.elements.iter().filter(|e| e.is_some()).count();
+        let geometry = GridGeometry::with_margin(3, 800.0, 800.0, 40.0).unwrap();
+        let palette = DigitPalette::generate(3, &Theme::default());
+        let svg = render_preview(&puzzle, &geometry, &palette);
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="800" height="800""#)
+        );
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(r#"<rect x="40.00" y="40.00" width="720.00" height="720.00""#));
+        assert_eq!(svg.matches("<line ").count(), 16);
+        assert_eq!(svg.matches("<text ").count(), clues);
+        let fill = format!(r#" fill="{}""#, palette.css_for(Element(5)).unwrap());
+        let fives = puzzle
+            .elements
+            .iter()
+            .filter(|e| **e == Some(Element(5)))
+            .count();
+        assert_eq!(svg.matches(&fill).count(), fives);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_render_rect_boxes() {
+        let puzzle = Sudoku::new_rect(2, 3);
+        let geometry = GridGeometry::for_puzzle(&puzzle, 600.0, 600.0);
+        let palette = DigitPalette::generate(2, &Theme::default());
+        let svg = render_preview(&puzzle, &geometry, &palette);
+        assert_eq!(svg.matches("<line ").count(), 10);
+        // The outline, then box lines after every third column and every
+        // second row.
+        assert_eq!(svg.matches(r#"stroke-width="6.75""#).count(), 4);
+        assert!(svg.contains(r#"<line x1="300.00" y1="30.00" x2="300.00" y2="570.00" stroke="black" stroke-width="6.75"/>"#));
+        assert!(svg.contains(r#"<line x1="30.00" y1="210.00" x2="570.00" y2="210.00" stroke="black" stroke-width="6.75"/>"#));
+    }
+}
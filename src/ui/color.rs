@@ -1,7 +1,9 @@
 //! Utilities for working with colors and color palettes.
 
+use crate::Element;
+
 use num_traits::{Bounded, FromPrimitive, NumCast};
-use std::fmt;
+use std::{fmt, str::FromStr};
 
 /// A construct that can be treated as encoding a solid color.
 pub trait SolidColor<T: NumCast + Bounded>: Color<T> + Into<Rgb<T>> + Into<Hsl<T>> {
@@ -82,7 +84,7 @@ impl<T: NumCast + Bounded + FromPrimitive + Default> From<Rgb<T>> for Hsl<T> {
         } else if max == b {
             (((r - g) / delta) + 4.0) / 6.0
         } else if max == r {
-            (((g - b) / delta) % 6.0) / 6.0
+            ((g - b) / delta).rem_euclid(6.0) / 6.0
         } else {
             unreachable!()
         };
@@ -107,15 +109,15 @@ impl<T: NumCast + Bounded + FromPrimitive + Default> From<Hsl<T>> for Rgb<T> {
         let x = c * (1.0 - (((h * 6.0) % 2.0) - 1.0).abs());
         let m = l - c / 2.0;
         let f_h = h * 6.0;
-        let (r, g, b) = if f_h >= 1.0 && f_h < 2.0 {
+        let (r, g, b) = if (1.0..2.0).contains(&f_h) {
             (x, c, 0.0)
-        } else if f_h >= 2.0 && f_h < 3.0 {
+        } else if (2.0..3.0).contains(&f_h) {
             (0.0, c, x)
-        } else if f_h >= 3.0 && f_h < 4.0 {
+        } else if (3.0..4.0).contains(&f_h) {
             (0.0, x, c)
-        } else if f_h >= 4.0 && f_h < 5.0 {
+        } else if (4.0..5.0).contains(&f_h) {
             (x, 0.0, c)
-        } else if f_h >= 5.0 && f_h < 6.0 {
+        } else if (5.0..6.0).contains(&f_h) {
             (c, 0.0, x)
         } else {
             (c, x, 0.0)
@@ -355,6 +357,7 @@ impl<T: NumCast + Bounded + FromPrimitive + Default> Name for Hsl<T> {
 }
 
 /// A color specified using a name.
+#[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub struct Named(String);
 
@@ -381,6 +384,123 @@ impl<T: NumCast + Bounded + FromPrimitive + Default> Color<T> for Hsl<T> {}
 impl<T: NumCast + Bounded + FromPrimitive + Default> Color<T> for Rgba<T> {}
 impl<T: NumCast + Bounded + FromPrimitive + Default> Color<T> for Hsla<T> {}
 
+/// Encodes errors encountered while parsing a color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParseColorError {
+    /// The string isn't of the form `rgb(r, g, b)`.
+    Malformed,
+    /// A component couldn't be parsed (e.g. it's out of range).
+    InvalidComponent,
+}
+
+impl<T: FromStr> FromStr for Rgb<T> {
+    type Err = ParseColorError;
+    /// Parses a color of the form produced by `Display` (`rgb(r, g, b)`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if !s.starts_with("rgb(") || !s.ends_with(')') {
+            return Err(ParseColorError::Malformed);
+        }
+        let components = s[4..s.len() - 1]
+            .split(',')
+            .map(|c| {
+                c.trim()
+                    .parse()
+                    .map_err(|_| ParseColorError::InvalidComponent)
+            })
+            .collect::<Result<Vec<T>, _>>()?;
+        let mut components = components.into_iter();
+        match (
+            components.next(),
+            components.next(),
+            components.next(),
+            components.next(),
+        ) {
+            (Some(r), Some(g), Some(b), None) => Ok(Rgb(r, g, b)),
+            _ => Err(ParseColorError::Malformed),
+        }
+    }
+}
+
+/// The parameters shared by the colors of a palette.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    /// The saturation of each color, from 0 to 1.
+    pub saturation: f64,
+    /// The lightness of each color, from 0 to 1.
+    pub lightness: f64,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            saturation: 0.7,
+            lightness: 0.5,
+        }
+    }
+}
+
+/// A color for each digit of a puzzle, looked up by element.
+///
+/// The hues are spread evenly around the color wheel, starting at red, so
+/// the colors of consecutive digits are `360 / order²` degrees apart (40° for
+/// an order-3 puzzle).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DigitPalette {
+    colors: Vec<Rgb<u8>>,
+}
+
+impl DigitPalette {
+    /// Generates a palette for puzzles of the given order.
+    pub fn generate(order: u8, theme: &Theme) -> Self {
+        let count = (order as usize).pow(2);
+        let scale = |value: f64| (value.clamp(0.0, 1.0) * 65535.0) as u16;
+        let colors = (0..count)
+            .map(|i| {
+                let hsl = Hsl::<u16>(
+                    scale(i as f64 / count as f64),
+                    scale(theme.saturation),
+                    scale(theme.lightness),
+                );
+                let rgb: Rgb<u16> = hsl.into();
+                Rgb::<u8>((rgb.0 >> 8) as u8, (rgb.1 >> 8) as u8, (rgb.2 >> 8) as u8)
+            })
+            .collect();
+        Self { colors }
+    }
+
+    /// Returns the color for the given element, if it's in range.
+    pub fn color_for(&self, element: Element) -> Option<&Rgb<u8>> {
+        (element.0 as usize)
+            .checked_sub(1)
+            .and_then(|index| self.colors.get(index))
+    }
+
+    /// Returns the color for the given element as a CSS color string, if it's
+    /// in range.
+    pub fn css_for(&self, element: Element) -> Option<String> {
+        self.color_for(element).map(ToString::to_string)
+    }
+
+    /// Returns an iterator over each element and its color, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (Element, &Rgb<u8>)> {
+        self.colors
+            .iter()
+            .enumerate()
+            .map(|(index, color)| (Element(index as u8 + 1), color))
+    }
+
+    /// Returns the number of colors in the palette.
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Whether the palette has no colors.
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+}
+
 // TODO(#21): Make at least fmt::Display CSS-compatible.
 macro_rules! fmt {
     ($style:ident) => {
@@ -445,7 +565,7 @@ fmt!(UpperHex);
 
 #[cfg(test)]
 mod tests {
-    use ui::color::*;
+    use crate::ui::color::*;
     #[test]
     fn test_rgb_to_hsl() {
         let rgb_colors = [
@@ -465,6 +585,7 @@ mod tests {
             (128, 0, 128),
             (0, 128, 128),
             (0, 0, 128),
+            (255, 0, 128),
         ];
         let hsl_colors = [
             (0, 0, 0),
@@ -483,16 +604,16 @@ mod tests {
             (212, 255, 64),
             (127, 255, 64),
             (170, 255, 64),
+            (233, 255, 127),
         ];
-        let pairs =
-            rgb_colors
-                .into_iter()
-                .zip(hsl_colors.into_iter())
-                .map(|(rgb_color, hsl_color)| {
-                    let rgb = Rgb::<u8>(rgb_color.0, rgb_color.1, rgb_color.2);
-                    let hsl: Hsl<u8> = rgb.into();
-                    (hsl, Hsl::<u8>(hsl_color.0, hsl_color.1, hsl_color.2))
-                });
+        let pairs = rgb_colors
+            .iter()
+            .zip(hsl_colors.iter())
+            .map(|(rgb_color, hsl_color)| {
+                let rgb = Rgb::<u8>(rgb_color.0, rgb_color.1, rgb_color.2);
+                let hsl: Hsl<u8> = rgb.into();
+                (hsl, Hsl::<u8>(hsl_color.0, hsl_color.1, hsl_color.2))
+            });
         for pair in pairs {
             assert_eq!(pair.0, pair.1);
         }
@@ -537,17 +658,75 @@ mod tests {
             (127, 255, 64),
             (170, 255, 64),
         ];
-        let pairs =
-            rgb_colors
-                .into_iter()
-                .zip(hsl_colors.into_iter())
-                .map(|(rgb_color, hsl_color)| {
-                    let hsl = Hsl::<u8>(hsl_color.0, hsl_color.1, hsl_color.2);
-                    let rgb: Rgb<u8> = hsl.into();
-                    (rgb, Rgb::<u8>(rgb_color.0, rgb_color.1, rgb_color.2))
-                });
+        let pairs = rgb_colors
+            .iter()
+            .zip(hsl_colors.iter())
+            .map(|(rgb_color, hsl_color)| {
+                let hsl = Hsl::<u8>(hsl_color.0, hsl_color.1, hsl_color.2);
+                let rgb: Rgb<u8> = hsl.into();
+                (rgb, Rgb::<u8>(rgb_color.0, rgb_color.1, rgb_color.2))
+            });
         for pair in pairs {
             assert_eq!(pair.0, pair.1);
         }
     }
+
+    #[test]
+    fn test_rgb_from_str() {
+        assert_eq!("rgb(1, 2, 3)".parse(), Ok(Rgb::<u8>(1, 2, 3)));
+        assert_eq!("rgb(1,2,3)".parse(), Ok(Rgb::<u8>(1, 2, 3)));
+        assert_eq!(
+            "rgb(1, 2)".parse::<Rgb<u8>>(),
+            Err(ParseColorError::Malformed)
+        );
+        assert_eq!(
+            "rgb(1, 2, 256)".parse::<Rgb<u8>>(),
+            Err(ParseColorError::InvalidComponent)
+        );
+        assert_eq!(
+            "hsl(1, 2, 3)".parse::<Rgb<u8>>(),
+            Err(ParseColorError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_digit_palette() {
+        for order in 1..=4 {
+            let palette = DigitPalette::generate(order, &Theme::default());
+            assert_eq!(palette.len(), (order as usize).pow(2));
+            assert_eq!(palette.iter().count(), palette.len());
+            assert!(palette.color_for(Element(0)).is_none());
+            assert!(palette.color_for(Element(order.pow(2) + 1)).is_none());
+            for (element, color) in palette.iter() {
+                let css = palette.css_for(element).unwrap();
+                assert_eq!(css.parse::<Rgb<u8>>().as_ref(), Ok(color));
+            }
+        }
+    }
+
+    #[test]
+    fn test_digit_palette_hue_distance() {
+        for order in 2..=4 {
+            let palette = DigitPalette::generate(order, &Theme::default());
+            let step = 360.0 / (order as f64).powi(2);
+            let hues = palette
+                .iter()
+                .map(|(_, color)| {
+                    let hsl: Hsl<u16> = Rgb::<u16>(
+                        (color.0 as u16) << 8,
+                        (color.1 as u16) << 8,
+                        (color.2 as u16) << 8,
+                    )
+                    .into();
+                    hsl.0 as f64 / 65535.0 * 360.0
+                })
+                .collect::<Vec<_>>();
+            for pair in hues.windows(2) {
+                let distance = (pair[1] - pair[0]).abs();
+                let distance = distance.min(360.0 - distance);
+                // Allow for the loss of precision in the conversions.
+                assert!(distance > step - 2.0, "{} < {}", distance, step);
+            }
+        }
+    }
 }
@@ -0,0 +1,219 @@
+//! Textual descriptions of a game, for screen readers and other non-visual
+//! frontends.
+//!
+//! The wording comes from a [`Messages`](trait.Messages.html)
+//! implementation, so descriptions can be translated; [`English`](struct.English.html)
+//! is provided. Rows and columns are numbered from 1.
+use crate::ui::model::{Game, GroupKind};
+use crate::Element;
+use crate::Point;
+
+/// The phrases descriptions are built from.
+pub trait Messages {
+    /// Names a cell by its (1-based) row and column.
+    fn location(&self, row: usize, column: usize) -> String;
+    /// Describes a cell holding one of the puzzle's givens.
+    fn given(&self, value: Element) -> String;
+    /// Describes a cell holding one of the player's entries, along with the
+    /// kinds of groups it conflicts with (if any).
+    fn entry(&self, value: Element, conflicts: &[GroupKind]) -> String;
+    /// Describes an empty cell along with its candidates.
+    fn empty(&self, candidates: &[Element]) -> String;
+    /// Joins a cell's location and description.
+    fn cell(&self, location: &str, description: &str) -> String {
+        format!("{}: {}", location, description)
+    }
+    /// Narrates a (1-based) row, given a description of each run of cells
+    /// (see [`run`](#tymethod.run)).
+    fn row(&self, row: usize, runs: &[String]) -> String;
+    /// Describes a run of `count` consecutive cells with the same value (or
+    /// lack thereof).
+    fn run(&self, value: Option<Element>, count: usize) -> String;
+}
+
+/// English phrasing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct English;
+
+impl English {
+    // Joins the items as an English list ("1, 2 and 3").
+    fn list(items: &[String]) -> String {
+        match items.split_last() {
+            None => String::new(),
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+        }
+    }
+}
+
+impl Messages for English {
+    fn location(&self, row: usize, column: usize) -> String {
+        format!("Row {}, column {}", row, column)
+    }
+
+    fn given(&self, value: Element) -> String {
+        format!("given {}", value.0)
+    }
+
+    fn entry(&self, value: Element, conflicts: &[GroupKind]) -> String {
+        if conflicts.is_empty() {
+            return format!("your entry {}", value.0);
+        }
+        let groups = conflicts
+            .iter()
+            .map(|kind| {
+                match kind {
+                    GroupKind::Band => "row",
+                    GroupKind::Stack => "column",
+                    GroupKind::Box => "box",
+                }
+                .to_string()
+            })
+            .collect::<Vec<_>>();
+        format!(
+            "your entry {}, conflicts with {}",
+            value.0,
+            English::list(&groups)
+        )
+    }
+
+    fn empty(&self, candidates: &[Element]) -> String {
+        if candidates.is_empty() {
+            return "empty, no candidates".to_string();
+        }
+        let values = candidates
+            .iter()
+            .map(|e| e.0.to_string())
+            .collect::<Vec<_>>();
+        let noun = if candidates.len() == 1 {
+            "candidate"
+        } else {
+            "candidates"
+        };
+        format!("empty, {} {}", noun, English::list(&values))
+    }
+
+    fn row(&self, row: usize, runs: &[String]) -> String {
+        format!("Row {}: {}.", row, runs.join(", "))
+    }
+
+    fn run(&self, value: Option<Element>, count: usize) -> String {
+        match (value, count) {
+            (Some(value), 1) => value.0.to_string(),
+            (Some(value), count) => format!("{} {}s", count, value.0),
+            (None, 1) => "blank".to_string(),
+            (None, count) => format!("{} blanks", count),
+        }
+    }
+}
+
+impl Game {
+    /// Describes the cell in English (e.g. "Row 3, column 5: empty,
+    /// candidates 2, 5 and 9").
+    pub fn describe_cell(&self, point: Point) -> String {
+        self.describe_cell_with(point, &English)
+    }
+
+    /// Describes the cell using the given phrasing.
+    pub fn describe_cell_with(&self, point: Point, messages: &dyn Messages) -> String {
+        let description = match self.current[point] {
+            Some(value) if !self.is_mutable(point) => messages.given(value),
+            Some(value) => messages.entry(value, &self.conflicts(point)),
+            None => messages.empty(&self.candidates(point)),
+        };
+        let location = messages.location(usize::from(point[1]) + 1, usize::from(point[0]) + 1);
+        messages.cell(&location, &description)
+    }
+
+    /// Narrates the grid in English, one line per row, collapsing runs of
+    /// the same value (e.g. "Row 1: 5, 3 blanks, 9, 3, 1, 6, blank.").
+    pub fn describe_grid(&self) -> String {
+        self.describe_grid_with(&English)
+    }
+
+    /// Narrates the grid using the given phrasing.
+    pub fn describe_grid_with(&self, messages: &dyn Messages) -> String {
+        let axis = self.current.axis();
+        (0..axis)
+            .map(|y| {
+                let mut runs: Vec<(Option<Element>, usize)> = vec![];
+                for x in 0..axis {
+                    let value = self.current[Point([x, y])];
+                    match runs.last_mut() {
+                        Some((last, count)) if *last == value => *count += 1,
+                        _ => runs.push((value, 1)),
+                    }
+                }
+                let runs = runs
+                    .into_iter()
+                    .map(|(value, count)| messages.run(value, count))
+                    .collect::<Vec<_>>();
+                messages.row(usize::from(y) + 1, &runs)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::describe::{English, Messages};
+    use crate::ui::model::Game;
+    use crate::Element;
+    use crate::Point;
+    use crate::Sudoku;
+
+    fn mid_solve() -> Game {
+        let puzzle: Sudoku = include_str!("../../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        Game::from_problem(puzzle)
+    }
+
+    #[test]
+    fn test_describe_cell() {
+        let mut game = mid_solve();
+        let _ = game.insert(
+            Point([1, 0]),
+            game.solution().unwrap()[Point([1, 0])].unwrap(),
+        );
+        let _ = game.insert(Point([2, 0]), Element(5));
+        assert_eq!(
+            game.describe_cell(Point([0, 0])),
+            "Row 1, column 1: given 5"
+        );
+        assert_eq!(
+            game.describe_cell(Point([1, 0])),
+            "Row 1, column 2: your entry 2"
+        );
+        assert_eq!(
+            game.describe_cell(Point([2, 0])),
+            "Row 1, column 3: your entry 5, conflicts with box and row"
+        );
+        assert_eq!(
+            game.describe_cell(Point([3, 0])),
+            "Row 1, column 4: empty, candidates 4 and 7"
+        );
+        assert_eq!(
+            game.describe_cell(Point([8, 0])),
+            "Row 1, column 9: empty, candidates 4 and 7"
+        );
+        assert_eq!(English.empty(&[Element(7)]), "empty, candidate 7");
+        assert_eq!(English.empty(&[]), "empty, no candidates");
+    }
+
+    #[test]
+    fn test_describe_grid() {
+        let game = mid_solve();
+        let description = game.describe_grid();
+        assert_eq!(description.lines().count(), 9);
+        assert_eq!(
+            description.lines().next(),
+            Some("Row 1: 5, 3 blanks, 9, 3, 1, 6, blank.")
+        );
+        let game = Game::placeholder(4);
+        // Even a full order-4 grid takes no more than about five characters
+        // per cell.
+        assert!(game.describe_grid().len() < 16 * 16 * 5);
+    }
+}
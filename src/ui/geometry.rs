@@ -0,0 +1,405 @@
+//! Layout math for drawing a two-dimensional grid, shared by frontends.
+//!
+//! Everything here is in canvas units (e.g. CSS pixels), with the origin in
+//! the top-left corner of the canvas.
+
+use crate::dimensions::validate_order;
+use crate::Point;
+use crate::Sudoku;
+
+/// The fraction of the canvas's smaller side taken up by the grid.
+const GRID_FILL: f64 = 0.9;
+
+/// The fraction of a cell's size taken up by a single glyph's font size.
+const SINGLE_GLYPH_FONT_FRACTION: f64 = 0.6;
+
+/// An axis-aligned rectangle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    /// The x-coordinate of the left edge.
+    pub x: f64,
+    /// The y-coordinate of the top edge.
+    pub y: f64,
+    /// The width.
+    pub width: f64,
+    /// The height.
+    pub height: f64,
+}
+
+impl Rect {
+    /// Whether the rectangles share any area (touching edges don't count).
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+
+    /// Whether the point lies within the rectangle (including its top and
+    /// left edges, but not its bottom and right ones).
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// Encodes errors encountered while laying out a grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GeometryError {
+    /// The canvas's width or height isn't a positive, finite number.
+    NonPositiveSize,
+    /// The margin is negative, not finite, or leaves no room for the grid.
+    InvalidMargin,
+    /// The order isn't supported (see
+    /// [`Sudoku::try_new`](../../struct.Sudoku.html#method.try_new)).
+    InvalidOrder,
+}
+
+/// The layout of a grid centered on a canvas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridGeometry {
+    /// The order of the grid.
+    pub order: u8,
+    /// The shape of the grid's boxes, as `(rows, columns)` (see
+    /// [`Sudoku::box_shape`](../../struct.Sudoku.html#method.box_shape)).
+    pub boxes: (u8, u8),
+    /// The width of the canvas.
+    pub width: f64,
+    /// The height of the canvas.
+    pub height: f64,
+    /// The area covered by the grid.
+    pub grid: Rect,
+}
+
+impl GridGeometry {
+    /// Lays out a grid of the given order, centered on a canvas of the given
+    /// size and filling 90% of its smaller side.
+    ///
+    /// # Panics
+    /// Panics if the order is unsupported (see
+    /// [`Sudoku::try_new`](../../struct.Sudoku.html#method.try_new)).
+    pub fn new(order: u8, width: f64, height: f64) -> Self {
+        validate_order(order).unwrap_or_else(|error| panic!("{}", error));
+        Self::centered(order, (order, order), width, height)
+    }
+
+    /// Lays out the given puzzle's grid (honoring
+    /// [rectangular boxes](../../struct.Sudoku.html#method.new_rect)),
+    /// centered on a canvas of the given size and filling 90% of its smaller
+    /// side.
+    pub fn for_puzzle(puzzle: &Sudoku, width: f64, height: f64) -> Self {
+        Self::centered(puzzle.order, puzzle.box_shape(), width, height)
+    }
+
+    fn centered(order: u8, boxes: (u8, u8), width: f64, height: f64) -> Self {
+        let length = GRID_FILL * width.min(height);
+        Self {
+            order,
+            boxes,
+            width,
+            height,
+            grid: Rect {
+                x: (width - length) / 2.0,
+                y: (height - length) / 2.0,
+                width: length,
+                height: length,
+            },
+        }
+    }
+
+    /// Lays out a grid of the given order, centered on a canvas of the given
+    /// size and filling its smaller side apart from a margin of the given
+    /// width on each edge.
+    ///
+    /// Unlike [`new`](#method.new), this checks that the sizes make sense,
+    /// since they often come from outside (e.g. a request for a preview
+    /// image).
+    pub fn with_margin(
+        order: u8,
+        width: f64,
+        height: f64,
+        margin: f64,
+    ) -> Result<Self, GeometryError> {
+        validate_order(order).map_err(|_| GeometryError::InvalidOrder)?;
+        if !(width.is_finite() && height.is_finite() && width > 0.0 && height > 0.0) {
+            return Err(GeometryError::NonPositiveSize);
+        }
+        let length = width.min(height) - 2.0 * margin;
+        if !(margin.is_finite() && margin >= 0.0 && length > 0.0) {
+            return Err(GeometryError::InvalidMargin);
+        }
+        Ok(Self {
+            order,
+            boxes: (order, order),
+            width,
+            height,
+            grid: Rect {
+                x: (width - length) / 2.0,
+                y: (height - length) / 2.0,
+                width: length,
+                height: length,
+            },
+        })
+    }
+
+    /// The number of cells along each side of the grid.
+    pub fn axis(&self) -> u8 {
+        self.boxes.0 * self.boxes.1
+    }
+
+    /// The length of each side of a cell.
+    pub fn cell_size(&self) -> f64 {
+        self.grid.width / f64::from(self.axis())
+    }
+
+    /// The area covered by the given cell.
+    pub fn cell_rect(&self, cell: Point) -> Rect {
+        let size = self.cell_size();
+        Rect {
+            x: self.grid.x + f64::from(cell[0]) * size,
+            y: self.grid.y + f64::from(cell[1]) * size,
+            width: size,
+            height: size,
+        }
+    }
+
+    /// The cell under the given point on the canvas, if any.
+    pub fn cell_at(&self, x: f64, y: f64) -> Option<Point> {
+        if !self.grid.contains(x, y) {
+            return None;
+        }
+        let size = self.cell_size();
+        let column = ((x - self.grid.x) / size) as u8;
+        let row = ((y - self.grid.y) / size) as u8;
+        let last = self.axis() - 1;
+        let mut point = Point::with_x(column.min(last));
+        point[1] = row.min(last);
+        Some(point)
+    }
+
+    /// The center of the given cell.
+    pub fn cell_center(&self, cell: Point) -> (f64, f64) {
+        let rect = self.cell_rect(cell);
+        (rect.x + rect.width / 2.0, rect.y + rect.height / 2.0)
+    }
+
+    /// The center of the spot for a pencil mark of the given value within the
+    /// given cell.
+    ///
+    /// A cell is divided into a mini-grid shaped like a box, filled with
+    /// values in reading order (so for order 3, 1 is top-left and 9
+    /// bottom-right).
+    pub fn note_position(&self, cell: Point, value: u8) -> (f64, f64) {
+        let rect = self.cell_rect(cell);
+        let (rows, columns) = (u32::from(self.boxes.0), u32::from(self.boxes.1));
+        let index = u32::from(value.max(1) - 1);
+        (
+            rect.x + (f64::from(index % columns) + 0.5) * rect.width / f64::from(columns),
+            rect.y + (f64::from(index / columns) + 0.5) * rect.height / f64::from(rows),
+        )
+    }
+
+    /// The recommended font size, as a fraction of the cell size, for drawing
+    /// the values of a puzzle of the given order.
+    ///
+    /// This shrinks as the largest value needs more glyphs (e.g. 0.6 for
+    /// order 3, whose values are single digits, but 0.4 for order 4, whose
+    /// values run up to 16).
+    pub fn recommended_font_fraction(&self, order: u8) -> f64 {
+        let glyphs = (usize::from(order).pow(2)).to_string().len();
+        SINGLE_GLYPH_FONT_FRACTION * 2.0 / (glyphs as f64 + 1.0)
+    }
+
+    /// Lays out a tray of the values of a puzzle of the given order (e.g. a
+    /// legend or a palette to pick from), in the margin beside the grid.
+    ///
+    /// The tray runs along the wider of the bottom and right margins, with
+    /// one square slot per value, and never overlaps the grid. If the margin
+    /// is narrower than a cell, the slots shrink to fit it.
+    pub fn tray_layout(&self, order: u8) -> Vec<Rect> {
+        let count = usize::from(order).pow(2);
+        if count == 0 {
+            return vec![];
+        }
+        let bottom = self.height - (self.grid.y + self.grid.height);
+        let right = self.width - (self.grid.x + self.grid.width);
+        let (margin, along) = if bottom >= right {
+            (bottom, self.grid.width)
+        } else {
+            (right, self.grid.height)
+        };
+        let size = (along / count as f64).min(margin * 0.8).max(0.0);
+        let offset = (margin - size) / 2.0;
+        let start = (along - size * count as f64) / 2.0;
+        (0..count)
+            .map(|i| {
+                let step = start + size * i as f64;
+                if bottom >= right {
+                    Rect {
+                        x: self.grid.x + step,
+                        y: self.grid.y + self.grid.height + offset,
+                        width: size,
+                        height: size,
+                    }
+                } else {
+                    Rect {
+                        x: self.grid.x + self.grid.width + offset,
+                        y: self.grid.y + step,
+                        width: size,
+                        height: size,
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::geometry::{GeometryError, GridGeometry, Rect};
+    use crate::Point;
+    #[cfg(feature = "2D")]
+    use crate::Sudoku;
+
+    #[test]
+    fn test_grid_centered() {
+        let geometry = GridGeometry::new(3, 1000.0, 500.0);
+        assert_eq!(
+            geometry.grid,
+            Rect {
+                x: 275.0,
+                y: 25.0,
+                width: 450.0,
+                height: 450.0
+            }
+        );
+        assert_eq!(geometry.cell_size(), 50.0);
+    }
+
+    #[test]
+    fn test_with_margin() {
+        let geometry = GridGeometry::with_margin(3, 1000.0, 500.0, 25.0).unwrap();
+        assert_eq!(geometry, GridGeometry::new(3, 1000.0, 500.0));
+        let geometry = GridGeometry::with_margin(3, 800.0, 800.0, 0.0).unwrap();
+        assert_eq!(geometry.cell_size(), 800.0 / 9.0);
+        for &(width, height) in &[(0.0, 800.0), (800.0, -1.0), (f64::NAN, 800.0)] {
+            assert_eq!(
+                GridGeometry::with_margin(3, width, height, 10.0),
+                Err(GeometryError::NonPositiveSize)
+            );
+        }
+        for &margin in &[-1.0, 400.0, f64::INFINITY] {
+            assert_eq!(
+                GridGeometry::with_margin(3, 800.0, 800.0, margin),
+                Err(GeometryError::InvalidMargin)
+            );
+        }
+        for &order in &[0, 9, 16] {
+            assert_eq!(
+                GridGeometry::with_margin(order, 800.0, 800.0, 10.0),
+                Err(GeometryError::InvalidOrder)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_invalid_order() {
+        let _ = GridGeometry::new(0, 800.0, 800.0);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_rect_boxes() {
+        let geometry = GridGeometry::for_puzzle(&Sudoku::new_rect(2, 3), 600.0, 600.0);
+        assert_eq!((geometry.axis(), geometry.boxes), (6, (2, 3)));
+        assert_eq!(geometry.cell_size(), 90.0);
+        assert_eq!(geometry.cell_at(599.0, 599.0), None);
+        assert_eq!(geometry.cell_at(569.0, 569.0), Some(Point([5, 5])));
+        // Notes fill a 2×3 mini-grid, like the boxes.
+        assert_eq!(geometry.note_position(Point([0, 0]), 1), (45.0, 52.5));
+        assert_eq!(geometry.note_position(Point([0, 0]), 6), (105.0, 97.5));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_cell_at() {
+        let geometry = GridGeometry::new(3, 1000.0, 500.0);
+        assert_eq!(geometry.cell_at(275.0, 25.0), Some(Point([0, 0])));
+        assert_eq!(geometry.cell_at(374.0, 76.0), Some(Point([1, 1])));
+        assert_eq!(geometry.cell_at(724.9, 474.9), Some(Point([8, 8])));
+        assert_eq!(geometry.cell_at(274.0, 30.0), None);
+        assert_eq!(geometry.cell_at(300.0, 475.0), None);
+        for point in &[Point([0, 0]), Point([4, 7]), Point([8, 8])] {
+            let (x, y) = geometry.cell_center(*point);
+            assert_eq!(geometry.cell_at(x, y), Some(*point));
+        }
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_note_positions_tile_cell() {
+        for order in 2..=4 {
+            let geometry = GridGeometry::new(order, 900.0, 900.0);
+            let cell = Point([1, 2]);
+            let rect = geometry.cell_rect(cell);
+            let size = rect.width / f64::from(order);
+            let spots = (1..=order.pow(2))
+                .map(|value| {
+                    let (x, y) = geometry.note_position(cell, value);
+                    Rect {
+                        x: x - size / 2.0,
+                        y: y - size / 2.0,
+                        width: size,
+                        height: size,
+                    }
+                })
+                .collect::<Vec<_>>();
+            for (i, spot) in spots.iter().enumerate() {
+                // Each spot lies within the cell...
+                assert!(spot.x >= rect.x - 1e-9 && spot.y >= rect.y - 1e-9);
+                assert!(spot.x + spot.width <= rect.x + rect.width + 1e-9);
+                assert!(spot.y + spot.height <= rect.y + rect.height + 1e-9);
+                // ...without overlapping any other.
+                for other in &spots[i + 1..] {
+                    assert!(!spot.overlaps(other), "{:?} overlaps {:?}", spot, other);
+                }
+            }
+            // Together, they cover the whole cell.
+            let area = spots.iter().map(|s| s.width * s.height).sum::<f64>();
+            assert!((area - rect.width * rect.height).abs() < 1e-6);
+        }
+        let geometry = GridGeometry::new(3, 900.0, 900.0);
+        let (x, y) = geometry.note_position(Point([0, 0]), 1);
+        assert_eq!((x, y), (60.0, 60.0));
+        let (x, y) = geometry.note_position(Point([0, 0]), 9);
+        assert_eq!((x, y), (120.0, 120.0));
+    }
+
+    #[test]
+    fn test_recommended_font_fraction() {
+        let geometry = GridGeometry::new(3, 900.0, 900.0);
+        assert_eq!(geometry.recommended_font_fraction(1), 0.6);
+        assert_eq!(geometry.recommended_font_fraction(3), 0.6);
+        assert!((geometry.recommended_font_fraction(4) - 0.4).abs() < 1e-12);
+        assert!((geometry.recommended_font_fraction(10) - 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_tray_layout() {
+        for &(width, height) in &[(800.0, 1000.0), (1000.0, 800.0), (900.0, 900.0)] {
+            for order in 2..=4 {
+                let geometry = GridGeometry::new(order, width, height);
+                let tray = geometry.tray_layout(order);
+                assert_eq!(tray.len(), usize::from(order).pow(2));
+                for (i, slot) in tray.iter().enumerate() {
+                    assert!(!slot.overlaps(&geometry.grid));
+                    assert!(slot.x >= 0.0 && slot.x + slot.width <= width);
+                    assert!(slot.y >= 0.0 && slot.y + slot.height <= height);
+                    for other in &tray[i + 1..] {
+                        assert!(!slot.overlaps(other));
+                    }
+                }
+            }
+        }
+    }
+}
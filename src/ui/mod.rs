@@ -1,5 +1,11 @@
 //! Provides tools to implement a UI presenting a puzzle for the user to solve,
 //! along with some other "nice" features.
 
+#[cfg(feature = "2D")]
+pub mod book;
 pub mod color;
+#[cfg(feature = "2D")]
+pub mod describe;
+pub mod geometry;
 pub mod model;
+pub mod stats;
@@ -1,68 +1,861 @@
 //! Constructs relevant to implementating game logic.
 
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    time::Duration,
+};
+
+use self::config::Behavior;
+use crate::sol::{solve_and_score_bounded, Bounds};
+pub use crate::sudoku::GroupKind;
+use crate::sudoku::{fold_coordinates, Shape};
 use crate::Difficulty;
 use crate::Element;
-use crate::Generate;
+use crate::FillMethod;
+use crate::GenerateError;
 use crate::Grid;
+use crate::Group;
+use crate::LockedSudoku;
+use crate::Placement;
 use crate::Point;
-use crate::Solve;
+use crate::Score;
 use crate::Sudoku;
+use crate::Technique;
+use crate::DIMENSIONS;
+
+/// The most search nodes a game will visit looking for a solution it wasn't
+/// given (see [`Game::find_solution`](struct.Game.html#method.find_solution)).
+pub const MAX_SOLVE_NODES: usize = 100_000;
 
 /// Represents an in-progress game.
 #[derive(Debug)]
 pub struct Game {
-    problem: Sudoku,
+    // The original puzzle, with its givens frozen.
+    problem: LockedSudoku,
     /// The current state of the game.
     pub current: Sudoku,
-    /// The solution for this game.
-    pub solution: Sudoku,
+    // The puzzle's unique solution, if known (see `has_solution`).
+    solution: Option<Sudoku>,
+    // Whether a budgeted search for the solution has already failed.
+    solve_failed: bool,
     /// The number of moves performed so far.
     pub moves: usize,
+    /// Whether [`move_focus`](#method.move_focus) wraps around to the
+    /// opposite edge of the grid (rather than stopping at the edge).
+    pub wrap_focus: bool,
+    /// How [`handle`](#method.handle) treats the player's input (by default,
+    /// the [default](config/struct.Behavior.html#method.for_mode) for the
+    /// game's mode).
+    pub behavior: Behavior,
+    focused: Option<Point>,
+    // The first digit of a multi-digit value being entered at the focus.
+    pending_digit: Option<u8>,
+    // The score of the original problem, computed once the solution is known.
+    original_score: Option<usize>,
+    // The score of the current state, along with the move count at which it
+    // was computed (`None` if the state couldn't be scored).
+    remaining_score: Cell<Option<(usize, Option<usize>)>>,
+    // The player's cell colors.
+    colors: BTreeMap<Point, AnnotationColor>,
+    // Each color change, as the previous color of every cell it touched.
+    color_history: Vec<Vec<(Point, Option<AnnotationColor>)>>,
+    // The number of correctly filled cells in each group, kept up to date by
+    // `set_value`.
+    group_progress: GroupProgress,
+    // The rules the game is played under.
+    mode: Mode,
+    // The time allowed to solve the puzzle, if limited.
+    time_budget: Option<Duration>,
+    // The time played so far, as of the last tick or pause.
+    elapsed: Duration,
+    // The clock reading when the timer was last started, if it's running.
+    running_since: Option<Duration>,
+    // Whether the timer has ever been started.
+    timed: bool,
+    // Whether the time budget ran out before the puzzle was solved.
+    expired: bool,
+    // The number of incorrect values entered.
+    mistakes: usize,
+    // The number of times the game filled cells for the player.
+    assists: usize,
+    // The seed the puzzle was generated from, if it was generated.
+    seed: Option<u64>,
+    // The cells whose rendering may have changed since the last
+    // `take_dirty`.
+    dirty: BTreeSet<Point>,
+}
+
+/// The rules a game is played under.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Mode {
+    /// The standard rules: no time limit, with mistakes counted.
+    Classic,
+    /// The puzzle must be solved within a time budget that depends on its
+    /// size and difficulty (see [`time_budget`](#method.time_budget)); the
+    /// game fails when time runs out.
+    TimeAttack,
+    /// A relaxed game: no timer, and mistakes aren't counted.
+    Zen,
+}
+
+impl Mode {
+    /// The time allowed to solve a puzzle with the given number of cells and
+    /// difficulty in this mode, if limited.
+    ///
+    /// Order-3 time attacks allow 10 minutes for beginner puzzles, 15 for
+    /// easy, 20 for intermediate, 30 for difficult, and 45 for advanced;
+    /// other grids (including ones with rectangular boxes) are scaled by
+    /// their number of cells.
+    pub fn time_budget(self, cells: usize, difficulty: Difficulty) -> Option<Duration> {
+        if self != Mode::TimeAttack {
+            return None;
+        }
+        let minutes = match difficulty {
+            Difficulty::Unplayable | Difficulty::Beginner => 10,
+            Difficulty::Easy => 15,
+            Difficulty::Intermediate => 20,
+            Difficulty::Difficult => 30,
+            _ => 45,
+        };
+        let cells = cells as u64;
+        let standard = 9u64.pow(DIMENSIONS as u32);
+        Some(Duration::from_secs(minutes * 60 * cells / standard))
+    }
+}
+
+/// A source of the current time, supplied by the frontend.
+///
+/// Only differences between readings matter, so the readings may be
+/// measured from any fixed starting point (e.g. page load).
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> Duration;
+}
+
+/// Where a game stands.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Status {
+    /// The puzzle hasn't been solved yet, and may still be.
+    Playing,
+    /// The puzzle has been solved.
+    Solved,
+    /// Time ran out before the puzzle was solved; no more moves are accepted.
+    Failed,
+}
+
+/// A color the player can apply to a cell (e.g. to mark a chain).
+///
+/// How each color is drawn is up to the frontend.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum AnnotationColor {
+    /// Red.
+    Red,
+    /// Orange.
+    Orange,
+    /// Yellow.
+    Yellow,
+    /// Green.
+    Green,
+    /// Blue.
+    Blue,
+    /// Purple.
+    Purple,
+}
+
+impl AnnotationColor {
+    /// Every annotation color, in order.
+    pub const ALL: [AnnotationColor; 6] = [
+        AnnotationColor::Red,
+        AnnotationColor::Orange,
+        AnnotationColor::Yellow,
+        AnnotationColor::Green,
+        AnnotationColor::Blue,
+        AnnotationColor::Purple,
+    ];
+}
+
+/// An estimate of how far through the puzzle the player is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    /// The number of cells that currently hold a value (including the
+    /// puzzle's givens).
+    pub cells_filled: usize,
+    /// The total number of cells in the puzzle.
+    pub cells_total: usize,
+    /// The fraction of the puzzle's difficulty that has been overcome, in
+    /// `0.0..=1.0`.
+    ///
+    /// This is `None` when the estimate isn't available (e.g. when the grid
+    /// contains an incorrect entry, or the solution isn't known), in which
+    /// case the plain
+    /// [`fraction`](#method.fraction) should be used instead.
+    pub weighted: Option<f64>,
+}
+
+/// How many cells of each group are correctly filled (i.e. hold the value
+/// in the solution).
+///
+/// Givens count as correctly filled; incorrect entries don't.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupProgress {
+    /// The count for each box, in the same order as
+    /// [`Sudoku::clues_per_box`](../../struct.Sudoku.html#method.clues_per_box).
+    pub boxes: Vec<usize>,
+    /// The count for each line, by the axis it runs along.
+    ///
+    /// Within an axis, lines are listed in reading order of their remaining
+    /// coordinates, so in two dimensions `lines[0][y]` is row `y` and
+    /// `lines[1][x]` is column `x`.
+    pub lines: Vec<Vec<usize>>,
+    /// The number of cells in each group.
+    pub group_size: usize,
+}
+
+impl GroupProgress {
+    /// Counts the correctly filled cells of each group from scratch.
+    pub fn of(current: &Sudoku, solution: &Sudoku) -> Self {
+        let shape = current.shape();
+        let axis = usize::from(shape.axis());
+        let mut progress = Self {
+            boxes: vec![0; shape.len() / axis],
+            lines: vec![vec![0; axis.pow(DIMENSIONS as u32 - 1)]; DIMENSIONS],
+            group_size: axis,
+        };
+        for point in current.points() {
+            if current[point].is_some() && current[point] == solution[point] {
+                progress.adjust(point, shape, true);
+            }
+        }
+        progress
+    }
+
+    /// Returns the smallest count of any group.
+    pub fn min(&self) -> usize {
+        self.counts().min().unwrap_or(0)
+    }
+
+    /// Returns the median count over all groups (the lower of the middle two
+    /// if there's an even number of groups).
+    pub fn median(&self) -> usize {
+        let mut counts = self.counts().collect::<Vec<_>>();
+        counts.sort_unstable();
+        counts
+            .get(counts.len().saturating_sub(1) / 2)
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    fn counts(&self) -> impl Iterator<Item = usize> + '_ {
+        self.boxes
+            .iter()
+            .chain(self.lines.iter().flatten())
+            .cloned()
+    }
+
+    // Counts the point as newly correct (or no longer correct) in each of its
+    // groups.
+    fn adjust(&mut self, point: Point, shape: Shape, correct: bool) {
+        let axis = usize::from(shape.axis());
+        let mut indices = vec![shape.box_index(point)];
+        for along in 0..DIMENSIONS {
+            let rest = (0..DIMENSIONS)
+                .filter(|i| *i != along)
+                .map(|i| point[i])
+                .collect::<Vec<_>>();
+            indices.push(fold_coordinates(&rest, axis).expect("line index overflowed"));
+        }
+        let counts = std::iter::once(&mut self.boxes).chain(self.lines.iter_mut());
+        for (counts, index) in counts.zip(indices) {
+            if correct {
+                counts[index] += 1;
+            } else {
+                counts[index] -= 1;
+            }
+        }
+    }
+}
+
+/// Why the game couldn't help with a cell.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HintError {
+    /// The puzzle's solution wasn't given, and it either couldn't be found
+    /// within [`MAX_SOLVE_NODES`](constant.MAX_SOLVE_NODES.html) or isn't
+    /// unique.
+    SolveFailed,
+    /// The game's [policy](config/struct.TechniquePolicy.html) forbids the
+    /// help asked for.
+    PolicyDenied,
+}
+
+/// The help a game currently offers (see
+/// [`Game::assistance`](struct.Game.html#method.assistance)), for enabling
+/// only the controls that would work.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Assistance {
+    /// The techniques hints may use (none while the game isn't being
+    /// played).
+    pub hint_techniques: Vec<Technique>,
+    /// Whether cells can be revealed from the solution.
+    pub reveal: bool,
+    /// Whether singles can be filled in.
+    pub fill_singles: bool,
+}
+
+/// Writes one `key: value` line per kind of help, with techniques
+/// comma-separated.
+impl fmt::Display for Assistance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let techniques = self
+            .hint_techniques
+            .iter()
+            .map(|technique| technique.name())
+            .collect::<Vec<_>>();
+        writeln!(f, "hint_techniques: {}", techniques.join(", "))?;
+        writeln!(f, "reveal: {}", self.reveal)?;
+        writeln!(f, "fill_singles: {}", self.fill_singles)
+    }
+}
+
+/// A direction in which to move the focus.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    /// Toward the top of the grid (decreasing y).
+    Up,
+    /// Toward the bottom of the grid (increasing y).
+    Down,
+    /// Toward the left of the grid (decreasing x).
+    Left,
+    /// Toward the right of the grid (increasing x).
+    Right,
+}
+
+/// Something noteworthy that happened as a result of a move, for the UI to
+/// react to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Event {
+    /// A group was completed without breaking any rules.
+    ///
+    /// The anchor is the group's first point in reading order (for a box,
+    /// its upper-left corner).
+    GroupCompleted {
+        /// The kind of group completed.
+        kind: GroupKind,
+        /// The group's first point.
+        anchor: Point,
+    },
+    /// A time attack's budget ran out before the puzzle was solved, failing
+    /// the game.
+    TimeExpired,
+}
+
+/// Something the player asked for, for frontends to pass to
+/// [`Game::handle`](struct.Game.html#method.handle) instead of changing the
+/// game themselves.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum GameCommand {
+    /// Focus the given point, or remove the focus.
+    Focus(Option<Point>),
+    /// Move the focus one cell in the given direction.
+    MoveFocus(Direction),
+    /// A key was pressed, named as by the DOM's `KeyboardEvent.key` (see
+    /// [`from_key`](#method.from_key) for the keys understood).
+    InputKey(String),
+    /// A digit was typed while a cell is focused.
+    ///
+    /// Values above 9 (for orders above 3) are typed as two digits: a digit
+    /// that could start a valid two-digit value is held until the next
+    /// digit, or until [`Commit`](#variant.Commit), and anything else is
+    /// entered right away.
+    InputDigit(u8),
+    /// Enter a held digit on its own.
+    Commit,
+    /// Clear the focused cell (or, if a digit is held, drop that digit).
+    Delete,
+    /// Revert the most recent color change.
+    Undo,
+    /// Fill the focused cell with its value in the solution.
+    Hint,
+    /// Color the focused cell with the next annotation color (removing the
+    /// color after the last one).
+    CycleColor,
+    /// Remove every cell color.
+    ClearColors,
+    /// Start over with a new puzzle.
+    NewGame {
+        /// The new puzzle's order.
+        order: u8,
+        /// The new puzzle's difficulty.
+        difficulty: Difficulty,
+    },
+}
+
+impl GameCommand {
+    /// Returns the command a key press stands for, if any.
+    ///
+    /// Digits are typed with `0` through `9`, and `Enter` enters a held
+    /// digit. `Backspace` and `Delete` clear the focused cell, and `Escape`
+    /// removes the focus. `c` cycles the focused cell's color, `C` removes
+    /// every color, and `u` reverts the last color change. Movement keys are
+    /// left to the frontend (they're a matter of taste; see
+    /// [`MoveFocus`](#variant.MoveFocus)).
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "Backspace" | "Delete" => Some(GameCommand::Delete),
+            "Escape" => Some(GameCommand::Focus(None)),
+            "Enter" => Some(GameCommand::Commit),
+            "c" => Some(GameCommand::CycleColor),
+            "C" => Some(GameCommand::ClearColors),
+            "u" => Some(GameCommand::Undo),
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next().and_then(|c| c.to_digit(10)), chars.next()) {
+                    (Some(digit), None) => Some(GameCommand::InputDigit(digit as u8)),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// What came of a [`GameCommand`](enum.GameCommand.html), for the frontend
+/// to react to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum GameEvent {
+    /// The game changed, so it should be rendered again.
+    Changed,
+    /// The input was refused: the value was incorrect (and incorrect answers
+    /// aren't [allowed](config/struct.Behavior.html#structfield.allow_incorrect_answers)),
+    /// out of range, or meant for a given.
+    Rejected,
+    /// A move caused the given event.
+    Move(Event),
+    /// The puzzle was solved in the given number of moves.
+    Solved {
+        /// The number of moves made.
+        moves: usize,
+    },
+    /// No hint could be given.
+    HintFailed(HintError),
+    /// No playable puzzle could be generated, so the game was replaced with a
+    /// [placeholder](struct.Game.html#method.placeholder).
+    GenerationFailed(GenerateError),
+    /// A new puzzle was generated and the game started over.
+    NewGame {
+        /// The seed the puzzle was generated from (see
+        /// [`Game::seed`](struct.Game.html#method.seed)).
+        seed: Option<u64>,
+    },
+}
+
+impl Progress {
+    /// Returns the fraction of cells that are filled.
+    pub fn fraction(&self) -> f64 {
+        if self.cells_total == 0 {
+            return 1.0;
+        }
+        self.cells_filled as f64 / self.cells_total as f64
+    }
+    /// Returns the weighted estimate if it's available, or the plain fraction
+    /// otherwise.
+    pub fn estimate(&self) -> f64 {
+        self.weighted.unwrap_or_else(|| self.fraction())
+    }
 }
 
 impl Game {
     /// Creates a new game with a sudoku of the specified order and difficulty.
+    ///
+    /// # Panics
+    /// Panics if no playable puzzle could be generated; use
+    /// [`try_new`](#method.try_new) to handle that case.
     pub fn new(order: u8, difficulty: Difficulty) -> Self {
-        let problem = Sudoku::generate(order, difficulty);
+        Self::try_new(order, difficulty).expect("couldn't generate a playable puzzle")
+    }
+    /// Creates a new game with a sudoku of the specified order and difficulty,
+    /// or returns an error if no playable puzzle could be generated.
+    pub fn try_new(order: u8, difficulty: Difficulty) -> Result<Self, GenerateError> {
+        Self::try_new_with_mode(order, difficulty, Mode::Classic)
+    }
+    /// Creates a new game with a sudoku of the specified order and
+    /// difficulty, played under the given mode, or returns an error if no
+    /// playable puzzle could be generated.
+    ///
+    /// A time attack's budget is based on the difficulty actually attained.
+    pub fn try_new_with_mode(
+        order: u8,
+        difficulty: Difficulty,
+        mode: Mode,
+    ) -> Result<Self, GenerateError> {
+        let generated = Sudoku::generate_with(order, difficulty, FillMethod::Random)?;
+        let mut game = Self::from_parts(generated.puzzle, Some(generated.solution));
+        game.set_mode(mode, generated.difficulty);
+        game.seed = generated.seed;
+        Ok(game)
+    }
+    /// Replaces the puzzle with a new one of the specified order and
+    /// difficulty, starting the game over in place (e.g. when the player
+    /// picks a different difficulty).
+    ///
+    /// Moves, mistakes, colors, the focus and the timer are all reset, while
+    /// the mode, [`wrap_focus`](#structfield.wrap_focus) and
+    /// [`behavior`](#structfield.behavior) carry over. If no playable puzzle
+    /// could be generated, the game is left untouched.
+    pub fn regenerate(&mut self, order: u8, difficulty: Difficulty) -> Result<(), GenerateError> {
+        let generated = Sudoku::generate_with(order, difficulty, FillMethod::Random)?;
+        let mut game = Self::from_parts(generated.puzzle, Some(generated.solution));
+        game.set_mode(self.mode, generated.difficulty);
+        game.seed = generated.seed;
+        game.wrap_focus = self.wrap_focus;
+        game.behavior = self.behavior;
+        *self = game;
+        Ok(())
+    }
+    /// Creates a trivial game of the specified order, for frontends to show
+    /// when [`try_new`](#method.try_new) fails.
+    ///
+    /// The puzzle is a fixed grid with the cells along its main diagonal
+    /// removed; since each row is missing a single value, it always has a
+    /// unique solution. That grid is built by
+    /// [shifting rows](../../enum.FillMethod.html#variant.Shift), which only
+    /// works in two dimensions; elsewhere, the placeholder is an empty grid
+    /// with no known solution.
+    ///
+    /// # Panics
+    /// Panics if the order is unsupported (see
+    /// [`Sudoku::try_new`](../../struct.Sudoku.html#method.try_new)).
+    pub fn placeholder(order: u8) -> Self {
+        let solution = match Sudoku::seed_full_grid(order, FillMethod::Shift) {
+            Some(solution) => solution,
+            None => return Self::from_parts(Sudoku::new(order), None),
+        };
+        let mut problem = solution.clone();
+        for i in 0..problem.axis() {
+            problem.substitute(Point([i; DIMENSIONS]), None);
+        }
+        Self::from_parts(problem, Some(solution))
+    }
+    /// Creates a game from an imported puzzle, without solving it up front.
+    ///
+    /// Until the solution is needed (and found; see
+    /// [`find_solution`](#method.find_solution)), the game is played by the
+    /// rules alone: insertions are judged by whether they conflict with the
+    /// grid, and the puzzle is solved once the grid is complete and valid.
+    /// This makes puzzles with several solutions playable, and spares hard
+    /// imports an expensive solve that might never be needed.
+    pub fn from_puzzle(problem: Sudoku) -> Self {
+        Self::from_parts(problem, None)
+    }
+    fn from_parts(problem: Sudoku, solution: Option<Sudoku>) -> Self {
         let current = problem.clone();
-        let solution = problem.solution().unwrap();
+        // Scoring means solving, so it waits for the solution too.
+        let original_score = solution.as_ref().and_then(|_| problem.score());
+        let group_progress = GroupProgress::of(&current, solution.as_ref().unwrap_or(&current));
         Self {
-            problem,
+            problem: LockedSudoku::new(problem),
             current,
             solution,
+            solve_failed: false,
             moves: 0,
+            wrap_focus: false,
+            behavior: Behavior::for_mode(Mode::Classic),
+            focused: None,
+            pending_digit: None,
+            original_score,
+            remaining_score: Cell::new(None),
+            colors: BTreeMap::new(),
+            color_history: vec![],
+            group_progress,
+            mode: Mode::Classic,
+            time_budget: None,
+            elapsed: Duration::from_secs(0),
+            running_since: None,
+            timed: false,
+            expired: false,
+            mistakes: 0,
+            assists: 0,
+            seed: None,
+            dirty: BTreeSet::new(),
         }
     }
+    pub(crate) fn set_mode(&mut self, mode: Mode, difficulty: Difficulty) {
+        self.mode = mode;
+        self.behavior = Behavior::for_mode(mode);
+        self.time_budget = mode.time_budget(self.current.elements.len(), difficulty);
+    }
+    /// Returns the rules the game is played under.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+    /// Returns the seed the puzzle was generated from, or `None` if the game
+    /// was given its puzzle.
+    ///
+    /// [`Sudoku::generate_seeded`](../../struct.Sudoku.html#method.generate_seeded)
+    /// regenerates the puzzle from the seed, so it's worth including in bug
+    /// reports.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+    /// Returns the difficulty the original puzzle is graded, once its
+    /// solution is known.
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        self.original_score
+            .map(|score| Difficulty::from_score_with_boxes(score, self.current.box_shape()))
+    }
     /// Returns the points relevant to the selection (for e.g. highlighting).
     ///
     /// The order of these points is intentionally left unspecified.
     pub fn relevant_points(&self, point: Point) -> Vec<Point> {
         self.problem.group_indices(point)
     }
-    /// Whether the proposed change is correct (according to the stored
-    /// solution).
+    /// Whether the puzzle's solution is known (it was given, or has since
+    /// been [found](#method.find_solution)).
+    pub fn has_solution(&self) -> bool {
+        self.solution.is_some()
+    }
+    /// Returns the puzzle's solution, if known.
+    pub fn solution(&self) -> Option<&Sudoku> {
+        self.solution.as_ref()
+    }
+    /// Returns the puzzle's solution, searching for it first if it isn't yet
+    /// known.
+    ///
+    /// The search, which also scores the puzzle, visits at most
+    /// [`MAX_SOLVE_NODES`](constant.MAX_SOLVE_NODES.html) nodes. If it fails (or the puzzle has several solutions), the failure
+    /// is remembered, and the game carries on by the rules alone. Once
+    /// found, the solution is kept, and the game switches to judging
+    /// insertions against it.
+    pub fn find_solution(&mut self) -> Result<&Sudoku, HintError> {
+        if self.solution.is_none() && !self.solve_failed {
+            let bounds = Bounds {
+                max_nodes: Some(MAX_SOLVE_NODES),
+                ..Bounds::default()
+            };
+            match solve_and_score_bounded(&self.problem, bounds) {
+                Ok((solution, score)) => {
+                    self.original_score = Some(score);
+                    self.group_progress = GroupProgress::of(&self.current, &solution);
+                    self.solution = Some(solution);
+                }
+                _ => self.solve_failed = true,
+            }
+        }
+        self.solution.as_ref().ok_or(HintError::SolveFailed)
+    }
+    /// Whether the proposed change is correct.
+    ///
+    /// With a known solution, a value is correct if it's the one in the
+    /// solution. Otherwise, it's correct if it doesn't appear elsewhere in
+    /// the cell's groups (so the
+    /// [`allow_incorrect_answers`](config/struct.Behavior.html#structfield.allow_incorrect_answers)
+    /// preference then governs conflicting insertions).
     pub fn insertion_is_correct(&self, point: Point, value: Element) -> bool {
-        self.solution[point] == Some(value)
+        match self.solution {
+            Some(ref solution) => solution[point] == Some(value),
+            None => self
+                .current
+                .group_indices(point)
+                .into_iter()
+                .all(|p| p == point || self.current[p] != Some(value)),
+        }
+    }
+    /// Suggests a cell to fill next, as found by deduction from the current
+    /// state (see [`Sudoku::deduce`](../../struct.Sudoku.html#method.deduce)).
+    ///
+    /// This doesn't need the solution. If the player has entered incorrect
+    /// values, though, deductions may be wrong; when the solution is known,
+    /// such suggestions are withheld. Returns `None` if the techniques make
+    /// no progress.
+    ///
+    /// Only the techniques the [policy](config/struct.TechniquePolicy.html)
+    /// allows are used. If they make no progress but a forbidden technique
+    /// would, this fails with
+    /// [`PolicyDenied`](enum.HintError.html#variant.PolicyDenied).
+    pub fn hint(&self) -> Result<Option<Placement>, HintError> {
+        let allowed = self.behavior.policy.techniques();
+        let placement = match self.current.deduce(&allowed).placements.first() {
+            Some(placement) => *placement,
+            None if allowed.len() < Technique::LADDER.len()
+                && !self
+                    .current
+                    .deduce(&Technique::LADDER)
+                    .placements
+                    .is_empty() =>
+            {
+                return Err(HintError::PolicyDenied)
+            }
+            None => return Ok(None),
+        };
+        match self.solution {
+            Some(ref solution) if solution[placement.point] != Some(placement.value) => Ok(None),
+            _ => Ok(Some(placement)),
+        }
+    }
+    /// Fills the cell with its value in the solution (replacing any entry),
+    /// returning any events it caused; givens are left alone.
+    ///
+    /// The solution is searched for first if it isn't yet known (see
+    /// [`find_solution`](#method.find_solution)). Fails with
+    /// [`PolicyDenied`](enum.HintError.html#variant.PolicyDenied), without
+    /// searching, if the [policy](config/struct.TechniquePolicy.html)
+    /// forbids revealing cells.
+    pub fn reveal(&mut self, point: Point) -> Result<Vec<Event>, HintError> {
+        if !self.behavior.policy.allow_reveal {
+            return Err(HintError::PolicyDenied);
+        }
+        let value = self.find_solution()?[point];
+        match value {
+            Some(value) if self.is_mutable(point) && self.current[point] != Some(value) => {
+                self.assists += 1;
+                Ok(self.insert(point, value))
+            }
+            _ => Ok(vec![]),
+        }
     }
-    /// Updates the game model to reflect the insertion.
+    /// Fills in every cell that naked and hidden singles deduce from the
+    /// current state, returning any events the insertions caused.
+    ///
+    /// As with [`hint`](#method.hint), deductions the known solution
+    /// contradicts are skipped. Fails with
+    /// [`PolicyDenied`](enum.HintError.html#variant.PolicyDenied) if the
+    /// [policy](config/struct.TechniquePolicy.html) forbids filling in
+    /// singles.
+    pub fn fill_singles(&mut self) -> Result<Vec<Event>, HintError> {
+        if !self.behavior.policy.allow_fill_singles {
+            return Err(HintError::PolicyDenied);
+        }
+        let singles = [Technique::NakedSingle, Technique::HiddenSingle];
+        let moves = self.moves;
+        let mut events = vec![];
+        for placement in self.current.deduce(&singles).placements {
+            let Placement { point, value, .. } = placement;
+            let contradicted = self
+                .solution
+                .as_ref()
+                .is_some_and(|solution| solution[point] != Some(value));
+            if !contradicted && self.is_mutable(point) && self.current[point].is_none() {
+                events.extend(self.insert(point, value));
+            }
+        }
+        if self.moves != moves {
+            self.assists += 1;
+        }
+        Ok(events)
+    }
+    /// Updates the game model to reflect the insertion, returning any events
+    /// it caused.
+    ///
+    /// An [`Event::GroupCompleted`](enum.Event.html#variant.GroupCompleted) is
+    /// returned for each group containing the point that is complete and
+    /// valid after the insertion but wasn't before. When incorrect answers
+    /// are allowed, a group filled in with a mistake isn't valid, so it only
+    /// counts as completed once the mistake is corrected; likewise, breaking
+    /// a completed group and then repairing it completes it again.
+    ///
+    /// Outside of [zen mode](enum.Mode.html#variant.Zen), an incorrect value
+    /// counts as a [mistake](#method.mistakes). Once the game has
+    /// [failed](enum.Status.html#variant.Failed), insertions are rejected
+    /// (the grid is left unchanged, and no events are returned).
     ///
     /// # Notes
     /// No validation of the insertion is made; use
     /// [`insertion_is_valid`](#method.insertion_is_valid) to double-check the
     /// change before insertion (and check whether invalid insertions
     /// should be allowed) before commiting.
-    pub fn insert(&mut self, point: Point, value: Element) {
-        self.current.substitute(point, Some(value));
-        self.moves += 1;
+    pub fn insert(&mut self, point: Point, value: Element) -> Vec<Event> {
+        if self.expired {
+            return vec![];
+        }
+        if self.mode != Mode::Zen && !self.insertion_is_correct(point, value) {
+            self.mistakes += 1;
+        }
+        let before = self.completed_groups(point);
+        self.set_value(point, Some(value));
+        let order = self.current.order;
+        self.completed_groups(point)
+            .iter()
+            .zip(before.iter())
+            .enumerate()
+            .filter(|(_, (after, before))| **after && !**before)
+            .map(|(i, _)| {
+                let mut anchor = point;
+                let kind = match i {
+                    0 => {
+                        anchor = point.snap(order);
+                        GroupKind::Box
+                    }
+                    1 => {
+                        anchor[1] = 0;
+                        GroupKind::Stack
+                    }
+                    // The first band runs along the x-axis, the rest along
+                    // the axes past the y-axis.
+                    _ => {
+                        anchor[if i == 2 { 0 } else { i - 1 }] = 0;
+                        GroupKind::Band
+                    }
+                };
+                Event::GroupCompleted { kind, anchor }
+            })
+            .collect()
+    }
+    // Whether each group containing the point (in the order returned by
+    // `Sudoku::groups`) is complete and valid.
+    fn completed_groups(&self, point: Point) -> Vec<bool> {
+        self.current
+            .groups(point)
+            .iter()
+            .map(|group: &Group| group.is_complete() && group.is_valid())
+            .collect()
     }
     /// Removes the indexed element from the puzzle, returning the old value
     /// (if applicable).
+    ///
+    /// Once the game has [failed](enum.Status.html#variant.Failed), removals
+    /// are rejected (the grid is left unchanged, and `None` is returned).
     pub fn remove(&mut self, point: Point) -> Option<Element> {
-        self.moves += 1;
+        if self.expired {
+            return None;
+        }
         let value = self.current[point];
-        self.current.substitute(point, None);
+        self.set_value(point, None);
         value
     }
+    // Makes a move, keeping the incremental bookkeeping up to date.
+    fn set_value(&mut self, point: Point, value: Option<Element>) {
+        // Without a solution, every filled cell counts as correct.
+        let correct = |value: Option<Element>| match self.solution {
+            Some(ref solution) => value.is_some() && value == solution[point],
+            None => value.is_some(),
+        };
+        let (was, is) = (correct(self.current[point]), correct(value));
+        // The cell's peers may gain or lose conflicts and candidates, and
+        // if it's focused, a different value is emphasized.
+        let emphasized = self.focus_region(self.focused);
+        self.dirty.extend(self.current.group_indices(point));
+        let _ = self.dirty.insert(point);
+        self.current.substitute(point, value);
+        self.dirty.extend(emphasized);
+        self.dirty.extend(self.focus_region(self.focused));
+        self.moves += 1;
+        if was != is {
+            self.group_progress.adjust(point, self.current.shape(), is);
+        }
+    }
+    /// Returns how many cells of each group are correctly filled (or just
+    /// filled, if the solution isn't known).
+    ///
+    /// This is kept up to date as moves are made (touching only the groups
+    /// containing the changed cell), so it's cheap to call after every move.
+    /// Changes made directly to [`current`](#structfield.current) aren't
+    /// tracked.
+    pub fn group_progress(&self) -> &GroupProgress {
+        &self.group_progress
+    }
     /// Returns all points associated with this game.
     pub fn points(&self) -> Vec<Point> {
         self.current.points()
@@ -75,40 +868,760 @@ impl Game {
     /// whether the original generated puzzle contained a supplied value at
     /// the given point.
     pub fn is_mutable(&self, point: Point) -> bool {
-        self.problem[point].is_none()
+        !self.problem.is_frozen(point)
+    }
+    /// Returns the values the (empty) cell could still take, given the values
+    /// currently in its groups, in ascending order.
+    ///
+    /// A filled cell has no candidates.
+    pub fn candidates(&self, point: Point) -> Vec<Element> {
+        if self.current[point].is_some() {
+            return vec![];
+        }
+        let used = self
+            .current
+            .group_indices(point)
+            .into_iter()
+            .filter_map(|p| self.current[p])
+            .collect::<Vec<_>>();
+        (1..=self.current.axis())
+            .map(Element)
+            .filter(|value| !used.contains(value))
+            .collect()
+    }
+    /// Returns the kinds of the cell's groups in which its value appears more
+    /// than once, in the order of [`Sudoku::groups`](../../struct.Sudoku.html#method.groups).
+    ///
+    /// An empty cell has no conflicts.
+    pub fn conflicts(&self, point: Point) -> Vec<GroupKind> {
+        let value = match self.current[point] {
+            Some(value) => value,
+            None => return vec![],
+        };
+        self.current
+            .groups(point)
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| {
+                group
+                    .as_slice()
+                    .iter()
+                    .filter(|e| **e == Some(value))
+                    .count()
+                    > 1
+            })
+            .map(|(i, _)| match i {
+                0 => GroupKind::Box,
+                1 => GroupKind::Stack,
+                _ => GroupKind::Band,
+            })
+            .collect()
+    }
+    /// Returns the number of incorrect values entered so far (always 0 in
+    /// [zen mode](enum.Mode.html#variant.Zen)).
+    ///
+    /// Every incorrect insertion counts, even if it's later corrected, so
+    /// this is suited to scoring systems that penalize mistakes (e.g. along
+    /// with [`elapsed`](#method.elapsed)). Mistakes can only be made when
+    /// the frontend allows incorrect answers.
+    pub fn mistakes(&self) -> usize {
+        self.mistakes
+    }
+    /// Returns the number of times the game filled cells for the player
+    /// (each [reveal](#method.reveal) or [fill](#method.fill_singles) that
+    /// changed the grid). Hints only suggest, so they aren't counted.
+    pub fn assists(&self) -> usize {
+        self.assists
+    }
+    /// Returns the help the game currently offers, as allowed by the
+    /// [policy](config/struct.TechniquePolicy.html).
+    ///
+    /// Nothing is offered once the game is no longer being played, and cells
+    /// can't be revealed once the search for the solution has failed.
+    pub fn assistance(&self) -> Assistance {
+        let playing = self.status() == Status::Playing;
+        let policy = &self.behavior.policy;
+        Assistance {
+            hint_techniques: if playing { policy.techniques() } else { vec![] },
+            reveal: playing && policy.allow_reveal && !self.solve_failed,
+            fill_singles: playing && policy.allow_fill_singles,
+        }
+    }
+    /// Returns where the game stands.
+    pub fn status(&self) -> Status {
+        if self.expired {
+            Status::Failed
+        } else if self.is_solved() {
+            Status::Solved
+        } else {
+            Status::Playing
+        }
+    }
+    /// Returns the time played, as of the last [`tick`](#method.tick) or
+    /// [`pause`](#method.pause).
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+    /// Returns the [time played](#method.elapsed), or `None` if the timer
+    /// has never run (as in [zen mode](enum.Mode.html#variant.Zen), or when
+    /// the frontend doesn't keep time).
+    pub fn time_played(&self) -> Option<Duration> {
+        if self.timed {
+            Some(self.elapsed)
+        } else {
+            None
+        }
+    }
+    /// Returns the time left in a time attack, as of the last
+    /// [`tick`](#method.tick) or [`pause`](#method.pause).
+    pub fn time_remaining(&self) -> Option<Duration> {
+        self.time_budget
+            .map(|budget| budget.checked_sub(self.elapsed).unwrap_or_default())
+    }
+    /// Whether the timer is stopped (as it is until the game is first
+    /// [resumed](#method.resume)).
+    pub fn is_paused(&self) -> bool {
+        self.running_since.is_none()
+    }
+    /// Starts (or restarts) the timer.
+    ///
+    /// This has no effect in [zen mode](enum.Mode.html#variant.Zen), which
+    /// has no timer, or once the game is over.
+    pub fn resume(&mut self, clock: &dyn Clock) {
+        if self.mode != Mode::Zen
+            && self.running_since.is_none()
+            && self.status() == Status::Playing
+        {
+            self.running_since = Some(clock.now());
+            self.timed = true;
+        }
+    }
+    /// Stops the timer, counting the time played since it was last started.
+    pub fn pause(&mut self, clock: &dyn Clock) {
+        let _ = self.tick(clock);
+        self.running_since = None;
+    }
+    /// Counts the time played since the last tick, returning any events it
+    /// caused; frontends should call this periodically (e.g. every second)
+    /// while the game is on screen.
+    ///
+    /// When a time attack's budget runs out before the puzzle is solved, the
+    /// game [fails](enum.Status.html#variant.Failed) and an
+    /// [`Event::TimeExpired`](enum.Event.html#variant.TimeExpired) is
+    /// returned. The first tick after the puzzle is solved counts the time
+    /// up to it (moves don't read the clock, so that's as close to the
+    /// moment of solving as the game can tell) and stops the timer; a
+    /// solved time attack never fails, though its time is capped at the
+    /// budget. Frontends should tick (or [pause](#method.pause)) promptly
+    /// once the puzzle is solved.
+    pub fn tick(&mut self, clock: &dyn Clock) -> Vec<Event> {
+        let since = match self.running_since {
+            Some(since) => since,
+            None => return vec![],
+        };
+        let now = clock.now();
+        self.elapsed += now.checked_sub(since).unwrap_or_default();
+        if self.is_solved() {
+            self.running_since = None;
+            if let Some(budget) = self.time_budget {
+                self.elapsed = self.elapsed.min(budget);
+            }
+            return vec![];
+        }
+        self.running_since = Some(now);
+        match self.time_budget {
+            Some(budget) if self.elapsed >= budget => {
+                self.elapsed = budget;
+                self.expired = true;
+                self.running_since = None;
+                vec![Event::TimeExpired]
+            }
+            _ => vec![],
+        }
+    }
+    /// Whether the player has solved the puzzle (the grid is complete and
+    /// follows the rules).
+    ///
+    /// If the puzzle's solution is unique, this is equivalent to the current
+    /// state matching it; otherwise, any valid completion counts.
+    pub fn is_solved(&self) -> bool {
+        self.current.is_solved()
+    }
+    /// Returns the color the player applied to the cell, if any.
+    pub fn cell_color(&self, point: Point) -> Option<AnnotationColor> {
+        self.colors.get(&point).cloned()
+    }
+    /// Returns every colored cell along with its color, in point order.
+    pub fn cell_colors(&self) -> impl Iterator<Item = (Point, AnnotationColor)> + '_ {
+        self.colors.iter().map(|(point, color)| (*point, *color))
+    }
+    /// Colors the cell (or removes its color), returning the previous color.
+    ///
+    /// Colors are independent of values: they stay put when values are
+    /// inserted or removed, and don't count as moves. Each change that
+    /// actually alters a color can be reverted with
+    /// [`undo_color`](#method.undo_color).
+    pub fn set_cell_color(
+        &mut self,
+        point: Point,
+        color: Option<AnnotationColor>,
+    ) -> Option<AnnotationColor> {
+        let previous = self.cell_color(point);
+        if previous != color {
+            self.color_history.push(vec![(point, previous)]);
+            self.apply_color(point, color);
+        }
+        previous
+    }
+    /// Removes every cell color (as a single undoable change).
+    pub fn clear_all_colors(&mut self) {
+        if !self.colors.is_empty() {
+            let previous = self.cell_colors().map(|(p, c)| (p, Some(c))).collect();
+            self.color_history.push(previous);
+            self.dirty.extend(self.colors.keys());
+            self.colors.clear();
+        }
+    }
+    /// Reverts the most recent color change, returning whether there was one
+    /// to revert.
+    pub fn undo_color(&mut self) -> bool {
+        match self.color_history.pop() {
+            Some(change) => {
+                for (point, color) in change {
+                    self.apply_color(point, color);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+    fn apply_color(&mut self, point: Point, color: Option<AnnotationColor>) {
+        let _ = self.dirty.insert(point);
+        match color {
+            Some(color) => {
+                let _ = self.colors.insert(point, color);
+            }
+            None => {
+                let _ = self.colors.remove(&point);
+            }
+        }
+    }
+    /// Returns the focused point (e.g. the selected cell), if any.
+    pub fn focused(&self) -> Option<Point> {
+        self.focused
+    }
+    /// Focuses the given point, returning whether it lies on the grid.
+    ///
+    /// Points off the grid are rejected, leaving the focus unchanged.
+    pub fn set_focus(&mut self, point: Point) -> bool {
+        let axis = self.current.axis();
+        if point.0.iter().any(|c| *c >= axis) {
+            return false;
+        }
+        self.refocus(Some(point));
+        true
+    }
+    /// Removes the focus.
+    pub fn clear_focus(&mut self) {
+        self.refocus(None);
+    }
+    /// Moves the focus one cell in the given direction, returning the newly
+    /// focused point.
+    ///
+    /// At the edge of the grid, the focus either stays put or wraps around to
+    /// the opposite edge, depending on [`wrap_focus`](#structfield.wrap_focus).
+    /// Nothing happens if no point is focused.
+    pub fn move_focus(&mut self, direction: Direction) -> Option<Point> {
+        let mut point = self.focused?;
+        let last = self.current.axis() - 1;
+        let (axis, forward) = match direction {
+            Direction::Up => (1, false),
+            Direction::Down => (1, true),
+            Direction::Left => (0, false),
+            Direction::Right => (0, true),
+        };
+        point[axis] = match (forward, point[axis]) {
+            (true, c) if c == last => {
+                if self.wrap_focus {
+                    0
+                } else {
+                    last
+                }
+            }
+            (true, c) => c + 1,
+            (false, 0) => {
+                if self.wrap_focus {
+                    last
+                } else {
+                    0
+                }
+            }
+            (false, c) => c - 1,
+        };
+        self.refocus(Some(point));
+        self.focused
+    }
+    // Moves the focus, marking the cells highlighted before and after.
+    fn refocus(&mut self, focus: Option<Point>) {
+        if focus != self.focused {
+            self.dirty.extend(self.focus_region(self.focused));
+            self.focused = focus;
+            self.dirty.extend(self.focus_region(focus));
+        }
+    }
+    // The cells a focus affects the rendering of: the focused cell, its
+    // peers (see `relevant_points`), and the cells holding its value.
+    fn focus_region(&self, focus: Option<Point>) -> Vec<Point> {
+        let point = match focus {
+            Some(point) => point,
+            None => return vec![],
+        };
+        let mut region = self.relevant_points(point);
+        region.push(point);
+        if let Some(value) = self.current[point] {
+            region.extend(
+                self.points()
+                    .into_iter()
+                    .filter(|p| self.current[*p] == Some(value)),
+            );
+        }
+        region
+    }
+    /// Returns (and forgets) the cells whose rendering may have changed
+    /// since the last call, in ascending order, so frontends can repaint
+    /// just those cells (and the grid lines) instead of the whole grid.
+    ///
+    /// Every change made through the game's methods is covered: values,
+    /// colors, and the focus, along with their knock-on effects on the
+    /// changed cell's peers (conflicts and candidates) and on the cells
+    /// highlighted or emphasized (by sharing the focused cell's value)
+    /// because of the focus. Changes made directly to
+    /// [`current`](#structfield.current) aren't tracked, and a new game
+    /// starts with nothing dirty (it needs a full render anyway).
+    pub fn take_dirty(&mut self) -> Vec<Point> {
+        let dirty = std::mem::take(&mut self.dirty);
+        dirty.into_iter().collect()
+    }
+    /// Carries out the player's command, returning what came of it (in
+    /// order).
+    ///
+    /// This is all a frontend needs to change the game: its event handlers
+    /// only have to turn input into commands and react to the events.
+    /// Commands that need a focused cell do nothing without one, and moves
+    /// are ignored once the game is over. Changing or removing the focus
+    /// drops a held digit.
+    pub fn handle(&mut self, command: GameCommand) -> Vec<GameEvent> {
+        match command {
+            GameCommand::Focus(Some(point)) => {
+                if !self.set_focus(point) {
+                    return vec![];
+                }
+                self.pending_digit = None;
+                vec![GameEvent::Changed]
+            }
+            GameCommand::Focus(None) => {
+                self.pending_digit = None;
+                self.clear_focus();
+                vec![GameEvent::Changed]
+            }
+            GameCommand::MoveFocus(direction) => {
+                if self.move_focus(direction).is_none() {
+                    return vec![];
+                }
+                self.pending_digit = None;
+                vec![GameEvent::Changed]
+            }
+            GameCommand::InputKey(key) => match GameCommand::from_key(&key) {
+                Some(command) => self.handle(command),
+                None => vec![],
+            },
+            GameCommand::InputDigit(digit) => self.input_digit(digit),
+            GameCommand::Commit => match self.pending_digit.take() {
+                Some(digit) => self.enter(digit),
+                None => vec![],
+            },
+            GameCommand::Delete => {
+                let point = match self.focused {
+                    Some(point) => point,
+                    None => return vec![],
+                };
+                if self.pending_digit.take().is_some() {
+                    return vec![GameEvent::Changed];
+                }
+                if !self.is_mutable(point) {
+                    return vec![GameEvent::Rejected];
+                }
+                match self.current[point] {
+                    Some(_) if self.status() == Status::Playing => {
+                        let _ = self.remove(point);
+                        vec![GameEvent::Changed]
+                    }
+                    _ => vec![],
+                }
+            }
+            GameCommand::Undo => {
+                if self.undo_color() {
+                    vec![GameEvent::Changed]
+                } else {
+                    vec![]
+                }
+            }
+            GameCommand::Hint => {
+                let point = match self.focused {
+                    Some(point) => point,
+                    None => return vec![],
+                };
+                if self.status() != Status::Playing {
+                    return vec![];
+                }
+                self.pending_digit = None;
+                let moves = self.moves;
+                match self.reveal(point) {
+                    Ok(_) if self.moves == moves => vec![],
+                    Ok(events) => self.after_move(events),
+                    Err(error) => vec![GameEvent::HintFailed(error)],
+                }
+            }
+            GameCommand::CycleColor => {
+                let point = match self.focused {
+                    Some(point) => point,
+                    None => return vec![],
+                };
+                let all = AnnotationColor::ALL;
+                let next = match self
+                    .cell_color(point)
+                    .and_then(|c| all.iter().position(|a| *a == c))
+                {
+                    None => Some(all[0]),
+                    Some(i) => all.get(i + 1).cloned(),
+                };
+                let _ = self.set_cell_color(point, next);
+                vec![GameEvent::Changed]
+            }
+            GameCommand::ClearColors => {
+                if self.colors.is_empty() {
+                    return vec![];
+                }
+                self.clear_all_colors();
+                vec![GameEvent::Changed]
+            }
+            GameCommand::NewGame { order, difficulty } => {
+                match self.regenerate(order, difficulty) {
+                    Ok(()) => vec![GameEvent::NewGame { seed: self.seed }, GameEvent::Changed],
+                    Err(error) => {
+                        let mut game = Game::placeholder(order);
+                        game.wrap_focus = self.wrap_focus;
+                        game.behavior = self.behavior;
+                        *self = game;
+                        vec![GameEvent::GenerationFailed(error), GameEvent::Changed]
+                    }
+                }
+            }
+        }
+    }
+    /// Returns the digit held while a multi-digit value is typed, if any
+    /// (see [`GameCommand::InputDigit`](enum.GameCommand.html#variant.InputDigit)).
+    pub fn pending_digit(&self) -> Option<u8> {
+        self.pending_digit
+    }
+    // Types a digit at the focus, holding it if it could start a two-digit
+    // value.
+    fn input_digit(&mut self, digit: u8) -> Vec<GameEvent> {
+        if self.focused.is_none() || digit > 9 {
+            return vec![];
+        }
+        let axis = self.current.axis();
+        match self.pending_digit.take() {
+            Some(first) => self.enter(first * 10 + digit),
+            None if digit != 0 && u16::from(digit) * 10 <= u16::from(axis) => {
+                self.pending_digit = Some(digit);
+                vec![GameEvent::Changed]
+            }
+            None => self.enter(digit),
+        }
+    }
+    // Enters the value at the focus, if it's allowed.
+    fn enter(&mut self, value: u8) -> Vec<GameEvent> {
+        let point = match self.focused {
+            Some(point) => point,
+            None => return vec![],
+        };
+        if self.status() != Status::Playing {
+            return vec![];
+        }
+        let element = Element(value);
+        if value == 0
+            || value > self.current.axis()
+            || !self.is_mutable(point)
+            || !(self.behavior.allow_incorrect_answers || self.insertion_is_correct(point, element))
+        {
+            return vec![GameEvent::Rejected];
+        }
+        let events = self.insert(point, element);
+        self.after_move(events)
+    }
+    // The events to report after a move that caused the given ones.
+    fn after_move(&self, events: Vec<Event>) -> Vec<GameEvent> {
+        let mut events = std::iter::once(GameEvent::Changed)
+            .chain(events.into_iter().map(GameEvent::Move))
+            .collect::<Vec<_>>();
+        if self.is_solved() {
+            events.push(GameEvent::Solved { moves: self.moves });
+        }
+        events
+    }
+    /// Estimates how far through the puzzle the player is.
+    ///
+    /// Besides the plain fraction of filled cells, this weighs progress by
+    /// the difficulty of what remains: the weighted fraction is
+    /// `1 − remaining score / original score`. Scoring the current state
+    /// requires a solve, so the result is cached until the next move.
+    pub fn progress(&self) -> Progress {
+        let cells_total = self.current.elements.len();
+        let cells_filled = self.current.elements.iter().filter(|e| e.is_some()).count();
+        let weighted = match (self.original_score, self.remaining_score()) {
+            (Some(0), Some(_)) => Some(1.0),
+            (Some(original), Some(remaining)) => {
+                Some((1.0 - remaining as f64 / original as f64).clamp(0.0, 1.0))
+            }
+            _ => None,
+        };
+        Progress {
+            cells_filled,
+            cells_total,
+            weighted,
+        }
+    }
+    // Scores the current state, reusing the cached score if no moves have
+    // been made since it was computed.
+    fn remaining_score(&self) -> Option<usize> {
+        if let Some((moves, score)) = self.remaining_score.get() {
+            if moves == self.moves {
+                return score;
+            }
+        }
+        // Any incorrect entry (or an unknown solution) leaves the weighted
+        // estimate undefined, so don't bother with the solver.
+        let correct = self.solution.as_ref().is_some_and(|solution| {
+            self.current
+                .elements
+                .iter()
+                .zip(solution.elements.iter())
+                .all(|(current, solution)| current.is_none() || current == solution)
+        });
+        let remaining = if correct { self.current.score() } else { None };
+        self.remaining_score.set(Some((self.moves, remaining)));
+        remaining
     }
 }
 
+#[cfg(feature = "2D")]
+pub mod replay;
+
 /// Tools for managing the user's preferences.
 pub mod config {
+    use crate::ui::model::Mode;
     use crate::Difficulty;
+    use crate::Technique;
+
+    use std::{fmt, str::FromStr};
 
     /// Monolithic struct containing all user-configurable preferences.
-    #[derive(Clone, Copy, Debug, Default)]
+    ///
+    /// Preferences are saved as one `key: value` line each (see the
+    /// `Display` and `FromStr` implementations).
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
     pub struct Preferences {
-        behavior: Behavior,
-        generation: Generation,
+        /// In-game behavior preferences.
+        pub behavior: Behavior,
+        /// Puzzle generation preferences.
+        pub generation: Generation,
+    }
+
+    /// Writes one `key: value` line per preference, with lists
+    /// comma-separated.
+    impl fmt::Display for Preferences {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let behavior = &self.behavior;
+            let policy = &behavior.policy;
+            let techniques = policy
+                .techniques()
+                .iter()
+                .map(|technique| technique.name())
+                .collect::<Vec<_>>();
+            writeln!(
+                f,
+                "allow_incorrect_answers: {}",
+                behavior.allow_incorrect_answers
+            )?;
+            writeln!(f, "hint_techniques: {}", techniques.join(", "))?;
+            writeln!(f, "allow_reveal: {}", policy.allow_reveal)?;
+            writeln!(f, "allow_fill_singles: {}", policy.allow_fill_singles)?;
+            writeln!(f, "default_order: {}", self.generation.default_order)?;
+            writeln!(
+                f,
+                "default_difficulty: {}",
+                self.generation.default_difficulty
+            )
+        }
+    }
+
+    impl FromStr for Preferences {
+        type Err = ParsePreferencesError;
+        /// Parses preferences written by `Display`, ignoring blank lines.
+        ///
+        /// Preferences that aren't mentioned keep their defaults.
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let mut preferences = Preferences::default();
+            for line in s.lines().filter(|line| !line.trim().is_empty()) {
+                let error = || ParsePreferencesError(line.to_string());
+                let (key, value) = line.split_once(':').ok_or_else(error)?;
+                let value = value.trim();
+                let behavior = &mut preferences.behavior;
+                let generation = &mut preferences.generation;
+                match key.trim() {
+                    "allow_incorrect_answers" => {
+                        behavior.allow_incorrect_answers = value.parse().map_err(|_| error())?
+                    }
+                    "hint_techniques" => {
+                        for technique in Technique::LADDER.iter() {
+                            behavior.policy.set_allowed(*technique, false);
+                        }
+                        for name in value.split(',').filter(|name| !name.trim().is_empty()) {
+                            let technique = name.parse().map_err(|_| error())?;
+                            behavior.policy.set_allowed(technique, true);
+                        }
+                    }
+                    "allow_reveal" => {
+                        behavior.policy.allow_reveal = value.parse().map_err(|_| error())?
+                    }
+                    "allow_fill_singles" => {
+                        behavior.policy.allow_fill_singles = value.parse().map_err(|_| error())?
+                    }
+                    "default_order" => {
+                        generation.default_order = value.parse().map_err(|_| error())?
+                    }
+                    "default_difficulty" => {
+                        generation.default_difficulty = value.parse().map_err(|_| error())?
+                    }
+                    _ => return Err(error()),
+                }
+            }
+            Ok(preferences)
+        }
+    }
+
+    /// Represents a failure to parse [`Preferences`](struct.Preferences.html).
+    ///
+    /// The associated value is the offending line.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct ParsePreferencesError(pub String);
+
+    impl fmt::Display for ParsePreferencesError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "invalid preference \"{}\"", self.0)
+        }
     }
 
     /// Specifies in-game behavior, such as what to do when the user answers
     /// incorrectly.
-    #[derive(Clone, Copy, Debug)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
     pub struct Behavior {
         /// Whether the user should be allowed to answer incorrectly.
         pub allow_incorrect_answers: bool,
+        /// The help the game may give.
+        pub policy: TechniquePolicy,
+    }
+
+    impl Behavior {
+        /// The default behavior for games played under the given mode.
+        ///
+        /// Incorrect answers are only allowed by default in
+        /// [zen mode](../enum.Mode.html#variant.Zen), where they aren't
+        /// counted as mistakes. Every kind of help is allowed.
+        pub fn for_mode(mode: Mode) -> Self {
+            Self {
+                allow_incorrect_answers: mode == Mode::Zen,
+                policy: TechniquePolicy::default(),
+            }
+        }
+    }
+
+    /// Specifies the help the game may give: the techniques
+    /// [hints](../struct.Game.html#method.hint) may use, and whether cells
+    /// may be [revealed](../struct.Game.html#method.reveal) or
+    /// [singles filled in](../struct.Game.html#method.fill_singles) for the
+    /// player.
+    ///
+    /// Requests the policy forbids fail with
+    /// [`PolicyDenied`](../enum.HintError.html#variant.PolicyDenied) (and
+    /// don't count as [assists](../struct.Game.html#method.assists)). By
+    /// default, everything is allowed.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct TechniquePolicy {
+        // Whether hints may use each technique, in ladder order.
+        techniques: [bool; Technique::LADDER.len()],
+        /// Whether cells may be revealed from the solution.
+        pub allow_reveal: bool,
+        /// Whether every single may be filled in at once.
+        pub allow_fill_singles: bool,
+    }
+
+    impl TechniquePolicy {
+        /// A policy allowing hints from naked and hidden singles only (so
+        /// harder deductions are never spoiled), and nothing else.
+        pub fn singles_only() -> Self {
+            let mut policy = Self {
+                techniques: [false; Technique::LADDER.len()],
+                allow_reveal: false,
+                allow_fill_singles: false,
+            };
+            policy.set_allowed(Technique::NakedSingle, true);
+            policy.set_allowed(Technique::HiddenSingle, true);
+            policy
+        }
+
+        /// Whether hints may use the technique.
+        pub fn allows(&self, technique: Technique) -> bool {
+            ladder_index(technique).is_some_and(|index| self.techniques[index])
+        }
+
+        /// Allows or forbids hints from using the technique.
+        pub fn set_allowed(&mut self, technique: Technique, allowed: bool) {
+            if let Some(index) = ladder_index(technique) {
+                self.techniques[index] = allowed;
+            }
+        }
+
+        /// Returns the techniques hints may use, in
+        /// [ladder](../../enum.Technique.html#associatedconstant.LADDER)
+        /// order.
+        pub fn techniques(&self) -> Vec<Technique> {
+            Technique::LADDER
+                .iter()
+                .filter(|technique| self.allows(**technique))
+                .cloned()
+                .collect()
+        }
     }
 
-    impl Default for Behavior {
+    impl Default for TechniquePolicy {
         fn default() -> Self {
             Self {
-                allow_incorrect_answers: false,
+                techniques: [true; Technique::LADDER.len()],
+                allow_reveal: true,
+                allow_fill_singles: true,
             }
         }
     }
 
+    fn ladder_index(technique: Technique) -> Option<usize> {
+        Technique::LADDER.iter().position(|rung| *rung == technique)
+    }
+
     /// Specifies puzzle generation behavior, such as the default sudoku
     /// difficulty and order.
-    #[derive(Clone, Copy, Debug)]
+    #[derive(Clone, Copy, Debug, PartialEq)]
     pub struct Generation {
         /// The default puzzle order.
         pub default_order: u8,
@@ -125,3 +1638,919 @@ pub mod config {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::random::{SeededSource, Source};
+    use crate::sol::{backtrack, Ascending};
+    use crate::ui::model::config::{
+        Behavior, Generation, ParsePreferencesError, Preferences, TechniquePolicy,
+    };
+    use crate::ui::model::{
+        AnnotationColor, Clock, Direction, Event, Game, GameCommand, GameEvent, GroupKind,
+        GroupProgress, HintError, Mode, Status,
+    };
+    use crate::Difficulty;
+    use crate::Element;
+    use crate::GenerateError;
+    use crate::Point;
+    use crate::Score;
+    use crate::Solve;
+    use crate::Sudoku;
+    use crate::Technique;
+    use crate::DIMENSIONS;
+
+    use std::{cell::Cell, time::Duration};
+
+    impl Game {
+        pub(crate) fn from_problem(problem: Sudoku) -> Self {
+            let solution = problem.solution().unwrap();
+            Self::from_parts(problem, Some(solution))
+        }
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_progress() {
+        let puzzle: Sudoku = include_str!("../../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let mut game = Game::from_problem(puzzle);
+        let points = game.points();
+        let total = points.len();
+        let mut filled = points
+            .iter()
+            .filter(|p| game.current[**p].is_some())
+            .count();
+        let mut last = game.progress();
+        assert_eq!(last.weighted, Some(0.0));
+        for point in points {
+            if game.current[point].is_some() {
+                continue;
+            }
+            let value = game.solution().unwrap()[point].unwrap();
+            let _ = game.insert(point, value);
+            filled += 1;
+            let progress = game.progress();
+            assert_eq!(progress.cells_filled, filled);
+            assert_eq!(progress.cells_total, total);
+            assert_eq!(progress.fraction(), filled as f64 / total as f64);
+            assert!(progress.weighted.unwrap() >= last.weighted.unwrap());
+            last = progress;
+        }
+        assert_eq!(last.weighted, Some(1.0));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_progress_incorrect_entry() {
+        let puzzle: Sudoku = include_str!("../../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let mut game = Game::from_problem(puzzle);
+        let point = game
+            .points()
+            .into_iter()
+            .find(|p| game.is_mutable(*p))
+            .unwrap();
+        let Element(correct) = game.solution().unwrap()[point].unwrap();
+        let _ = game.insert(point, Element(correct % 9 + 1));
+        let progress = game.progress();
+        assert_eq!(progress.weighted, None);
+        assert_eq!(progress.estimate(), progress.fraction());
+        let _ = game.insert(point, Element(correct));
+        assert!(game.progress().weighted.is_some());
+    }
+
+    #[cfg(feature = "2D")]
+    fn completed(kind: GroupKind, x: u8, y: u8) -> Event {
+        Event::GroupCompleted {
+            kind,
+            anchor: Point([x, y]),
+        }
+    }
+
+    // A game whose only empty cells are (8, 0) and (8, 1).
+    #[cfg(feature = "2D")]
+    fn nearly_solved() -> Game {
+        let puzzle: Sudoku = include_str!("../../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let mut problem = puzzle.solution().unwrap();
+        problem.substitute(Point([8, 0]), None);
+        problem.substitute(Point([8, 1]), None);
+        Game::from_problem(problem)
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_insert_group_completed() {
+        let mut game = nearly_solved();
+        let (one, two) = (Point([8, 0]), Point([8, 1]));
+        let events = game.insert(one, game.solution().unwrap()[one].unwrap());
+        assert_eq!(events, vec![completed(GroupKind::Band, 0, 0)]);
+        let events = game.insert(two, game.solution().unwrap()[two].unwrap());
+        assert_eq!(
+            events,
+            vec![
+                completed(GroupKind::Box, 6, 0),
+                completed(GroupKind::Stack, 8, 0),
+                completed(GroupKind::Band, 0, 1),
+            ]
+        );
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_insert_group_completed_after_correction() {
+        let mut game = nearly_solved();
+        let (one, two) = (Point([8, 0]), Point([8, 1]));
+        let _ = game.insert(one, game.solution().unwrap()[one].unwrap());
+        let Element(correct) = game.solution().unwrap()[two].unwrap();
+        let wrong = Element(correct % 9 + 1);
+        assert_eq!(game.insert(two, wrong), vec![]);
+        let fixed = game.insert(two, Element(correct));
+        assert_eq!(fixed.len(), 3);
+        // Breaking the groups again and repairing them fires again.
+        assert_eq!(game.insert(two, wrong), vec![]);
+        assert_eq!(game.insert(two, Element(correct)), fixed);
+    }
+
+    // Handles each command in turn, returning the events of the last.
+    #[cfg(feature = "2D")]
+    fn script(game: &mut Game, commands: Vec<GameCommand>) -> Vec<GameEvent> {
+        commands
+            .into_iter()
+            .fold(vec![], |_, command| game.handle(command))
+    }
+
+    #[cfg(feature = "2D")]
+    fn key(key: &str) -> GameCommand {
+        GameCommand::InputKey(key.to_string())
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_handle_keys() {
+        let mut game = Game::placeholder(3);
+        let (empty, given) = (Point([0; DIMENSIONS]), Point([1, 0]));
+        let Element(correct) = game.solution().unwrap()[empty].unwrap();
+        let (right, wrong) = (correct.to_string(), (correct % 9 + 1).to_string());
+        // Nothing happens without a focus.
+        assert_eq!(game.handle(key(&right)), vec![]);
+        assert_eq!(game.handle(key("Delete")), vec![]);
+        assert_eq!(
+            game.handle(GameCommand::Focus(Some(empty))),
+            vec![GameEvent::Changed]
+        );
+        assert_eq!(game.handle(key("x")), vec![]);
+        assert_eq!(game.handle(key("0")), vec![GameEvent::Rejected]);
+        assert_eq!(game.handle(key(&wrong)), vec![GameEvent::Rejected]);
+        assert_eq!(game.current[empty], None);
+        // Every row of the placeholder is missing a single value.
+        let events = game.handle(key(&right));
+        assert_eq!(events[0], GameEvent::Changed);
+        assert_eq!(
+            events[1],
+            GameEvent::Move(completed(GroupKind::Stack, 0, 0))
+        );
+        assert_eq!(game.current[empty], Some(Element(correct)));
+        assert_eq!(game.handle(key("Backspace")), vec![GameEvent::Changed]);
+        assert_eq!(game.current[empty], None);
+        assert_eq!(game.handle(key("Delete")), vec![]);
+        // Givens can't be changed.
+        let _ = game.handle(GameCommand::MoveFocus(Direction::Right));
+        assert_eq!(game.focused(), Some(given));
+        assert_eq!(game.handle(key("Delete")), vec![GameEvent::Rejected]);
+        assert_eq!(game.handle(key(&right)), vec![GameEvent::Rejected]);
+        assert!(game.current[given].is_some());
+        // Colors follow the focus, and can be undone.
+        let _ = script(&mut game, vec![key("c"), key("c")]);
+        assert_eq!(game.cell_color(given), Some(AnnotationColor::ALL[1]));
+        assert_eq!(game.handle(key("u")), vec![GameEvent::Changed]);
+        assert_eq!(game.cell_color(given), Some(AnnotationColor::ALL[0]));
+        assert_eq!(game.handle(key("C")), vec![GameEvent::Changed]);
+        assert_eq!(game.cell_color(given), None);
+        assert_eq!(game.handle(key("Escape")), vec![GameEvent::Changed]);
+        assert_eq!(game.focused(), None);
+        // Incorrect answers go in when they're allowed.
+        game.behavior.allow_incorrect_answers = true;
+        let _ = game.handle(GameCommand::Focus(Some(empty)));
+        assert_eq!(game.handle(key(&wrong)), vec![GameEvent::Changed]);
+        assert_eq!(game.mistakes(), 1);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_handle_multi_digit() {
+        let mut game = Game::placeholder(4);
+        let solution = game.solution().unwrap().clone();
+        let diagonal = (0..16).map(|i| Point([i; DIMENSIONS])).collect::<Vec<_>>();
+        let value = |point: Point| solution[point].unwrap().0;
+        // Values above 9 take two digits; the first is held meanwhile.
+        let point = *diagonal.iter().find(|p| value(**p) >= 10).unwrap();
+        let (first, second) = (value(point) / 10, value(point) % 10);
+        let _ = game.handle(GameCommand::Focus(Some(point)));
+        assert_eq!(
+            game.handle(GameCommand::InputDigit(first)),
+            vec![GameEvent::Changed]
+        );
+        assert_eq!(game.pending_digit(), Some(first));
+        assert_eq!(game.current[point], None);
+        assert_eq!(
+            game.handle(GameCommand::InputDigit(second))[0],
+            GameEvent::Changed
+        );
+        assert_eq!(game.pending_digit(), None);
+        assert_eq!(game.current[point], Some(Element(value(point))));
+        // A held 1 goes in on its own with Enter.
+        let point = *diagonal.iter().find(|p| value(**p) == 1).unwrap();
+        let events = script(
+            &mut game,
+            vec![GameCommand::Focus(Some(point)), key("1"), key("Enter")],
+        );
+        assert_eq!(events[0], GameEvent::Changed);
+        assert_eq!(game.current[point], Some(Element(1)));
+        // Digits that can't start a valid value go in right away.
+        let point = *diagonal
+            .iter()
+            .find(|p| (2..10).contains(&value(**p)))
+            .unwrap();
+        let events = script(
+            &mut game,
+            vec![
+                GameCommand::Focus(Some(point)),
+                key(&value(point).to_string()),
+            ],
+        );
+        assert_eq!(events[0], GameEvent::Changed);
+        assert_eq!(game.pending_digit(), None);
+        // Out of range values are refused, and Delete, Escape and moving the
+        // focus all drop a held digit.
+        let point = *diagonal
+            .iter()
+            .find(|p| game.current[**p].is_none())
+            .unwrap();
+        let _ = game.handle(GameCommand::Focus(Some(point)));
+        assert_eq!(
+            script(&mut game, vec![key("1"), key("7")]),
+            vec![GameEvent::Rejected]
+        );
+        assert_eq!(
+            script(&mut game, vec![key("1"), key("Delete")]),
+            vec![GameEvent::Changed]
+        );
+        assert_eq!(game.pending_digit(), None);
+        for command in [key("Escape"), GameCommand::MoveFocus(Direction::Down)] {
+            let _ = script(&mut game, vec![GameCommand::Focus(Some(point)), key("1")]);
+            let _ = game.handle(command);
+            assert_eq!(game.pending_digit(), None);
+        }
+        assert_eq!(game.current[point], None);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_handle_solved() {
+        let mut game = nearly_solved();
+        let (one, two) = (Point([8, 0]), Point([8, 1]));
+        let _ = game.handle(GameCommand::Focus(Some(one)));
+        assert_eq!(
+            game.handle(GameCommand::Hint),
+            vec![
+                GameEvent::Changed,
+                GameEvent::Move(completed(GroupKind::Band, 0, 0))
+            ]
+        );
+        // Hinting a correct cell again does nothing.
+        assert_eq!(game.handle(GameCommand::Hint), vec![]);
+        let Element(value) = game.solution().unwrap()[two].unwrap();
+        let _ = game.handle(GameCommand::Focus(Some(two)));
+        let events = game.handle(key(&value.to_string()));
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[4], GameEvent::Solved { moves: 2 });
+        // Once solved, moves are ignored, but a new game can be started.
+        assert_eq!(game.handle(key("Delete")), vec![]);
+        let events = game.handle(GameCommand::NewGame {
+            order: 2,
+            difficulty: Difficulty::Beginner,
+        });
+        assert_eq!(
+            events,
+            vec![GameEvent::NewGame { seed: game.seed() }, GameEvent::Changed]
+        );
+        assert_eq!((game.current.order, game.moves), (2, 0));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_focus() {
+        let mut game = nearly_solved();
+        assert_eq!(game.focused(), None);
+        assert!(game.set_focus(Point([8, 3])));
+        assert_eq!(game.focused(), Some(Point([8, 3])));
+        assert!(!game.set_focus(Point([9, 3])));
+        assert!(!game.set_focus(Point([0, 9])));
+        assert_eq!(game.focused(), Some(Point([8, 3])));
+        game.clear_focus();
+        assert_eq!(game.focused(), None);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_move_focus() {
+        let mut game = nearly_solved();
+        assert_eq!(game.move_focus(Direction::Right), None);
+        assert!(game.set_focus(Point([7, 0])));
+        assert_eq!(game.move_focus(Direction::Right), Some(Point([8, 0])));
+        assert_eq!(game.move_focus(Direction::Right), Some(Point([8, 0])));
+        assert_eq!(game.move_focus(Direction::Up), Some(Point([8, 0])));
+        assert_eq!(game.move_focus(Direction::Down), Some(Point([8, 1])));
+        game.wrap_focus = true;
+        assert_eq!(game.move_focus(Direction::Right), Some(Point([0, 1])));
+        assert_eq!(game.move_focus(Direction::Left), Some(Point([8, 1])));
+        assert_eq!(game.move_focus(Direction::Up), Some(Point([8, 0])));
+        assert_eq!(game.move_focus(Direction::Up), Some(Point([8, 8])));
+        assert_eq!(game.focused(), Some(Point([8, 8])));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_new_is_not_solved() {
+        for difficulty in Difficulty::ALL.iter().cloned() {
+            for _ in 0..3 {
+                let game = Game::new(2, difficulty);
+                assert_ne!(Some(&game.current), game.solution());
+                assert!(!game.is_solved());
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_new_unplayable() {
+        // A single cell can't make a playable puzzle.
+        assert_eq!(
+            Game::try_new(1, Difficulty::Beginner).err(),
+            Some(GenerateError::Unplayable)
+        );
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_regenerate() {
+        let mut game = Game::try_new_with_mode(3, Difficulty::Easy, Mode::Zen).unwrap();
+        game.wrap_focus = true;
+        let point = game.points()[0];
+        let _ = game.set_focus(point);
+        let _ = game.reveal(point);
+        let original = game.current.clone();
+        game.regenerate(2, Difficulty::Beginner).unwrap();
+        assert_eq!(game.moves, 0);
+        assert_eq!(game.mistakes(), 0);
+        assert_eq!(game.focused(), None);
+        assert_eq!(game.mode(), Mode::Zen);
+        assert!(game.wrap_focus);
+        assert_ne!(game.current, original);
+        assert_eq!(game.current.order, 2);
+        assert!(game.current.is_uniquely_solvable());
+        assert!(!game.is_solved());
+        assert_eq!(game.current.solution().ok().as_ref(), game.solution());
+        // The seed regenerates the puzzle.
+        let seed = game.seed().unwrap();
+        let generated = Sudoku::generate_seeded(2, Difficulty::Beginner, seed).unwrap();
+        assert_eq!(generated.puzzle, game.current);
+        // A failed regeneration leaves the game as it was.
+        let current = game.current.clone();
+        assert_eq!(
+            game.regenerate(1, Difficulty::Beginner),
+            Err(GenerateError::Unplayable)
+        );
+        assert_eq!(game.current, current);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_placeholder() {
+        // Generation can't succeed for a single cell, so frontends fall back.
+        let game = Game::try_new(1, Difficulty::Beginner).unwrap_or_else(|_| Game::placeholder(1));
+        assert!(!game.is_solved());
+        for order in 1..=4 {
+            let game = Game::placeholder(order);
+            assert!(!game.is_solved());
+            assert_eq!(game.current.solution().ok().as_ref(), game.solution());
+            assert!(game.solution().unwrap().is_solved());
+        }
+    }
+
+    #[cfg_attr(not(feature = "2D"), test)]
+    #[cfg(not(feature = "2D"))]
+    fn test_placeholder_3d() {
+        // There's no shifted grid to fall back on, so the grid is empty.
+        let game = Game::placeholder(2);
+        assert_eq!(game.solution(), None);
+        assert!(!game.is_solved());
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_cell_colors() {
+        use crate::ui::model::AnnotationColor::*;
+        let mut game = Game::placeholder(3);
+        let colors = |game: &Game| game.cell_colors().collect::<Vec<_>>();
+        let (a, b) = (Point([0, 0]), Point([1, 0]));
+        assert_eq!(game.set_cell_color(a, Some(Red)), None);
+        assert_eq!(game.set_cell_color(b, Some(Blue)), None);
+        assert_eq!(game.set_cell_color(a, Some(Green)), Some(Red));
+        assert_eq!(colors(&game), vec![(a, Green), (b, Blue)]);
+        // Values come and go without touching colors (or their history).
+        let moves = game.moves;
+        let _ = game.insert(a, game.solution().unwrap()[a].unwrap());
+        let _ = game.remove(a);
+        assert_eq!(game.moves, moves + 2);
+        assert_eq!(colors(&game), vec![(a, Green), (b, Blue)]);
+        assert!(game.undo_color());
+        assert_eq!(colors(&game), vec![(a, Red), (b, Blue)]);
+        // Setting a cell to its current color isn't a change.
+        assert_eq!(game.set_cell_color(b, Some(Blue)), Some(Blue));
+        game.clear_all_colors();
+        assert_eq!(colors(&game), vec![]);
+        assert_eq!(game.set_cell_color(b, Some(Purple)), None);
+        assert!(game.undo_color());
+        assert_eq!(colors(&game), vec![]);
+        assert!(game.undo_color());
+        assert_eq!(colors(&game), vec![(a, Red), (b, Blue)]);
+        assert!(game.undo_color());
+        assert_eq!(colors(&game), vec![(a, Red)]);
+        assert!(game.undo_color());
+        assert_eq!(colors(&game), vec![]);
+        assert!(!game.undo_color());
+        assert_eq!(AnnotationColor::ALL.len(), 6);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_group_progress() {
+        // The diagonal is missing.
+        let mut game = Game::placeholder(2);
+        let progress = game.group_progress().clone();
+        assert_eq!(progress.boxes, vec![2, 4, 4, 2]);
+        assert_eq!(progress.lines, vec![vec![3; 4], vec![3; 4]]);
+        assert_eq!((progress.min(), progress.median()), (2, 3));
+        let _ = game.insert(
+            Point([0, 0]),
+            game.solution().unwrap()[Point([0, 0])].unwrap(),
+        );
+        let progress = game.group_progress().clone();
+        assert_eq!(progress.boxes, vec![3, 4, 4, 2]);
+        assert_eq!(progress.lines, vec![vec![4, 3, 3, 3], vec![4, 3, 3, 3]]);
+        // An incorrect entry fills a cell without correctly filling it.
+        let right = game.solution().unwrap()[Point([1, 1])].unwrap();
+        let wrong = Element(right.0 % 4 + 1);
+        let _ = game.insert(Point([1, 1]), wrong);
+        assert_eq!(game.group_progress(), &progress);
+        let _ = game.insert(Point([1, 1]), right);
+        assert_eq!(game.group_progress().boxes, vec![4, 4, 4, 2]);
+        let _ = game.remove(Point([1, 1]));
+        assert_eq!(game.group_progress(), &progress);
+        // Givens can be removed too.
+        let _ = game.remove(Point([3, 0]));
+        assert_eq!(game.group_progress().boxes, vec![3, 3, 4, 2]);
+        assert_eq!(game.group_progress().group_size, 4);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_group_progress_incremental() {
+        let mut game = Game::placeholder(3);
+        let points = game.points();
+        let mut source = SeededSource::new(7);
+        for _ in 0..500 {
+            let point = points[source.below(points.len())];
+            match source.below(3) {
+                0 => {
+                    let _ = game.remove(point);
+                }
+                1 => {
+                    let _ = game.insert(point, game.solution().unwrap()[point].unwrap());
+                }
+                _ => {
+                    let _ = game.insert(point, Element(source.below(9) as u8 + 1));
+                }
+            }
+            assert_eq!(
+                game.group_progress(),
+                &GroupProgress::of(&game.current, game.solution().unwrap())
+            );
+        }
+    }
+
+    // Everything a full render shows for one cell.
+    fn render_state(game: &Game, point: Point) -> impl PartialEq + std::fmt::Debug {
+        let focused = game.focused();
+        let emphasized = match focused.and_then(|f| game.current[f]) {
+            Some(value) => game.current[point] == Some(value),
+            None => false,
+        };
+        (
+            game.current[point],
+            game.cell_color(point),
+            focused == Some(point),
+            focused.is_some_and(|f| game.relevant_points(f).contains(&point)),
+            emphasized,
+            game.conflicts(point),
+            game.candidates(point),
+        )
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_dirty_covers_changes() {
+        let mut game = Game::placeholder(3);
+        let points = game.points();
+        let mut source = SeededSource::new(11);
+        assert!(game.take_dirty().is_empty());
+        for _ in 0..500 {
+            let before = points
+                .iter()
+                .map(|p| render_state(&game, *p))
+                .collect::<Vec<_>>();
+            let point = points[source.below(points.len())];
+            match source.below(8) {
+                0 => {
+                    let _ = game.insert(point, Element(source.below(9) as u8 + 1));
+                }
+                1 => {
+                    let _ = game.remove(point);
+                }
+                2 => {
+                    let color = AnnotationColor::ALL[source.below(6)];
+                    let _ = game.set_cell_color(point, Some(color));
+                }
+                3 => {
+                    let _ = game.undo_color();
+                }
+                4 => game.clear_all_colors(),
+                5 => {
+                    let _ = game.set_focus(point);
+                }
+                6 => {
+                    let direction = [
+                        Direction::Up,
+                        Direction::Down,
+                        Direction::Left,
+                        Direction::Right,
+                    ][source.below(4)];
+                    let _ = game.move_focus(direction);
+                }
+                _ => game.clear_focus(),
+            }
+            let dirty = game.take_dirty();
+            for (p, state) in points.iter().zip(before) {
+                if render_state(&game, *p) != state {
+                    assert!(dirty.contains(p), "{:?} changed but isn't dirty", p);
+                }
+            }
+            assert!(game.take_dirty().is_empty());
+        }
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_rect_game() {
+        let puzzle: Sudoku = include_str!("../../tests/sudokus/solvable/2D-R2x3.txt")
+            .parse()
+            .unwrap();
+        let mut game = Game::from_puzzle(puzzle);
+        let progress = game.group_progress().clone();
+        assert_eq!(progress.boxes.len(), 6);
+        assert_eq!(progress.group_size, 6);
+        assert_eq!(progress.boxes, game.current.clues_per_box());
+        assert_eq!(game.describe_grid().lines().count(), 6);
+        assert!(game.set_focus(Point([5, 5])));
+        assert!(!game.set_focus(Point([6, 0])));
+        assert_eq!(game.move_focus(Direction::Right), Some(Point([5, 5])));
+        assert_eq!(
+            game.candidates(Point([0, 0])),
+            vec![Element(1), Element(4), Element(5)]
+        );
+        let solution = game.find_solution().unwrap().clone();
+        for point in game.points() {
+            if game.current[point].is_none() {
+                let _ = game.insert(point, solution[point].unwrap());
+                assert_eq!(
+                    game.group_progress(),
+                    &GroupProgress::of(&game.current, &solution)
+                );
+            }
+        }
+        assert_eq!(game.status(), Status::Solved);
+        assert!(game.group_progress().boxes.iter().all(|count| *count == 6));
+    }
+
+    struct MockClock(Cell<Duration>);
+
+    impl MockClock {
+        fn advance(&self, seconds: u64) {
+            self.0.set(self.0.get() + Duration::from_secs(seconds));
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_time_attack_expires() {
+        let clock = MockClock(Cell::new(Duration::from_secs(1000)));
+        let mut game = Game::placeholder(3);
+        game.set_mode(Mode::TimeAttack, Difficulty::Beginner);
+        assert_eq!(game.time_remaining(), Some(Duration::from_secs(600)));
+        // The timer doesn't run until the game is resumed, or while paused.
+        clock.advance(100);
+        assert!(game.tick(&clock).is_empty());
+        game.resume(&clock);
+        clock.advance(300);
+        assert!(game.tick(&clock).is_empty());
+        game.pause(&clock);
+        clock.advance(1000);
+        assert!(game.tick(&clock).is_empty());
+        assert_eq!(game.elapsed(), Duration::from_secs(300));
+        game.resume(&clock);
+        let point = Point([0; DIMENSIONS]);
+        let _ = game.insert(point, game.solution().unwrap()[point].unwrap());
+        clock.advance(299);
+        assert!(game.tick(&clock).is_empty());
+        assert_eq!(game.status(), Status::Playing);
+        clock.advance(1);
+        assert_eq!(game.tick(&clock), vec![Event::TimeExpired]);
+        assert_eq!(game.status(), Status::Failed);
+        assert_eq!(game.time_remaining(), Some(Duration::from_secs(0)));
+        // Expiry is only reported once, and completing the puzzle afterward
+        // is rejected.
+        clock.advance(10);
+        assert!(game.tick(&clock).is_empty());
+        for point in game.points() {
+            if let Some(value) = game.solution().unwrap()[point] {
+                assert!(game.insert(point, value).is_empty());
+            }
+        }
+        assert_eq!(game.remove(point), None);
+        assert!(!game.is_solved());
+        assert_eq!(game.status(), Status::Failed);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_solved_stops_timer() {
+        let clock = MockClock(Cell::new(Duration::from_secs(0)));
+        let mut game = Game::placeholder(3);
+        game.set_mode(Mode::TimeAttack, Difficulty::Beginner);
+        game.resume(&clock);
+        clock.advance(60);
+        let _ = game.tick(&clock);
+        for point in game.points() {
+            let _ = game.insert(point, game.solution().unwrap()[point].unwrap());
+        }
+        // The time between the last tick and solving counts.
+        clock.advance(30);
+        assert!(game.tick(&clock).is_empty());
+        assert_eq!(game.status(), Status::Solved);
+        assert_eq!(game.elapsed(), Duration::from_secs(90));
+        assert_eq!(game.time_played(), Some(Duration::from_secs(90)));
+        assert!(game.is_paused());
+        clock.advance(6000);
+        assert!(game.tick(&clock).is_empty());
+        assert_eq!(game.elapsed(), Duration::from_secs(90));
+        // A solved time attack doesn't fail for want of a timely tick.
+        let mut game = Game::placeholder(3);
+        game.set_mode(Mode::TimeAttack, Difficulty::Beginner);
+        game.resume(&clock);
+        for point in game.points() {
+            let _ = game.insert(point, game.solution().unwrap()[point].unwrap());
+        }
+        clock.advance(6000);
+        assert!(game.tick(&clock).is_empty());
+        assert_eq!(game.status(), Status::Solved);
+        assert_eq!(game.time_remaining(), Some(Duration::from_secs(0)));
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_zen_mode() {
+        let clock = MockClock(Cell::new(Duration::from_secs(0)));
+        let mut game = Game::placeholder(3);
+        game.set_mode(Mode::Zen, Difficulty::Beginner);
+        assert!(Behavior::for_mode(Mode::Zen).allow_incorrect_answers);
+        let point = Point([0; DIMENSIONS]);
+        let wrong = Element(game.solution().unwrap()[point].unwrap().0 % 9 + 1);
+        let _ = game.insert(point, wrong);
+        let _ = game.insert(point, wrong);
+        assert_eq!(game.mistakes(), 0);
+        // There's no timer.
+        game.resume(&clock);
+        clock.advance(100_000);
+        assert!(game.tick(&clock).is_empty());
+        assert_eq!(game.elapsed(), Duration::from_secs(0));
+        assert_eq!(game.time_played(), None);
+        assert_eq!(game.time_remaining(), None);
+        // Classic games count the same entries as mistakes.
+        let mut game = Game::placeholder(3);
+        assert!(!Behavior::for_mode(Mode::Classic).allow_incorrect_answers);
+        let _ = game.insert(point, wrong);
+        let _ = game.insert(point, wrong);
+        assert_eq!(game.mistakes(), 2);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_mistakes() {
+        let mut game = Game::placeholder(3);
+        let point = Point([0; DIMENSIONS]);
+        let right = game.solution().unwrap()[point].unwrap();
+        let wrong = Element(right.0 % 9 + 1);
+        let _ = game.insert(point, wrong);
+        let _ = game.insert(point, right);
+        assert_eq!(game.mistakes(), 1);
+        // Correcting or clearing a mistake doesn't undo it.
+        let _ = game.remove(point);
+        let _ = game.insert(point, right);
+        assert_eq!(game.mistakes(), 1);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_from_puzzle_non_unique() {
+        let mut problem = Sudoku::new(2);
+        problem.substitute(Point([0, 0]), Some(Element(1)));
+        let mut game = Game::from_puzzle(problem.clone());
+        assert!(!game.has_solution());
+        // Without a solution, conflicts are what make an insertion incorrect.
+        assert!(!game.insertion_is_correct(Point([1, 0]), Element(1)));
+        let _ = game.insert(Point([1, 0]), Element(1));
+        assert_eq!(game.mistakes(), 1);
+        assert_eq!(game.remove(Point([1, 0])), Some(Element(1)));
+        assert_eq!(game.reveal(Point([1, 1])), Err(HintError::SolveFailed));
+        assert!(!game.has_solution());
+        // Any valid completion solves the puzzle.
+        let completion = backtrack(&problem, 1, false, &mut Ascending)
+            .solution
+            .unwrap();
+        for point in game.points() {
+            if game.is_mutable(point) {
+                let value = completion[point].unwrap();
+                assert!(game.insertion_is_correct(point, value));
+                let _ = game.insert(point, value);
+            }
+        }
+        assert_eq!(game.status(), Status::Solved);
+        assert_eq!(game.mistakes(), 1);
+        assert_eq!(game.group_progress().min(), 4);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_reveal_finds_solution() {
+        let puzzle: Sudoku = include_str!("../../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let solution = puzzle.solution().unwrap();
+        let mut game = Game::from_puzzle(puzzle.clone());
+        assert_eq!(game.progress().weighted, None);
+        // Hints come from deduction, so they don't need the solution.
+        let hint = game.hint().unwrap().unwrap();
+        assert_eq!(solution[hint.point], Some(hint.value));
+        assert!(!game.has_solution());
+        let point = Point([1, 0]);
+        assert_eq!(game.current[point], None);
+        let _ = game.reveal(point).unwrap();
+        assert!(game.has_solution());
+        assert_eq!(game.solution(), Some(&solution));
+        assert_eq!(game.difficulty(), puzzle.difficulty());
+        assert_eq!(game.current[point], solution[point]);
+        assert_eq!(game.mistakes(), 0);
+        assert_eq!(game.assists(), 1);
+        assert!(game.progress().weighted.is_some());
+        // Revealing a given changes nothing.
+        assert_eq!(game.reveal(Point([0, 0])), Ok(vec![]));
+        assert_eq!(game.assists(), 1);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_technique_policy() {
+        // Stuck for singles, but not for coloring.
+        let stuck: Sudoku = "5 _ 8 _ 9 3 1 6 _\n\
+                             4 _ 9 _ 6 1 8 3 5\n\
+                             3 6 1 8 _ _ _ _ 9\n\
+                             _ 3 _ 6 2 _ 9 8 _\n\
+                             6 8 4 1 7 9 3 5 2\n\
+                             _ _ 2 _ _ 8 6 _ _\n\
+                             _ _ _ _ _ 2 _ 9 6\n\
+                             _ _ 6 5 _ _ _ 1 3\n\
+                             _ 4 3 9 1 6 5 _ 8"
+            .parse()
+            .unwrap();
+        let mut game = Game::from_problem(stuck);
+        assert!(game.hint().unwrap().is_some());
+        game.behavior.policy = TechniquePolicy::singles_only();
+        assert_eq!(game.hint(), Err(HintError::PolicyDenied));
+        assert_eq!(game.reveal(Point([1, 0])), Err(HintError::PolicyDenied));
+        assert_eq!(game.fill_singles(), Err(HintError::PolicyDenied));
+        assert_eq!(game.current[Point([1, 0])], None);
+        assert_eq!((game.moves, game.assists()), (0, 0));
+        // A denied reveal doesn't go looking for the solution.
+        let mut game = Game::from_puzzle(game.current.clone());
+        game.behavior.policy.allow_reveal = false;
+        assert_eq!(game.reveal(Point([1, 0])), Err(HintError::PolicyDenied));
+        assert!(!game.has_solution());
+        let assistance = game.assistance();
+        assert_eq!(assistance.hint_techniques, Technique::LADDER.to_vec());
+        assert!(!assistance.reveal && assistance.fill_singles);
+        assert_eq!(
+            assistance.to_string(),
+            "hint_techniques: naked-single, hidden-single, coloring\n\
+             reveal: false\n\
+             fill_singles: true\n"
+        );
+        // Singles still make progress on the original puzzle.
+        let puzzle: Sudoku = include_str!("../../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let mut game = Game::from_problem(puzzle);
+        game.behavior.policy = TechniquePolicy::singles_only();
+        assert!(game.hint().unwrap().is_some());
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_fill_singles() {
+        let puzzle: Sudoku = include_str!("../../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap();
+        let mut game = Game::from_problem(puzzle);
+        let before = game.current.clone();
+        let _ = game.fill_singles().unwrap();
+        assert_eq!(game.assists(), 1);
+        assert!(game.moves > 0);
+        let solution = game.solution().unwrap();
+        for point in game.points() {
+            if before[point].is_none() {
+                assert!(game.current[point].is_none() || game.current[point] == solution[point]);
+            }
+        }
+        // Once the singles are exhausted, filling changes nothing.
+        let moves = game.moves;
+        let _ = game.fill_singles().unwrap();
+        assert_eq!((game.moves, game.assists()), (moves, 1));
+    }
+
+    #[test]
+    fn test_preferences() {
+        let mut preferences = Preferences::default();
+        preferences.behavior.policy = TechniquePolicy::singles_only();
+        preferences.behavior.policy.allow_reveal = true;
+        preferences.generation.default_difficulty = Difficulty::Advanced;
+        let saved = preferences.to_string();
+        assert_eq!(
+            saved,
+            "allow_incorrect_answers: false\n\
+             hint_techniques: naked-single, hidden-single\n\
+             allow_reveal: true\n\
+             allow_fill_singles: false\n\
+             default_order: 3\n\
+             default_difficulty: Advanced\n"
+        );
+        assert_eq!(saved.parse(), Ok(preferences));
+        // Missing preferences keep their defaults.
+        let parsed: Preferences = "hint_techniques:\n\nallow_reveal: false".parse().unwrap();
+        assert!(parsed.behavior.policy.techniques().is_empty());
+        assert!(!parsed.behavior.policy.allow_reveal);
+        assert!(parsed.behavior.policy.allow_fill_singles);
+        assert_eq!(parsed.generation, Generation::default());
+        assert_eq!(
+            "allow_reveal: maybe".parse::<Preferences>(),
+            Err(ParsePreferencesError("allow_reveal: maybe".to_string()))
+        );
+        assert!("hint_techniques: guessing".parse::<Preferences>().is_err());
+        assert!("volume: 11".parse::<Preferences>().is_err());
+    }
+
+    #[test]
+    fn test_time_budget() {
+        let budget = |mode: Mode, difficulty| mode.time_budget(81, difficulty);
+        assert_eq!(budget(Mode::Classic, Difficulty::Advanced), None);
+        assert_eq!(budget(Mode::Zen, Difficulty::Advanced), None);
+        let mut last = Duration::from_secs(0);
+        for difficulty in Difficulty::ALL.iter().skip(1) {
+            let budget = budget(Mode::TimeAttack, *difficulty).unwrap();
+            assert!(budget > last);
+            last = budget;
+            // Smaller grids (e.g. 6×6 ones) get less time.
+            let smaller = Mode::TimeAttack.time_budget(36, *difficulty).unwrap();
+            assert!(smaller < budget);
+        }
+    }
+}
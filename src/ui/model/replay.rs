@@ -0,0 +1,574 @@
+//! Recorded games, for replaying and verifying (e.g. by a leaderboard
+//! server).
+//!
+//! A replay carries the rules its moves were made under, so it's judged by
+//! those rules wherever it's replayed, rather than by the preferences of
+//! whoever replays it.
+
+use std::{cell::Cell, fmt, str::FromStr, time::Duration};
+
+use crate::cellref::RefStyle;
+use crate::ui::model::{Clock, Game, Mode, Status};
+use crate::Element;
+use crate::Point;
+use crate::Sudoku;
+
+/// The version written in (and required of) a replay's first line.
+pub const REPLAY_VERSION: u32 = 1;
+
+/// A game's moves, along with the puzzle and the rules they were made under.
+///
+/// Replays are written as a `ku-replay` version line and one `key: value`
+/// header line per rule, then a blank line and one move per line (see the
+/// `Display` and `FromStr` implementations):
+///
+/// ```text
+/// ku-replay: 1
+/// puzzle: AQMA…
+/// mode: classic
+/// allow_incorrect_answers: true
+///
+/// r1c2 4 @1500
+/// r1c2 _ @2250
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    /// The puzzle, holding only its givens.
+    pub puzzle: Sudoku,
+    /// The mode the game was played under.
+    pub mode: Mode,
+    /// The time allowed to solve the puzzle, if limited (as it is in a
+    /// [time attack](../enum.Mode.html#variant.TimeAttack)).
+    pub time_budget: Option<Duration>,
+    /// Whether incorrect values were accepted.
+    pub allow_incorrect_answers: bool,
+    /// The moves made, in order.
+    pub moves: Vec<ReplayMove>,
+}
+
+/// A move in a [`Replay`](struct.Replay.html).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ReplayMove {
+    /// The cell changed.
+    pub point: Point,
+    /// The value entered, or `None` if the cell was cleared.
+    pub value: Option<Element>,
+    /// The time played when the move was made, if recorded.
+    ///
+    /// Either every move of a replay has a time, or none does.
+    pub at: Option<Duration>,
+}
+
+/// The outcome of a verified replay.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ReplaySummary {
+    /// Whether the moves solved the puzzle.
+    pub completed: bool,
+    /// The number of moves made.
+    pub moves: usize,
+    /// The number of incorrect values entered (see
+    /// [`Game::mistakes`](../struct.Game.html#method.mistakes)).
+    pub mistakes: usize,
+    /// The time played up to the last move, if the moves were timed.
+    pub duration: Option<Duration>,
+}
+
+/// Why a recorded move breaks the rules it was recorded under.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum IllegalMove {
+    /// The point lies outside the grid.
+    OffGrid,
+    /// The cell holds one of the puzzle's givens.
+    Given,
+    /// The value lies outside `1..=axis`.
+    OutOfRange,
+    /// The value is incorrect, and incorrect answers weren't allowed.
+    Incorrect,
+    /// The game was already over (solved, or out of time).
+    GameOver,
+    /// The move is timed earlier than the one before it, or only some moves
+    /// are timed.
+    BadTime,
+}
+
+/// Encodes errors encountered while replaying a game.
+///
+/// More variants may be added in future releases, so matches outside this
+/// crate need a wildcard arm.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ReplayError {
+    /// The game isn't a fresh game of the replay's puzzle.
+    PuzzleMismatch,
+    /// The game is played under different rules than the replay was
+    /// recorded under (see [`Replay::game`](struct.Replay.html#method.game)
+    /// for a game with the right ones).
+    PreferenceMismatch,
+    /// The move at the given index (counting from 0) breaks the rules.
+    IllegalMove(usize, IllegalMove),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::PuzzleMismatch => write!(f, "the game isn't a fresh game of the puzzle"),
+            ReplayError::PreferenceMismatch => {
+                write!(f, "the game's rules differ from the recorded ones")
+            }
+            ReplayError::IllegalMove(index, reason) => {
+                write!(f, "move {} is illegal ({:?})", index + 1, reason)
+            }
+        }
+    }
+}
+
+// Reads out the times of a replay's moves as they're applied.
+struct ReplayClock(Cell<Duration>);
+
+impl Clock for ReplayClock {
+    fn now(&self) -> Duration {
+        self.0.get()
+    }
+}
+
+impl Replay {
+    /// Starts a replay of the game, with no moves yet, recording its puzzle
+    /// and the rules it's played under.
+    pub fn new(game: &Game) -> Self {
+        Self {
+            puzzle: (*game.problem).clone(),
+            mode: game.mode,
+            time_budget: game.time_budget,
+            allow_incorrect_answers: game.behavior.allow_incorrect_answers,
+            moves: vec![],
+        }
+    }
+
+    /// Returns a fresh game of the puzzle, played under the recorded rules
+    /// (and with its solution looked for, so that moves are judged against
+    /// it where possible).
+    pub fn game(&self) -> Game {
+        let mut game = Game::from_puzzle(self.puzzle.clone());
+        let _ = game.find_solution();
+        game.mode = self.mode;
+        game.time_budget = self.time_budget;
+        game.behavior.allow_incorrect_answers = self.allow_incorrect_answers;
+        game
+    }
+
+    /// Plays the moves on the game, which must be a fresh game of the puzzle
+    /// played under the recorded rules, and returns a summary of the result.
+    ///
+    /// Each move is checked against the recorded rules as it's made, so a
+    /// replay recorded with incorrect answers allowed stays legal, and one
+    /// that sneaks an incorrect value past rules forbidding it doesn't. If
+    /// the moves are timed, the game's clock follows them, so a time attack
+    /// runs out of time as it did when it was played.
+    ///
+    /// On error, the game is left partway through the replay.
+    pub fn apply(&self, game: &mut Game) -> Result<ReplaySummary, ReplayError> {
+        if *game.problem != self.puzzle || game.current != self.puzzle || game.moves != 0 {
+            return Err(ReplayError::PuzzleMismatch);
+        }
+        if game.mode != self.mode
+            || game.time_budget != self.time_budget
+            || game.behavior.allow_incorrect_answers != self.allow_incorrect_answers
+        {
+            return Err(ReplayError::PreferenceMismatch);
+        }
+        let timed = self.moves.first().is_some_and(|first| first.at.is_some());
+        let clock = ReplayClock(Cell::new(Duration::from_secs(0)));
+        if timed {
+            game.resume(&clock);
+        }
+        for (index, step) in self.moves.iter().enumerate() {
+            let illegal = |reason| Err(ReplayError::IllegalMove(index, reason));
+            match step.at {
+                Some(at) if timed && at >= clock.now() => {
+                    clock.0.set(at);
+                    let _ = game.tick(&clock);
+                }
+                None if !timed => {}
+                _ => return illegal(IllegalMove::BadTime),
+            }
+            if game.status() != Status::Playing {
+                return illegal(IllegalMove::GameOver);
+            }
+            if self.puzzle.shape().fold(step.point).is_none() {
+                return illegal(IllegalMove::OffGrid);
+            }
+            if !game.is_mutable(step.point) {
+                return illegal(IllegalMove::Given);
+            }
+            match step.value {
+                Some(value) if value.0 == 0 || value.0 > self.puzzle.axis() => {
+                    return illegal(IllegalMove::OutOfRange)
+                }
+                Some(value) => {
+                    if !self.allow_incorrect_answers
+                        && !game.insertion_is_correct(step.point, value)
+                    {
+                        return illegal(IllegalMove::Incorrect);
+                    }
+                    let _ = game.insert(step.point, value);
+                }
+                None => {
+                    let _ = game.remove(step.point);
+                }
+            }
+        }
+        Ok(ReplaySummary {
+            completed: game.status() == Status::Solved,
+            moves: game.moves,
+            mistakes: game.mistakes(),
+            duration: self.moves.last().and_then(|last| last.at),
+        })
+    }
+
+    /// Replays the moves on a fresh [game](#method.game) under the recorded
+    /// rules, returning a summary of the result.
+    ///
+    /// This is what a server checking submitted games should call.
+    pub fn verify(&self) -> Result<ReplaySummary, ReplayError> {
+        self.apply(&mut self.game())
+    }
+}
+
+fn mode_name(mode: Mode) -> &'static str {
+    match mode {
+        Mode::Classic => "classic",
+        Mode::TimeAttack => "time-attack",
+        Mode::Zen => "zen",
+    }
+}
+
+/// Writes the header (with the puzzle in base64, and the time budget in
+/// whole seconds, if limited), a blank line, then one move per line: the
+/// cell in `r3c5` notation, the value (`_` for clearing it), and the time
+/// in milliseconds after an `@`, if recorded.
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "ku-replay: {}", REPLAY_VERSION)?;
+        writeln!(f, "puzzle: {}", self.puzzle.to_base64())?;
+        writeln!(f, "mode: {}", mode_name(self.mode))?;
+        if let Some(budget) = self.time_budget {
+            writeln!(f, "time_budget: {}", budget.as_secs())?;
+        }
+        writeln!(
+            f,
+            "allow_incorrect_answers: {}",
+            self.allow_incorrect_answers
+        )?;
+        writeln!(f)?;
+        let axis = self.puzzle.axis();
+        for step in &self.moves {
+            write!(
+                f,
+                "{} ",
+                step.point.to_ref_within(RefStyle::RowColumn, axis)
+            )?;
+            match step.value {
+                Some(value) => write!(f, "{}", value.0)?,
+                None => write!(f, "_")?,
+            }
+            if let Some(at) = step.at {
+                write!(f, " @{}", at.as_millis())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Replay {
+    type Err = ParseReplayError;
+    /// Parses a replay written by `Display`.
+    ///
+    /// The version line must come first, and the puzzle and mode must be
+    /// given; a missing time budget means an unlimited game, and
+    /// incorrect answers are forbidden unless the header allows them. Moves
+    /// may give cells in any [notation](../../../enum.RefStyle.html).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        let version = format!("ku-replay: {}", REPLAY_VERSION);
+        match lines.next() {
+            Some(line) if line.trim() == version => {}
+            line => return Err(ParseReplayError(line.unwrap_or_default().to_string())),
+        }
+        let (mut puzzle, mut mode, mut time_budget) = (None, None, None);
+        let mut allow_incorrect_answers = false;
+        for line in lines.by_ref() {
+            if line.trim().is_empty() {
+                break;
+            }
+            let error = || ParseReplayError(line.to_string());
+            let (key, value) = line.split_once(':').ok_or_else(error)?;
+            let value = value.trim();
+            match key.trim() {
+                "puzzle" => puzzle = Some(Sudoku::from_base64(value).map_err(|_| error())?),
+                "mode" => {
+                    let named = [Mode::Classic, Mode::TimeAttack, Mode::Zen]
+                        .iter()
+                        .find(|mode| mode_name(**mode) == value);
+                    mode = Some(*named.ok_or_else(error)?);
+                }
+                "time_budget" => {
+                    let seconds = value.parse().map_err(|_| error())?;
+                    time_budget = Some(Duration::from_secs(seconds));
+                }
+                "allow_incorrect_answers" => {
+                    allow_incorrect_answers = value.parse().map_err(|_| error())?
+                }
+                _ => return Err(error()),
+            }
+        }
+        let header = || ParseReplayError("(header)".to_string());
+        let puzzle = puzzle.ok_or_else(header)?;
+        let mode = mode.ok_or_else(header)?;
+        let axis = puzzle.axis();
+        let mut moves = vec![];
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let error = || ParseReplayError(line.to_string());
+            let mut tokens = line.split_whitespace();
+            let (cell, value, at) = match (tokens.next(), tokens.next(), tokens.next()) {
+                (Some(cell), Some(value), at) if tokens.next().is_none() => (cell, value, at),
+                _ => return Err(error()),
+            };
+            let point = Point::parse_ref_within(cell, axis).map_err(|_| error())?;
+            let value = match value {
+                "_" => None,
+                value => Some(Element(value.parse().map_err(|_| error())?)),
+            };
+            let at = match at {
+                Some(at) => {
+                    let millis = at.strip_prefix('@').ok_or_else(error)?;
+                    Some(Duration::from_millis(millis.parse().map_err(|_| error())?))
+                }
+                None => None,
+            };
+            moves.push(ReplayMove { point, value, at });
+        }
+        Ok(Self {
+            puzzle,
+            mode,
+            time_budget,
+            allow_incorrect_answers,
+            moves,
+        })
+    }
+}
+
+/// Represents a failure to parse a [`Replay`](struct.Replay.html).
+///
+/// The associated value is the offending line (or `(header)` if the header
+/// is missing the puzzle or mode).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseReplayError(pub String);
+
+impl fmt::Display for ParseReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid replay line \"{}\"", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::model::replay::{
+        IllegalMove, ParseReplayError, Replay, ReplayError, ReplayMove, ReplaySummary,
+    };
+    use crate::ui::model::{Game, Mode};
+    use crate::Element;
+    use crate::Point;
+    use crate::Solve;
+    use crate::Sudoku;
+
+    use std::time::Duration;
+
+    fn puzzle() -> Sudoku {
+        include_str!("../../../tests/sudokus/solvable/2D-O3.txt")
+            .parse()
+            .unwrap()
+    }
+
+    // A replay that enters a wrong value in the first empty cell, corrects
+    // it, then fills in the rest, one second per move.
+    fn sloppy_replay(game: &Game) -> Replay {
+        let solution = puzzle().solution().unwrap();
+        let mut replay = Replay::new(game);
+        let empty = game
+            .points()
+            .into_iter()
+            .filter(|point| game.current[*point].is_none())
+            .collect::<Vec<_>>();
+        let correct = solution[empty[0]].unwrap();
+        let wrong = Element(correct.0 % 9 + 1);
+        let mut values = vec![(empty[0], Some(wrong))];
+        values.extend(empty.iter().map(|point| (*point, solution[*point])));
+        for (i, (point, value)) in values.into_iter().enumerate() {
+            let at = Some(Duration::from_secs(i as u64 + 1));
+            replay.moves.push(ReplayMove { point, value, at });
+        }
+        replay
+    }
+
+    #[test]
+    fn test_verify_incorrect_allowed() {
+        let mut game = Game::from_puzzle(puzzle());
+        game.behavior.allow_incorrect_answers = true;
+        let replay = sloppy_replay(&game);
+        let summary = replay.verify().unwrap();
+        let moves = replay.moves.len();
+        assert_eq!(
+            summary,
+            ReplaySummary {
+                completed: true,
+                moves,
+                mistakes: 1,
+                duration: Some(Duration::from_secs(moves as u64)),
+            }
+        );
+        // Applying it to a compatible game gives the same result.
+        assert_eq!(replay.apply(&mut replay.game()), Ok(summary));
+        // The same moves aren't legal once incorrect answers are forbidden.
+        let strict = Replay {
+            allow_incorrect_answers: false,
+            ..replay.clone()
+        };
+        assert_eq!(
+            strict.verify(),
+            Err(ReplayError::IllegalMove(0, IllegalMove::Incorrect))
+        );
+    }
+
+    #[test]
+    fn test_preference_mismatch() {
+        let mut game = Game::from_puzzle(puzzle());
+        game.behavior.allow_incorrect_answers = true;
+        let replay = sloppy_replay(&game);
+        // The replaying game's own preferences don't count.
+        let mut strict = Game::from_puzzle(puzzle());
+        strict.behavior.allow_incorrect_answers = false;
+        assert_eq!(
+            replay.apply(&mut strict),
+            Err(ReplayError::PreferenceMismatch)
+        );
+        let mut zen = replay.game();
+        zen.mode = Mode::Zen;
+        assert_eq!(replay.apply(&mut zen), Err(ReplayError::PreferenceMismatch));
+        let mut played = replay.game();
+        let _ = played.remove(replay.moves[0].point);
+        assert_eq!(replay.apply(&mut played), Err(ReplayError::PuzzleMismatch));
+    }
+
+    #[test]
+    fn test_illegal_moves() {
+        let game = Game::from_puzzle(puzzle());
+        let given = game
+            .points()
+            .into_iter()
+            .find(|point| game.current[*point].is_some())
+            .unwrap();
+        let check = |point, value, expected| {
+            let mut replay = Replay::new(&game);
+            replay.moves.push(ReplayMove {
+                point,
+                value,
+                at: None,
+            });
+            assert_eq!(replay.verify(), Err(ReplayError::IllegalMove(0, expected)));
+        };
+        check(given, None, IllegalMove::Given);
+        check(Point([9, 0]), None, IllegalMove::OffGrid);
+        check(Point([1, 0]), Some(Element(10)), IllegalMove::OutOfRange);
+        // Once solved, the game takes no more moves.
+        let mut game = Game::from_puzzle(puzzle());
+        game.behavior.allow_incorrect_answers = true;
+        let mut replay = sloppy_replay(&game);
+        let last = *replay.moves.last().unwrap();
+        replay.moves.push(last);
+        let index = replay.moves.len() - 1;
+        assert_eq!(
+            replay.verify(),
+            Err(ReplayError::IllegalMove(index, IllegalMove::GameOver))
+        );
+        // Times can't run backward, or be given for only some moves.
+        let mut replay = sloppy_replay(&game);
+        replay.moves[1].at = Some(Duration::from_millis(500));
+        assert_eq!(
+            replay.verify(),
+            Err(ReplayError::IllegalMove(1, IllegalMove::BadTime))
+        );
+        replay.moves[1].at = None;
+        assert_eq!(
+            replay.verify(),
+            Err(ReplayError::IllegalMove(1, IllegalMove::BadTime))
+        );
+    }
+
+    #[test]
+    fn test_time_attack_replay() {
+        let mut game = Game::from_puzzle(puzzle());
+        game.mode = Mode::TimeAttack;
+        game.time_budget = Some(Duration::from_secs(10));
+        let mut replay = sloppy_replay(&game);
+        replay.allow_incorrect_answers = true;
+        // The budget runs out as the tenth move is made.
+        assert_eq!(
+            replay.verify(),
+            Err(ReplayError::IllegalMove(9, IllegalMove::GameOver))
+        );
+    }
+
+    #[test]
+    fn test_replay_round_trip() {
+        let mut game = Game::from_puzzle(puzzle());
+        game.behavior.allow_incorrect_answers = true;
+        let mut replay = sloppy_replay(&game);
+        replay.moves[1].value = None;
+        let text = replay.to_string();
+        assert!(text.starts_with("ku-replay: 1\npuzzle: "));
+        assert!(text.contains("\nmode: classic\nallow_incorrect_answers: true\n\n"));
+        assert!(text.contains(" _ @2000\n"));
+        assert_eq!(text.parse::<Replay>(), Ok(replay.clone()));
+        let untimed = Replay {
+            mode: Mode::TimeAttack,
+            time_budget: Some(Duration::from_secs(600)),
+            moves: vec![ReplayMove {
+                point: Point([4, 2]),
+                value: Some(Element(7)),
+                at: None,
+            }],
+            ..replay
+        };
+        let text = untimed.to_string();
+        assert!(text.contains("\nmode: time-attack\ntime_budget: 600\n"));
+        assert!(text.ends_with("\n\nr3c5 7\n"));
+        assert_eq!(text.parse::<Replay>(), Ok(untimed));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |line: &str| Err(ParseReplayError(line.to_string()));
+        assert_eq!("".parse::<Replay>(), error(""));
+        assert_eq!("ku-replay: 2\n".parse::<Replay>(), error("ku-replay: 2"));
+        assert_eq!(
+            "ku-replay: 1\nmode: classic\n".parse::<Replay>(),
+            error("(header)")
+        );
+        assert_eq!(
+            "ku-replay: 1\nmode: blitz\n".parse::<Replay>(),
+            error("mode: blitz")
+        );
+        let header = format!(
+            "ku-replay: 1\npuzzle: {}\nmode: zen\n\n",
+            puzzle().to_base64()
+        );
+        for line in &["r1c1", "r1c1 x", "r10c1 1", "r1c1 1 1500", "r1c1 1 @1 2"] {
+            assert_eq!(
+                format!("{}{}\n", header, line).parse::<Replay>(),
+                error(line)
+            );
+        }
+    }
+}
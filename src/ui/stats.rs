@@ -0,0 +1,345 @@
+//! Records of the player's finished games, kept separately for each
+//! [mode](../model/enum.Mode.html).
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+
+use crate::ui::model::{Game, Mode, Status};
+use crate::Difficulty;
+
+/// The number of recent results [`AdaptiveDifficulty`](struct.AdaptiveDifficulty.html)
+/// bases its recommendation on.
+pub const ADAPTIVE_WINDOW: usize = 3;
+
+/// The results of the finished games played under one mode.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Record {
+    /// The number of games solved.
+    pub solved: usize,
+    /// The number of games failed (time attacks that ran out of time).
+    pub failed: usize,
+    /// The shortest time in which a game was solved, if any was timed.
+    pub best_time: Option<Duration>,
+    /// The fewest mistakes made in a solved game, if any was solved.
+    pub fewest_mistakes: Option<usize>,
+}
+
+impl Record {
+    /// Returns the number of games finished (solved or failed).
+    pub fn played(&self) -> usize {
+        self.solved + self.failed
+    }
+}
+
+/// The player's results, kept separately for each mode so that (e.g.) zen
+/// games don't count toward time-attack records.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statistics {
+    records: BTreeMap<Mode, Record>,
+}
+
+impl Statistics {
+    /// Creates an empty set of statistics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the result of a game under its mode, returning whether it was
+    /// recorded (games still in progress aren't).
+    pub fn record(&mut self, game: &Game) -> bool {
+        let record = match game.status() {
+            Status::Playing => return false,
+            _ => self.records.entry(game.mode()).or_default(),
+        };
+        if game.status() == Status::Failed {
+            record.failed += 1;
+            return true;
+        }
+        record.solved += 1;
+        // Games whose timer never ran (zen games among them) weren't timed.
+        if let Some(time) = game.time_played() {
+            record.best_time = Some(record.best_time.map_or(time, |best| best.min(time)));
+        }
+        let mistakes = game.mistakes();
+        record.fewest_mistakes = Some(
+            record
+                .fewest_mistakes
+                .map_or(mistakes, |fewest| fewest.min(mistakes)),
+        );
+        true
+    }
+
+    /// Returns the results of the games played under the given mode.
+    pub fn get(&self, mode: Mode) -> Record {
+        self.records.get(&mode).cloned().unwrap_or_default()
+    }
+}
+
+/// The result of one finished game, as judged by
+/// [`AdaptiveDifficulty`](struct.AdaptiveDifficulty.html).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Outcome {
+    /// The number of cells in the puzzle.
+    pub cells: usize,
+    /// The difficulty of the puzzle.
+    pub difficulty: Difficulty,
+    /// Whether the puzzle was solved (rather than failed).
+    pub solved: bool,
+    /// The time taken, if the game was timed.
+    pub time: Option<Duration>,
+    /// The number of mistakes made.
+    pub mistakes: usize,
+}
+
+impl Outcome {
+    /// The most mistakes a game can have and still not count against the
+    /// player.
+    pub const MAX_MISTAKES: usize = 2;
+
+    /// Judges the result: 1 if the puzzle was solved quickly (in at most half
+    /// of its [time-attack budget](../model/enum.Mode.html#method.time_budget))
+    /// and without mistakes, -1 if it was failed, solved too slowly (over its
+    /// budget) or with more than [`MAX_MISTAKES`](#associatedconstant.MAX_MISTAKES),
+    /// and 0 otherwise.
+    pub fn rating(&self) -> i8 {
+        let budget = Mode::TimeAttack.time_budget(self.cells, self.difficulty);
+        let time = self.time.zip(budget);
+        if !self.solved
+            || self.mistakes > Self::MAX_MISTAKES
+            || time.is_some_and(|(time, budget)| time > budget)
+        {
+            -1
+        } else if self.mistakes == 0 && time.is_some_and(|(time, budget)| time <= budget / 2) {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+/// Recommends the difficulty of the player's next puzzle from their recent
+/// results.
+///
+/// The recommendation moves up a difficulty once the last
+/// [`ADAPTIVE_WINDOW`](constant.ADAPTIVE_WINDOW.html) results since it last
+/// moved were all [rated](struct.Outcome.html#method.rating) 1, and down a
+/// difficulty once they add up to -2 or less. It never leaves the playable
+/// difficulties.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdaptiveDifficulty {
+    recommendation: Difficulty,
+    // The ratings of the results since the recommendation last moved, most
+    // recent last.
+    recent: VecDeque<i8>,
+}
+
+impl AdaptiveDifficulty {
+    /// Starts recommending the given difficulty (or beginner puzzles, if it
+    /// isn't playable).
+    pub fn new(start: Difficulty) -> Self {
+        Self {
+            recommendation: if start.is_playable() {
+                start
+            } else {
+                Difficulty::Beginner
+            },
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Returns the difficulty recommended for the next puzzle.
+    pub fn recommendation(&self) -> Difficulty {
+        self.recommendation
+    }
+
+    /// Takes a result into account, returning the new recommendation.
+    pub fn push(&mut self, outcome: Outcome) -> Difficulty {
+        if self.recent.len() == ADAPTIVE_WINDOW {
+            let _ = self.recent.pop_front();
+        }
+        self.recent.push_back(outcome.rating());
+        let total = self
+            .recent
+            .iter()
+            .map(|rating| i32::from(*rating))
+            .sum::<i32>();
+        let step = if total == ADAPTIVE_WINDOW as i32 {
+            1
+        } else if total <= -2 {
+            -1
+        } else {
+            0
+        };
+        let index = Difficulty::ALL
+            .iter()
+            .position(|difficulty| *difficulty == self.recommendation)
+            .unwrap_or(1) as isize;
+        let next = (index + step).clamp(1, Difficulty::ALL.len() as isize - 1);
+        if next != index {
+            self.recommendation = Difficulty::ALL[next as usize];
+            self.recent.clear();
+        }
+        self.recommendation
+    }
+
+    /// Takes a finished game into account, returning whether it was (games
+    /// still in progress, zen games and games whose difficulty isn't known
+    /// aren't).
+    pub fn record(&mut self, game: &Game) -> bool {
+        let difficulty = match (game.status(), game.mode(), game.difficulty()) {
+            (Status::Playing, _, _) | (_, Mode::Zen, _) | (_, _, None) => return false,
+            (_, _, Some(difficulty)) => difficulty,
+        };
+        let _ = self.push(Outcome {
+            cells: game.current.elements.len(),
+            difficulty,
+            solved: game.status() == Status::Solved,
+            time: game.time_played(),
+            mistakes: game.mistakes(),
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::model::{Clock, Game, Mode};
+    use crate::ui::stats::{AdaptiveDifficulty, Outcome, Record, Statistics, ADAPTIVE_WINDOW};
+    use crate::Difficulty;
+    use crate::DIMENSIONS;
+
+    use std::time::Duration;
+
+    struct FixedClock(Duration);
+
+    impl Clock for FixedClock {
+        fn now(&self) -> Duration {
+            self.0
+        }
+    }
+
+    fn solve(game: &mut Game) {
+        for point in game.points() {
+            let _ = game.insert(point, game.solution().unwrap()[point].unwrap());
+        }
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_statistics_per_mode() {
+        let mut statistics = Statistics::new();
+        // A game that's never been resumed wasn't timed.
+        let mut game = Game::placeholder(2);
+        assert!(!statistics.record(&game));
+        solve(&mut game);
+        assert!(statistics.record(&game));
+        assert_eq!(statistics.get(Mode::Classic).best_time, None);
+        let mut game = Game::placeholder(2);
+        game.resume(&FixedClock(Duration::from_secs(0)));
+        solve(&mut game);
+        game.pause(&FixedClock(Duration::from_secs(90)));
+        assert!(statistics.record(&game));
+        // A time attack that runs out of time.
+        let mut game = Game::placeholder(2);
+        game.set_mode(Mode::TimeAttack, Difficulty::Beginner);
+        game.resume(&FixedClock(Duration::from_secs(0)));
+        let _ = game.tick(&FixedClock(Duration::from_secs(3600)));
+        assert!(statistics.record(&game));
+        assert_eq!(
+            statistics.get(Mode::Classic),
+            Record {
+                solved: 2,
+                failed: 0,
+                best_time: Some(Duration::from_secs(90)),
+                fewest_mistakes: Some(0),
+            }
+        );
+        let time_attack = statistics.get(Mode::TimeAttack);
+        assert_eq!((time_attack.solved, time_attack.failed), (0, 1));
+        assert_eq!(time_attack.played(), 1);
+        assert_eq!(statistics.get(Mode::Zen), Record::default());
+    }
+
+    // The outcome of an intermediate order-3 puzzle.
+    fn outcome(minutes: u64, mistakes: usize) -> Outcome {
+        Outcome {
+            cells: 9usize.pow(DIMENSIONS as u32),
+            difficulty: Difficulty::Intermediate,
+            solved: true,
+            time: Some(Duration::from_secs(minutes * 60)),
+            mistakes,
+        }
+    }
+
+    #[test]
+    fn test_outcome_rating() {
+        // Intermediate order-3 puzzles have a 20-minute budget.
+        assert_eq!(outcome(10, 0).rating(), 1);
+        assert_eq!(outcome(10, 1).rating(), 0);
+        assert_eq!(outcome(15, 0).rating(), 0);
+        assert_eq!(outcome(25, 0).rating(), -1);
+        assert_eq!(outcome(5, 3).rating(), -1);
+        let failed = Outcome {
+            solved: false,
+            ..outcome(20, 0)
+        };
+        assert_eq!(failed.rating(), -1);
+    }
+
+    #[test]
+    fn test_adaptive_difficulty() {
+        let mut adaptive = AdaptiveDifficulty::new(Difficulty::Intermediate);
+        // Fast, mistake-free solves push the recommendation up, one
+        // difficulty per full window.
+        assert_eq!(adaptive.push(outcome(5, 0)), Difficulty::Intermediate);
+        assert_eq!(adaptive.push(outcome(5, 0)), Difficulty::Intermediate);
+        assert_eq!(adaptive.push(outcome(5, 0)), Difficulty::Difficult);
+        for _ in 0..3 {
+            let _ = adaptive.push(outcome(5, 0));
+        }
+        assert_eq!(adaptive.recommendation(), Difficulty::Advanced);
+        for _ in 0..3 {
+            let _ = adaptive.push(outcome(5, 0));
+        }
+        assert_eq!(adaptive.recommendation(), Difficulty::Advanced);
+        // An unremarkable solve breaks the streak.
+        let _ = adaptive.push(outcome(15, 0));
+        // Slow or error-prone solves push it down.
+        assert_eq!(adaptive.push(outcome(30, 0)), Difficulty::Advanced);
+        assert_eq!(adaptive.push(outcome(5, 4)), Difficulty::Difficult);
+        assert_eq!(adaptive.push(outcome(30, 5)), Difficulty::Difficult);
+        assert_eq!(adaptive.push(outcome(30, 5)), Difficulty::Intermediate);
+        let mut adaptive = AdaptiveDifficulty::new(Difficulty::Unplayable);
+        assert_eq!(adaptive.recommendation(), Difficulty::Beginner);
+        for _ in 0..4 {
+            let _ = adaptive.push(outcome(30, 5));
+        }
+        assert_eq!(adaptive.recommendation(), Difficulty::Beginner);
+    }
+
+    #[cfg_attr(feature = "2D", test)]
+    #[cfg(feature = "2D")]
+    fn test_adaptive_difficulty_record() {
+        let mut adaptive = AdaptiveDifficulty::new(Difficulty::Easy);
+        let mut game = Game::placeholder(3);
+        game.resume(&FixedClock(Duration::from_secs(0)));
+        assert!(!adaptive.record(&game));
+        // An hour is well over the budget of any order-3 puzzle.
+        let _ = game.tick(&FixedClock(Duration::from_secs(3600)));
+        solve(&mut game);
+        assert!(game.difficulty().is_some());
+        assert!(adaptive.record(&game));
+        assert!(adaptive.record(&game));
+        assert_eq!(adaptive.recommendation(), Difficulty::Beginner);
+        // An untimed game can't have been quick, however soon it was solved.
+        let mut adaptive = AdaptiveDifficulty::new(Difficulty::Easy);
+        let mut game = Game::placeholder(3);
+        solve(&mut game);
+        for _ in 0..ADAPTIVE_WINDOW {
+            assert!(adaptive.record(&game));
+        }
+        assert_eq!(adaptive.recommendation(), Difficulty::Easy);
+    }
+}
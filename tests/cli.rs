@@ -0,0 +1,200 @@
+use std::process::Command;
+
+#[test]
+fn test_generate_bad_difficulty() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+        .args(["generate", "--difficulty", "impossible"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr.trim_end(),
+        "Invalid difficulty: unknown difficulty \"impossible\" (expected one of: unplayable, \
+         beginner, easy, intermediate, difficult, advanced)."
+    );
+}
+
+#[cfg_attr(feature = "2D", test)]
+#[cfg(feature = "2D")]
+fn test_generate_difficulty() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+        .args(["generate", "2", "--difficulty", "Beginner"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!output.stdout.is_empty());
+}
+
+#[test]
+fn test_bad_order() {
+    for args in &[
+        &["generate", "0"][..],
+        &["generate", "three"],
+        &["generate", "--daily", "2024-06-01", "300"],
+        &["solve", "--order=x", "tests/sudokus/solvable/2D-O3.txt"],
+        &["rate", "--order=0", "tests/sudokus/solvable/2D-O3.txt"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+            .args(*args)
+            .output()
+            .unwrap();
+        assert!(!output.status.success(), "{:?}", args);
+        assert!(output.stdout.is_empty());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("order"), "{}", stderr);
+    }
+}
+
+#[test]
+fn test_generate_bad_preset() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+        .args(["generate", "--preset", "tabloid"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr.trim_end(),
+        "Invalid preset: unknown preset \"tabloid\" (expected one of: newspaper-easy, kids, \
+         expert, large-print)."
+    );
+}
+
+#[cfg_attr(feature = "2D", test)]
+#[cfg(feature = "2D")]
+fn test_generate_preset() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+        .args(["generate", "--preset", "kids"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.trim_end().lines().count(), 4);
+}
+
+#[cfg_attr(feature = "2D", test)]
+#[cfg(feature = "2D")]
+fn test_reduce() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+        .args([
+            "reduce",
+            "--upto",
+            "hidden-single",
+            "--steps",
+            "tests/sudokus/solvable/2D-O3.txt",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some("5 _ 8 _ 9 3 1 6 _"));
+    assert_eq!(lines.filter(|line| line.ends_with("-single)")).count(), 22);
+}
+
+#[test]
+fn test_solve_bad_limits() {
+    // Values are attached with `=` so that clap doesn't read "-1" as a flag.
+    for (arg, value) in &[
+        ("--timeout", "-1"),
+        ("--timeout", "NaN"),
+        ("--timeout", "1e300"),
+        ("--max-nodes", "many"),
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+            .arg("solve")
+            .arg(format!("{}={}", arg, value))
+            .arg("tests/sudokus/solvable/2D-O3.txt")
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains(&format!("\"{}\"", value)), "{}", stderr);
+    }
+}
+
+#[cfg(feature = "ui")]
+#[test]
+fn test_book_bad_per_page() {
+    for value in &["0", "-1", "four"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+            .arg("book")
+            .arg("--from=tests/sudokus/solvable/2D-O3.txt")
+            .arg(format!("--per-page={}", value))
+            .arg("--out=target/never-written")
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains(&format!("\"{}\"", value)), "{}", stderr);
+    }
+}
+
+#[cfg_attr(feature = "2D", test)]
+#[cfg(feature = "2D")]
+fn test_rate_compare() {
+    let fixture = "tests/sudokus/solvable/2D-O3.txt";
+    let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+        .args(["rate", fixture])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let fields = stdout.trim_end().split(',').collect::<Vec<_>>();
+    assert_eq!(fields.len(), 3);
+    let labels = std::env::temp_dir().join(format!("ku-labels-{}.csv", std::process::id()));
+    std::fs::write(&labels, format!("fingerprint,label\n{},hard\n", fields[0])).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+        .args(["rate", fixture, "--scale", "easy,hard", "--compare"])
+        .arg(&labels)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Compared: 1\nSpearman: n/a\n"));
+    std::fs::write(&labels, "ffffffff,hard\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+        .args(["rate", fixture, "--scale", "easy,hard", "--compare"])
+        .arg(&labels)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&labels).unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr.trim_end(),
+        "Couldn't read labels: no puzzle has the fingerprint ffffffff."
+    );
+}
+
+#[cfg_attr(feature = "2D", test)]
+#[cfg(feature = "2D")]
+fn test_rect_boxes() {
+    let fixture = "tests/sudokus/solvable/2D-R2x3.txt";
+    let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+        .args(["solve", fixture])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout.trim_end(),
+        "#boxes: 2x3\n\
+         1 2 3 4 5 6\n\
+         4 5 6 1 2 3\n\
+         2 3 1 5 6 4\n\
+         5 6 4 2 3 1\n\
+         3 1 2 6 4 5\n\
+         6 4 5 3 1 2"
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_ku"))
+        .args(["rate", fixture])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.ends_with(",Intermediate,25\n"), "{}", stdout);
+}
@@ -0,0 +1,16 @@
+//! Helpers shared by the integration tests.
+
+/// Asserts that two grids are equal, printing a diff of the two on failure
+/// (see `Sudoku::render_diff`).
+#[allow(unused_macros)]
+macro_rules! assert_grids_eq {
+    ($left:expr, $right:expr $(,)?) => {{
+        let (left, right): (&sudoku::Sudoku, &sudoku::Sudoku) = (&$left, &$right);
+        if left != right {
+            match left.render_diff(right, &sudoku::DiffRenderOptions::default()) {
+                Ok(diff) => panic!("grids differ (left -, right +):\n{}", diff),
+                Err(error) => panic!("grids differ: {}", error),
+            }
+        }
+    }};
+}
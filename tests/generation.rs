@@ -0,0 +1,86 @@
+#[macro_use]
+mod common;
+
+#[cfg(feature = "2D")]
+use sudoku::{Difficulty, Sudoku};
+
+// Locks down the generator's output for a fixed seed: any change to the
+// generator (or to how it draws from its source) that changes which puzzle
+// a seed produces fails here, and needs the fixture regenerating on purpose.
+#[cfg_attr(feature = "2D", test)]
+#[cfg(feature = "2D")]
+fn test_generate_seeded_vector() {
+    let expected: Sudoku = include_str!("sudokus/generated/2D-O3-beginner-42.txt")
+        .parse()
+        .unwrap();
+    let generated = Sudoku::generate_seeded(3, Difficulty::Beginner, 42).unwrap();
+    assert_grids_eq!(generated.puzzle, expected);
+    assert_eq!(generated.difficulty, Difficulty::Beginner);
+}
+
+// The puzzle of the day must never change for a date, so it's locked down
+// too.
+#[cfg_attr(feature = "2D", test)]
+#[cfg(feature = "2D")]
+fn test_daily_vector() {
+    let expected: Sudoku = include_str!("sudokus/generated/2D-O3-daily-2024-06-02-easy.txt")
+        .parse()
+        .unwrap();
+    let generated = Sudoku::daily((2024, 6, 2), 3, Difficulty::Easy).unwrap();
+    assert_grids_eq!(generated.puzzle, expected);
+    assert_eq!(generated.difficulty, Difficulty::Easy);
+}
+
+// Seeded generation latency per difficulty, as a guard against regressions
+// in the fast path for low difficulties. Timing-sensitive, so it only runs
+// on request: `cargo test --release --features slo-tests -- --ignored`.
+#[cfg(all(feature = "2D", feature = "slo-tests"))]
+mod slo {
+    use std::time::{Duration, Instant};
+    use sudoku::{Difficulty, Sudoku};
+
+    // The number of seeds timed for each difficulty.
+    const SAMPLES: u64 = 20;
+
+    // Beginner and Easy generation must stay well under Advanced's: their p95
+    // may be at most this fraction of Advanced's p95.
+    const FAST_PATH_SHARE: f64 = 0.5;
+
+    // The 95th-percentile time to generate an order-3 puzzle of the given
+    // difficulty across the seeds (through the fast path, for the
+    // difficulties that have one).
+    fn p95(difficulty: Difficulty) -> Duration {
+        let mut times = (0..SAMPLES)
+            .map(|seed| {
+                let start = Instant::now();
+                let generated = if difficulty == Difficulty::Advanced {
+                    Sudoku::generate_seeded(3, difficulty, seed)
+                } else {
+                    Sudoku::generate_quick(3, difficulty, Some(seed))
+                }
+                .unwrap();
+                let elapsed = start.elapsed();
+                assert!(!generated.puzzle.is_complete());
+                elapsed
+            })
+            .collect::<Vec<_>>();
+        times.sort();
+        times[(times.len() * 95).div_ceil(100) - 1]
+    }
+
+    #[test]
+    #[ignore]
+    fn test_generation_latency() {
+        let advanced = p95(Difficulty::Advanced);
+        for difficulty in &[Difficulty::Beginner, Difficulty::Easy] {
+            let p95 = p95(*difficulty);
+            assert!(
+                p95.as_secs_f64() <= advanced.as_secs_f64() * FAST_PATH_SHARE,
+                "{} p95 of {:?} vs. {:?} for advanced",
+                difficulty,
+                p95,
+                advanced
+            );
+        }
+    }
+}
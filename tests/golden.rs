@@ -0,0 +1,385 @@
+//! Golden-file tests for every format the crate emits.
+//!
+//! Each format is rendered for each fixture and compared byte-for-byte with
+//! the expected output checked in as `tests/golden/<fixture>.<format>.<ext>`,
+//! and formats that can be parsed must parse their golden back to the
+//! fixture. Formats that don't depend on a puzzle (such as the preferences
+//! file) are rendered once, into `tests/golden/<document>.<ext>`. This keeps
+//! accidental format drift (which breaks downstream parsers and
+//! fingerprints) from going unnoticed.
+//!
+//! After an intentional format change, regenerate the goldens with
+//! `KU_UPDATE_GOLDEN=1 cargo test --features ui --test golden`, and review
+//! the diff before committing it. New emitters get a [`Format`] (or
+//! [`Document`]) entry below, and their goldens from the same command.
+#![cfg(feature = "2D")]
+
+extern crate sudoku;
+#[macro_use]
+mod common;
+
+use sudoku::{
+    analysis, capabilities, Alphabet, Capabilities, DiffRenderOptions, Element, Grid, LockedSudoku,
+    Solve, Sudoku, UniquenessCache,
+};
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Regenerates the goldens instead of comparing against them when set (to
+/// anything but `0`).
+const UPDATE_VAR: &str = "KU_UPDATE_GOLDEN";
+
+struct Format {
+    name: &'static str,
+    extension: &'static str,
+    // Returns `None` for fixtures the format can't represent.
+    emit: fn(&Sudoku) -> Option<Vec<u8>>,
+    // Asserts that the golden parses back to the fixture, for formats that
+    // have a parser.
+    reparse: Option<fn(&[u8], &Sudoku)>,
+}
+
+/// A format that doesn't depend on a puzzle, rendered once.
+struct Document {
+    name: &'static str,
+    extension: &'static str,
+    emit: fn() -> Vec<u8>,
+    // Asserts that the golden parses back to what was emitted.
+    reparse: Option<fn(&[u8])>,
+}
+
+fn text(bytes: &[u8]) -> &str {
+    std::str::from_utf8(bytes).unwrap()
+}
+
+fn is_square(puzzle: &Sudoku) -> bool {
+    puzzle.box_shape() == (puzzle.order, puzzle.order)
+}
+
+// Solving the order-4 fixture takes too long in debug builds, so formats
+// that solve (or score) the puzzle leave it out.
+fn is_quick(puzzle: &Sudoku) -> bool {
+    puzzle.order <= 3
+}
+
+fn formats() -> Vec<Format> {
+    let mut formats = vec![
+        Format {
+            name: "display",
+            extension: "txt",
+            emit: |puzzle| Some(puzzle.to_string().into_bytes()),
+            reparse: Some(|bytes, puzzle| {
+                assert_grids_eq!(text(bytes).parse::<Sudoku>().unwrap(), *puzzle)
+            }),
+        },
+        Format {
+            name: "hex",
+            extension: "txt",
+            emit: |puzzle| Some(format!("{:X}", puzzle).into_bytes()),
+            // Nothing parses the hexadoku convention (yet).
+            reparse: None,
+        },
+        Format {
+            name: "letters",
+            extension: "txt",
+            emit: |puzzle| {
+                if is_square(puzzle) {
+                    Some(puzzle.to_string_with(&Alphabet::letters()).into_bytes())
+                } else {
+                    None
+                }
+            },
+            reparse: Some(|bytes, puzzle| {
+                let parsed = Sudoku::from_str_with(text(bytes), &Alphabet::letters()).unwrap();
+                assert_grids_eq!(parsed, *puzzle)
+            }),
+        },
+        Format {
+            name: "bytes",
+            extension: "bin",
+            emit: |puzzle| Some(puzzle.to_bytes()),
+            reparse: Some(|bytes, puzzle| {
+                assert_grids_eq!(Sudoku::from_bytes(bytes).unwrap(), *puzzle)
+            }),
+        },
+        Format {
+            name: "base64",
+            extension: "txt",
+            emit: |puzzle| Some(puzzle.to_base64().into_bytes()),
+            reparse: Some(|bytes, puzzle| {
+                assert_grids_eq!(Sudoku::from_base64(text(bytes)).unwrap(), *puzzle)
+            }),
+        },
+        Format {
+            name: "locked-bytes",
+            extension: "bin",
+            emit: |puzzle| Some(locked(puzzle).to_bytes()),
+            reparse: Some(|bytes, puzzle| {
+                assert_eq!(LockedSudoku::from_bytes(bytes).unwrap(), locked(puzzle))
+            }),
+        },
+        Format {
+            name: "locked-base64",
+            extension: "txt",
+            emit: |puzzle| Some(locked(puzzle).to_base64().into_bytes()),
+            reparse: Some(|bytes, puzzle| {
+                assert_eq!(
+                    LockedSudoku::from_base64(text(bytes)).unwrap(),
+                    locked(puzzle)
+                )
+            }),
+        },
+        Format {
+            name: "cache",
+            extension: "txt",
+            emit: |puzzle| {
+                if !is_quick(puzzle) {
+                    return None;
+                }
+                let mut cache = UniquenessCache::new();
+                let _ = cache.score(puzzle);
+                let mut saved = vec![];
+                cache.save(&mut saved).unwrap();
+                Some(saved)
+            },
+            reparse: Some(|bytes, puzzle| {
+                // The loaded entry answers for the fixture without a solve.
+                let mut cache = UniquenessCache::load(bytes).unwrap();
+                assert_eq!(cache.len(), 1);
+                let _ = cache.score(puzzle);
+                assert_eq!((cache.hits(), cache.misses()), (1, 0));
+            }),
+        },
+        Format {
+            name: "diff",
+            extension: "txt",
+            emit: |puzzle| {
+                let diff = puzzle.render_diff(&altered(puzzle), &DiffRenderOptions::default());
+                Some(diff.unwrap().into_bytes())
+            },
+            // Diffs are for people; nothing parses them.
+            reparse: None,
+        },
+        Format {
+            name: "fingerprint",
+            extension: "txt",
+            emit: |puzzle| {
+                let fingerprint = format!(
+                    "{}\n{:016x}\n",
+                    analysis::fingerprint(puzzle),
+                    puzzle.fingerprint()
+                );
+                Some(fingerprint.into_bytes())
+            },
+            reparse: None,
+        },
+    ];
+    formats.extend(ui_formats());
+    formats
+}
+
+/// The fixture, with each of its clues frozen.
+fn locked(puzzle: &Sudoku) -> LockedSudoku {
+    LockedSudoku::new(puzzle.clone())
+}
+
+/// The fixture with its first clue cleared, its second clue changed and its
+/// first empty cell (if any) filled in, so that a diff against the fixture
+/// shows every kind of difference.
+fn altered(puzzle: &Sudoku) -> Sudoku {
+    let mut altered = puzzle.clone();
+    let axis = puzzle.box_shape().0 * puzzle.box_shape().1;
+    let (clues, empty): (Vec<_>, Vec<_>) = puzzle
+        .points()
+        .into_iter()
+        .partition(|point| puzzle[*point].is_some());
+    altered.substitute(clues[0], None);
+    if let Some(Element(value)) = puzzle[clues[1]] {
+        altered.substitute(clues[1], Some(Element(value % axis + 1)));
+    }
+    if let Some(point) = empty.first() {
+        altered.substitute(*point, Some(Element(1)));
+    }
+    altered
+}
+
+fn documents() -> Vec<Document> {
+    let mut documents = vec![Document {
+        name: "capabilities",
+        extension: "txt",
+        emit: || {
+            // The version and features vary from build to build, but the
+            // format doesn't.
+            let capabilities = Capabilities {
+                version: "0.0.0",
+                features: vec!["use_rand"],
+                ..capabilities()
+            };
+            capabilities.to_string().into_bytes()
+        },
+        // Nothing parses capabilities; they're a report.
+        reparse: None,
+    }];
+    documents.extend(ui_documents());
+    documents
+}
+
+#[cfg(feature = "ui")]
+fn ui_formats() -> Vec<Format> {
+    vec![
+        Format {
+            name: "preview",
+            extension: "svg",
+            emit: |puzzle| {
+                use sudoku::ui::book::render_preview;
+                use sudoku::ui::color::{DigitPalette, Theme};
+                use sudoku::ui::geometry::GridGeometry;
+                if !is_square(puzzle) {
+                    return None;
+                }
+                let geometry = GridGeometry::with_margin(puzzle.order, 400.0, 400.0, 20.0).ok()?;
+                let palette = DigitPalette::generate(puzzle.order, &Theme::default());
+                Some(render_preview(puzzle, &geometry, &palette).into_bytes())
+            },
+            reparse: None,
+        },
+        Format {
+            name: "book",
+            extension: "txt",
+            // The pages, one SVG document after another: a page of the
+            // puzzle, then one of its solution.
+            emit: |puzzle| {
+                use sudoku::ui::book::{BookOptions, Collection};
+                if !is_square(puzzle) || !is_quick(puzzle) {
+                    return None;
+                }
+                let options = BookOptions {
+                    per_page: 1,
+                    with_solutions: true,
+                    ..BookOptions::default()
+                };
+                let collection = Collection::new("Golden", vec![puzzle.clone()]);
+                Some(collection.render_book(&options).join("\n").into_bytes())
+            },
+            reparse: None,
+        },
+    ]
+}
+
+#[cfg(not(feature = "ui"))]
+fn ui_formats() -> Vec<Format> {
+    vec![]
+}
+
+#[cfg(feature = "ui")]
+fn ui_documents() -> Vec<Document> {
+    use sudoku::ui::model::config::Preferences;
+    use sudoku::{Difficulty, Technique};
+
+    // Preferences that differ from the defaults wherever they can.
+    fn preferences() -> Preferences {
+        let mut preferences = Preferences::default();
+        let behavior = &mut preferences.behavior;
+        behavior.allow_incorrect_answers = !behavior.allow_incorrect_answers;
+        behavior.policy.set_allowed(Technique::Coloring, false);
+        behavior.policy.allow_reveal = !behavior.policy.allow_reveal;
+        behavior.policy.allow_fill_singles = !behavior.policy.allow_fill_singles;
+        preferences.generation.default_order = 4;
+        preferences.generation.default_difficulty = Difficulty::Advanced;
+        preferences
+    }
+
+    vec![Document {
+        name: "preferences",
+        extension: "txt",
+        emit: || preferences().to_string().into_bytes(),
+        reparse: Some(|bytes| {
+            assert_eq!(text(bytes).parse::<Preferences>().unwrap(), preferences())
+        }),
+    }]
+}
+
+#[cfg(not(feature = "ui"))]
+fn ui_documents() -> Vec<Document> {
+    vec![]
+}
+
+// Representative puzzles: square and rectangular boxes, single- and
+// multi-digit values, and a complete grid.
+fn fixtures() -> Vec<(&'static str, Sudoku)> {
+    let parse = |s: &str| s.parse::<Sudoku>().unwrap();
+    let o3 = parse(include_str!("sudokus/solvable/2D-O3.txt"));
+    vec![
+        ("2D-O3-solved", o3.solution().unwrap()),
+        ("2D-O3", o3),
+        ("2D-O4", parse(include_str!("sudokus/solvable/2D-O4.txt"))),
+        (
+            "2D-R2x3",
+            parse(include_str!("sudokus/solvable/2D-R2x3.txt")),
+        ),
+    ]
+}
+
+/// Compares the emitted bytes with the named golden (first overwriting the
+/// golden, when updating), returning the golden if there is one.
+fn compare(
+    directory: &Path,
+    name: &str,
+    emitted: &[u8],
+    update: bool,
+    failures: &mut Vec<String>,
+) -> Option<Vec<u8>> {
+    let path = directory.join(name);
+    if update {
+        fs::create_dir_all(directory).unwrap();
+        fs::write(&path, emitted).unwrap();
+    }
+    let golden = match fs::read(&path) {
+        Ok(golden) => golden,
+        Err(_) => {
+            failures.push(format!("{} has no golden", name));
+            return None;
+        }
+    };
+    if golden != emitted {
+        failures.push(format!("{} differs from its golden", name));
+    }
+    Some(golden)
+}
+
+#[test]
+fn test_golden() {
+    let update = env::var(UPDATE_VAR).is_ok_and(|value| value != "0");
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut failures = vec![];
+    for format in formats() {
+        for (fixture, puzzle) in fixtures() {
+            let emitted = match (format.emit)(&puzzle) {
+                Some(emitted) => emitted,
+                None => continue,
+            };
+            let name = format!("{}.{}.{}", fixture, format.name, format.extension);
+            let golden = compare(&directory, &name, &emitted, update, &mut failures);
+            if let (Some(golden), Some(reparse)) = (golden, format.reparse) {
+                reparse(&golden, &puzzle);
+            }
+        }
+    }
+    for document in documents() {
+        let name = format!("{}.{}", document.name, document.extension);
+        let emitted = (document.emit)();
+        let golden = compare(&directory, &name, &emitted, update, &mut failures);
+        if let (Some(golden), Some(reparse)) = (golden, document.reparse) {
+            reparse(&golden);
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{}\n(if the change is intentional, rerun with {}=1 and review the goldens)",
+        failures.join("\n"),
+        UPDATE_VAR
+    );
+}
@@ -0,0 +1,129 @@
+extern crate sudoku;
+
+#[cfg(feature = "2D")]
+use sudoku::ParseError;
+use sudoku::{
+    DecodeError, Difficulty, FillMethod, GenerateError, OrderError, Sudoku, MAX_ORDER, MIN_ORDER,
+};
+
+// The orders just outside and at either end of the supported range.
+const UNSUPPORTED: [u8; 2] = [MIN_ORDER - 1, MAX_ORDER + 1];
+const SUPPORTED: [u8; 2] = [MIN_ORDER, MAX_ORDER];
+
+#[test]
+fn test_try_new_orders() {
+    for order in SUPPORTED.iter() {
+        assert_eq!(Sudoku::try_new(*order).unwrap().order, *order);
+    }
+    for order in UNSUPPORTED.iter() {
+        assert_eq!(Sudoku::try_new(*order), Err(OrderError(*order)));
+    }
+}
+
+#[test]
+fn test_from_bytes_orders() {
+    for order in SUPPORTED.iter() {
+        let bytes = Sudoku::new(*order).to_bytes();
+        assert_eq!(Sudoku::from_bytes(&bytes).unwrap().order, *order);
+    }
+    // The version, then the order, then the flags.
+    let flags = Sudoku::new(MIN_ORDER).to_bytes()[2];
+    for order in UNSUPPORTED.iter() {
+        assert_eq!(
+            Sudoku::from_bytes(&[1, *order, flags]),
+            Err(DecodeError::UnsupportedOrder(*order))
+        );
+    }
+}
+
+#[test]
+fn test_seed_full_grid_orders() {
+    for order in SUPPORTED.iter() {
+        let grid = Sudoku::seed_full_grid(*order, FillMethod::Shift).unwrap();
+        assert_eq!(grid.order, *order);
+    }
+    for order in UNSUPPORTED.iter() {
+        assert_eq!(Sudoku::seed_full_grid(*order, FillMethod::Shift), None);
+    }
+}
+
+#[test]
+fn test_generate_orders() {
+    for order in UNSUPPORTED.iter() {
+        let unsupported = Err(GenerateError::UnsupportedOrder(*order));
+        let generated = Sudoku::generate_with(*order, Difficulty::Beginner, FillMethod::Random);
+        assert_eq!(generated.map(|g| g.puzzle), unsupported);
+        let generated = Sudoku::generate_seeded(*order, Difficulty::Beginner, 0);
+        assert_eq!(generated.map(|g| g.puzzle), unsupported);
+        let generated = Sudoku::generate_with_givens(*order, Difficulty::Beginner, &[]);
+        assert_eq!(generated.map(|g| g.puzzle), unsupported);
+        let generated = Sudoku::daily((2024, 6, 1), *order, Difficulty::Beginner);
+        assert_eq!(generated.map(|g| g.puzzle), unsupported);
+        let generated = Sudoku::generate_distribution(*order, &[(Difficulty::Beginner, 1)]);
+        assert_eq!(generated, Err(GenerateError::UnsupportedOrder(*order)));
+    }
+    // A single cell is never a playable puzzle, but its order is supported.
+    let generated = Sudoku::generate_seeded(MIN_ORDER, Difficulty::Beginner, 0);
+    assert_eq!(generated.map(|g| g.puzzle), Err(GenerateError::Unplayable));
+}
+
+#[cfg_attr(feature = "2D", test)]
+#[cfg(feature = "2D")]
+fn test_parse_orders() {
+    use sudoku::PuzzleSource;
+
+    // An empty grid of the given order, in the grid format.
+    fn grid(order: u8) -> String {
+        let axis = usize::from(order).pow(2);
+        let row = vec!["_"; axis].join(" ");
+        vec![row; axis].join("\n")
+    }
+
+    for order in SUPPORTED.iter() {
+        let text = grid(*order);
+        assert_eq!(text.parse::<Sudoku>().unwrap().order, *order);
+        assert_eq!(
+            Sudoku::parse_with_order(&text, *order).unwrap().order,
+            *order
+        );
+        let matrix = vec![vec![None; usize::from(*order).pow(2)]; usize::from(*order).pow(2)];
+        assert_eq!(Sudoku::from_matrix(&matrix).unwrap().order, *order);
+        let source = PuzzleSource::Str(&text);
+        assert_eq!(source.load_with_order(*order).unwrap().order, *order);
+    }
+    let line = ".".repeat(usize::from(MIN_ORDER).pow(4));
+    assert_eq!(
+        Sudoku::from_ascii(line.as_bytes()).unwrap().order,
+        MIN_ORDER
+    );
+    let too_large = MAX_ORDER + 1;
+    let axis = usize::from(too_large).pow(2);
+    let text = grid(too_large);
+    match text.parse::<Sudoku>() {
+        Err(ParseError::UnsupportedOrder(order)) => assert_eq!(order, too_large),
+        other => panic!(
+            "expected an unsupported order, got {:?}",
+            other.map(|s| s.order)
+        ),
+    }
+    let matrix = vec![vec![None; axis]; axis];
+    assert!(matches!(
+        Sudoku::from_matrix(&matrix),
+        Err(ParseError::UnsupportedOrder(order)) if order == too_large
+    ));
+    let line = ".".repeat(axis * axis);
+    assert!(matches!(
+        Sudoku::from_ascii(line.as_bytes()),
+        Err(ParseError::UnsupportedOrder(order)) if order == too_large
+    ));
+    // Asking for an unsupported order fails up front.
+    for order in UNSUPPORTED.iter() {
+        let text = grid(2);
+        assert!(matches!(
+            Sudoku::parse_with_order(&text, *order),
+            Err(ParseError::UnsupportedOrder(o)) if o == *order
+        ));
+        let source = PuzzleSource::Str(&text);
+        assert!(source.load_collection_with_order(*order).is_err());
+    }
+}
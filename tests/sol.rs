@@ -1,4 +1,8 @@
 extern crate sudoku;
+#[macro_use]
+mod common;
+
+#[cfg(feature = "2D")]
 use sudoku::{Grid, Solve, Sudoku};
 
 #[cfg_attr(feature = "2D", test)]
@@ -11,6 +15,46 @@ fn test_solve_o3_2d() {
     assert!(solution.is_ok());
 }
 
+#[cfg_attr(feature = "2D", test)]
+#[cfg(feature = "2D")]
+fn test_solve_into_o3_2d() {
+    let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+        .parse()
+        .unwrap();
+    let mut buffer = Sudoku::new(3);
+    puzzle.solve_into(&mut buffer).unwrap();
+    assert_grids_eq!(buffer, puzzle.solution().unwrap());
+    let capacity = buffer.elements.capacity();
+    puzzle.solve_into(&mut buffer).unwrap();
+    assert_eq!(buffer.elements.capacity(), capacity);
+}
+
+#[cfg_attr(feature = "2D", test)]
+#[cfg(feature = "2D")]
+#[should_panic(expected = "the output grid's shape doesn't match the puzzle's")]
+fn test_solve_into_wrong_shape() {
+    let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+        .parse()
+        .unwrap();
+    let _ = puzzle.solve_into(&mut Sudoku::new(2));
+}
+
+#[cfg_attr(feature = "2D", test)]
+#[cfg(feature = "2D")]
+fn test_solve_path_o3_2d() {
+    let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-O3.txt")
+        .parse()
+        .unwrap();
+    let empty = puzzle.elements.iter().filter(|e| e.is_none()).count();
+    let path = puzzle.solve_path().unwrap();
+    assert_eq!(path.len(), empty);
+    let solution = puzzle.solution().unwrap();
+    for (point, value, branch_factor) in path.iter() {
+        assert_eq!(solution[*point], Some(*value));
+        assert!(*branch_factor >= 1);
+    }
+}
+
 #[cfg_attr(feature = "2D", test)]
 #[cfg_attr(feature = "2D", ignore)]
 #[cfg(feature = "2D")]
@@ -22,6 +66,33 @@ fn test_solve_o4_2d() {
     assert!(solution.is_ok());
 }
 
+#[cfg_attr(feature = "2D", test)]
+#[cfg(feature = "2D")]
+fn test_solve_rect_2d() {
+    let puzzle: Sudoku = include_str!("../tests/sudokus/solvable/2D-R2x3.txt")
+        .parse()
+        .unwrap();
+    assert!(puzzle.is_uniquely_solvable());
+    let solution = puzzle.solution().unwrap();
+    assert!(solution.is_solved());
+    assert_eq!(solution.box_shape(), (2, 3));
+    let expected: Sudoku = "1 2 3 4 5 6\n\
+                            4 5 6 1 2 3\n\
+                            2 3 1 5 6 4\n\
+                            5 6 4 2 3 1\n\
+                            3 1 2 6 4 5\n\
+                            6 4 5 3 1 2"
+        .parse()
+        .unwrap();
+    assert_grids_eq!(solution, expected);
+    for point in solution.points() {
+        for group in solution.groups(point).iter() {
+            assert!(group.is_complete());
+            assert!(group.is_valid());
+        }
+    }
+}
+
 #[cfg_attr(feature = "2D", test)]
 #[cfg_attr(feature = "2D", ignore)]
 #[cfg(feature = "2D")]
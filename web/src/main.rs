@@ -2,7 +2,10 @@
 extern crate stdweb;
 extern crate sudoku;
 
-use sudoku::{ui::model::Game, Difficulty, Point};
+use sudoku::{
+    ui::model::{Game, GameCommand, GameEvent},
+    Difficulty, GenerateError, Sudoku,
+};
 
 use std::{cell::RefCell, rc::Rc};
 
@@ -10,17 +13,89 @@ use std::{cell::RefCell, rc::Rc};
 // Because this will contain references that are platform-specific, this lives here, not in ku::ui.
 pub struct Context {
     game: Game,
-    focused: Option<Point>,
+    /// The difficulty new games are generated at.
+    difficulty: Difficulty,
+    /// A message to show above the grid (e.g. when generation failed).
+    message: Option<String>,
 }
 
 impl Context {
     /// Constructs a context with a new game of the specified order and difficulty.
     pub fn new(order: u8, difficulty: Difficulty) -> Self {
+        let mut game = Game::placeholder(order);
+        game.behavior.allow_incorrect_answers = cfg!(feature = "allow_incorrect");
+        let mut context = Self {
+            game,
+            difficulty,
+            message: None,
+        };
+        let _ = context.handle(GameCommand::NewGame { order, difficulty });
+        context
+    }
+
+    /// Constructs a context playing the given puzzle, generating new games at
+    /// the specified difficulty.
+    pub fn from_puzzle(puzzle: Sudoku, difficulty: Difficulty) -> Self {
+        let mut game = Game::from_puzzle(puzzle);
+        game.behavior.allow_incorrect_answers = cfg!(feature = "allow_incorrect");
         Self {
-            game: Game::new(order, difficulty),
-            focused: None,
+            game,
+            difficulty,
+            message: None,
+        }
+    }
+
+    /// Passes the command to the game, noting any generation failure, and
+    /// returns what came of it.
+    pub fn handle(&mut self, command: GameCommand) -> Vec<GameEvent> {
+        if let GameCommand::NewGame { difficulty, .. } = command {
+            self.difficulty = difficulty;
+            self.message = None;
         }
+        let events = self.game.handle(command);
+        for event in &events {
+            match event {
+                GameEvent::GenerationFailed(error) => {
+                    self.message = Some(generation_failed(error));
+                }
+                // Logged so that bug reports can include it.
+                GameEvent::NewGame { seed: Some(seed) } => {
+                    let seed = seed.to_string();
+                    js! { console.info("Puzzle seed: " + @{seed}); }
+                }
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+/// The message shown above the grid when generating a puzzle failed.
+fn generation_failed(error: &GenerateError) -> String {
+    format!("Couldn't generate a puzzle ({}).", error)
+}
+
+/// Decodes the puzzle shared in the URL's hash (without its `#`), if there
+/// is one, returning the message to show instead if it can't be played.
+fn shared_puzzle(hash: &str) -> Option<Result<Sudoku, String>> {
+    if hash.is_empty() {
+        return None;
     }
+    Some(match Sudoku::from_base64(hash) {
+        Ok(puzzle) if is_playable(&puzzle) => Ok(puzzle),
+        Ok(_) => Err("The shared puzzle can't be played here.".to_string()),
+        Err(error) => Err(format!("Couldn't load the shared puzzle ({}).", error)),
+    })
+}
+
+/// Returns whether the frontend can lay out and play the puzzle.
+///
+/// The grid is drawn with square boxes, so rectangular ones are rejected, as
+/// are order-1 grids (a single cell, which is no puzzle at all). Decoding
+/// already rejects orders the library doesn't support.
+fn is_playable(puzzle: &Sudoku) -> bool {
+    let order = puzzle.order;
+    order >= 2 && puzzle.box_shape() == (order, order)
 }
 
 mod view;
@@ -29,7 +104,56 @@ use view::{play, render};
 
 fn main() {
     render(None);
-    let context = Context::new(3, Difficulty::Advanced);
+    // A puzzle shared in the URL (as `#` and its base64 encoding) is played
+    // instead of a new one.
+    let hash = js! { return window.location.hash; }
+        .into_string()
+        .unwrap_or_default();
+    let context = match shared_puzzle(hash.trim_start_matches('#')) {
+        Some(Ok(puzzle)) => Context::from_puzzle(puzzle, Difficulty::Advanced),
+        shared => {
+            let mut context = Context::new(3, Difficulty::Advanced);
+            if let (Some(Err(message)), None) = (shared, &context.message) {
+                context.message = Some(message);
+            }
+            context
+        }
+    };
     let context = Rc::new(RefCell::new(context));
     play(context);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{generation_failed, shared_puzzle};
+    use sudoku::{GenerateError, Sudoku};
+
+    #[test]
+    fn test_generation_failed() {
+        assert_eq!(
+            generation_failed(&GenerateError::NoGrid),
+            "Couldn't generate a puzzle (no complete grid could be constructed)."
+        );
+    }
+
+    #[test]
+    fn test_shared_puzzle() {
+        assert_eq!(shared_puzzle(""), None);
+        let puzzle = Sudoku::new(3);
+        assert_eq!(shared_puzzle(&puzzle.to_base64()), Some(Ok(puzzle)));
+        let message = shared_puzzle("!").unwrap().unwrap_err();
+        assert!(
+            message.starts_with("Couldn't load the shared puzzle (") && !message.contains("Error"),
+            "{}",
+            message
+        );
+        assert_eq!(
+            shared_puzzle(&Sudoku::new_rect(2, 3).to_base64()),
+            Some(Err("The shared puzzle can't be played here.".to_string()))
+        );
+        assert_eq!(
+            shared_puzzle(&Sudoku::new(1).to_base64()),
+            Some(Err("The shared puzzle can't be played here.".to_string()))
+        );
+    }
+}
@@ -12,7 +12,14 @@ use stdweb::{
 
 use Context;
 
-use sudoku::{ui::model::Game, Difficulty, Element, Point};
+use sudoku::{
+    ui::{
+        color::{DigitPalette, Theme},
+        geometry::{GridGeometry, Rect},
+        model::{AnnotationColor, Direction, GameCommand, GameEvent},
+    },
+    Element, Point,
+};
 
 use std::{cell::RefCell, rc::Rc};
 
@@ -40,24 +47,10 @@ const SUB_HIGHLIGHT: &'static str = "rgba(15, 15, 15, 0.05)";
 
 const COLORIZE_ON_HIGHLIGHT: bool = true;
 
-// partial_min
-#[cfg_attr(rustfmt, rustfmt_skip)]
-fn min(l: f64, r: f64) -> f64 {
-    if l > r { r } else { l }
-}
-
 fn get_order(context: &Option<&Context>) -> u8 {
     context.map(|c| c.game.current.order).unwrap_or(3)
 }
 
-fn grid_length() -> f64 {
-    let (width, height) = (
-        window().inner_width() as f64,
-        window().inner_height() as f64,
-    );
-    min(0.9 * width, 0.9 * height)
-}
-
 fn get_canvas() -> CanvasElement {
     document()
         .get_element_by_id("canvas")
@@ -66,38 +59,79 @@ fn get_canvas() -> CanvasElement {
         .unwrap()
 }
 
-fn grid_origin(context: &Option<&Context>) -> (f64, f64) {
-    let axis = get_order(&context).pow(2);
-
-    let (width, height) = (
+fn geometry(context: &Option<&Context>) -> GridGeometry {
+    GridGeometry::new(
+        get_order(context),
         window().inner_width() as f64,
         window().inner_height() as f64,
-    );
+    )
+}
 
-    let center = (width / 2.0, height / 2.0);
-    let length = grid_length();
-    let spacing = length / (axis as f64);
+fn point_for_click(context: &Context, click: &ClickEvent) -> Option<Point> {
+    geometry(&Some(context)).cell_at(click.client_x() as f64, click.client_y() as f64)
+}
 
-    let left = center.0 - (axis as f64) * spacing / 2.0;
-    let top = center.1 - (axis as f64) * spacing / 2.0;
+/// The fill drawn under a cell the player has colored.
+fn annotation_fill(color: AnnotationColor) -> &'static str {
+    match color {
+        AnnotationColor::Red => "hsla(0, 70%, 50%, 0.3)",
+        AnnotationColor::Orange => "hsla(30, 70%, 50%, 0.3)",
+        AnnotationColor::Yellow => "hsla(60, 70%, 50%, 0.3)",
+        AnnotationColor::Green => "hsla(120, 70%, 50%, 0.3)",
+        AnnotationColor::Blue => "hsla(220, 70%, 50%, 0.3)",
+        AnnotationColor::Purple => "hsla(280, 70%, 50%, 0.3)",
+    }
+}
 
-    (left, top)
+/// Maps arrow keys (and, with `vim_movement`, WASD and HJKL) to directions.
+fn direction_for_key(key: &str) -> Option<Direction> {
+    let vim = cfg!(feature = "vim_movement");
+    match key {
+        "ArrowUp" => Some(Direction::Up),
+        "ArrowDown" => Some(Direction::Down),
+        "ArrowLeft" => Some(Direction::Left),
+        "ArrowRight" => Some(Direction::Right),
+        "w" | "k" if vim => Some(Direction::Up),
+        "s" | "j" if vim => Some(Direction::Down),
+        "a" | "h" if vim => Some(Direction::Left),
+        "d" | "l" if vim => Some(Direction::Right),
+        _ => None,
+    }
 }
 
-fn point_for_click(context: &Context, click: &ClickEvent) -> Option<Point> {
-    let origin = grid_origin(&Some(context));
-    let length = grid_length();
-    let max = (origin.0 + length, origin.1 + length);
-    let locus = (click.client_x() as f64, click.client_y() as f64);
-    if locus.0 < origin.0 || locus.0 > max.0 || locus.1 < origin.1 || locus.1 > max.1 {
-        None
-    } else {
-        let order = context.game.current.order as f64;
-        let axis = order.powf(2.0);
-        let specific = length / axis;
-        let x = ((locus.0 - origin.0) / specific).floor() as u8;
-        let y = ((locus.1 - origin.1) / specific).floor() as u8;
-        Some(Point([x, y]))
+/// Handles the command, keeping the context borrowed only for the call
+/// itself, then renders and reacts to what came of it.
+fn dispatch(context: &Rc<RefCell<Context>>, command: GameCommand) {
+    let events = context.borrow_mut().handle(command);
+    if events.is_empty() {
+        return;
+    }
+    render(Some(&context.borrow()));
+    for event in events {
+        if let GameEvent::Solved { moves } = event {
+            let congrats = format!("Sudoku solved in {} moves!", moves);
+            js! { alert(@{congrats}); }
+            let (order, difficulty) = {
+                let context = context.borrow();
+                (context.game.current.order, context.difficulty)
+            };
+            dispatch(context, GameCommand::NewGame { order, difficulty });
+        }
+    }
+}
+
+/// Maps a key press to a command, given whether a cell is focused.
+fn command_for_key(key: &str, focused: bool) -> Option<GameCommand> {
+    if !focused {
+        return if cfg!(feature = "vim_movement") && key == "i" {
+            Some(GameCommand::Focus(Some(Point::origin())))
+        } else {
+            None
+        };
+    }
+    match direction_for_key(key) {
+        Some(direction) => Some(GameCommand::MoveFocus(direction)),
+        None => Some(GameCommand::InputKey(key.to_string())),
     }
 }
 
@@ -112,120 +146,34 @@ pub fn play(context: Rc<RefCell<Context>>) {
     });
     let canvas = get_canvas();
     document().add_event_listener(move |event: KeyDownEvent| {
-        if let Ok(mut context) = key_context.try_borrow_mut() {
-            if let Some(point) = context.focused {
-                match event.key().as_str() {
-                    "Backspace" | "Delete" => {
-                        event.prevent_default();
-                        if context.game.is_mutable(point) {
-                            let _old = context.game.remove(point);
-                            render(Some(&context));
-                        }
-                    }
-                    "Escape" => {
-                        context.focused = None;
-                        render(Some(&context));
-                    }
-                    "ArrowUp" | "w" | "k" if cfg!(feature = "vim_movement") => {
-                        if point[1] > 0 {
-                            let mut new = point;
-                            new[1] -= 1;
-                            context.focused = Some(new);
-                            render(Some(&context));
-                        }
-                        event.prevent_default();
-                    }
-                    "ArrowDown" | "s" | "j" if cfg!(feature = "vim_movement") => {
-                        if point[1] < context.game.current.order.pow(2) - 1 {
-                            let mut new = point;
-                            new[1] += 1;
-                            context.focused = Some(new);
-                            render(Some(&context));
-                        }
-                        event.prevent_default();
-                    }
-                    "ArrowLeft" | "a" | "h" if cfg!(feature = "vim_movement") => {
-                        if point[0] > 0 {
-                            let mut new = point;
-                            new[0] -= 1;
-                            context.focused = Some(new);
-                            render(Some(&context));
-                        }
-                        event.prevent_default();
-                    }
-                    "ArrowRight" | "d" | "l" if cfg!(feature = "vim_movement") => {
-                        if point[0] < context.game.current.order.pow(2) - 1 {
-                            let mut new = point;
-                            new[0] += 1;
-                            context.focused = Some(new);
-                            render(Some(&context));
-                        }
-                        event.prevent_default();
-                    }
-                    key => {
-                        if let Ok(value) = key.parse::<u8>() {
-                            let order = get_order(&Some(&context));
-                            if value > 0 && value <= order.pow(2) {
-                                let element = Element(value);
-                                if context.game.insertion_is_correct(point, element)
-                                    || cfg!(feature = "allow_incorrect")
-                                {
-                                    context.game.insert(point, element);
-                                    render(Some(&context));
-                                    if context.game.current == context.game.solution {
-                                        let congrats = format!(
-                                            "Sudoku solved in {} moves!",
-                                            context.game.moves
-                                        );
-                                        js! { alert(@{congrats}); }
-                                        context.game = Game::new(
-                                            context.game.current.order,
-                                            Difficulty::Advanced,
-                                        );
-                                        context.focused = None;
-                                        render(Some(&context));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            } else {
-                if cfg!(feature = "vim_movement") && event.key().as_str() == "i" {
-                    context.focused = Some(Point::origin());
-                    render(Some(&context));
-                }
+        let key = event.key();
+        let focused = key_context.borrow().game.focused().is_some();
+        if let Some(command) = command_for_key(&key, focused) {
+            // Keep the page from scrolling or navigating back.
+            if let GameCommand::MoveFocus(_) = command {
+                event.prevent_default();
+            } else if key == "Backspace" || key == "Delete" {
+                event.prevent_default();
             }
+            dispatch(&key_context, command);
         }
     });
     canvas.add_event_listener(move |event: ClickEvent| {
-        if let Ok(mut context) = click_context.try_borrow_mut() {
-            context.focused = point_for_click(&context, &event);
-            render(Some(&context));
-        }
+        let point = point_for_click(&click_context.borrow(), &event);
+        dispatch(&click_context, GameCommand::Focus(point));
     });
 }
 
-pub fn fill_box(
-    ctx: &CanvasRenderingContext2d,
-    context: &Context,
-    point: Point,
-    color: &'static str,
-) {
-    let (left, top) = grid_origin(&Some(context));
-    let axis = get_order(&Some(context)).pow(2);
-    let length = grid_length();
-    let spacing = length / (axis as f64);
+pub fn fill_box(ctx: &CanvasRenderingContext2d, rect: Rect, color: &str) {
     ctx.set_fill_style_color(color);
-    ctx.fill_rect(
-        left + point[0] as f64 * spacing,
-        top + point[1] as f64 * spacing,
-        spacing,
-        spacing,
-    );
+    ctx.fill_rect(rect.x, rect.y, rect.width, rect.height);
     ctx.set_fill_style_color(BG);
 }
 
+fn set_font_size(ctx: &CanvasRenderingContext2d, size: f64) {
+    ctx.set_font(&format!("{}px sans-serif", size));
+}
+
 pub fn render(context: Option<&Context>) {
     let canvas: CanvasElement = get_canvas();
     canvas.set_width(window().inner_width() as u32);
@@ -238,68 +186,96 @@ pub fn render(context: Option<&Context>) {
     ctx.fill_rect(0.0, 0.0, width, height);
     ctx.set_stroke_style_color(GRID);
 
-    let (left, top) = grid_origin(&context);
-    let order = get_order(&context);
-    let axis = order.pow(2);
-    let length = grid_length();
-    let spacing = length / (axis as f64);
+    let geometry = geometry(&context);
+    let order = geometry.order;
+    let axis = geometry.axis();
+    let grid = geometry.grid;
+    let spacing = geometry.cell_size();
 
     for i in 0..=axis {
         ctx.set_line_width(if i % order == 0 { 4.0 } else { 2.0 });
         ctx.begin_path();
-        ctx.move_to(left + (i as f64) * spacing, top);
-        ctx.line_to(left + (i as f64) * spacing, top + length);
-        ctx.move_to(left, top + (i as f64) * spacing);
-        ctx.line_to(left + length, top + (i as f64) * spacing);
+        ctx.move_to(grid.x + (i as f64) * spacing, grid.y);
+        ctx.line_to(grid.x + (i as f64) * spacing, grid.y + grid.height);
+        ctx.move_to(grid.x, grid.y + (i as f64) * spacing);
+        ctx.line_to(grid.x + grid.width, grid.y + (i as f64) * spacing);
         ctx.stroke();
     }
 
-    let font_size = length / 14.0;
-    ctx.set_font(&format!("{}px sans-serif", font_size));
+    let font_size = spacing * geometry.recommended_font_fraction(order);
+    set_font_size(&ctx, font_size);
     ctx.set_text_baseline(TextBaseline::Middle);
     ctx.set_text_align(TextAlign::Center);
     if let Some(context) = context {
-        let highlighted: Option<Vec<Point>> = context.focused.map(|f| {
+        if let Some(ref message) = context.message {
+            ctx.set_fill_style_color(TEXT);
+            set_font_size(&ctx, font_size / 2.0);
+            ctx.fill_text(message, width / 2.0, grid.y / 2.0, None);
+            set_font_size(&ctx, font_size);
+        }
+        let highlighted: Option<Vec<Point>> = context.game.focused().map(|f| {
             let mut group = context.game.current.group_indices(f);
             group.sort();
             group.dedup();
             group
         });
+        for (point, color) in context.game.cell_colors() {
+            fill_box(&ctx, geometry.cell_rect(point), annotation_fill(color));
+        }
         if let Some(ref group) = &highlighted {
             for point in group {
-                fill_box(&ctx, &context, *point, SUB_HIGHLIGHT);
+                fill_box(&ctx, geometry.cell_rect(*point), SUB_HIGHLIGHT);
             }
         }
-        if let Some(focused) = context.focused {
-            fill_box(&ctx, &context, focused, HIGHLIGHT);
+        if let Some(focused) = context.game.focused() {
+            fill_box(&ctx, geometry.cell_rect(focused), HIGHLIGHT);
         }
-        let focused_value = context.focused.and_then(|p| context.game.current[p]);
-        let angles = [0, 15, 40, 60, 100, 160, 230, 275, 315];
-        let colors = angles
-            .into_iter()
-            .map(|angle| format!("hsl({}, 70%, 50%)", angle))
-            .collect::<Vec<_>>();
+        let focused_value = context.game.focused().and_then(|p| context.game.current[p]);
+        let palette = DigitPalette::generate(order, &Theme::default());
         let highlighted = highlighted.unwrap_or_default();
         for point in context.game.points() {
             if let Some(Element(value)) = context.game.current[point] {
-                let x = point[0];
-                let y = point[1];
                 let color = if COLORIZE_ON_HIGHLIGHT
                     && !highlighted.contains(&point)
                     && Some(Element(value)) != focused_value
                 {
-                    TEXT
+                    None
                 } else {
-                    &colors[(value - 1) as usize]
+                    palette.css_for(Element(value))
                 };
-                ctx.set_fill_style_color(color);
-                ctx.fill_text(
-                    &format!("{}", value),
-                    left + spacing * (x as f64 + 0.5),
-                    top + spacing * (y as f64 + 0.5),
-                    None,
-                );
+                ctx.set_fill_style_color(color.as_ref().map_or(TEXT, String::as_str));
+                let (x, y) = geometry.cell_center(point);
+                ctx.fill_text(&format!("{}", value), x, y, None);
             }
         }
+        // Pencil in the values the focused cell could still take.
+        if let Some(focused) = context.game.focused() {
+            if context.game.current[focused].is_none() {
+                ctx.set_fill_style_color(TEXT);
+                set_font_size(&ctx, font_size / f64::from(order));
+                for Element(value) in context.game.candidates(focused) {
+                    let (x, y) = geometry.note_position(focused, value);
+                    ctx.fill_text(&format!("{}", value), x, y, None);
+                }
+                set_font_size(&ctx, font_size);
+            }
+        }
+        // A legend of the digit colors, beside the grid.
+        for (rect, (Element(value), _)) in
+            geometry.tray_layout(order).into_iter().zip(palette.iter())
+        {
+            let css = palette.css_for(Element(value));
+            ctx.set_fill_style_color(css.as_ref().map_or(TEXT, String::as_str));
+            set_font_size(
+                &ctx,
+                rect.height * geometry.recommended_font_fraction(order),
+            );
+            ctx.fill_text(
+                &format!("{}", value),
+                rect.x + rect.width / 2.0,
+                rect.y + rect.height / 2.0,
+                None,
+            );
+        }
     }
 }